@@ -0,0 +1,205 @@
+//! How long checking a large crate takes, end to end through the library API.
+//!
+//! Run with `cargo bench --bench check`. By default, this checks a generated crate with
+//! thousands of items spread over many modules and re-exported from its root. To measure
+//! a real crate instead, set `BENCH_BASELINE` and `BENCH_CURRENT` to the paths of its
+//! baseline and current rustdoc JSON.
+
+use std::time::{Duration, Instant};
+
+use cargo_semver_checks::{Check, Rustdoc};
+use rustdoc_types::{Crate, FORMAT_VERSION};
+use serde_json::{json, Value};
+
+const ITERATIONS: usize = 5;
+const MODULES: usize = 40;
+const STRUCTS_PER_MODULE: usize = 25;
+
+fn item(id: &str, name: Option<&str>, kind: &str, inner: Value) -> Value {
+    json!({
+        "id": id, "crate_id": 0, "name": name, "span": null, "visibility": "public",
+        "docs": null, "links": {}, "attrs": [], "deprecation": null, "kind": kind, "inner": inner,
+    })
+}
+
+/// A crate with `MODULES` modules of `STRUCTS_PER_MODULE` structs, each with two fields,
+/// an inherent impl with two methods, and a constructor function, all glob-imported into
+/// the crate root. Every `removed_every`-th struct and function is left out.
+fn generated_crate(removed_every: Option<usize>) -> Crate {
+    let generics = json!({ "params": [], "where_predicates": [] });
+    let header = json!({ "const": false, "unsafe": false, "async": false, "abi": "Rust" });
+    let u64_type = json!({ "kind": "primitive", "inner": "u64" });
+    let mut index = serde_json::Map::new();
+    let mut root_items = vec![];
+    let mut next_id = 1;
+    let mut new_id = || {
+        next_id += 1;
+        format!("0:{next_id}")
+    };
+
+    for module in 0..MODULES {
+        let mut module_items = vec![];
+        for number in 0..STRUCTS_PER_MODULE {
+            if removed_every
+                .is_some_and(|every| (module * STRUCTS_PER_MODULE + number).is_multiple_of(every))
+            {
+                continue;
+            }
+            let name = format!("Struct{module}x{number}");
+            let [struct_id, impl_id, function_id] = [new_id(), new_id(), new_id()];
+            let struct_type = json!({ "kind": "resolved_path", "inner": {
+                "name": name, "id": struct_id, "args": null, "param_names": [],
+            } });
+
+            let mut fields = vec![];
+            for field in ["first", "second"] {
+                let field_id = new_id();
+                index.insert(
+                    field_id.clone(),
+                    item(&field_id, Some(field), "struct_field", u64_type.clone()),
+                );
+                fields.push(field_id);
+            }
+            let mut methods = vec![];
+            for method in ["get", "set"] {
+                let method_id = new_id();
+                let decl = json!({
+                    "inputs": [["self", { "kind": "borrowed_ref", "inner": {
+                        "lifetime": null, "mutable": method == "set",
+                        "type": { "kind": "generic", "inner": "Self" },
+                    } }]],
+                    "output": u64_type,
+                    "c_variadic": false,
+                });
+                index.insert(
+                    method_id.clone(),
+                    item(
+                        &method_id,
+                        Some(method),
+                        "method",
+                        json!({ "decl": decl, "generics": generics, "header": header, "has_body": true }),
+                    ),
+                );
+                methods.push(method_id);
+            }
+
+            index.insert(
+                struct_id.clone(),
+                item(
+                    &struct_id,
+                    Some(&name),
+                    "struct",
+                    json!({
+                        "struct_type": "plain", "generics": generics, "fields_stripped": false,
+                        "fields": fields, "impls": [impl_id],
+                    }),
+                ),
+            );
+            index.insert(
+                impl_id.clone(),
+                item(
+                    &impl_id,
+                    None,
+                    "impl",
+                    json!({
+                        "is_unsafe": false, "generics": generics, "provided_trait_methods": [],
+                        "trait": null, "for": struct_type, "items": methods, "negative": false,
+                        "synthetic": false, "blanket_impl": null,
+                    }),
+                ),
+            );
+            index.insert(
+                function_id.clone(),
+                item(
+                    &function_id,
+                    Some(&format!("make_{}", name.to_lowercase())),
+                    "function",
+                    json!({
+                        "decl": { "inputs": [], "output": struct_type, "c_variadic": false },
+                        "generics": generics,
+                        "header": header,
+                    }),
+                ),
+            );
+            module_items.extend([struct_id, function_id]);
+        }
+
+        let [module_id, import_id] = [new_id(), new_id()];
+        let module_name = format!("module{module}");
+        index.insert(
+            module_id.clone(),
+            item(
+                &module_id,
+                Some(&module_name),
+                "module",
+                json!({ "is_crate": false, "items": module_items }),
+            ),
+        );
+        index.insert(
+            import_id.clone(),
+            item(
+                &import_id,
+                None,
+                "import",
+                json!({
+                    "source": format!("self::{module_name}"), "name": module_name,
+                    "id": module_id, "glob": true,
+                }),
+            ),
+        );
+        root_items.extend([module_id, import_id]);
+    }
+    index.insert(
+        "0:0".to_string(),
+        item(
+            "0:0",
+            Some("generated"),
+            "module",
+            json!({ "is_crate": true, "items": root_items }),
+        ),
+    );
+
+    serde_json::from_value(json!({
+        "root": "0:0",
+        "crate_version": "1.0.0",
+        "includes_private": false,
+        "index": index,
+        "paths": {},
+        "external_crates": {},
+        "format_version": FORMAT_VERSION,
+    }))
+    .expect("generated crate is not valid rustdoc JSON")
+}
+
+fn main() {
+    let (baseline, current) = match (
+        std::env::var("BENCH_BASELINE"),
+        std::env::var("BENCH_CURRENT"),
+    ) {
+        (Ok(baseline), Ok(current)) => (Rustdoc::from_path(baseline), Rustdoc::from_path(current)),
+        _ => (
+            Rustdoc::from_crate(generated_crate(None)),
+            Rustdoc::from_crate(generated_crate(Some(97))),
+        ),
+    };
+
+    let mut durations: Vec<Duration> = (0..ITERATIONS)
+        .map(|_| {
+            let check = Check::new()
+                .with_baseline(baseline.clone())
+                .with_current(current.clone());
+            let start_instant = Instant::now();
+            let report = check.run().expect("check failed to run");
+            let duration = start_instant.elapsed();
+            assert!(!report.success(), "the removed items weren't found");
+            duration
+        })
+        .collect();
+    durations.sort();
+    println!(
+        "check: min {:.3}s, median {:.3}s, max {:.3}s over {ITERATIONS} runs",
+        durations[0].as_secs_f64(),
+        durations[ITERATIONS / 2].as_secs_f64(),
+        durations[ITERATIONS - 1].as_secs_f64(),
+    );
+}
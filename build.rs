@@ -0,0 +1,143 @@
+//! Compiles the built-in checks in `src/queries` into the crate.
+//!
+//! Each check's `.ron` file is parsed, its query compiled to Trustfall IR against the
+//! rustdoc schema, and its templates parsed, so a malformed check fails the build instead
+//! of every run. The checks are written out as Rust code that constructs them, with their
+//! compiled queries as JSON, which `src/query.rs` includes.
+
+use std::{fmt::Write, path::Path};
+
+use handlebars::Handlebars;
+use trustfall_core::{frontend::parse_to_ir, ir::TransparentValue, schema::Schema};
+
+// Only the fields written in `.ron` files are used here.
+#[allow(dead_code)]
+#[path = "src/query_definition.rs"]
+mod query_definition;
+
+use query_definition::SemverQuery;
+
+/// Rust code for an optional string.
+fn option_code(value: &Option<String>) -> String {
+    match value {
+        Some(value) => format!("Some({value:?}.to_string())"),
+        None => "None".to_string(),
+    }
+}
+
+/// Rust code for a query argument.
+fn argument_code(value: &TransparentValue) -> String {
+    match value {
+        TransparentValue::Null => "TransparentValue::Null".to_string(),
+        TransparentValue::Int64(value) => format!("TransparentValue::Int64({value})"),
+        TransparentValue::Uint64(value) => format!("TransparentValue::Uint64({value})"),
+        TransparentValue::Float64(value) => format!("TransparentValue::Float64({value:?})"),
+        TransparentValue::String(value) => {
+            format!("TransparentValue::String({value:?}.to_string())")
+        }
+        TransparentValue::Boolean(value) => format!("TransparentValue::Boolean({value})"),
+        TransparentValue::Enum(value) => format!("TransparentValue::Enum({value:?}.to_string())"),
+        TransparentValue::List(values) => format!(
+            "TransparentValue::List(vec![{}])",
+            values
+                .iter()
+                .map(argument_code)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        TransparentValue::DateTimeUtc(_) => panic!("dates aren't supported as query arguments"),
+    }
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=src/queries");
+    println!("cargo:rerun-if-changed=src/rustdoc_schema.graphql");
+    println!("cargo:rerun-if-changed=src/query_definition.rs");
+
+    let schema_text =
+        std::fs::read_to_string("src/rustdoc_schema.graphql").expect("failed to read schema");
+    let schema = Schema::parse(schema_text).expect("schema not valid");
+
+    let mut paths: Vec<_> = std::fs::read_dir("src/queries")
+        .expect("failed to read src/queries")
+        .map(|entry| entry.expect("failed to read src/queries").path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "ron"))
+        .collect();
+    paths.sort();
+
+    let mut code = String::from("vec![\n");
+    for path in &paths {
+        println!("cargo:rerun-if-changed={}", path.display());
+        let text = std::fs::read_to_string(path).expect("failed to read check");
+        let query: SemverQuery = ron::from_str(&text)
+            .unwrap_or_else(|e| panic!("check {} failed to parse: {e}", path.display()));
+        let ir = parse_to_ir(&schema, &query.query).unwrap_or_else(|e| {
+            panic!(
+                "check {} does not contain a valid query: {e:?}",
+                path.display()
+            )
+        });
+        let templates = [
+            (
+                "per_result_error_template",
+                &query.per_result_error_template,
+            ),
+            ("witness_template", &query.witness_template),
+        ];
+        for (name, template) in templates {
+            if let Some(template) = template.as_deref() {
+                Handlebars::new()
+                    .register_template_string(name, template)
+                    .unwrap_or_else(|e| {
+                        panic!("check {} has an invalid {name}: {e}", path.display())
+                    });
+            }
+        }
+        let compiled_query = serde_json::to_string(&ir).expect("failed to serialize query");
+
+        let arguments: String = query
+            .arguments
+            .iter()
+            .map(|(name, value)| format!("({name:?}.to_string(), {}), ", argument_code(value)))
+            .collect();
+        write!(
+            code,
+            "SemverQuery {{
+                id: {:?}.to_string(),
+                human_readable_name: {:?}.to_string(),
+                description: {:?}.to_string(),
+                required_update: RequiredSemverUpdate::{:?},
+                lint_level: LintLevel::{:?},
+                min_schema_version: {},
+                reference_link: {},
+                query: {:?}.to_string(),
+                arguments: BTreeMap::from([{arguments}]),
+                error_message: {:?}.to_string(),
+                per_result_error_template: {},
+                witness_template: {},
+                example: {},
+                compiled_query: Some({compiled_query:?}),
+                source_path: None,
+                is_builtin: true,
+            }},\n",
+            query.id,
+            query.human_readable_name,
+            query.description,
+            query.required_update,
+            query.lint_level,
+            query.min_schema_version,
+            option_code(&query.reference_link),
+            query.query,
+            query.error_message,
+            option_code(&query.per_result_error_template),
+            option_code(&query.witness_template),
+            option_code(&query.example),
+        )
+        .expect("formatting failed");
+    }
+    code.push_str("]\n");
+
+    let out_dir = std::env::var("OUT_DIR").expect("OUT_DIR not set");
+    std::fs::write(Path::new(&out_dir).join("builtin_queries.rs"), code)
+        .expect("failed to write the built-in checks");
+}
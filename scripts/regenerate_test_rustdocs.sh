@@ -19,15 +19,34 @@ mv "$RUSTDOC_OUTPUT" "$TARGET_DIR/baseline.json"
 
 # For each feature, re-run rustdoc with it enabled.
 features=(
+    'constant_missing'
     'enum_missing'
     'enum_variant_added'
     'enum_variant_missing'
     'function_missing'
+    'function_must_use_added'
+    'function_now_deprecated'
+    'function_parameter_type_changed'
+    'generic_parameter_missing'
+    'inherent_method_missing'
+    'lifetime_bound_added'
+    'macro_missing'
+    'return_impl_trait_changed'
+    'static_missing'
     'struct_marked_non_exhaustive'
     'struct_missing'
     'struct_pub_field_missing'
+    'trait_associated_constant_missing'
+    'trait_associated_type_missing'
+    'trait_impl_missing'
+    'trait_method_missing'
+    'trait_object_auto_traits_changed'
+    'type_alias_missing'
+    'union_missing'
     'unit_struct_changed_kind'
+    'variant_field_missing'
     'variant_marked_non_exhaustive'
+    'where_predicate_added'
 )
 for feat in "${features[@]}"
 do
@@ -0,0 +1,20 @@
+//! Changes that don't break code that depends on the crate, but make it emit new warnings.
+//! The checks for them report warnings, which don't fail the run.
+
+#[cfg(not(feature = "function_now_deprecated"))]
+pub fn will_be_deprecated_fn() {}
+
+#[cfg(feature = "function_now_deprecated")]
+#[deprecated(note = "use a different function")]
+pub fn will_be_deprecated_fn() {}
+
+#[cfg(not(feature = "function_must_use_added"))]
+pub fn will_be_must_use_fn() -> u64 {
+    0
+}
+
+#[cfg(feature = "function_must_use_added")]
+#[must_use]
+pub fn will_be_must_use_fn() -> u64 {
+    0
+}
@@ -0,0 +1,12 @@
+//! Testing: the parameters of functions.
+
+#[cfg(not(feature = "function_parameter_type_changed"))]
+pub fn parameter_type_will_change<T>(items: &[T], count: u8) {
+    let _ = (items, count);
+}
+
+// Renaming the generic parameter doesn't change the type of `items`.
+#[cfg(feature = "function_parameter_type_changed")]
+pub fn parameter_type_will_change<U>(items: &[U], count: u16) {
+    let _ = (items, count);
+}
@@ -0,0 +1,20 @@
+//! Testing: the generic parameters of items.
+
+use std::marker::PhantomData;
+
+pub struct TypeParameterWillBeRemoved<
+    'a,
+    #[cfg(not(feature = "generic_parameter_missing"))] T: Clone + 'a = u8,
+> {
+    pub lifetime: PhantomData<&'a ()>,
+
+    #[cfg(not(feature = "generic_parameter_missing"))]
+    pub value: PhantomData<T>,
+}
+
+pub struct ConstParameterWillBeRemoved<
+    T,
+    #[cfg(not(feature = "generic_parameter_missing"))] const N: usize = 4,
+> {
+    pub value: T,
+}
@@ -0,0 +1,15 @@
+//! Testing: the `inherent_impl` and `trait_impl` edges of types.
+
+pub trait ImplementedMarker {}
+
+pub struct ImplsWillChange;
+
+impl ImplsWillChange {
+    pub fn stays() {}
+
+    #[cfg(not(feature = "inherent_method_missing"))]
+    pub fn will_be_removed() {}
+}
+
+#[cfg(not(feature = "trait_impl_missing"))]
+impl ImplementedMarker for ImplsWillChange {}
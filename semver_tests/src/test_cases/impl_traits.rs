@@ -0,0 +1,11 @@
+//! Testing: the `impl Trait` types in the signatures of functions.
+
+#[cfg(not(feature = "return_impl_trait_changed"))]
+pub fn return_impl_trait_will_change() -> impl Iterator<Item = u8> + Send {
+    std::iter::empty()
+}
+
+#[cfg(feature = "return_impl_trait_changed")]
+pub fn return_impl_trait_will_change() -> impl Iterator<Item = u8> {
+    std::iter::empty()
+}
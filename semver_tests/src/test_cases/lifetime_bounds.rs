@@ -0,0 +1,28 @@
+//! Testing: the bounds that lifetimes and types outlive a lifetime.
+
+use std::marker::PhantomData;
+
+#[cfg(not(feature = "lifetime_bound_added"))]
+pub struct ParameterLifetimeBoundWillBeAdded<'a, 'b> {
+    pub first: &'a (),
+    pub second: &'b (),
+}
+
+#[cfg(feature = "lifetime_bound_added")]
+pub struct ParameterLifetimeBoundWillBeAdded<'a, 'b: 'a> {
+    pub first: &'a (),
+    pub second: &'b (),
+}
+
+#[cfg(not(feature = "lifetime_bound_added"))]
+pub struct WhereClauseLifetimeBoundWillBeAdded<T> {
+    pub value: PhantomData<T>,
+}
+
+#[cfg(feature = "lifetime_bound_added")]
+pub struct WhereClauseLifetimeBoundWillBeAdded<T>
+where
+    T: 'static,
+{
+    pub value: PhantomData<T>,
+}
@@ -0,0 +1,7 @@
+//! Testing: macros defined with `macro_rules!`.
+
+#[cfg(not(feature = "macro_missing"))]
+#[macro_export]
+macro_rules! will_be_removed_macro {
+    () => {};
+}
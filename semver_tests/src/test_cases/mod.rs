@@ -4,3 +4,15 @@ pub mod enum_variant_missing;
 pub mod enum_variant_added;
 pub mod unit_struct_changed_kind;
 pub mod item_missing;
+pub mod advisory;
+pub mod impl_edges;
+pub mod macros;
+pub mod other_items;
+pub mod variant_fields;
+pub mod trait_items;
+pub mod generic_parameters;
+pub mod trait_objects;
+pub mod function_parameters;
+pub mod where_predicates;
+pub mod impl_traits;
+pub mod lifetime_bounds;
@@ -0,0 +1,16 @@
+//! Testing: unions, statics, constants, and type aliases.
+
+#[cfg(not(feature = "union_missing"))]
+pub union WillBeRemovedUnion {
+    pub int: u32,
+    pub float: f32,
+}
+
+#[cfg(not(feature = "static_missing"))]
+pub static WILL_BE_REMOVED_STATIC: u32 = 0;
+
+#[cfg(not(feature = "constant_missing"))]
+pub const WILL_BE_REMOVED_CONSTANT: u32 = 0;
+
+#[cfg(not(feature = "type_alias_missing"))]
+pub type WillBeRemovedTypeAlias = u32;
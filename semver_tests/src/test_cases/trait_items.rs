@@ -0,0 +1,14 @@
+//! Testing: the associated items of traits.
+
+pub trait TraitItemsWillBeRemoved {
+    fn stays(&self);
+
+    #[cfg(not(feature = "trait_method_missing"))]
+    fn will_be_removed(&self) {}
+
+    #[cfg(not(feature = "trait_associated_type_missing"))]
+    type WillBeRemoved: Clone;
+
+    #[cfg(not(feature = "trait_associated_constant_missing"))]
+    const WILL_BE_REMOVED: u8 = 0;
+}
@@ -0,0 +1,17 @@
+//! Testing: the `dyn Trait` types in the types of fields.
+
+use std::fmt::Debug;
+
+pub struct TraitObjectAutoTraitsWillChange<'a> {
+    #[cfg(not(feature = "trait_object_auto_traits_changed"))]
+    pub loses_auto_trait: Box<dyn Iterator<Item = u8> + Send + Sync + 'a>,
+
+    #[cfg(feature = "trait_object_auto_traits_changed")]
+    pub loses_auto_trait: Box<dyn Iterator<Item = u8> + Sync + 'a>,
+
+    #[cfg(not(feature = "trait_object_auto_traits_changed"))]
+    pub gains_auto_trait: Box<dyn Debug>,
+
+    #[cfg(feature = "trait_object_auto_traits_changed")]
+    pub gains_auto_trait: Box<dyn Debug + Send>,
+}
@@ -0,0 +1,10 @@
+//! Testing: the fields of enum variants.
+
+pub enum VariantFieldWillBeRemoved {
+    Struct {
+        stays: u8,
+
+        #[cfg(not(feature = "variant_field_missing"))]
+        will_be_removed: u8,
+    },
+}
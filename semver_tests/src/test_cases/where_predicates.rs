@@ -0,0 +1,20 @@
+//! Testing: the where clauses of items.
+
+#[cfg(not(feature = "where_predicate_added"))]
+pub fn where_predicate_will_be_added<T, U>(value: T, other: U) -> T
+where
+    T: Clone,
+{
+    let _ = other;
+    value
+}
+
+#[cfg(feature = "where_predicate_added")]
+pub fn where_predicate_will_be_added<T, U>(value: T, other: U) -> T
+where
+    T: Clone,
+    U: Send + 'static,
+{
+    let _ = other;
+    value
+}
@@ -1,27 +1,256 @@
-use std::sync::Arc;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, OnceLock,
+    },
+};
 
-use rustdoc_types::{Crate, Enum, Function, Item, Method, Span, Struct, Type, Variant};
+use rustdoc_types::{
+    Constant, Crate, Enum, FnDecl, Function, GenericBound, GenericParamDef, GenericParamDefKind,
+    Generics, Header, Id, Impl, Item, ItemKind, MacroKind, Method, ProcMacro, Span, Static, Struct,
+    Trait, Type, Typedef, Union, Variant, WherePredicate,
+};
 use trustfall_core::{
     interpreter::{Adapter, DataContext, InterpretedQuery},
     ir::{EdgeParameters, Eid, FieldValue, Vid},
     schema::Schema,
 };
 
+use crate::{
+    changed_items::UnchangedItems,
+    importable_paths::{
+        externally_visible_items, importable_path_index, reexported_items, CrateGraph, CrateRef,
+        ImportablePath, ImportablePathIndex, ItemKey,
+    },
+    renames::Renames,
+    type_display::{
+        bounds_to_string, lifetime_bounds, nested_types, signature_to_string, type_to_string,
+        where_predicate_parts, where_predicate_to_string, LifetimeBound, OutlivesSubject,
+    },
+    type_normalize::{normalized_bound_strings, normalized_lifetime, normalized_type_string},
+};
+
+/// The schema of the rustdoc data that queries can use, in GraphQL SDL.
+pub const SCHEMA_TEXT: &str = include_str!("rustdoc_schema.graphql");
+
+/// The version of the schema, increased whenever it changes. Queries declare the version
+/// they need, so those written for a newer schema are rejected with a clear error.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// The oldest schema version whose queries are still valid, increased whenever a field
+/// or type is removed or changed, rather than added.
+pub const MIN_SUPPORTED_SCHEMA_VERSION: u32 = 1;
+
+/// Clones of the adapter share its indexes, so checks running on several threads at once
+/// can each borrow their own clone mutably, as the query engine needs, without building
+/// the indexes again.
+#[derive(Clone)]
 pub struct RustdocAdapter<'a> {
     current_crate: &'a Crate,
     previous_crate: Option<&'a Crate>,
+    current_dependencies: &'a [Crate],
+    previous_dependencies: &'a [Crate],
+    /// Items that are the same in both crates, which aren't queried, if any.
+    unchanged: Option<&'a UnchangedItems<'a>>,
+    indexes: Arc<Indexes<'a>>,
+}
+
+/// What answering some queries takes walking every item of the crates to find.
+/// Each is built the first time a query needs it, then reused by every later query,
+/// including those that clones of the adapter run on other threads.
+#[derive(Default)]
+struct Indexes<'a> {
+    current: CrateIndexes<'a>,
+    previous: CrateIndexes<'a>,
+    renames: Cached<Renames<'a>>,
+}
+
+#[derive(Default)]
+struct CrateIndexes<'a> {
+    visible_items: Cached<HashSet<ItemKey<'a>>>,
+    importable_paths: Cached<ImportablePathIndex<'a>>,
+    reexported_items: Cached<Vec<(&'a Crate, &'a Item)>>,
+    items_by_name: Cached<ItemsByName<'a>>,
+    impls_by_type: Cached<ImplsByType<'a>>,
+}
+
+/// An index that's built the first time it's used, counting how often it's used.
+struct Cached<T> {
+    value: OnceLock<Arc<T>>,
+    uses: AtomicUsize,
+}
+
+impl<T> Default for Cached<T> {
+    fn default() -> Self {
+        Self {
+            value: OnceLock::new(),
+            uses: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl<T> Cached<T> {
+    fn get_or_init(&self, build: impl FnOnce() -> T) -> Arc<T> {
+        self.uses.fetch_add(1, Ordering::Relaxed);
+        self.value.get_or_init(|| Arc::new(build())).clone()
+    }
+
+    fn usage(&self) -> IndexUsage {
+        let uses = self.uses.load(Ordering::Relaxed);
+        IndexUsage {
+            uses,
+            hits: uses.saturating_sub(usize::from(self.value.get().is_some())),
+        }
+    }
 }
 
+/// How many times queries used one of the adapter's indexes,
+/// and how many of those found it already built.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct IndexUsage {
+    pub(crate) uses: usize,
+    pub(crate) hits: usize,
+}
+
+impl std::ops::AddAssign for IndexUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.uses += other.uses;
+        self.hits += other.hits;
+    }
+}
+
+/// The main crate's own items that queries can reach, by name, with unnamed items
+/// like impls under `None`. Queries find the items in this order, the same every run.
+type ItemsByName<'a> = BTreeMap<Option<&'a str>, Vec<&'a Item>>;
+
+/// The impls of each struct, enum, and union of the crates, by the type's crate and id.
+type ImplsByType<'a> = HashMap<ItemKey<'a>, Vec<&'a Item>>;
+
 impl<'a> RustdocAdapter<'a> {
     pub fn new(current_crate: &'a Crate, previous_crate: Option<&'a Crate>) -> Self {
         Self {
             current_crate,
             previous_crate,
+            current_dependencies: &[],
+            previous_dependencies: &[],
+            unchanged: None,
+            indexes: Arc::default(),
+        }
+    }
+
+    /// Leave out the items that are the same in both crates when querying the items of
+    /// either crate, since checks find nothing in them.
+    pub(crate) fn with_unchanged_items(mut self, unchanged: &'a UnchangedItems<'a>) -> Self {
+        self.unchanged = Some(unchanged);
+        self
+    }
+
+    /// Use the rustdoc JSON of the crates whose items the current and previous crates
+    /// re-export, so those items are queryable like the crates' own.
+    pub fn with_dependencies(
+        mut self,
+        current_dependencies: &'a [Crate],
+        previous_dependencies: &'a [Crate],
+    ) -> Self {
+        self.current_dependencies = current_dependencies;
+        self.previous_dependencies = previous_dependencies;
+        // The dependencies' items are part of every index.
+        self.indexes = Arc::default();
+        self
+    }
+
+    /// The crate being queried on the given side of the comparison,
+    /// and the crates whose re-exported items are queried with it.
+    fn crate_graph(&self, origin: Origin) -> CrateGraph<'a> {
+        match origin {
+            Origin::CurrentCrate => CrateGraph {
+                main: self.current_crate,
+                dependencies: self.current_dependencies,
+            },
+            Origin::PreviousCrate => CrateGraph {
+                main: self.previous_crate.expect("no previous crate provided"),
+                dependencies: self.previous_dependencies,
+            },
+        }
+    }
+
+    fn crate_indexes(&self, origin: Origin) -> &CrateIndexes<'a> {
+        match origin {
+            Origin::CurrentCrate => &self.indexes.current,
+            Origin::PreviousCrate => &self.indexes.previous,
+        }
+    }
+
+    /// The items of the crate on the given side, and of its dependencies,
+    /// that users of the crate can reach.
+    fn visible_items(&self, origin: Origin) -> Arc<HashSet<ItemKey<'a>>> {
+        self.crate_indexes(origin)
+            .visible_items
+            .get_or_init(|| externally_visible_items(self.crate_graph(origin)))
+    }
+
+    /// The paths at which users of the crate on the given side can import its items,
+    /// and those of its dependencies.
+    fn importable_paths(&self, origin: Origin) -> Arc<ImportablePathIndex<'a>> {
+        self.crate_indexes(origin)
+            .importable_paths
+            .get_or_init(|| importable_path_index(self.crate_graph(origin)))
+    }
+
+    /// The items of the dependencies that the crate on the given side re-exports.
+    fn reexported_items(&self, origin: Origin) -> Arc<Vec<(&'a Crate, &'a Item)>> {
+        self.crate_indexes(origin)
+            .reexported_items
+            .get_or_init(|| reexported_items(self.crate_graph(origin)))
+    }
+
+    /// The queryable items of the crate on the given side, by name.
+    fn items_by_name(&self, origin: Origin) -> Arc<ItemsByName<'a>> {
+        self.crate_indexes(origin)
+            .items_by_name
+            .get_or_init(|| items_by_name(self.crate_graph(origin).main))
+    }
+
+    /// The impls of the types of the crate on the given side, and of its dependencies.
+    fn impls_by_type(&self, origin: Origin) -> Arc<ImplsByType<'a>> {
+        self.crate_indexes(origin)
+            .impls_by_type
+            .get_or_init(|| impls_by_type(self.crate_graph(origin)))
+    }
+
+    fn renames(&self) -> Arc<Renames<'a>> {
+        self.indexes
+            .renames
+            .get_or_init(|| match self.previous_crate {
+                Some(_) => Renames::between(
+                    self.crate_graph(Origin::PreviousCrate),
+                    self.crate_graph(Origin::CurrentCrate),
+                ),
+                None => Renames::default(),
+            })
+    }
+
+    /// How the queries run so far, by this adapter and its clones, used each index,
+    /// counting both sides of the comparison together.
+    pub(crate) fn index_usage(&self) -> BTreeMap<&'static str, IndexUsage> {
+        let mut usage = BTreeMap::from([("renames", self.indexes.renames.usage())]);
+        for indexes in [&self.indexes.current, &self.indexes.previous] {
+            for (name, index_usage) in [
+                ("visible_items", indexes.visible_items.usage()),
+                ("importable_paths", indexes.importable_paths.usage()),
+                ("reexported_items", indexes.reexported_items.usage()),
+                ("items_by_name", indexes.items_by_name.usage()),
+                ("impls_by_type", indexes.impls_by_type.usage()),
+            ] {
+                *usage.entry(name).or_default() += index_usage;
+            }
         }
+        usage
     }
 
     pub fn schema() -> Schema {
-        Schema::parse(include_str!("rustdoc_schema.graphql")).expect("schema not valid")
+        Schema::parse(SCHEMA_TEXT).expect("schema not valid")
     }
 }
 
@@ -32,38 +261,144 @@ pub enum Origin {
 }
 
 impl Origin {
-    fn make_item_token<'a>(&self, item: &'a Item) -> Token<'a> {
+    /// The name of the side of the comparison, as queries see it.
+    fn label(self) -> &'static str {
+        match self {
+            Origin::CurrentCrate => "current",
+            Origin::PreviousCrate => "baseline",
+        }
+    }
+}
+
+/// Where a token's data comes from: its side of the comparison, and the crate whose rustdoc
+/// JSON contains it, which is a dependency for items re-exported from other crates.
+#[derive(Debug, Clone, Copy)]
+pub struct TokenSource<'a> {
+    origin: Origin,
+    crate_: &'a Crate,
+}
+
+impl<'a> TokenSource<'a> {
+    fn make_item_token(&self, item: &'a Item) -> Token<'a> {
         Token {
-            origin: *self,
+            source: *self,
             kind: item.into(),
         }
     }
 
-    fn make_span_token<'a>(&self, span: &'a Span) -> Token<'a> {
+    fn make_span_token(&self, span: &'a Span) -> Token<'a> {
         Token {
-            origin: *self,
+            source: *self,
             kind: span.into(),
         }
     }
 
-    fn make_path_token<'a>(&self, path: &'a [String]) -> Token<'a> {
+    fn make_path_token(&self, path: ImportablePath<'a>) -> Token<'a> {
         Token {
-            origin: *self,
+            source: *self,
             kind: TokenKind::Path(path),
         }
     }
+
+    fn make_function_parameter_token(
+        &self,
+        position: usize,
+        parameter: &'a (String, Type),
+        generics: &'a Generics,
+    ) -> Token<'a> {
+        Token {
+            source: *self,
+            kind: TokenKind::FunctionParameter(position, parameter, generics),
+        }
+    }
+
+    fn make_generic_parameter_token(
+        &self,
+        position: usize,
+        parameter: &'a GenericParamDef,
+    ) -> Token<'a> {
+        Token {
+            source: *self,
+            kind: TokenKind::GenericParameter(position, parameter),
+        }
+    }
+
+    fn make_variant_field_token(
+        &self,
+        position: usize,
+        name: Option<&'a str>,
+        type_: &'a Type,
+    ) -> Token<'a> {
+        Token {
+            source: *self,
+            kind: TokenKind::VariantField(position, name, type_),
+        }
+    }
+
+    fn make_where_predicate_token(&self, predicate: &'a WherePredicate) -> Token<'a> {
+        Token {
+            source: *self,
+            kind: TokenKind::WherePredicate(predicate),
+        }
+    }
+
+    fn make_impl_trait_token(
+        &self,
+        position: usize,
+        type_: &'a Type,
+        generics: Option<&'a Generics>,
+    ) -> Token<'a> {
+        Token {
+            source: *self,
+            kind: TokenKind::ImplTrait(position, type_, generics),
+        }
+    }
+
+    fn make_trait_object_token(
+        &self,
+        position: usize,
+        type_: &'a Type,
+        generics: Option<&'a Generics>,
+    ) -> Token<'a> {
+        Token {
+            source: *self,
+            kind: TokenKind::TraitObject(position, type_, generics),
+        }
+    }
+
+    fn make_lifetime_bound_token(
+        &self,
+        bound: LifetimeBound<'a>,
+        generics: &'a Generics,
+    ) -> Token<'a> {
+        Token {
+            source: *self,
+            kind: TokenKind::LifetimeBound(bound, generics),
+        }
+    }
+
+    fn make_implemented_trait_token(
+        &self,
+        trait_: &'a Type,
+        path: Option<&'a [String]>,
+    ) -> Token<'a> {
+        Token {
+            source: *self,
+            kind: TokenKind::ImplementedTrait(trait_, path),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
 pub struct Token<'a> {
-    origin: Origin,
+    source: TokenSource<'a>,
     kind: TokenKind<'a>,
 }
 
 impl<'a> Token<'a> {
     fn new_crate(origin: Origin, crate_: &'a Crate) -> Self {
         Self {
-            origin,
+            source: TokenSource { origin, crate_ },
             kind: TokenKind::Crate(crate_),
         }
     }
@@ -75,7 +410,25 @@ pub enum TokenKind<'a> {
     Crate(&'a Crate),
     Item(&'a Item),
     Span(&'a Span),
-    Path(&'a [String]),
+    Path(ImportablePath<'a>),
+    /// A function's parameter: its position, its name and type, and the generics of its
+    /// function for normalizing its type.
+    FunctionParameter(usize, &'a (String, Type), &'a Generics),
+    /// A generic parameter of an item, and its position among the item's generic parameters.
+    GenericParameter(usize, &'a GenericParamDef),
+    /// A variant's field, with its name unless it's positional.
+    VariantField(usize, Option<&'a str>, &'a Type),
+    WherePredicate(&'a WherePredicate),
+    /// The trait implemented by an impl block, and its full path if rustdoc recorded it.
+    ImplementedTrait(&'a Type, Option<&'a [String]>),
+    /// An `impl Trait` type, its position among those in the type it's part of,
+    /// and the generics of its function for normalizing its bounds.
+    ImplTrait(usize, &'a Type, Option<&'a Generics>),
+    /// A `dyn Trait` type, its position among those in the type it's part of,
+    /// and the generics of its function, if any, for normalizing it.
+    TraitObject(usize, &'a Type, Option<&'a Generics>),
+    /// A lifetime bound of an item, with the item's generics for normalizing it.
+    LifetimeBound(LifetimeBound<'a>, &'a Generics),
 }
 
 #[allow(dead_code)]
@@ -94,10 +447,32 @@ impl<'a> Token<'a> {
                 rustdoc_types::ItemEnum::Variant(Variant::Tuple(..)) => "TupleVariant",
                 rustdoc_types::ItemEnum::Variant(Variant::Struct(..)) => "StructVariant",
                 rustdoc_types::ItemEnum::StructField(..) => "StructField",
+                rustdoc_types::ItemEnum::Impl(..) => "Impl",
+                rustdoc_types::ItemEnum::Trait(..) => "Trait",
+                rustdoc_types::ItemEnum::AssocType { .. } => "AssociatedType",
+                rustdoc_types::ItemEnum::AssocConst { .. } => "AssociatedConstant",
+                rustdoc_types::ItemEnum::Union(..) => "Union",
+                rustdoc_types::ItemEnum::Static(..) => "Static",
+                rustdoc_types::ItemEnum::Constant(..) => "Constant",
+                rustdoc_types::ItemEnum::Typedef(..) => "TypeAlias",
+                rustdoc_types::ItemEnum::Macro(..) => "Macro",
+                rustdoc_types::ItemEnum::ProcMacro(proc_macro) => match proc_macro.kind {
+                    MacroKind::Bang => "FunctionLikeProcMacro",
+                    MacroKind::Attr => "AttributeMacro",
+                    MacroKind::Derive => "DeriveMacro",
+                },
                 _ => unreachable!("unexpected item.inner for item: {item:?}"),
             },
             TokenKind::Span(..) => "Span",
             TokenKind::Path(..) => "Path",
+            TokenKind::FunctionParameter(..) => "FunctionParameter",
+            TokenKind::GenericParameter(..) => "GenericParameter",
+            TokenKind::VariantField(..) => "VariantField",
+            TokenKind::WherePredicate(..) => "WherePredicate",
+            TokenKind::ImplementedTrait(..) => "ImplementedTrait",
+            TokenKind::ImplTrait(..) => "ImplTrait",
+            TokenKind::TraitObject(..) => "TraitObject",
+            TokenKind::LifetimeBound(..) => "LifetimeBound",
             TokenKind::Crate(..) => "Crate",
             TokenKind::CrateDiff(..) => "CrateDiff",
         }
@@ -159,9 +534,9 @@ impl<'a> Token<'a> {
         })
     }
 
-    fn as_path(&self) -> Option<&'a [String]> {
+    fn as_path(&self) -> Option<&ImportablePath<'a>> {
         match &self.kind {
-            TokenKind::Path(path) => Some(*path),
+            TokenKind::Path(path) => Some(path),
             _ => None,
         }
     }
@@ -179,6 +554,165 @@ impl<'a> Token<'a> {
             _ => None,
         })
     }
+
+    fn as_trait(&self) -> Option<&'a Trait> {
+        self.as_item().and_then(|item| match &item.inner {
+            rustdoc_types::ItemEnum::Trait(t) => Some(t),
+            _ => None,
+        })
+    }
+
+    fn as_union(&self) -> Option<&'a Union> {
+        self.as_item().and_then(|item| match &item.inner {
+            rustdoc_types::ItemEnum::Union(u) => Some(u),
+            _ => None,
+        })
+    }
+
+    fn as_static(&self) -> Option<&'a Static> {
+        self.as_item().and_then(|item| match &item.inner {
+            rustdoc_types::ItemEnum::Static(s) => Some(s),
+            _ => None,
+        })
+    }
+
+    fn as_constant(&self) -> Option<&'a Constant> {
+        self.as_item().and_then(|item| match &item.inner {
+            rustdoc_types::ItemEnum::Constant(c) => Some(c),
+            _ => None,
+        })
+    }
+
+    fn as_type_alias(&self) -> Option<&'a Typedef> {
+        self.as_item().and_then(|item| match &item.inner {
+            rustdoc_types::ItemEnum::Typedef(t) => Some(t),
+            _ => None,
+        })
+    }
+
+    fn as_proc_macro(&self) -> Option<&'a ProcMacro> {
+        self.as_item().and_then(|item| match &item.inner {
+            rustdoc_types::ItemEnum::ProcMacro(proc_macro) => Some(proc_macro),
+            _ => None,
+        })
+    }
+
+    fn as_impl(&self) -> Option<&'a Impl> {
+        self.as_item().and_then(|item| match &item.inner {
+            rustdoc_types::ItemEnum::Impl(impl_) => Some(impl_),
+            _ => None,
+        })
+    }
+
+    /// The impl blocks of a struct or enum.
+    fn as_impl_owner(&self) -> Option<&'a [Id]> {
+        self.as_item().and_then(|item| match &item.inner {
+            rustdoc_types::ItemEnum::Struct(s) => Some(s.impls.as_slice()),
+            rustdoc_types::ItemEnum::Enum(e) => Some(e.impls.as_slice()),
+            rustdoc_types::ItemEnum::Union(u) => Some(u.impls.as_slice()),
+            _ => None,
+        })
+    }
+
+    /// The fields of a struct or union.
+    fn as_field_owner(&self) -> Option<&'a [Id]> {
+        self.as_item().and_then(|item| match &item.inner {
+            rustdoc_types::ItemEnum::Struct(s) => Some(s.fields.as_slice()),
+            rustdoc_types::ItemEnum::Union(u) => Some(u.fields.as_slice()),
+            _ => None,
+        })
+    }
+
+    fn as_function_like(&self) -> Option<(&'a Header, &'a FnDecl)> {
+        self.as_function()
+            .map(|func| (&func.header, &func.decl))
+            .or_else(|| {
+                self.as_method()
+                    .map(|method| (&method.header, &method.decl))
+            })
+    }
+
+    fn as_function_parameter(&self) -> Option<(usize, &'a (String, Type), &'a Generics)> {
+        match self.kind {
+            TokenKind::FunctionParameter(position, parameter, generics) => {
+                Some((position, parameter, generics))
+            }
+            _ => None,
+        }
+    }
+
+    fn as_generic_parameter(&self) -> Option<(usize, &'a GenericParamDef)> {
+        match self.kind {
+            TokenKind::GenericParameter(position, parameter) => Some((position, parameter)),
+            _ => None,
+        }
+    }
+
+    fn as_variant_field(&self) -> Option<(usize, Option<&'a str>, &'a Type)> {
+        match self.kind {
+            TokenKind::VariantField(position, name, type_) => Some((position, name, type_)),
+            _ => None,
+        }
+    }
+
+    fn as_where_predicate(&self) -> Option<&'a WherePredicate> {
+        match self.kind {
+            TokenKind::WherePredicate(predicate) => Some(predicate),
+            _ => None,
+        }
+    }
+
+    fn as_implemented_trait(&self) -> Option<(&'a Type, Option<&'a [String]>)> {
+        match self.kind {
+            TokenKind::ImplementedTrait(trait_, path) => Some((trait_, path)),
+            _ => None,
+        }
+    }
+
+    fn as_impl_trait(&self) -> Option<(usize, &'a Type, Option<&'a Generics>)> {
+        match self.kind {
+            TokenKind::ImplTrait(position, type_, generics) => Some((position, type_, generics)),
+            _ => None,
+        }
+    }
+
+    fn as_trait_object(&self) -> Option<(usize, &'a Type, Option<&'a Generics>)> {
+        match self.kind {
+            TokenKind::TraitObject(position, type_, generics) => Some((position, type_, generics)),
+            _ => None,
+        }
+    }
+
+    fn as_lifetime_bound(&self) -> Option<(&LifetimeBound<'a>, &'a Generics)> {
+        match &self.kind {
+            TokenKind::LifetimeBound(bound, generics) => Some((bound, generics)),
+            _ => None,
+        }
+    }
+
+    /// The type of a parameter or field, with the generics of its function, if any.
+    fn as_typed_value(&self) -> Option<(&'a Type, Option<&'a Generics>)> {
+        match self.kind {
+            TokenKind::FunctionParameter(_, (_, type_), generics) => Some((type_, Some(generics))),
+            TokenKind::VariantField(_, _, type_) => Some((type_, None)),
+            _ => self.as_struct_field_item().map(|(_, type_)| (type_, None)),
+        }
+    }
+
+    /// The generics of an item that can have generic parameters.
+    fn as_generics(&self) -> Option<&'a Generics> {
+        self.as_item().and_then(|item| match &item.inner {
+            rustdoc_types::ItemEnum::Struct(s) => Some(&s.generics),
+            rustdoc_types::ItemEnum::Enum(e) => Some(&e.generics),
+            rustdoc_types::ItemEnum::Union(u) => Some(&u.generics),
+            rustdoc_types::ItemEnum::Typedef(t) => Some(&t.generics),
+            rustdoc_types::ItemEnum::Function(func) => Some(&func.generics),
+            rustdoc_types::ItemEnum::Method(method) => Some(&method.generics),
+            rustdoc_types::ItemEnum::Impl(impl_) => Some(&impl_.generics),
+            rustdoc_types::ItemEnum::Trait(trait_) => Some(&trait_.generics),
+            _ => None,
+        })
+    }
 }
 
 impl<'a> From<&'a Item> for TokenKind<'a> {
@@ -217,6 +751,12 @@ fn get_item_property(item_token: &Token, field_name: &str) -> FieldValue {
         "crate_id" => (&item.crate_id).into(),
         "name" => (&item.name).into(),
         "docs" => (&item.docs).into(),
+        "has_docs" => item
+            .docs
+            .as_deref()
+            .is_some_and(|docs| !docs.trim().is_empty())
+            .into(),
+        "doc_summary" => doc_summary(item.docs.as_deref()).into(),
         "attrs" => item.attrs.clone().into(),
         "visibility_limit" => match &item.visibility {
             rustdoc_types::Visibility::Public => "public".into(),
@@ -226,10 +766,236 @@ fn get_item_property(item_token: &Token, field_name: &str) -> FieldValue {
                 format!("restricted ({path})").into()
             }
         },
+        "visibility" => match &item.visibility {
+            rustdoc_types::Visibility::Public => "public",
+            rustdoc_types::Visibility::Default => "default",
+            rustdoc_types::Visibility::Crate => "crate",
+            rustdoc_types::Visibility::Restricted { .. } => "restricted",
+        }
+        .into(),
+        "visibility_restricted_to" => match &item.visibility {
+            rustdoc_types::Visibility::Restricted { parent: _, path } => path.into(),
+            _ => FieldValue::Null,
+        },
+        "span_filename" => match &item.span {
+            Some(span) => span
+                .filename
+                .to_str()
+                .expect("non-representable path")
+                .into(),
+            None => FieldValue::Null,
+        },
+        "span_begin_line" => match &item.span {
+            Some(span) => (span.begin.0 as u64).into(),
+            None => FieldValue::Null,
+        },
+        "non_exhaustive" => has_attribute(&item.attrs, "non_exhaustive").into(),
+        "doc_hidden" => is_doc_hidden(&item.attrs).into(),
+        "deprecated" => item.deprecation.is_some().into(),
+        "deprecated_since" => item
+            .deprecation
+            .as_ref()
+            .and_then(|deprecation| deprecation.since.as_ref())
+            .into(),
+        "deprecation_note" => item
+            .deprecation
+            .as_ref()
+            .and_then(|deprecation| deprecation.note.as_ref())
+            .into(),
+        "cfg" => cfg_predicates(&item.attrs).into(),
+        "cfg_features" => cfg_features(&item.attrs).into(),
+        "must_use" => has_attribute(&item.attrs, "must_use").into(),
         _ => unreachable!("Item property {field_name}"),
     }
 }
 
+/// The first paragraph of the doc comments, with its lines joined by spaces,
+/// or `None` if the item has no doc comments with any text.
+pub(crate) fn doc_summary(docs: Option<&str>) -> Option<String> {
+    let summary: Vec<&str> = docs?
+        .lines()
+        .map(str::trim)
+        .skip_while(|line| line.is_empty())
+        .take_while(|line| !line.is_empty())
+        .collect();
+    (!summary.is_empty()).then(|| summary.join(" "))
+}
+
+/// Whether one of the attributes is `#[name]`, possibly with arguments like `#[name = "..."]`.
+fn has_attribute(attrs: &[String], name: &str) -> bool {
+    attrs.iter().any(|attr| {
+        attr.strip_prefix("#[")
+            .and_then(|attr| attr.trim_start().strip_prefix(name))
+            .is_some_and(|rest| rest.trim_start().starts_with([']', '(', '=']))
+    })
+}
+
+/// Whether the item is hidden from documentation with `#[doc(hidden)]`,
+/// possibly alongside other `doc` arguments.
+pub(crate) fn is_doc_hidden(attrs: &[String]) -> bool {
+    attrs.iter().any(|attr| {
+        let attr: String = attr.chars().filter(|c| !c.is_whitespace()).collect();
+        attr.strip_prefix("#[doc(")
+            .and_then(|attr| attr.strip_suffix(")]"))
+            .is_some_and(|args| args.split(',').any(|arg| arg == "hidden"))
+    })
+}
+
+/// The predicates of `#[cfg(...)]` attributes and of `cfg(...)` arguments in `#[doc(...)]`
+/// attributes, like `feature = "serde"` in `#[doc(cfg(feature = "serde"))]`.
+fn cfg_predicates(attrs: &[String]) -> Vec<String> {
+    let mut predicates = vec![];
+    for attr in attrs {
+        let attr = match attr.trim().strip_prefix("#[") {
+            Some(attr) => attr.trim_start(),
+            None => continue,
+        };
+        if let Some(args) = attr.strip_prefix("cfg(").and_then(balanced_prefix) {
+            predicates.push(args.trim().to_string());
+        } else if let Some(args) = attr.strip_prefix("doc(").and_then(balanced_prefix) {
+            predicates.extend(
+                split_top_level(args)
+                    .into_iter()
+                    .filter_map(|arg| arg.trim().strip_prefix("cfg("))
+                    .filter_map(balanced_prefix)
+                    .map(|predicate| predicate.trim().to_string()),
+            );
+        }
+    }
+    predicates
+}
+
+/// The cargo features named by `feature = "..."` in the item's cfg predicates.
+fn cfg_features(attrs: &[String]) -> Vec<String> {
+    let mut features = vec![];
+    for predicate in cfg_predicates(attrs) {
+        let mut rest = predicate.as_str();
+        while let Some(start) = rest.find("feature") {
+            // Skip names that merely end in "feature", like `target_feature`.
+            let is_whole_word = !rest[..start].ends_with(|c: char| c.is_alphanumeric() || c == '_');
+            rest = &rest[start + "feature".len()..];
+            let name = rest
+                .trim_start()
+                .strip_prefix('=')
+                .and_then(|value| value.trim_start().strip_prefix('"'))
+                .and_then(|value| value.split_once('"'))
+                .map(|(name, _)| name);
+            if let (true, Some(name)) = (is_whole_word, name) {
+                features.push(name.to_string());
+            }
+        }
+    }
+    features.sort();
+    features.dedup();
+    features
+}
+
+/// The hints of `#[repr(...)]` attributes, like `C` and `packed(2)` in `#[repr(C, packed(2))]`.
+fn repr_hints(attrs: &[String]) -> Vec<String> {
+    attrs
+        .iter()
+        .filter_map(|attr| attr.trim().strip_prefix("#["))
+        .filter_map(|attr| attr.trim_start().strip_prefix("repr"))
+        .filter_map(|attr| attr.trim_start().strip_prefix('('))
+        .filter_map(balanced_prefix)
+        .flat_map(split_top_level)
+        .map(|hint| hint.split_whitespace().collect())
+        .filter(|hint: &String| !hint.is_empty())
+        .collect()
+}
+
+/// The layout that a type's `#[repr(...)]` attributes ask for.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Repr {
+    c: bool,
+    transparent: bool,
+    packed: Option<u64>,
+    align: Option<u64>,
+    int: Option<String>,
+}
+
+impl Repr {
+    fn from_hints(hints: &[String]) -> Self {
+        let mut repr = Self::default();
+        for hint in hints {
+            let argument = |name: &str| {
+                hint.strip_prefix(name)
+                    .and_then(|rest| rest.strip_prefix('('))
+                    .and_then(|rest| rest.strip_suffix(')'))
+                    .and_then(|value| value.parse::<u64>().ok())
+            };
+            match hint.as_str() {
+                "C" => repr.c = true,
+                "transparent" => repr.transparent = true,
+                // Packing to the smallest alignment, and aligning to the largest, wins.
+                "packed" => repr.packed = Some(1),
+                "u8" | "u16" | "u32" | "u64" | "u128" | "usize" | "i8" | "i16" | "i32" | "i64"
+                | "i128" | "isize" => repr.int = Some(hint.clone()),
+                _ => {
+                    if let Some(packed) = argument("packed") {
+                        repr.packed = Some(repr.packed.map_or(packed, |n| n.min(packed)));
+                    } else if let Some(align) = argument("align") {
+                        repr.align = Some(repr.align.map_or(align, |n| n.max(align)));
+                    }
+                }
+            }
+        }
+        repr
+    }
+}
+
+/// The text up to the parenthesis closing one that was opened just before `text`.
+fn balanced_prefix(text: &str) -> Option<&str> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+    for (index, c) in text.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '(' => depth += 1,
+            ')' if depth == 0 => return Some(&text[..index]),
+            ')' => depth -= 1,
+            _ => {}
+        }
+    }
+    None
+}
+
+/// The comma-separated arguments in `text`, ignoring commas within nested parentheses.
+fn split_top_level(text: &str) -> Vec<&str> {
+    let mut args = vec![];
+    let mut rest = text;
+    while !rest.is_empty() {
+        let mut depth = 0usize;
+        let mut in_string = false;
+        let mut end = rest.len();
+        for (index, c) in rest.char_indices() {
+            match c {
+                '"' => in_string = !in_string,
+                '(' if !in_string => depth += 1,
+                ')' if !in_string => depth = depth.saturating_sub(1),
+                ',' if !in_string && depth == 0 => {
+                    end = index;
+                    break;
+                }
+                _ => {}
+            }
+        }
+        args.push(&rest[..end]);
+        rest = rest.get(end + 1..).unwrap_or_default();
+    }
+    args
+}
+
 fn get_struct_property(item_token: &Token, field_name: &str) -> FieldValue {
     let (_, struct_item) = item_token.as_struct_item().expect("token was not a Struct");
     match field_name {
@@ -244,22 +1010,285 @@ fn get_struct_property(item_token: &Token, field_name: &str) -> FieldValue {
     }
 }
 
-fn get_span_property(item_token: &Token, field_name: &str) -> FieldValue {
-    let span = item_token.as_span().expect("token was not a Span");
+fn get_repr_property(item_token: &Token, field_name: &str) -> FieldValue {
+    let item = item_token.as_item().expect("token was not an Item");
+    let hints = repr_hints(&item.attrs);
+    if field_name == "repr" {
+        return hints.into();
+    }
+    let repr = Repr::from_hints(&hints);
     match field_name {
-        "filename" => span
-            .filename
-            .to_str()
-            .expect("non-representable path")
-            .into(),
-        "begin_line" => (span.begin.0 as u64).into(),
-        "begin_column" => (span.begin.1 as u64).into(),
+        "repr_c" => repr.c.into(),
+        "repr_transparent" => repr.transparent.into(),
+        "repr_packed" => repr.packed.into(),
+        "repr_align" => repr.align.into(),
+        "repr_int" => repr.int.into(),
+        _ => unreachable!("repr property {field_name}"),
+    }
+}
+
+fn get_union_property(item_token: &Token, field_name: &str) -> FieldValue {
+    let union_item = item_token.as_union().expect("token was not a Union");
+    match field_name {
+        "fields_stripped" => union_item.fields_stripped.into(),
+        _ => unreachable!("Union property {field_name}"),
+    }
+}
+
+fn get_static_property(item_token: &Token, field_name: &str) -> FieldValue {
+    let static_item = item_token.as_static().expect("token was not a Static");
+    match field_name {
+        "type" => type_to_string(&static_item.type_).into(),
+        "type_normalized" => {
+            normalized_type_string(item_token.source.crate_, None, &static_item.type_).into()
+        }
+        "mutable" => static_item.mutable.into(),
+        _ => unreachable!("Static property {field_name}"),
+    }
+}
+
+fn get_constant_property(item_token: &Token, field_name: &str) -> FieldValue {
+    let constant = item_token.as_constant().expect("token was not a Constant");
+    match field_name {
+        "type" => type_to_string(&constant.type_).into(),
+        "type_normalized" => {
+            normalized_type_string(item_token.source.crate_, None, &constant.type_).into()
+        }
+        "expr" => (&constant.expr).into(),
+        "value" => constant.value.clone().into(),
+        "is_literal" => constant.is_literal.into(),
+        _ => unreachable!("Constant property {field_name}"),
+    }
+}
+
+fn get_type_alias_property(item_token: &Token, field_name: &str) -> FieldValue {
+    let alias = item_token
+        .as_type_alias()
+        .expect("token was not a TypeAlias");
+    match field_name {
+        "type" => type_to_string(&alias.type_).into(),
+        "type_normalized" => normalized_type_string(
+            item_token.source.crate_,
+            Some(&alias.generics),
+            &alias.type_,
+        )
+        .into(),
+        _ => unreachable!("TypeAlias property {field_name}"),
+    }
+}
+
+fn get_method_property(item_token: &Token, field_name: &str) -> FieldValue {
+    let method = item_token.as_method().expect("token was not a Method");
+    match field_name {
+        "has_body" => method.has_body.into(),
+        _ => unreachable!("Method property {field_name}"),
+    }
+}
+
+fn get_trait_property(item_token: &Token, field_name: &str) -> FieldValue {
+    let trait_ = item_token.as_trait().expect("token was not a Trait");
+    match field_name {
+        "unsafe" => trait_.is_unsafe.into(),
+        "auto" => trait_.is_auto.into(),
+        "bounds" => trait_
+            .bounds
+            .iter()
+            .map(|bound| bounds_to_string(std::slice::from_ref(bound)))
+            .collect::<Vec<_>>()
+            .into(),
+        _ => unreachable!("Trait property {field_name}"),
+    }
+}
+
+fn get_associated_type_property(item_token: &Token, field_name: &str) -> FieldValue {
+    let item = item_token
+        .as_item()
+        .expect("token was not an AssociatedType");
+    let (bounds, default) = match &item.inner {
+        rustdoc_types::ItemEnum::AssocType {
+            bounds, default, ..
+        } => (bounds, default),
+        _ => unreachable!("token was not an AssociatedType: {item_token:?}"),
+    };
+    match field_name {
+        "has_default" => default.is_some().into(),
+        "default" => default.as_ref().map(type_to_string).into(),
+        "bounds" => bounds
+            .iter()
+            .map(|bound| bounds_to_string(std::slice::from_ref(bound)))
+            .collect::<Vec<_>>()
+            .into(),
+        _ => unreachable!("AssociatedType property {field_name}"),
+    }
+}
+
+fn get_associated_constant_property(item_token: &Token, field_name: &str) -> FieldValue {
+    let item = item_token
+        .as_item()
+        .expect("token was not an AssociatedConstant");
+    let (type_, default) = match &item.inner {
+        rustdoc_types::ItemEnum::AssocConst { type_, default } => (type_, default),
+        _ => unreachable!("token was not an AssociatedConstant: {item_token:?}"),
+    };
+    match field_name {
+        "type" => type_to_string(type_).into(),
+        "has_default" => default.is_some().into(),
+        "default" => default.clone().into(),
+        _ => unreachable!("AssociatedConstant property {field_name}"),
+    }
+}
+
+fn get_impl_property(item_token: &Token, field_name: &str) -> FieldValue {
+    let impl_ = item_token.as_impl().expect("token was not an Impl");
+    match field_name {
+        "unsafe" => impl_.is_unsafe.into(),
+        "negative" => impl_.negative.into(),
+        "synthetic" => impl_.synthetic.into(),
+        "blanket" => impl_.blanket_impl.is_some().into(),
+        _ => unreachable!("Impl property {field_name}"),
+    }
+}
+
+fn get_implemented_trait_property(token: &Token, field_name: &str) -> FieldValue {
+    let (trait_, path) = token
+        .as_implemented_trait()
+        .expect("token was not an ImplementedTrait");
+    match field_name {
+        "name" => match trait_ {
+            Type::ResolvedPath { name, .. } => name.into(),
+            _ => type_to_string(trait_).into(),
+        },
+        "type" => type_to_string(trait_).into(),
+        "path" => match path {
+            Some(path) => path.into(),
+            None => FieldValue::Null,
+        },
+        _ => unreachable!("ImplementedTrait property {field_name}"),
+    }
+}
+
+fn get_impl_trait_property(token: &Token, field_name: &str) -> FieldValue {
+    let (position, type_, generics) = token.as_impl_trait().expect("token was not an ImplTrait");
+    let bounds = match type_ {
+        Type::ImplTrait(bounds) => bounds,
+        _ => unreachable!("ImplTrait token for another type: {type_:?}"),
+    };
+    match field_name {
+        "position" => (position as u64).into(),
+        "type" => type_to_string(type_).into(),
+        "type_normalized" => normalized_type_string(token.source.crate_, generics, type_).into(),
+        "bounds" => bounds
+            .iter()
+            .map(|bound| bounds_to_string(std::slice::from_ref(bound)))
+            .collect::<Vec<_>>()
+            .into(),
+        "bounds_normalized" => {
+            normalized_bound_strings(token.source.crate_, generics, bounds).into()
+        }
+        _ => unreachable!("ImplTrait property {field_name}"),
+    }
+}
+
+/// The traits that are implemented automatically, which trait objects can add to their
+/// principal trait.
+const AUTO_TRAITS: [&str; 5] = ["Send", "Sync", "Unpin", "UnwindSafe", "RefUnwindSafe"];
+
+fn get_trait_object_property(token: &Token, field_name: &str) -> FieldValue {
+    let (position, type_, generics) = token
+        .as_trait_object()
+        .expect("token was not a TraitObject");
+    let (principal, bounds) = match type_ {
+        Type::ResolvedPath {
+            name,
+            id,
+            args,
+            param_names,
+        } => {
+            // The principal trait is the path, without the bounds that follow it.
+            let principal = Type::ResolvedPath {
+                name: name.clone(),
+                id: id.clone(),
+                args: args.clone(),
+                param_names: vec![],
+            };
+            (type_to_string(&principal), param_names)
+        }
+        _ => unreachable!("TraitObject token for another type: {type_:?}"),
+    };
+    let traits = std::iter::once(principal).chain(bounds.iter().filter_map(|bound| match bound {
+        GenericBound::TraitBound { .. } => Some(bounds_to_string(std::slice::from_ref(bound))),
+        GenericBound::Outlives(..) => None,
+    }));
+    let is_auto_trait =
+        |trait_: &String| AUTO_TRAITS.contains(&trait_.rsplit("::").next().unwrap_or_default());
+    match field_name {
+        "position" => (position as u64).into(),
+        "type" => with_dyn(type_to_string(type_)).into(),
+        "type_normalized" => {
+            with_dyn(normalized_type_string(token.source.crate_, generics, type_)).into()
+        }
+        "traits" => traits
+            .filter(|trait_| !is_auto_trait(trait_))
+            .collect::<Vec<_>>()
+            .into(),
+        "auto_traits" => {
+            let mut auto_traits: Vec<_> = traits.filter(is_auto_trait).collect();
+            auto_traits.sort();
+            auto_traits.into()
+        }
+        "lifetime" => bounds
+            .iter()
+            .find_map(|bound| match bound {
+                GenericBound::Outlives(lifetime) => Some(lifetime.into()),
+                GenericBound::TraitBound { .. } => None,
+            })
+            .unwrap_or(FieldValue::Null),
+        _ => unreachable!("TraitObject property {field_name}"),
+    }
+}
+
+/// The trait object type as written, since it's rendered without `dyn` when it has no bounds
+/// besides its principal trait, which rustdoc records like a path to a type.
+fn with_dyn(rendered: String) -> String {
+    if rendered.starts_with("dyn ") {
+        rendered
+    } else {
+        format!("dyn {rendered}")
+    }
+}
+
+fn get_span_property(item_token: &Token, field_name: &str) -> FieldValue {
+    let span = item_token.as_span().expect("token was not a Span");
+    match field_name {
+        "filename" => span
+            .filename
+            .to_str()
+            .expect("non-representable path")
+            .into(),
+        "begin_line" => (span.begin.0 as u64).into(),
+        "begin_column" => (span.begin.1 as u64).into(),
         "end_line" => (span.end.0 as u64).into(),
         "end_column" => (span.end.1 as u64).into(),
         _ => unreachable!("Span property {field_name}"),
     }
 }
 
+fn get_proc_macro_property(item_token: &Token, field_name: &str) -> FieldValue {
+    let proc_macro = item_token
+        .as_proc_macro()
+        .expect("token was not a ProcMacro");
+    match field_name {
+        "kind" => match proc_macro.kind {
+            MacroKind::Bang => "function_like",
+            MacroKind::Attr => "attribute",
+            MacroKind::Derive => "derive",
+        }
+        .into(),
+        "helper_attributes" => proc_macro.helpers.clone().into(),
+        _ => unreachable!("ProcMacro property {field_name}"),
+    }
+}
+
 fn get_enum_property(item_token: &Token, field_name: &str) -> FieldValue {
     let enum_item = item_token.as_enum().expect("token was not an Enum");
     match field_name {
@@ -271,32 +1300,280 @@ fn get_enum_property(item_token: &Token, field_name: &str) -> FieldValue {
 fn get_path_property(token: &Token, field_name: &str) -> FieldValue {
     let path_token = token.as_path().expect("token was not a Path");
     match field_name {
-        "path" => path_token.into(),
+        "path" => path_token.path.clone().into(),
+        "doc_hidden" => path_token.doc_hidden.into(),
         _ => unreachable!("Path property {field_name}"),
     }
 }
 
 fn get_function_like_property(token: &Token, field_name: &str) -> FieldValue {
-    let maybe_function = token.as_function();
-    let maybe_method = token.as_method();
-
-    let (header, _decl) = maybe_function
-        .map(|func| (&func.header, &func.decl))
-        .unwrap_or_else(|| {
-            let method = maybe_method.unwrap_or_else(|| {
-                unreachable!("token was neither a function nor a method: {token:?}")
-            });
-            (&method.header, &method.decl)
-        });
+    let (header, decl) = token
+        .as_function_like()
+        .unwrap_or_else(|| unreachable!("token was neither a function nor a method: {token:?}"));
 
     match field_name {
         "const" => header.const_.into(),
         "async" => header.async_.into(),
         "unsafe" => header.unsafe_.into(),
+        "return_type" => match &decl.output {
+            Some(output) => type_to_string(output).into(),
+            None => FieldValue::Null,
+        },
+        "return_type_normalized" => match &decl.output {
+            Some(output) => {
+                normalized_type_string(token.source.crate_, token.as_generics(), output).into()
+            }
+            None => FieldValue::Null,
+        },
+        "signature" => {
+            let item = token.as_item().expect("token was not an item");
+            signature_to_string(
+                item.name.as_deref().unwrap_or_default(),
+                header,
+                decl,
+                token
+                    .as_generics()
+                    .expect("function-like item has generics"),
+            )
+            .into()
+        }
         _ => unreachable!("FunctionLike property {field_name}"),
     }
 }
 
+fn get_function_parameter_property(token: &Token, field_name: &str) -> FieldValue {
+    let (position, (name, type_), generics) = token
+        .as_function_parameter()
+        .expect("token was not a FunctionParameter");
+    match field_name {
+        "name" => name.into(),
+        "type" => type_to_string(type_).into(),
+        "type_normalized" => {
+            normalized_type_string(token.source.crate_, Some(generics), type_).into()
+        }
+        "position" => (position as u64).into(),
+        _ => unreachable!("FunctionParameter property {field_name}"),
+    }
+}
+
+fn get_generic_parameter_property(token: &Token, field_name: &str) -> FieldValue {
+    let (position, parameter) = token
+        .as_generic_parameter()
+        .expect("token was not a GenericParameter");
+    match field_name {
+        "name" => (&parameter.name).into(),
+        "kind" => match parameter.kind {
+            GenericParamDefKind::Lifetime { .. } => "lifetime",
+            GenericParamDefKind::Type { .. } => "type",
+            GenericParamDefKind::Const { .. } => "const",
+        }
+        .into(),
+        "position" => (position as u64).into(),
+        "has_default" => match &parameter.kind {
+            GenericParamDefKind::Lifetime { .. } => false,
+            GenericParamDefKind::Type { default, .. } => default.is_some(),
+            GenericParamDefKind::Const { default, .. } => default.is_some(),
+        }
+        .into(),
+        "default" => match &parameter.kind {
+            GenericParamDefKind::Type {
+                default: Some(default),
+                ..
+            } => type_to_string(default).into(),
+            GenericParamDefKind::Const {
+                default: Some(default),
+                ..
+            } => default.into(),
+            _ => FieldValue::Null,
+        },
+        "bounds" => match &parameter.kind {
+            GenericParamDefKind::Lifetime { outlives } => outlives.clone().into(),
+            GenericParamDefKind::Type { bounds, .. } => bounds
+                .iter()
+                .map(|bound| bounds_to_string(std::slice::from_ref(bound)))
+                .collect::<Vec<_>>()
+                .into(),
+            GenericParamDefKind::Const { .. } => Vec::<String>::new().into(),
+        },
+        "const_type" => match &parameter.kind {
+            GenericParamDefKind::Const { type_, .. } => type_to_string(type_).into(),
+            _ => FieldValue::Null,
+        },
+        "synthetic" => matches!(
+            parameter.kind,
+            GenericParamDefKind::Type {
+                synthetic: true,
+                ..
+            }
+        )
+        .into(),
+        _ => unreachable!("GenericParameter property {field_name}"),
+    }
+}
+
+fn get_variant_field_property(token: &Token, field_name: &str) -> FieldValue {
+    let (position, name, type_) = token
+        .as_variant_field()
+        .expect("token was not a VariantField");
+    match field_name {
+        "name" => match name {
+            Some(name) => name.into(),
+            None => position.to_string().into(),
+        },
+        "position" => (position as u64).into(),
+        "type" => type_to_string(type_).into(),
+        "type_normalized" => normalized_type_string(token.source.crate_, None, type_).into(),
+        _ => unreachable!("VariantField property {field_name}"),
+    }
+}
+
+fn get_where_predicate_property(token: &Token, field_name: &str) -> FieldValue {
+    let predicate = token
+        .as_where_predicate()
+        .expect("token was not a WherePredicate");
+    match field_name {
+        "kind" => match predicate {
+            WherePredicate::BoundPredicate { .. } => "bound",
+            WherePredicate::RegionPredicate { .. } => "lifetime",
+            WherePredicate::EqPredicate { .. } => "equality",
+        }
+        .into(),
+        "subject" => where_predicate_parts(predicate).0.into(),
+        "bounds" => where_predicate_parts(predicate).1.into(),
+        "predicate" => where_predicate_to_string(predicate).into(),
+        _ => unreachable!("WherePredicate property {field_name}"),
+    }
+}
+
+/// The `impl Trait` types within the type, if any, and their positions among them.
+fn impl_trait_types(type_: Option<&Type>) -> impl Iterator<Item = (usize, &Type)> {
+    type_
+        .into_iter()
+        .flat_map(nested_types)
+        .filter(|type_| matches!(type_, Type::ImplTrait(..)))
+        .enumerate()
+}
+
+/// The `dyn Trait` types within the type, if any, and their positions among them.
+///
+/// Rustdoc records these as paths to their principal trait, with any other bounds alongside.
+fn trait_object_types<'a>(
+    crate_: &'a Crate,
+    type_: Option<&'a Type>,
+) -> impl Iterator<Item = (usize, &'a Type)> {
+    type_
+        .into_iter()
+        .flat_map(nested_types)
+        .filter(move |type_| match type_ {
+            Type::ResolvedPath {
+                id, param_names, ..
+            } => {
+                !param_names.is_empty()
+                    || matches!(
+                        crate_.index.get(id).map(|item| &item.inner),
+                        Some(rustdoc_types::ItemEnum::Trait(..))
+                    )
+                    || crate_
+                        .paths
+                        .get(id)
+                        .is_some_and(|summary| summary.kind == ItemKind::Trait)
+            }
+            _ => false,
+        })
+        .enumerate()
+}
+
+fn get_lifetime_bound_property(token: &Token, field_name: &str) -> FieldValue {
+    let (bound, generics) = token
+        .as_lifetime_bound()
+        .expect("token was not a LifetimeBound");
+    match field_name {
+        "subject" => bound.subject_to_string().into(),
+        "subject_kind" => match bound.subject {
+            OutlivesSubject::Lifetime(..) => "lifetime",
+            OutlivesSubject::Generic(..) | OutlivesSubject::Type(..) => "type",
+        }
+        .into(),
+        "subject_normalized" => match bound.subject {
+            OutlivesSubject::Lifetime(lifetime) => normalized_lifetime(Some(generics), lifetime),
+            OutlivesSubject::Generic(name) => normalized_type_string(
+                token.source.crate_,
+                Some(generics),
+                &Type::Generic(name.to_string()),
+            ),
+            OutlivesSubject::Type(type_) => {
+                normalized_type_string(token.source.crate_, Some(generics), type_)
+            }
+        }
+        .into(),
+        "lifetime" => bound.lifetime.into(),
+        "lifetime_normalized" => normalized_lifetime(Some(generics), bound.lifetime).into(),
+        "declared_in" => if bound.in_where_clause {
+            "where_clause"
+        } else {
+            "parameter"
+        }
+        .into(),
+        _ => unreachable!("LifetimeBound property {field_name}"),
+    }
+}
+
+/// Whether the item is of a kind that the schema describes.
+fn is_supported_item(item: &Item) -> bool {
+    matches!(
+        item.inner,
+        rustdoc_types::ItemEnum::Struct(..)
+            | rustdoc_types::ItemEnum::StructField(..)
+            | rustdoc_types::ItemEnum::Enum(..)
+            | rustdoc_types::ItemEnum::Variant(..)
+            | rustdoc_types::ItemEnum::Function(..)
+            | rustdoc_types::ItemEnum::Method(..)
+            | rustdoc_types::ItemEnum::Impl(..)
+            | rustdoc_types::ItemEnum::Trait(..)
+            | rustdoc_types::ItemEnum::AssocType { .. }
+            | rustdoc_types::ItemEnum::AssocConst { .. }
+            | rustdoc_types::ItemEnum::Union(..)
+            | rustdoc_types::ItemEnum::Static(..)
+            | rustdoc_types::ItemEnum::Constant(..)
+            | rustdoc_types::ItemEnum::Typedef(..)
+            | rustdoc_types::ItemEnum::Macro(..)
+            | rustdoc_types::ItemEnum::ProcMacro(..)
+    )
+}
+
+fn items_by_name(crate_: &Crate) -> ItemsByName<'_> {
+    let mut index: ItemsByName = BTreeMap::new();
+    for item in crate_.index.values().filter(|item| is_supported_item(item)) {
+        index.entry(item.name.as_deref()).or_default().push(item);
+    }
+    for items in index.values_mut() {
+        items.sort_by(|a, b| a.id.0.cmp(&b.id.0));
+    }
+    index
+}
+
+fn impls_by_type(graph: CrateGraph<'_>) -> ImplsByType<'_> {
+    let mut index = HashMap::new();
+    for crate_ in std::iter::once(graph.main).chain(graph.dependencies) {
+        for item in crate_.index.values() {
+            let impl_ids = match &item.inner {
+                rustdoc_types::ItemEnum::Struct(s) => &s.impls,
+                rustdoc_types::ItemEnum::Enum(e) => &e.impls,
+                rustdoc_types::ItemEnum::Union(u) => &u.impls,
+                _ => continue,
+            };
+            let impls = impl_ids
+                .iter()
+                // Impls from other crates aren't always in the index.
+                .filter_map(|impl_id| crate_.index.get(impl_id))
+                .filter(|impl_| matches!(impl_.inner, rustdoc_types::ItemEnum::Impl(..)))
+                .collect();
+            index.insert((CrateRef(crate_), &item.id), impls);
+        }
+    }
+    index
+}
+
 fn property_mapper<'a>(
     ctx: DataContext<Token<'a>>,
     field_name: &str,
@@ -327,7 +1604,10 @@ impl<'a> Adapter<'a> for RustdocAdapter<'a> {
             "CrateDiff" => {
                 let previous_crate = self.previous_crate.expect("no previous crate provided");
                 Box::new(std::iter::once(Token {
-                    origin: Origin::CurrentCrate,
+                    source: TokenSource {
+                        origin: Origin::CurrentCrate,
+                        crate_: self.current_crate,
+                    },
                     kind: TokenKind::CrateDiff((self.current_crate, previous_crate)),
                 }))
             }
@@ -351,6 +1631,35 @@ impl<'a> Adapter<'a> for RustdocAdapter<'a> {
                 }
                 None => (ctx, FieldValue::Null),
             }))
+        } else if field_name.as_ref() == "origin" {
+            // Every vertex type but CrateDiff has the side of the comparison it comes from.
+            Box::new(data_contexts.map(|ctx| match &ctx.current_token {
+                Some(token) => {
+                    let value = token.source.origin.label().into();
+                    (ctx, value)
+                }
+                None => (ctx, FieldValue::Null),
+            }))
+        } else if field_name.as_ref() == "is_externally_visible" {
+            let current_visible = self.visible_items(Origin::CurrentCrate);
+            let previous_visible = match self.previous_crate {
+                Some(_) => self.visible_items(Origin::PreviousCrate),
+                None => Arc::default(),
+            };
+            Box::new(data_contexts.map(move |ctx| match &ctx.current_token {
+                Some(token) => {
+                    let item = token.as_item().expect("token was not an Item");
+                    let visible = match token.source.origin {
+                        Origin::CurrentCrate => &current_visible,
+                        Origin::PreviousCrate => &previous_visible,
+                    };
+                    let value = visible
+                        .contains(&(CrateRef(token.source.crate_), &item.id))
+                        .into();
+                    (ctx, value)
+                }
+                None => (ctx, FieldValue::Null),
+            }))
         } else {
             match current_type_name.as_ref() {
                 "Crate" => {
@@ -363,11 +1672,49 @@ impl<'a> Adapter<'a> for RustdocAdapter<'a> {
                         property_mapper(ctx, field_name.as_ref(), get_item_property)
                     }))
                 }
-                "Struct" | "StructField" | "Enum" | "Variant" | "PlainVariant" | "TupleVariant"
-                | "StructVariant" | "Function" | "Method"
+                "Struct"
+                | "StructField"
+                | "Enum"
+                | "Variant"
+                | "PlainVariant"
+                | "TupleVariant"
+                | "StructVariant"
+                | "Function"
+                | "Method"
+                | "Impl"
+                | "Trait"
+                | "AssociatedType"
+                | "AssociatedConstant"
+                | "Union"
+                | "Static"
+                | "Constant"
+                | "TypeAlias"
+                | "Macro"
+                | "ProcMacro"
+                | "FunctionLikeProcMacro"
+                | "AttributeMacro"
+                | "DeriveMacro"
                     if matches!(
                         field_name.as_ref(),
-                        "id" | "crate_id" | "name" | "docs" | "attrs" | "visibility_limit"
+                        "id" | "crate_id"
+                            | "name"
+                            | "docs"
+                            | "has_docs"
+                            | "doc_summary"
+                            | "attrs"
+                            | "visibility_limit"
+                            | "visibility"
+                            | "visibility_restricted_to"
+                            | "span_filename"
+                            | "span_begin_line"
+                            | "non_exhaustive"
+                            | "doc_hidden"
+                            | "deprecated"
+                            | "deprecated_since"
+                            | "deprecation_note"
+                            | "cfg"
+                            | "cfg_features"
+                            | "must_use"
                     ) =>
                 {
                     // properties inherited from Item, accesssed on Item subtypes
@@ -375,6 +1722,21 @@ impl<'a> Adapter<'a> for RustdocAdapter<'a> {
                         property_mapper(ctx, field_name.as_ref(), get_item_property)
                     }))
                 }
+                "Struct" | "Enum" | "Union"
+                    if matches!(
+                        field_name.as_ref(),
+                        "repr"
+                            | "repr_c"
+                            | "repr_transparent"
+                            | "repr_packed"
+                            | "repr_align"
+                            | "repr_int"
+                    ) =>
+                {
+                    Box::new(data_contexts.map(move |ctx| {
+                        property_mapper(ctx, field_name.as_ref(), get_repr_property)
+                    }))
+                }
                 "Struct" => Box::new(data_contexts.map(move |ctx| {
                     property_mapper(ctx, field_name.as_ref(), get_struct_property)
                 })),
@@ -383,6 +1745,41 @@ impl<'a> Adapter<'a> for RustdocAdapter<'a> {
                         property_mapper(ctx, field_name.as_ref(), get_enum_property)
                     }))
                 }
+                "Union" => {
+                    Box::new(data_contexts.map(move |ctx| {
+                        property_mapper(ctx, field_name.as_ref(), get_union_property)
+                    }))
+                }
+                "Static" => Box::new(data_contexts.map(move |ctx| {
+                    property_mapper(ctx, field_name.as_ref(), get_static_property)
+                })),
+                "Constant" => Box::new(data_contexts.map(move |ctx| {
+                    property_mapper(ctx, field_name.as_ref(), get_constant_property)
+                })),
+                "TypeAlias" => Box::new(data_contexts.map(move |ctx| {
+                    property_mapper(ctx, field_name.as_ref(), get_type_alias_property)
+                })),
+                "Trait" => {
+                    Box::new(data_contexts.map(move |ctx| {
+                        property_mapper(ctx, field_name.as_ref(), get_trait_property)
+                    }))
+                }
+                "AssociatedType" => Box::new(data_contexts.map(move |ctx| {
+                    property_mapper(ctx, field_name.as_ref(), get_associated_type_property)
+                })),
+                "AssociatedConstant" => Box::new(data_contexts.map(move |ctx| {
+                    property_mapper(ctx, field_name.as_ref(), get_associated_constant_property)
+                })),
+                "Method" if field_name.as_ref() == "has_body" => {
+                    Box::new(data_contexts.map(move |ctx| {
+                        property_mapper(ctx, field_name.as_ref(), get_method_property)
+                    }))
+                }
+                "Impl" => {
+                    Box::new(data_contexts.map(move |ctx| {
+                        property_mapper(ctx, field_name.as_ref(), get_impl_property)
+                    }))
+                }
                 "Span" => {
                     Box::new(data_contexts.map(move |ctx| {
                         property_mapper(ctx, field_name.as_ref(), get_span_property)
@@ -393,13 +1790,56 @@ impl<'a> Adapter<'a> for RustdocAdapter<'a> {
                         property_mapper(ctx, field_name.as_ref(), get_path_property)
                     }))
                 }
+                "ProcMacro" | "FunctionLikeProcMacro" | "AttributeMacro" | "DeriveMacro" => {
+                    Box::new(data_contexts.map(move |ctx| {
+                        property_mapper(ctx, field_name.as_ref(), get_proc_macro_property)
+                    }))
+                }
                 "FunctionLike" | "Function" | "Method"
-                    if matches!(field_name.as_ref(), "const" | "unsafe" | "async") =>
+                    if matches!(
+                        field_name.as_ref(),
+                        "const"
+                            | "unsafe"
+                            | "async"
+                            | "return_type"
+                            | "return_type_normalized"
+                            | "signature"
+                    ) =>
                 {
                     Box::new(data_contexts.map(move |ctx| {
                         property_mapper(ctx, field_name.as_ref(), get_function_like_property)
                     }))
                 }
+                "FunctionParameter" => Box::new(data_contexts.map(move |ctx| {
+                    property_mapper(ctx, field_name.as_ref(), get_function_parameter_property)
+                })),
+                "GenericParameter" => Box::new(data_contexts.map(move |ctx| {
+                    property_mapper(ctx, field_name.as_ref(), get_generic_parameter_property)
+                })),
+                "WherePredicate" => Box::new(data_contexts.map(move |ctx| {
+                    property_mapper(ctx, field_name.as_ref(), get_where_predicate_property)
+                })),
+                "VariantField" => Box::new(data_contexts.map(move |ctx| {
+                    property_mapper(ctx, field_name.as_ref(), get_variant_field_property)
+                })),
+                "Variant" | "PlainVariant" | "TupleVariant" | "StructVariant"
+                    if field_name.as_ref() == "discriminant" =>
+                {
+                    // The rustdoc JSON format this reads doesn't record discriminants.
+                    Box::new(data_contexts.map(|ctx| (ctx, FieldValue::Null)))
+                }
+                "ImplementedTrait" => Box::new(data_contexts.map(move |ctx| {
+                    property_mapper(ctx, field_name.as_ref(), get_implemented_trait_property)
+                })),
+                "ImplTrait" => Box::new(data_contexts.map(move |ctx| {
+                    property_mapper(ctx, field_name.as_ref(), get_impl_trait_property)
+                })),
+                "TraitObject" => Box::new(data_contexts.map(move |ctx| {
+                    property_mapper(ctx, field_name.as_ref(), get_trait_object_property)
+                })),
+                "LifetimeBound" => Box::new(data_contexts.map(move |ctx| {
+                    property_mapper(ctx, field_name.as_ref(), get_lifetime_bound_property)
+                })),
                 _ => unreachable!("project_property {current_type_name} {field_name}"),
             }
         }
@@ -460,76 +1900,148 @@ impl<'a> Adapter<'a> for RustdocAdapter<'a> {
             },
             "Crate" => {
                 match edge_name.as_ref() {
-                    "item" => Box::new(data_contexts.map(move |ctx| {
-                        let neighbors: Box<dyn Iterator<Item = Self::DataToken> + 'a> = match &ctx
-                            .current_token
-                        {
-                            None => Box::new(std::iter::empty()),
-                            Some(token) => {
-                                let origin = token.origin;
-                                let crate_token = token.as_crate().expect("token was not a Crate");
-                                let iter = crate_token
-                                    .index
-                                    .values()
-                                    .filter(|item| {
-                                        // Filter out item types that are not currently supported.
-                                        matches!(
-                                            item.inner,
-                                            rustdoc_types::ItemEnum::Struct(..)
-                                                | rustdoc_types::ItemEnum::StructField(..)
-                                                | rustdoc_types::ItemEnum::Enum(..)
-                                                | rustdoc_types::ItemEnum::Variant(..)
-                                                | rustdoc_types::ItemEnum::Function(..)
-                                                | rustdoc_types::ItemEnum::Method(..)
-                                        )
-                                    })
-                                    .map(move |value| origin.make_item_token(value));
-                                Box::new(iter)
-                            }
+                    "item" => {
+                        let current_items = self.items_by_name(Origin::CurrentCrate);
+                        let previous_items = match self.previous_crate {
+                            Some(_) => self.items_by_name(Origin::PreviousCrate),
+                            None => Arc::default(),
+                        };
+                        let current_reexported = self.reexported_items(Origin::CurrentCrate);
+                        let previous_reexported = match self.previous_crate {
+                            Some(_) => self.reexported_items(Origin::PreviousCrate),
+                            None => Arc::default(),
                         };
+                        let unchanged = self.unchanged;
+                        Box::new(data_contexts.map(move |ctx| {
+                            let neighbors: Box<dyn Iterator<Item = Self::DataToken> + 'a> =
+                                match &ctx.current_token {
+                                    None => Box::new(std::iter::empty()),
+                                    Some(token) => {
+                                        let source = token.source;
+                                        let items = match source.origin {
+                                            Origin::CurrentCrate => &current_items,
+                                            Origin::PreviousCrate => &previous_items,
+                                        };
+                                        let reexported = match source.origin {
+                                            Origin::CurrentCrate => current_reexported.clone(),
+                                            Origin::PreviousCrate => previous_reexported.clone(),
+                                        };
+
+                                        let unchanged =
+                                            unchanged.map(|unchanged| match source.origin {
+                                                Origin::CurrentCrate => &unchanged.current,
+                                                Origin::PreviousCrate => &unchanged.baseline,
+                                            });
+                                        let own_items: Vec<_> = items
+                                            .values()
+                                            .flatten()
+                                            .filter(|item| {
+                                                !unchanged.is_some_and(|unchanged| {
+                                                    unchanged.contains(&item.id)
+                                                })
+                                            })
+                                            .map(|item| source.make_item_token(item))
+                                            .collect();
+                                        // Items re-exported from other crates are part of this
+                                        // crate's API, but are described by their own crate.
+                                        let reexported_items = (0..reexported.len())
+                                            .map(move |index| reexported[index])
+                                            .filter(|(_, item)| is_supported_item(item))
+                                            .map(move |(crate_, item)| {
+                                                TokenSource {
+                                                    origin: source.origin,
+                                                    crate_,
+                                                }
+                                                .make_item_token(item)
+                                            });
+                                        Box::new(own_items.into_iter().chain(reexported_items))
+                                    }
+                                };
 
-                        (ctx, neighbors)
-                    })),
+                            (ctx, neighbors)
+                        }))
+                    }
                     _ => unreachable!(
                         "project_neighbors {current_type_name} {edge_name} {parameters:?}"
                     ),
                 }
             }
-            "Importable" | "Struct" | "Enum" | "Function" if edge_name.as_ref() == "path" => {
-                let current_crate = self.current_crate;
-                let previous_crate = self.previous_crate;
+            "Importable"
+            | "Struct"
+            | "Enum"
+            | "Function"
+            | "Trait"
+            | "Union"
+            | "Static"
+            | "Constant"
+            | "TypeAlias"
+            | "Macro"
+            | "ProcMacro"
+            | "FunctionLikeProcMacro"
+            | "AttributeMacro"
+            | "DeriveMacro"
+                if edge_name.as_ref() == "path" =>
+            {
+                let current_paths = self.importable_paths(Origin::CurrentCrate);
+                let previous_paths = match self.previous_crate {
+                    Some(_) => self.importable_paths(Origin::PreviousCrate),
+                    None => Arc::default(),
+                };
+                let include_hidden = parameters
+                    .as_ref()
+                    .and_then(|parameters| parameters.0.get("include_hidden"))
+                    .is_some_and(|value| value == &FieldValue::Boolean(true));
 
                 Box::new(data_contexts.map(move |ctx| {
                     let neighbors: Box<dyn Iterator<Item = Self::DataToken> + 'a> =
                         match &ctx.current_token {
                             None => Box::new(std::iter::empty()),
                             Some(token) => {
-                                let origin = token.origin;
+                                let source = token.source;
                                 let item = token.as_item().expect("token was not an Item");
-                                let item_id = &item.id;
+                                let paths = match source.origin {
+                                    Origin::CurrentCrate => &current_paths,
+                                    Origin::PreviousCrate => &previous_paths,
+                                };
 
-                                if let Some(path) = match origin {
-                                    Origin::CurrentCrate => {
-                                        current_crate.paths.get(item_id).map(|x| &x.path)
-                                    }
-                                    Origin::PreviousCrate => previous_crate
-                                        .expect("no baseline provided")
-                                        .paths
-                                        .get(item_id)
-                                        .map(|x| &x.path),
-                                } {
-                                    Box::new(std::iter::once(origin.make_path_token(path)))
-                                } else {
-                                    Box::new(std::iter::empty())
-                                }
+                                Box::new(
+                                    paths
+                                        .get(&(CrateRef(source.crate_), &item.id))
+                                        .cloned()
+                                        .unwrap_or_default()
+                                        .into_iter()
+                                        .filter(move |path| include_hidden || !path.doc_hidden)
+                                        .map(move |path| source.make_path_token(path)),
+                                )
                             }
                         };
 
                     (ctx, neighbors)
                 }))
             }
-            "Item" | "Struct" | "StructField" | "Enum" | "Variant" | "PlainVariant"
-            | "TupleVariant" | "StructVariant" | "Function" | "Method"
+            "Item"
+            | "Struct"
+            | "StructField"
+            | "Enum"
+            | "Variant"
+            | "PlainVariant"
+            | "TupleVariant"
+            | "StructVariant"
+            | "Function"
+            | "Method"
+            | "Impl"
+            | "Trait"
+            | "AssociatedType"
+            | "AssociatedConstant"
+            | "Union"
+            | "Static"
+            | "Constant"
+            | "TypeAlias"
+            | "Macro"
+            | "ProcMacro"
+            | "FunctionLikeProcMacro"
+            | "AttributeMacro"
+            | "DeriveMacro"
                 if edge_name.as_ref() == "span" =>
             {
                 Box::new(data_contexts.map(move |ctx| {
@@ -537,10 +2049,10 @@ impl<'a> Adapter<'a> for RustdocAdapter<'a> {
                         match &ctx.current_token {
                             None => Box::new(std::iter::empty()),
                             Some(token) => {
-                                let origin = token.origin;
+                                let source = token.source;
                                 let item = token.as_item().expect("token was not an Item");
                                 if let Some(span) = &item.span {
-                                    Box::new(std::iter::once(origin.make_span_token(span)))
+                                    Box::new(std::iter::once(source.make_span_token(span)))
                                 } else {
                                     Box::new(std::iter::empty())
                                 }
@@ -550,73 +2062,442 @@ impl<'a> Adapter<'a> for RustdocAdapter<'a> {
                     (ctx, neighbors)
                 }))
             }
-            "Struct" => match edge_name.as_ref() {
-                "field" => {
-                    let current_crate = self.current_crate;
-                    let previous_crate = self.previous_crate;
-                    Box::new(data_contexts.map(move |ctx| {
-                        let neighbors: Box<dyn Iterator<Item = Self::DataToken> + 'a> = match &ctx
-                            .current_token
-                        {
+            "Struct" | "Enum" | "Union" | "TypeAlias" | "Trait" | "FunctionLike" | "Function"
+            | "Method" | "Impl"
+                if edge_name.as_ref() == "generic_parameter" =>
+            {
+                Box::new(data_contexts.map(move |ctx| {
+                    let neighbors: Box<dyn Iterator<Item = Self::DataToken> + 'a> =
+                        match &ctx.current_token {
                             None => Box::new(std::iter::empty()),
                             Some(token) => {
-                                let origin = token.origin;
-                                let (_, struct_item) =
-                                    token.as_struct_item().expect("token was not a Struct");
-
-                                let item_index = match origin {
-                                    Origin::CurrentCrate => &current_crate.index,
-                                    Origin::PreviousCrate => {
-                                        &previous_crate.expect("no previous crate provided").index
-                                    }
-                                };
-                                Box::new(struct_item.fields.clone().into_iter().map(
-                                    move |field_id| {
-                                        origin.make_item_token(
-                                            item_index.get(&field_id).expect("missing item"),
-                                        )
+                                let source = token.source;
+                                let generics = token.as_generics().expect("token had no generics");
+                                Box::new(generics.params.iter().enumerate().map(
+                                    move |(position, parameter)| {
+                                        source.make_generic_parameter_token(position, parameter)
                                     },
                                 ))
                             }
                         };
 
-                        (ctx, neighbors)
-                    }))
+                    (ctx, neighbors)
+                }))
+            }
+            "Struct" | "Enum" | "Union" | "TypeAlias" | "Trait" | "FunctionLike" | "Function"
+            | "Method" | "Impl"
+                if edge_name.as_ref() == "lifetime_bound" =>
+            {
+                Box::new(data_contexts.map(move |ctx| {
+                    let neighbors: Box<dyn Iterator<Item = Self::DataToken> + 'a> =
+                        match &ctx.current_token {
+                            None => Box::new(std::iter::empty()),
+                            Some(token) => {
+                                let source = token.source;
+                                let generics = token.as_generics().expect("token had no generics");
+                                Box::new(lifetime_bounds(generics).into_iter().map(move |bound| {
+                                    source.make_lifetime_bound_token(bound, generics)
+                                }))
+                            }
+                        };
+
+                    (ctx, neighbors)
+                }))
+            }
+            "Struct" | "Enum" | "Union" | "TypeAlias" | "Trait" | "FunctionLike" | "Function"
+            | "Method" | "Impl"
+                if edge_name.as_ref() == "where_predicate" =>
+            {
+                Box::new(data_contexts.map(move |ctx| {
+                    let neighbors: Box<dyn Iterator<Item = Self::DataToken> + 'a> =
+                        match &ctx.current_token {
+                            None => Box::new(std::iter::empty()),
+                            Some(token) => {
+                                let source = token.source;
+                                let generics = token.as_generics().expect("token had no generics");
+                                Box::new(generics.where_predicates.iter().map(move |predicate| {
+                                    source.make_where_predicate_token(predicate)
+                                }))
+                            }
+                        };
+
+                    (ctx, neighbors)
+                }))
+            }
+            "FunctionLike" | "Function" | "Method" if edge_name.as_ref() == "return_impl_trait" => {
+                Box::new(data_contexts.map(move |ctx| {
+                    let neighbors: Box<dyn Iterator<Item = Self::DataToken> + 'a> =
+                        match &ctx.current_token {
+                            None => Box::new(std::iter::empty()),
+                            Some(token) => {
+                                let source = token.source;
+                                let (_, decl) = token
+                                    .as_function_like()
+                                    .expect("token was not a FunctionLike");
+                                let generics = token.as_generics();
+                                Box::new(impl_trait_types(decl.output.as_ref()).map(
+                                    move |(position, type_)| {
+                                        source.make_impl_trait_token(position, type_, generics)
+                                    },
+                                ))
+                            }
+                        };
+
+                    (ctx, neighbors)
+                }))
+            }
+            "FunctionLike" | "Function" | "Method"
+                if edge_name.as_ref() == "return_trait_object" =>
+            {
+                Box::new(data_contexts.map(move |ctx| {
+                    let neighbors: Box<dyn Iterator<Item = Self::DataToken> + 'a> = match &ctx
+                        .current_token
+                    {
+                        None => Box::new(std::iter::empty()),
+                        Some(token) => {
+                            let source = token.source;
+                            let (_, decl) = token
+                                .as_function_like()
+                                .expect("token was not a FunctionLike");
+                            let generics = token.as_generics();
+                            Box::new(trait_object_types(source.crate_, decl.output.as_ref()).map(
+                                move |(position, type_)| {
+                                    source.make_trait_object_token(position, type_, generics)
+                                },
+                            ))
+                        }
+                    };
+
+                    (ctx, neighbors)
+                }))
+            }
+            "FunctionParameter" | "StructField" | "VariantField"
+                if edge_name.as_ref() == "trait_object" =>
+            {
+                Box::new(data_contexts.map(move |ctx| {
+                    let neighbors: Box<dyn Iterator<Item = Self::DataToken> + 'a> =
+                        match &ctx.current_token {
+                            None => Box::new(std::iter::empty()),
+                            Some(token) => {
+                                let source = token.source;
+                                let (type_, generics) = token
+                                    .as_typed_value()
+                                    .expect("token was not a parameter or field");
+                                Box::new(trait_object_types(source.crate_, Some(type_)).map(
+                                    move |(position, type_)| {
+                                        source.make_trait_object_token(position, type_, generics)
+                                    },
+                                ))
+                            }
+                        };
+
+                    (ctx, neighbors)
+                }))
+            }
+            "FunctionParameter" if edge_name.as_ref() == "impl_trait" => {
+                Box::new(data_contexts.map(move |ctx| {
+                    let neighbors: Box<dyn Iterator<Item = Self::DataToken> + 'a> = match &ctx
+                        .current_token
+                    {
+                        None => Box::new(std::iter::empty()),
+                        Some(token) => {
+                            let source = token.source;
+                            let (_, (_, type_), generics) = token
+                                .as_function_parameter()
+                                .expect("token was not a FunctionParameter");
+                            Box::new(impl_trait_types(Some(type_)).map(move |(position, type_)| {
+                                source.make_impl_trait_token(position, type_, Some(generics))
+                            }))
+                        }
+                    };
+
+                    (ctx, neighbors)
+                }))
+            }
+            "FunctionLike" | "Function" | "Method" if edge_name.as_ref() == "parameter" => {
+                Box::new(data_contexts.map(move |ctx| {
+                    let neighbors: Box<dyn Iterator<Item = Self::DataToken> + 'a> = match &ctx
+                        .current_token
+                    {
+                        None => Box::new(std::iter::empty()),
+                        Some(token) => {
+                            let source = token.source;
+                            let (_, decl) = token
+                                .as_function_like()
+                                .expect("token was not a FunctionLike");
+                            let generics = token.as_generics().expect("function had no generics");
+                            Box::new(decl.inputs.iter().enumerate().map(
+                                move |(position, parameter)| {
+                                    source.make_function_parameter_token(
+                                        position, parameter, generics,
+                                    )
+                                },
+                            ))
+                        }
+                    };
+
+                    (ctx, neighbors)
+                }))
+            }
+            "Struct" | "Enum" | "Function" if edge_name.as_ref() == "possible_rename" => {
+                let renames = self.renames();
+
+                Box::new(data_contexts.map(move |ctx| {
+                    let neighbors: Box<dyn Iterator<Item = Self::DataToken> + 'a> =
+                        match &ctx.current_token {
+                            None => Box::new(std::iter::empty()),
+                            Some(token) => {
+                                let source = token.source;
+                                let item = token.as_item().expect("token was not an Item");
+                                let other_origin = match source.origin {
+                                    Origin::CurrentCrate => Origin::PreviousCrate,
+                                    Origin::PreviousCrate => Origin::CurrentCrate,
+                                };
+                                let candidates: Vec<_> = renames
+                                    .candidates(
+                                        matches!(source.origin, Origin::PreviousCrate),
+                                        source.crate_,
+                                        &item.id,
+                                    )
+                                    .iter()
+                                    .map(|&(crate_, candidate)| {
+                                        TokenSource {
+                                            origin: other_origin,
+                                            crate_,
+                                        }
+                                        .make_item_token(candidate)
+                                    })
+                                    .collect();
+                                Box::new(candidates.into_iter())
+                            }
+                        };
+
+                    (ctx, neighbors)
+                }))
+            }
+            "Struct" | "Enum" | "Union"
+                if matches!(edge_name.as_ref(), "inherent_impl" | "trait_impl") =>
+            {
+                let want_trait_impls = edge_name.as_ref() == "trait_impl";
+                let current_impls = self.impls_by_type(Origin::CurrentCrate);
+                let previous_impls = match self.previous_crate {
+                    Some(_) => self.impls_by_type(Origin::PreviousCrate),
+                    None => Arc::default(),
+                };
+                Box::new(data_contexts.map(move |ctx| {
+                    let neighbors: Box<dyn Iterator<Item = Self::DataToken> + 'a> =
+                        match &ctx.current_token {
+                            None => Box::new(std::iter::empty()),
+                            Some(token) => {
+                                let source = token.source;
+                                let item = token.as_item().expect("token was not an Item");
+                                let impls = match source.origin {
+                                    Origin::CurrentCrate => &current_impls,
+                                    Origin::PreviousCrate => &previous_impls,
+                                };
+
+                                Box::new(
+                                    impls
+                                        .get(&(CrateRef(source.crate_), &item.id))
+                                        .cloned()
+                                        .unwrap_or_default()
+                                        .into_iter()
+                                        .filter(move |item| match &item.inner {
+                                            rustdoc_types::ItemEnum::Impl(impl_) => {
+                                                impl_.trait_.is_some() == want_trait_impls
+                                            }
+                                            _ => false,
+                                        })
+                                        .map(move |item| source.make_item_token(item)),
+                                )
+                            }
+                        };
+
+                    (ctx, neighbors)
+                }))
+            }
+            "Trait"
+                if matches!(
+                    edge_name.as_ref(),
+                    "method" | "associated_type" | "associated_constant"
+                ) =>
+            {
+                let wanted_kind = match edge_name.as_ref() {
+                    "method" => "Method",
+                    "associated_type" => "AssociatedType",
+                    _ => "AssociatedConstant",
+                };
+                Box::new(data_contexts.map(move |ctx| {
+                    let neighbors: Box<dyn Iterator<Item = Self::DataToken> + 'a> =
+                        match &ctx.current_token {
+                            None => Box::new(std::iter::empty()),
+                            Some(token) => {
+                                let source = token.source;
+                                let trait_ = token.as_trait().expect("token was not a Trait");
+
+                                let item_index = &source.crate_.index;
+                                Box::new(
+                                    trait_
+                                        .items
+                                        .iter()
+                                        .filter_map(move |item_id| item_index.get(item_id))
+                                        .map(move |item| source.make_item_token(item))
+                                        .filter(move |token| token.typename() == wanted_kind),
+                                )
+                            }
+                        };
+
+                    (ctx, neighbors)
+                }))
+            }
+            "Impl" => match edge_name.as_ref() {
+                "method" => Box::new(data_contexts.map(move |ctx| {
+                    let neighbors: Box<dyn Iterator<Item = Self::DataToken> + 'a> = match &ctx
+                        .current_token
+                    {
+                        None => Box::new(std::iter::empty()),
+                        Some(token) => {
+                            let source = token.source;
+                            let impl_ = token.as_impl().expect("token was not an Impl");
+
+                            let item_index = &source.crate_.index;
+                            Box::new(
+                                impl_
+                                    .items
+                                    .iter()
+                                    .filter_map(move |item_id| item_index.get(item_id))
+                                    .filter(|item| {
+                                        matches!(item.inner, rustdoc_types::ItemEnum::Method(..))
+                                    })
+                                    .map(move |item| source.make_item_token(item)),
+                            )
+                        }
+                    };
+
+                    (ctx, neighbors)
+                })),
+                "implemented_trait" => Box::new(data_contexts.map(move |ctx| {
+                    let neighbors: Box<dyn Iterator<Item = Self::DataToken> + 'a> =
+                        match &ctx.current_token {
+                            None => Box::new(std::iter::empty()),
+                            Some(token) => {
+                                let source = token.source;
+                                let impl_ = token.as_impl().expect("token was not an Impl");
+
+                                match &impl_.trait_ {
+                                    Some(trait_) => {
+                                        let path = match trait_ {
+                                            Type::ResolvedPath { id, .. } => source
+                                                .crate_
+                                                .paths
+                                                .get(id)
+                                                .map(|summary| summary.path.as_slice()),
+                                            _ => None,
+                                        };
+                                        Box::new(std::iter::once(
+                                            source.make_implemented_trait_token(trait_, path),
+                                        ))
+                                    }
+                                    None => Box::new(std::iter::empty()),
+                                }
+                            }
+                        };
+
+                    (ctx, neighbors)
+                })),
+                _ => {
+                    unreachable!("project_neighbors {current_type_name} {edge_name} {parameters:?}")
                 }
+            },
+            "Struct" | "Union" => match edge_name.as_ref() {
+                "field" => Box::new(data_contexts.map(move |ctx| {
+                    let neighbors: Box<dyn Iterator<Item = Self::DataToken> + 'a> =
+                        match &ctx.current_token {
+                            None => Box::new(std::iter::empty()),
+                            Some(token) => {
+                                let source = token.source;
+                                let fields = token
+                                    .as_field_owner()
+                                    .expect("token was not a Struct or Union");
+
+                                let item_index = &source.crate_.index;
+                                Box::new(fields.iter().map(move |field_id| {
+                                    source.make_item_token(
+                                        item_index.get(field_id).expect("missing item"),
+                                    )
+                                }))
+                            }
+                        };
+
+                    (ctx, neighbors)
+                })),
                 _ => {
                     unreachable!("project_neighbors {current_type_name} {edge_name} {parameters:?}")
                 }
             },
+            "Variant" | "PlainVariant" | "TupleVariant" | "StructVariant"
+                if edge_name.as_ref() == "field" =>
+            {
+                Box::new(data_contexts.map(move |ctx| {
+                    let neighbors: Box<dyn Iterator<Item = Self::DataToken> + 'a> =
+                        match &ctx.current_token {
+                            None => Box::new(std::iter::empty()),
+                            Some(token) => {
+                                let source = token.source;
+                                let variant = token.as_variant().expect("token was not a Variant");
+                                match variant {
+                                    Variant::Plain => Box::new(std::iter::empty()),
+                                    Variant::Tuple(types) => Box::new(
+                                        types.iter().enumerate().map(move |(position, type_)| {
+                                            source.make_variant_field_token(position, None, type_)
+                                        }),
+                                    ),
+                                    Variant::Struct(field_ids) => {
+                                        let item_index = &source.crate_.index;
+                                        Box::new(field_ids.iter().enumerate().filter_map(
+                                            move |(position, field_id)| {
+                                                let field =
+                                                    item_index.get(field_id).expect("missing item");
+                                                match &field.inner {
+                                                    rustdoc_types::ItemEnum::StructField(type_) => {
+                                                        Some(source.make_variant_field_token(
+                                                            position,
+                                                            field.name.as_deref(),
+                                                            type_,
+                                                        ))
+                                                    }
+                                                    _ => None,
+                                                }
+                                            },
+                                        ))
+                                    }
+                                }
+                            }
+                        };
+
+                    (ctx, neighbors)
+                }))
+            }
             "Enum" => match edge_name.as_ref() {
-                "variant" => {
-                    let current_crate = self.current_crate;
-                    let previous_crate = self.previous_crate;
-                    Box::new(data_contexts.map(move |ctx| {
-                        let neighbors: Box<dyn Iterator<Item = Self::DataToken> + 'a> = match &ctx
-                            .current_token
-                        {
+                "variant" => Box::new(data_contexts.map(move |ctx| {
+                    let neighbors: Box<dyn Iterator<Item = Self::DataToken> + 'a> =
+                        match &ctx.current_token {
                             None => Box::new(std::iter::empty()),
                             Some(token) => {
-                                let origin = token.origin;
+                                let source = token.source;
                                 let enum_item = token.as_enum().expect("token was not a Enum");
 
-                                let item_index = match origin {
-                                    Origin::CurrentCrate => &current_crate.index,
-                                    Origin::PreviousCrate => {
-                                        &previous_crate.expect("no previous crate provided").index
-                                    }
-                                };
+                                let item_index = &source.crate_.index;
                                 Box::new(enum_item.variants.iter().map(move |field_id| {
-                                    origin.make_item_token(
+                                    source.make_item_token(
                                         item_index.get(field_id).expect("missing item"),
                                     )
                                 }))
                             }
                         };
 
-                        (ctx, neighbors)
-                    }))
-                }
+                    (ctx, neighbors)
+                })),
                 _ => {
                     unreachable!("project_neighbors {current_type_name} {edge_name} {parameters:?}")
                 }
@@ -634,7 +2515,7 @@ impl<'a> Adapter<'a> for RustdocAdapter<'a> {
         _vertex_hint: Vid,
     ) -> Box<dyn Iterator<Item = (DataContext<Self::DataToken>, bool)> + 'a> {
         match current_type_name.as_ref() {
-            "Item" | "Variant" | "FunctionLike" => {
+            "Item" | "Variant" | "FunctionLike" | "Importable" | "ProcMacro" => {
                 Box::new(data_contexts.map(move |ctx| {
                     let can_coerce = match &ctx.current_token {
                         None => false,
@@ -646,6 +2527,25 @@ impl<'a> Adapter<'a> for RustdocAdapter<'a> {
                                     actual_type_name,
                                     "PlainVariant" | "TupleVariant" | "StructVariant"
                                 ),
+                                "ProcMacro" => matches!(
+                                    actual_type_name,
+                                    "FunctionLikeProcMacro" | "AttributeMacro" | "DeriveMacro"
+                                ),
+                                "Importable" => matches!(
+                                    actual_type_name,
+                                    "Struct"
+                                        | "Enum"
+                                        | "Function"
+                                        | "Trait"
+                                        | "Union"
+                                        | "Static"
+                                        | "Constant"
+                                        | "TypeAlias"
+                                        | "Macro"
+                                        | "FunctionLikeProcMacro"
+                                        | "AttributeMacro"
+                                        | "DeriveMacro"
+                                ),
                                 _ => {
                                     // The remaining types are final (don't have any subtypes)
                                     // so we can just compare the actual type name to
@@ -666,14 +2566,140 @@ impl<'a> Adapter<'a> for RustdocAdapter<'a> {
 
 #[cfg(test)]
 mod tests {
-    use std::{cell::RefCell, collections::BTreeMap, rc::Rc, sync::Arc};
+    use std::{cell::RefCell, collections::BTreeMap, path::Path, rc::Rc, sync::Arc};
 
     use anyhow::Context;
     use trustfall_core::{frontend::parse, interpreter::execution::interpret_ir, ir::FieldValue};
 
     use crate::{query::SemverQuery, util::load_rustdoc_from_file};
 
-    use super::RustdocAdapter;
+    use super::{
+        cfg_features, cfg_predicates, doc_summary, has_attribute, is_doc_hidden, repr_hints,
+        Origin, Repr, RustdocAdapter,
+    };
+
+    #[test]
+    fn doc_summary_is_the_first_paragraph() {
+        assert_eq!(doc_summary(None), None);
+        assert_eq!(doc_summary(Some(" \n\n")), None);
+        assert_eq!(
+            doc_summary(Some(
+                "\nParses the input,\n  returning its items.\n\n# Errors\n"
+            )),
+            Some("Parses the input, returning its items.".to_string())
+        );
+    }
+
+    #[test]
+    fn attributes_are_recognized_with_arguments_and_spacing() {
+        let attrs = |attrs: &[&str]| attrs.iter().map(ToString::to_string).collect::<Vec<_>>();
+
+        assert!(has_attribute(
+            &attrs(&["#[non_exhaustive]"]),
+            "non_exhaustive"
+        ));
+        assert!(has_attribute(
+            &attrs(&["#[must_use = \"results must be checked\"]"]),
+            "must_use"
+        ));
+        assert!(!has_attribute(&attrs(&["#[must_use_other]"]), "must_use"));
+        assert!(!has_attribute(&attrs(&["#[repr(C)]"]), "non_exhaustive"));
+
+        assert!(is_doc_hidden(&attrs(&["#[doc(hidden)]"])));
+        assert!(is_doc_hidden(&attrs(&["#[doc(inline, hidden)]"])));
+        assert!(!is_doc_hidden(&attrs(&["#[doc = \"hidden\"]"])));
+    }
+
+    #[test]
+    fn repr_attributes_are_parsed() {
+        let attrs = [
+            "#[repr(C, packed(4))]".to_string(),
+            "#[repr( align(8) )]".to_string(),
+            "#[repr(u8)]".to_string(),
+            "#[derive(Clone)]".to_string(),
+        ];
+        let hints = repr_hints(&attrs);
+        assert_eq!(vec!["C", "packed(4)", "align(8)", "u8"], hints);
+        assert_eq!(
+            Repr {
+                c: true,
+                transparent: false,
+                packed: Some(4),
+                align: Some(8),
+                int: Some("u8".to_string()),
+            },
+            Repr::from_hints(&hints)
+        );
+
+        let transparent = Repr::from_hints(&repr_hints(&["#[repr(transparent)]".to_string()]));
+        assert!(transparent.transparent);
+        assert_eq!(None, transparent.packed);
+        assert_eq!(
+            Some(1),
+            Repr::from_hints(&repr_hints(&["#[repr(packed)]".to_string()])).packed
+        );
+    }
+
+    #[test]
+    fn clones_on_other_threads_share_the_indexes() {
+        let crate_: rustdoc_types::Crate = serde_json::from_value(serde_json::json!({
+            "root": "0:0",
+            "crate_version": null,
+            "includes_private": false,
+            "index": {},
+            "paths": {},
+            "external_crates": {},
+            "format_version": rustdoc_types::FORMAT_VERSION,
+        }))
+        .unwrap();
+        let adapter = RustdocAdapter::new(&crate_, None);
+
+        let built_elsewhere = std::thread::scope(|scope| {
+            let clone = adapter.clone();
+            scope
+                .spawn(move || clone.visible_items(Origin::CurrentCrate))
+                .join()
+                .unwrap()
+        });
+        assert!(Arc::ptr_eq(
+            &built_elsewhere,
+            &adapter.visible_items(Origin::CurrentCrate)
+        ));
+    }
+
+    #[test]
+    fn cfg_predicates_and_features_are_extracted() {
+        let attrs = [
+            "#[cfg(all(unix, feature = \"serde\"))]",
+            "#[doc(cfg(any(feature = \"std\", target_feature = \"sse2\")), hidden)]",
+            "#[doc = \"cfg(windows)\"]",
+            "#[must_use]",
+        ]
+        .map(String::from);
+
+        assert_eq!(
+            vec![
+                "all(unix, feature = \"serde\")",
+                "any(feature = \"std\", target_feature = \"sse2\")",
+            ],
+            cfg_predicates(&attrs)
+        );
+        assert_eq!(vec!["serde", "std"], cfg_features(&attrs));
+        assert!(cfg_predicates(&attrs[2..]).is_empty());
+    }
+
+    #[test]
+    fn test_queries_are_valid() {
+        let schema = RustdocAdapter::schema();
+        for entry in std::fs::read_dir("./src/test_data/queries").unwrap() {
+            let path = entry.unwrap().path();
+            let query_text = std::fs::read_to_string(&path).unwrap();
+            let semver_query: SemverQuery = ron::from_str(&query_text)
+                .unwrap_or_else(|e| panic!("{} failed to parse: {e}", path.display()));
+            parse(&schema, &semver_query.query)
+                .unwrap_or_else(|e| panic!("{} is not a valid query: {e:?}", path.display()));
+        }
+    }
 
     fn check_query_execution(query_name: &str) {
         // Ensure the rustdocs JSON outputs have been regenerated.
@@ -685,12 +2711,17 @@ mod tests {
             .with_context(|| format!("Could not load localdata/test_data/{}.json file, did you forget to run ./scripts/regenerate_test_rustdocs.sh ?", query_name))
             .expect("failed to load rustdoc under test");
 
-        let query_text =
-            std::fs::read_to_string(&format!("./src/queries/{}.ron", query_name)).unwrap();
+        // Parts of the schema that no check uses yet are tested with queries of their own.
+        let query_path = ["./src/queries", "./src/test_data/queries"]
+            .iter()
+            .map(|dir| format!("{}/{}.ron", dir, query_name))
+            .find(|path| Path::new(path).exists())
+            .unwrap_or_else(|| panic!("no query named {}", query_name));
+        let query_text = std::fs::read_to_string(query_path).unwrap();
         let semver_query: SemverQuery = ron::from_str(&query_text).unwrap();
 
         let expected_result_text =
-            std::fs::read_to_string(&format!("./src/test_data/{}.output.ron", query_name))
+            std::fs::read_to_string(format!("./src/test_data/{}.output.ron", query_name))
             .with_context(|| format!("Could not load src/test_data/{}.output.ron expected-outputs file, did you forget to add it?", query_name))
             .expect("failed to load expected outputs");
         let mut expected_results: Vec<BTreeMap<String, FieldValue>> =
@@ -740,14 +2771,33 @@ mod tests {
     }
 
     query_execution_tests!(
+        constant_missing,
         enum_missing,
         enum_variant_added,
         enum_variant_missing,
         function_missing,
+        function_must_use_added,
+        function_now_deprecated,
+        function_parameter_type_changed,
+        generic_parameter_missing,
+        inherent_method_missing,
+        lifetime_bound_added,
+        macro_missing,
+        return_impl_trait_changed,
+        static_missing,
         struct_marked_non_exhaustive,
         struct_missing,
         struct_pub_field_missing,
+        trait_associated_constant_missing,
+        trait_associated_type_missing,
+        trait_impl_missing,
+        trait_method_missing,
+        trait_object_auto_traits_changed,
+        type_alias_missing,
+        union_missing,
         unit_struct_changed_kind,
+        variant_field_missing,
         variant_marked_non_exhaustive,
+        where_predicate_added,
     );
 }
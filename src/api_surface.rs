@@ -0,0 +1,232 @@
+//! The size of a crate's public API, counted by kind of item, so that the baseline and
+//! current versions can be compared at a glance at the end of a run.
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+use rustdoc_types::{Crate, ItemEnum, Visibility};
+
+use crate::{
+    adapter::is_doc_hidden,
+    importable_paths::{public_item_paths, CrateGraph, CrateRef, ItemKey},
+};
+
+/// The kinds of items that are counted, in the order they're listed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub(crate) enum ItemKind {
+    Module,
+    Function,
+    Method,
+    Struct,
+    Enum,
+    Union,
+    Trait,
+    TypeAlias,
+    Constant,
+    Static,
+    Macro,
+}
+
+impl ItemKind {
+    pub(crate) fn plural_name(self) -> &'static str {
+        match self {
+            Self::Module => "modules",
+            Self::Function => "functions",
+            Self::Method => "methods",
+            Self::Struct => "structs",
+            Self::Enum => "enums",
+            Self::Union => "unions",
+            Self::Trait => "traits",
+            Self::TypeAlias => "type aliases",
+            Self::Constant => "constants",
+            Self::Static => "statics",
+            Self::Macro => "macros",
+        }
+    }
+
+    fn of(item: &ItemEnum) -> Option<Self> {
+        Some(match item {
+            ItemEnum::Module(_) => Self::Module,
+            ItemEnum::Function(_) => Self::Function,
+            ItemEnum::Method(_) => Self::Method,
+            ItemEnum::Struct(_) => Self::Struct,
+            ItemEnum::Enum(_) => Self::Enum,
+            ItemEnum::Union(_) => Self::Union,
+            ItemEnum::Trait(_) => Self::Trait,
+            ItemEnum::Typedef(_) => Self::TypeAlias,
+            ItemEnum::Constant(_) => Self::Constant,
+            ItemEnum::Static(_) => Self::Static,
+            ItemEnum::Macro(_) | ItemEnum::ProcMacro(_) => Self::Macro,
+            _ => return None,
+        })
+    }
+}
+
+/// The number of public items of each kind in a crate: the items importable from it at
+/// paths that aren't `#[doc(hidden)]` other than the crate root, and the `pub` inherent methods of its public types.
+/// Items importable at several paths are counted once.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct ApiSurface {
+    counts: BTreeMap<ItemKind, usize>,
+}
+
+impl ApiSurface {
+    pub(crate) fn of(crate_: &Crate, dependencies: &[Crate]) -> Self {
+        let graph = CrateGraph {
+            main: crate_,
+            dependencies,
+        };
+        let mut counts: BTreeMap<ItemKind, usize> = BTreeMap::new();
+        let mut methods: HashSet<ItemKey> = HashSet::new();
+        for (item_crate, item, paths) in public_item_paths(graph) {
+            if paths.is_empty() {
+                continue;
+            }
+            if let Some(kind) = ItemKind::of(&item.inner) {
+                *counts.entry(kind).or_default() += 1;
+            }
+            let impls = match &item.inner {
+                ItemEnum::Struct(struct_) => &struct_.impls,
+                ItemEnum::Enum(enum_) => &enum_.impls,
+                ItemEnum::Union(union_) => &union_.impls,
+                _ => continue,
+            };
+            let inherent_items = impls
+                .iter()
+                .filter_map(|id| match &item_crate.index.get(id)?.inner {
+                    ItemEnum::Impl(impl_) if impl_.trait_.is_none() => Some(&impl_.items),
+                    _ => None,
+                })
+                .flatten();
+            for id in inherent_items {
+                if let Some(method) = item_crate.index.get(id) {
+                    if matches!(method.inner, ItemEnum::Method(_))
+                        && method.visibility == Visibility::Public
+                        && !is_doc_hidden(&method.attrs)
+                    {
+                        methods.insert((CrateRef(item_crate), id));
+                    }
+                }
+            }
+        }
+        if !methods.is_empty() {
+            counts.insert(ItemKind::Method, methods.len());
+        }
+        Self { counts }
+    }
+
+    pub(crate) fn count(&self, kind: ItemKind) -> usize {
+        self.counts.get(&kind).copied().unwrap_or_default()
+    }
+}
+
+/// The public items of each kind in the baseline and the current version,
+/// for the kinds that either of them has.
+pub(crate) fn compare(
+    baseline: &ApiSurface,
+    current: &ApiSurface,
+) -> Vec<(ItemKind, usize, usize)> {
+    let kinds: BTreeSet<ItemKind> = baseline
+        .counts
+        .keys()
+        .chain(current.counts.keys())
+        .copied()
+        .collect();
+    kinds
+        .into_iter()
+        .map(|kind| (kind, baseline.count(kind), current.count(kind)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use rustdoc_types::{Crate, FORMAT_VERSION};
+    use serde_json::json;
+
+    use super::{compare, ApiSurface, ItemKind};
+
+    fn item(
+        id: &str,
+        name: &str,
+        kind: &str,
+        visibility: &str,
+        inner: serde_json::Value,
+    ) -> serde_json::Value {
+        json!({
+            "id": id, "crate_id": 0, "name": name, "span": null, "visibility": visibility,
+            "docs": null, "links": {}, "attrs": [], "deprecation": null, "kind": kind, "inner": inner,
+        })
+    }
+
+    /// A crate with a public struct that has a public and a private method,
+    /// a function that's exported twice, and a private module.
+    fn example_crate() -> Crate {
+        let generics = json!({ "params": [], "where_predicates": [] });
+        let header = json!({ "const": false, "unsafe": false, "async": false, "abi": "Rust" });
+        let decl = json!({ "inputs": [], "output": null, "c_variadic": false });
+        let method =
+            json!({ "decl": decl, "generics": generics, "header": header, "has_body": true });
+        serde_json::from_value(json!({
+            "root": "0:0",
+            "crate_version": "1.0.0",
+            "includes_private": false,
+            "index": {
+                "0:0": item("0:0", "demo", "module", "public", json!({ "is_crate": true, "items": ["0:1", "0:2", "0:3", "0:6"] })),
+                "0:1": item("0:1", "Foo", "struct", "public", json!({
+                    "struct_type": "unit", "generics": generics, "fields_stripped": false,
+                    "fields": [], "impls": ["0:4"],
+                })),
+                "0:2": item("0:2", "bar", "function", "public", json!({
+                    "decl": decl, "generics": generics, "header": header,
+                })),
+                "0:3": item("0:3", "reexport", "import", "public", json!({
+                    "source": "crate::bar", "name": "baz", "id": "0:2", "glob": false,
+                })),
+                "0:4": item("0:4", "", "impl", "default", json!({
+                    "is_unsafe": false, "generics": generics, "provided_trait_methods": [],
+                    "trait": null, "for": { "kind": "resolved_path", "inner": {
+                        "name": "Foo", "id": "0:1", "args": null, "param_names": [],
+                    } }, "items": ["0:5", "0:7"], "negative": false, "synthetic": false,
+                    "blanket_impl": null,
+                })),
+                "0:5": item("0:5", "new", "method", "public", method.clone()),
+                "0:7": item("0:7", "helper", "method", "crate", method),
+                "0:6": item("0:6", "private", "module", "crate", json!({ "is_crate": false, "items": [] })),
+            },
+            "paths": {},
+            "external_crates": {},
+            "format_version": FORMAT_VERSION,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn public_items_are_counted_once_by_kind() {
+        let surface = ApiSurface::of(&example_crate(), &[]);
+        assert_eq!(0, surface.count(ItemKind::Module));
+        assert_eq!(1, surface.count(ItemKind::Struct));
+        assert_eq!(1, surface.count(ItemKind::Function));
+        assert_eq!(1, surface.count(ItemKind::Method));
+        assert_eq!(0, surface.count(ItemKind::Trait));
+
+        assert_eq!(
+            vec![
+                (ItemKind::Function, 1, 0),
+                (ItemKind::Method, 1, 1),
+                (ItemKind::Struct, 1, 1),
+            ],
+            compare(
+                &surface,
+                &ApiSurface {
+                    counts: surface
+                        .counts
+                        .iter()
+                        .filter(|(kind, _)| **kind != ItemKind::Function)
+                        .map(|(kind, count)| (*kind, *count))
+                        .collect(),
+                }
+            )
+        );
+        // Crates without public items have nothing to compare, and no table is printed.
+        assert!(compare(&ApiSurface::default(), &ApiSurface::default()).is_empty());
+    }
+}
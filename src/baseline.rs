@@ -0,0 +1,1099 @@
+use std::{
+    ffi::OsStr,
+    fs::{File, OpenOptions, TryLockError},
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+use rustdoc_types::Crate;
+use sha2::{Digest, Sha256};
+use termcolor::Color;
+use tracing::debug;
+
+use crate::{
+    docs_rs::{download_rustdoc_json, DocsRsRelease},
+    interrupt::TempPath,
+    rustdoc_gen::{
+        host_triple, load_package_info, resolve_dependency_version, toolchain_version, CargoFlags,
+        PackageInfo, RustdocCommand,
+    },
+    snapshot::load_snapshot,
+    util::{fnv1a, parse_rustdoc, SUPPORTED_FORMAT_VERSIONS},
+    StatusPrinter,
+};
+
+/// Marks errors caused by being unable to obtain the baseline, as opposed to other failures,
+/// so that they can be reported with their own exit code.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct BaselineUnavailable;
+
+impl std::fmt::Display for BaselineUnavailable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Failed to obtain the baseline")
+    }
+}
+
+/// Where to get the baseline version of the crate from.
+#[derive(Debug, Clone)]
+pub(crate) enum BaselineSource {
+    /// A previously-generated rustdoc JSON file.
+    RustdocFile(PathBuf),
+
+    /// A snapshot of the baseline's API, written by `dump-api`.
+    Snapshot(PathBuf),
+
+    /// A directory containing the baseline version of the crate's source.
+    Root(PathBuf),
+
+    /// A version of the crate published in a registry. If no registry is specified,
+    /// the crate is downloaded from crates.io.
+    Registry {
+        crate_name: String,
+        version: String,
+        registry: Option<String>,
+    },
+}
+
+impl BaselineSource {
+    /// The release whose documentation docs.rs hosts, for versions published on crates.io.
+    pub(crate) fn docs_rs_release(&self) -> Option<DocsRsRelease> {
+        match self {
+            Self::Registry {
+                crate_name,
+                version,
+                registry: None,
+            } => Some(DocsRsRelease {
+                crate_name: crate_name.clone(),
+                version: version.clone(),
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Identifies a cached baseline rustdoc JSON file.
+///
+/// Rustdoc output depends on the crate version, the enabled features, the target, the build flags,
+/// and the toolchain that generated it. Cached files are also named after the format version
+/// they were actually generated in, and are only used if it's one we are able to parse.
+/// Crates from registries other than crates.io are cached separately.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct CacheKey {
+    registry: Option<String>,
+    crate_name: String,
+    version: String,
+    features: Vec<String>,
+    target: String,
+    build_flags: Vec<String>,
+    /// The toolchain's full version, since a channel name like `nightly`
+    /// refers to a different toolchain over time.
+    toolchain: String,
+}
+
+impl CacheKey {
+    pub(crate) fn new(
+        registry: Option<&str>,
+        crate_name: &str,
+        version: &str,
+        rustdoc_cmd: &RustdocCommand,
+    ) -> anyhow::Result<Self> {
+        let target = match rustdoc_cmd.target_triple() {
+            Some(target) => target.to_string(),
+            None => host_triple()?,
+        };
+        let toolchain = rustdoc_cmd.active_toolchain();
+        Ok(Self {
+            registry: registry.map(ToString::to_string),
+            crate_name: crate_name.to_string(),
+            version: version.to_string(),
+            features: rustdoc_cmd.active_features().to_vec(),
+            target,
+            build_flags: rustdoc_cmd.active_build_flags().describe(),
+            toolchain: toolchain_version(toolchain).unwrap_or_else(|| toolchain.to_string()),
+        })
+    }
+
+    /// The path of the cached file generated in the given format version.
+    fn relative_path(&self, format_version: u32) -> PathBuf {
+        let mut features = if self.features.is_empty() {
+            "default".to_string()
+        } else {
+            format!("default+{}", self.features.join("+"))
+        };
+        if !self.build_flags.is_empty() {
+            // Flags can contain any characters, so they are identified by their hash instead.
+            features.push_str(&format!(
+                "-flags{:016x}",
+                fnv1a(&self.build_flags.join("\n"))
+            ));
+        }
+
+        let mut path = PathBuf::new();
+        if let Some(registry) = self.registry.as_deref() {
+            path.push(format!("registry-{registry}"));
+        }
+        path.push(format!("{}-{}", self.crate_name, self.version));
+        path.push(&self.target);
+        path.push(format!("toolchain-{:016x}", fnv1a(&self.toolchain)));
+        path.push(format!("{}-v{}.json", features, format_version));
+        path
+    }
+}
+
+/// An on-disk cache of generated baseline rustdoc JSON files.
+#[derive(Debug, Clone)]
+pub(crate) struct BaselineCache {
+    dir: PathBuf,
+    /// Whether to save the parsed form of loaded rustdoc JSON files, and reuse it.
+    keeps_parsed: bool,
+}
+
+impl BaselineCache {
+    pub(crate) fn new(dir: PathBuf) -> Self {
+        Self {
+            dir,
+            keeps_parsed: true,
+        }
+    }
+
+    /// Load the cached baseline, preferring the newest format version if the toolchain's output
+    /// was cached in more than one, such as across upgrades of cargo-semver-checks.
+    pub(crate) fn load(&self, key: &CacheKey) -> anyhow::Result<Option<Crate>> {
+        let cached = SUPPORTED_FORMAT_VERSIONS
+            .rev()
+            .map(|format_version| self.dir.join(key.relative_path(format_version)))
+            .find(|path| path.is_file());
+        match cached {
+            Some(path) => self.load_rustdoc(&path).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Load a baseline's rustdoc JSON, reusing the parsed form that was saved the last time
+    /// rustdoc JSON with exactly the same contents was loaded, so that it needn't be parsed again.
+    ///
+    /// The parsed form is MessagePack, keyed by the SHA-256 hash of the JSON. It's specific to
+    /// this version of cargo-semver-checks, so that changes to `rustdoc-types` can't make it
+    /// be misread.
+    pub(crate) fn load_rustdoc(&self, path: &Path) -> anyhow::Result<Crate> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read rustdoc JSON output file {:?}", path))?;
+        if !self.keeps_parsed {
+            return parse_rustdoc(&contents)
+                .with_context(|| format!("Failed to parse rustdoc JSON output file {:?}", path));
+        }
+        let digest: String = Sha256::digest(&contents)
+            .iter()
+            .map(|byte| format!("{byte:02x}"))
+            .collect();
+        let parsed_path = self
+            .dir
+            .join("parsed")
+            .join(format!("{digest}-{}.bin", env!("CARGO_PKG_VERSION"),));
+        if let Ok(encoded) = std::fs::read(&parsed_path) {
+            match rmp_serde::from_slice(&encoded) {
+                Ok(baseline) => {
+                    debug!("using parsed baseline {:?} for {:?}", parsed_path, path);
+                    return Ok(baseline);
+                }
+                Err(e) => debug!("failed to read parsed baseline {:?}: {e}", parsed_path),
+            }
+        }
+
+        let baseline = parse_rustdoc(&contents)
+            .with_context(|| format!("Failed to parse rustdoc JSON output file {:?}", path))?;
+        // Free the JSON before encoding the baseline, to not hold both at once.
+        drop(contents);
+        // Not being able to save it only makes the next run slower.
+        if let Err(e) = Self::store_parsed(&baseline, &parsed_path) {
+            debug!("failed to save parsed baseline {:?}: {e:#}", parsed_path);
+        }
+        Ok(baseline)
+    }
+
+    fn store_parsed(baseline: &Crate, path: &Path) -> anyhow::Result<()> {
+        let parent = path.parent().expect("cache path has no parent");
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create cache directory {:?}", parent))?;
+        // Structs are saved with their field names, since the tagged enums of `rustdoc-types`
+        // can only be read back from them.
+        let encoded = rmp_serde::to_vec_named(baseline)?;
+        let temp = TempPath::next_to(path);
+        std::fs::write(temp.path(), encoded)
+            .with_context(|| format!("Failed to write parsed baseline {:?}", path))?;
+        temp.persist(path)
+    }
+
+    /// Cache rustdoc JSON that was generated in the given format version.
+    pub(crate) fn store(
+        &self,
+        key: &CacheKey,
+        format_version: u32,
+        rustdoc_json_path: &Path,
+    ) -> anyhow::Result<()> {
+        let path = self.dir.join(key.relative_path(format_version));
+        let parent = path.parent().expect("cache path has no parent");
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create cache directory {:?}", parent))?;
+        // Copy via a temporary file, so that an interrupted copy can't corrupt the cache.
+        let temp = TempPath::next_to(&path);
+        std::fs::copy(rustdoc_json_path, temp.path())
+            .with_context(|| format!("Failed to write cached baseline {:?}", path))?;
+        temp.persist(&path)
+    }
+}
+
+/// Loads baseline rustdoc JSON from any [`BaselineSource`], generating it if necessary.
+///
+/// Generated baselines are cached. Baselines published on crates.io are downloaded
+/// from docs.rs when possible, instead of being generated locally, unless offline.
+///
+/// Baselines are built in a target directory of their own inside the working directory,
+/// so they neither invalidate nor are invalidated by the user's builds. The working directory
+/// is locked while in use, so that concurrent invocations don't corrupt each other's builds.
+#[derive(Debug, Clone)]
+pub(crate) struct BaselineLoader {
+    cache: BaselineCache,
+    work_dir: PathBuf,
+    scratch_dir: PathBuf,
+    build_dir: PathBuf,
+    cargo_flags: CargoFlags,
+    use_docs_rs: bool,
+    status: StatusPrinter,
+}
+
+impl BaselineLoader {
+    /// Create a loader that keeps its working files in `work_dir`, and caches
+    /// baselines in `cache_dir` if set or in a subdirectory of `work_dir` otherwise.
+    pub(crate) fn new(
+        work_dir: &Path,
+        cache_dir: Option<PathBuf>,
+        cargo_flags: CargoFlags,
+        use_docs_rs: bool,
+        status: StatusPrinter,
+    ) -> Self {
+        let cache_dir = cache_dir.unwrap_or_else(|| work_dir.join("cache"));
+        Self {
+            cache: BaselineCache::new(cache_dir),
+            work_dir: work_dir.to_path_buf(),
+            scratch_dir: work_dir.join("registry"),
+            build_dir: work_dir.join("target"),
+            cargo_flags,
+            use_docs_rs,
+            status,
+        }
+    }
+
+    /// Load rustdoc JSON files without saving their parsed form, for when there's no
+    /// directory of the user's to save it in.
+    pub(crate) fn without_parsed_cache(mut self) -> Self {
+        self.cache.keeps_parsed = false;
+        self
+    }
+
+    /// The directory in which baseline rustdoc JSON files are cached.
+    pub(crate) fn cache_dir(&self) -> &Path {
+        &self.cache.dir
+    }
+
+    /// The directory in which baselines are downloaded and built.
+    pub(crate) fn work_dir(&self) -> &Path {
+        &self.work_dir
+    }
+
+    /// Remove every cached baseline, and everything downloaded or built in the working directory.
+    pub(crate) fn clean(&self) -> anyhow::Result<()> {
+        let _lock = self.lock()?;
+        if self.cache.dir.exists() {
+            std::fs::remove_dir_all(&self.cache.dir)
+                .with_context(|| format!("Failed to remove {:?}", self.cache.dir))?;
+        }
+        let entries = std::fs::read_dir(&self.work_dir)
+            .with_context(|| format!("Failed to read directory {:?}", self.work_dir))?;
+        for entry in entries {
+            let path = entry?.path();
+            // The lock file is in use until we are done.
+            if path.file_name() == Some(OsStr::new(".lock")) {
+                continue;
+            }
+            if path.is_dir() {
+                std::fs::remove_dir_all(&path)
+            } else {
+                std::fs::remove_file(&path)
+            }
+            .with_context(|| format!("Failed to remove {:?}", path))?;
+        }
+        Ok(())
+    }
+
+    /// The working directory used when one isn't explicitly specified:
+    /// a subdirectory of the target directory of the crate being checked.
+    pub(crate) fn default_work_dir(manifest_path: &Path) -> anyhow::Result<PathBuf> {
+        Ok(Self::package_work_dir(&load_package_info(manifest_path)?))
+    }
+
+    /// The default working directory for the given package.
+    pub(crate) fn package_work_dir(package: &PackageInfo) -> PathBuf {
+        package.target_directory.join("semver-checks")
+    }
+
+    pub(crate) fn load(
+        &self,
+        source: &BaselineSource,
+        rustdoc_cmd: &RustdocCommand,
+    ) -> anyhow::Result<Crate> {
+        let (registry, crate_name, version) = match source {
+            BaselineSource::RustdocFile(path) => {
+                return self.cache.load_rustdoc(path);
+            }
+            BaselineSource::Snapshot(path) => return load_snapshot(path),
+            BaselineSource::Root(root) => {
+                let package = load_package_info(&root.join("Cargo.toml"))?;
+                (None, package.name, package.version)
+            }
+            BaselineSource::Registry {
+                crate_name,
+                version,
+                registry,
+            } => (registry.as_deref(), crate_name.clone(), version.clone()),
+        };
+
+        let key = CacheKey::new(registry, &crate_name, &version, rustdoc_cmd)?;
+        if let Some(cached) = self.cache.load(&key)? {
+            debug!(
+                "using cached baseline {crate_name} v{version} for {}",
+                key.target
+            );
+            return Ok(cached);
+        }
+
+        let _lock = self.lock()?;
+        // Another invocation may have generated the baseline while we waited for the lock.
+        if let Some(cached) = self.cache.load(&key)? {
+            debug!("using baseline {crate_name} v{version} cached while waiting for the lock");
+            return Ok(cached);
+        }
+
+        if let Some(json_path) = self.download_from_docs_rs(source, &key) {
+            return self.load_and_cache(&key, &json_path);
+        }
+
+        let rustdoc_cmd = rustdoc_cmd
+            .clone()
+            .target_dir(Some(self.build_dir.clone()))
+            .cargo_flags(self.cargo_flags);
+        let json_path = match source {
+            BaselineSource::Root(root) => rustdoc_cmd.generate_json_file(&root.join("Cargo.toml")),
+            BaselineSource::Registry { .. } => {
+                let placeholder_manifest = self.write_placeholder_manifest(
+                    registry,
+                    &crate_name,
+                    &format!("={version}"),
+                    &format!("{crate_name}-{version}"),
+                )?;
+                rustdoc_cmd.generate_dependency_json_file(
+                    &placeholder_manifest,
+                    &crate_name,
+                    &version,
+                )
+            }
+            BaselineSource::RustdocFile(..) | BaselineSource::Snapshot(..) => {
+                unreachable!("handled above")
+            }
+        }
+        .with_context(|| {
+            if self.cargo_flags.offline {
+                format!(
+                    "No cached baseline found for {} v{} (target {}), and it could not be \
+                    built offline. Run without --offline to download what it needs.",
+                    crate_name, version, key.target,
+                )
+            } else {
+                format!("Failed to build baseline {} v{}", crate_name, version)
+            }
+        })?;
+        self.load_and_cache(&key, &json_path)
+    }
+
+    /// Load a newly-obtained baseline, caching it only if it could be loaded successfully.
+    fn load_and_cache(&self, key: &CacheKey, json_path: &Path) -> anyhow::Result<Crate> {
+        let baseline = self.cache.load_rustdoc(json_path)?;
+        self.cache.store(key, baseline.format_version, json_path)?;
+        Ok(baseline)
+    }
+
+    /// Download the baseline's rustdoc JSON from docs.rs, returning the path it was saved at.
+    ///
+    /// Only crates.io crates documented without extra features or build flags are fetched,
+    /// since docs.rs only hosts those. Any failure, including docs.rs not having the file or not being
+    /// reachable, returns `None` so that the baseline is generated locally instead.
+    fn download_from_docs_rs(&self, source: &BaselineSource, key: &CacheKey) -> Option<PathBuf> {
+        let is_crates_io_crate = matches!(source, BaselineSource::Registry { registry: None, .. });
+        if !self.use_docs_rs
+            || self.cargo_flags.offline
+            || !is_crates_io_crate
+            || !key.features.is_empty()
+            || !key.build_flags.is_empty()
+        {
+            return None;
+        }
+
+        let json_path = self
+            .scratch_dir
+            .join("docs-rs")
+            .join(format!("{}-{}", key.crate_name, key.version))
+            .join(format!("{}.json", key.target));
+        match download_rustdoc_json(&key.crate_name, &key.version, &key.target, &json_path) {
+            Ok(true) => {
+                debug!("downloaded baseline from docs.rs to {:?}", json_path);
+                Some(json_path)
+            }
+            Ok(false) => {
+                debug!(
+                    "docs.rs has no rustdoc JSON for {} v{} in a supported format",
+                    key.crate_name, key.version
+                );
+                None
+            }
+            Err(e) => {
+                debug!("downloading from docs.rs failed, building locally instead: {e:#}");
+                None
+            }
+        }
+    }
+
+    /// The largest version of the crate published in the registry that is smaller
+    /// than `below_version`, or `None` if there isn't one.
+    pub(crate) fn find_latest_version(
+        &self,
+        registry: Option<&str>,
+        crate_name: &str,
+        below_version: &str,
+    ) -> anyhow::Result<Option<String>> {
+        self.find_matching_version(registry, crate_name, &format!("<{below_version}"))
+    }
+
+    /// The published versions of the crate that are semver-compatible with, and smaller than,
+    /// `current_version`, from largest to smallest.
+    pub(crate) fn find_compatible_versions(
+        &self,
+        registry: Option<&str>,
+        crate_name: &str,
+        current_version: &semver::Version,
+    ) -> anyhow::Result<Vec<String>> {
+        // Only changes in the left-most non-zero version component are incompatible.
+        let lowest_compatible = match (current_version.major, current_version.minor) {
+            (0, 0) => return Ok(vec![]),
+            (0, minor) => format!("0.{minor}.0"),
+            (major, _) => format!("{major}.0.0"),
+        };
+
+        let mut versions: Vec<String> = vec![];
+        let mut below_version = current_version.to_string();
+        while let Some(version) = self.find_matching_version(
+            registry,
+            crate_name,
+            &format!(">={lowest_compatible}, <{below_version}"),
+        )? {
+            below_version = version.clone();
+            versions.push(version);
+        }
+        Ok(versions)
+    }
+
+    /// The largest version of the crate published in the registry that matches
+    /// the version requirement, or `None` if there isn't one.
+    fn find_matching_version(
+        &self,
+        registry: Option<&str>,
+        crate_name: &str,
+        version_req: &str,
+    ) -> anyhow::Result<Option<String>> {
+        let _lock = self.lock()?;
+        let placeholder_manifest = self.write_placeholder_manifest(
+            registry,
+            crate_name,
+            version_req,
+            &format!("{crate_name}-latest"),
+        )?;
+        resolve_dependency_version(&placeholder_manifest, crate_name, self.cargo_flags.offline)
+    }
+
+    /// Take an exclusive lock on the working directory, waiting for other invocations
+    /// to release it if necessary. The lock is released when the returned file is dropped.
+    fn lock(&self) -> anyhow::Result<File> {
+        std::fs::create_dir_all(&self.work_dir)
+            .with_context(|| format!("Failed to create directory {:?}", self.work_dir))?;
+        let lock_path = self.work_dir.join(".lock");
+        let lock_file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(false)
+            .open(&lock_path)
+            .with_context(|| format!("Failed to open lock file {:?}", lock_path))?;
+
+        match lock_file.try_lock() {
+            Ok(()) => {}
+            Err(TryLockError::WouldBlock) => {
+                self.status.print(
+                    Color::Cyan,
+                    "Blocking",
+                    format_args!("waiting for file lock on {:?}", self.work_dir),
+                );
+                lock_file
+                    .lock()
+                    .with_context(|| format!("Failed to lock {:?}", lock_path))?;
+            }
+            Err(TryLockError::Error(e)) => {
+                return Err(e).with_context(|| format!("Failed to lock {:?}", lock_path));
+            }
+        }
+        Ok(lock_file)
+    }
+
+    /// Write a manifest for an empty crate whose only dependency is the crate being
+    /// checked, with the given version requirement. Cargo then takes care of
+    /// downloading the crate from the appropriate registry, as configured
+    /// in `.cargo/config.toml`.
+    fn write_placeholder_manifest(
+        &self,
+        registry: Option<&str>,
+        crate_name: &str,
+        version_req: &str,
+        dir_name: &str,
+    ) -> anyhow::Result<PathBuf> {
+        let mut dir = self.scratch_dir.clone();
+        if let Some(registry) = registry {
+            dir.push(format!("registry-{registry}"));
+        }
+        dir.push(dir_name);
+        let src_dir = dir.join("src");
+        std::fs::create_dir_all(&src_dir)
+            .with_context(|| format!("Failed to create directory {:?}", src_dir))?;
+        std::fs::write(src_dir.join("lib.rs"), "")
+            .with_context(|| format!("Failed to write placeholder crate in {:?}", dir))?;
+
+        let registry_field = registry
+            .map(|registry| format!(", registry = {registry:?}"))
+            .unwrap_or_default();
+        let manifest = format!(
+            "\
+[package]
+name = \"semver-checks-baseline-placeholder\"
+version = \"0.0.0\"
+edition = \"2021\"
+publish = false
+
+[dependencies]
+{crate_name} = {{ version = \"{version_req}\"{registry_field} }}
+
+[workspace]
+"
+        );
+        let manifest_path = dir.join("Cargo.toml");
+        std::fs::write(&manifest_path, manifest)
+            .with_context(|| format!("Failed to write placeholder manifest {:?}", manifest_path))?;
+
+        Ok(manifest_path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use std::collections::HashMap;
+
+    use rustdoc_types::{
+        Abi, Constant, Crate, Deprecation, Enum, ExternalCrate, FnDecl, Function, FunctionPointer,
+        GenericArg, GenericArgs, GenericBound, GenericParamDef, GenericParamDefKind, Generics,
+        Header, Id, Impl, Import, Item, ItemEnum, ItemSummary, MacroKind, Method, Module, OpaqueTy,
+        ProcMacro, Span, Static, Struct, StructType, Term, Trait, TraitAlias, TraitBoundModifier,
+        Type, TypeBinding, TypeBindingKind, Typedef, Union, Variant, Visibility, WherePredicate,
+        FORMAT_VERSION,
+    };
+
+    use super::{BaselineCache, CacheKey};
+    use crate::util::fnv1a;
+
+    #[test]
+    fn cache_path_includes_every_key_component() {
+        let key = CacheKey {
+            registry: None,
+            crate_name: "foo".to_string(),
+            version: "1.2.3".to_string(),
+            features: vec!["alloc".to_string(), "serde".to_string()],
+            target: "wasm32-unknown-unknown".to_string(),
+            build_flags: vec![],
+            toolchain: "rustc 1.66.0-nightly (b8c35ca26 2022-10-15)".to_string(),
+        };
+        let toolchain_dir = format!("toolchain-{:016x}", fnv1a(&key.toolchain));
+        assert_eq!(
+            PathBuf::from(format!(
+                "foo-1.2.3/wasm32-unknown-unknown/{toolchain_dir}/default+alloc+serde-v15.json"
+            )),
+            key.relative_path(15),
+        );
+
+        let key = CacheKey {
+            registry: Some("my-registry".to_string()),
+            features: vec![],
+            ..key
+        };
+        assert_eq!(
+            PathBuf::from(format!(
+                "registry-my-registry/foo-1.2.3/wasm32-unknown-unknown/{toolchain_dir}/default-v16.json"
+            )),
+            key.relative_path(16),
+        );
+
+        let other_toolchain = CacheKey {
+            toolchain: "rustc 1.67.0-nightly (01af5040f 2022-10-31)".to_string(),
+            ..key.clone()
+        };
+        assert_ne!(key.relative_path(16), other_toolchain.relative_path(16));
+
+        let with_flags = |flags: &str| CacheKey {
+            build_flags: vec![flags.to_string()],
+            ..key.clone()
+        };
+        let path = with_flags(r#"RUSTFLAGS="--cfg foo""#).relative_path(15);
+        assert!(
+            path.to_string_lossy().contains("/default-flags"),
+            "{}",
+            path.display()
+        );
+        assert_ne!(
+            path,
+            with_flags(r#"RUSTFLAGS="--cfg bar""#).relative_path(15)
+        );
+    }
+
+    #[test]
+    fn cached_baselines_are_found_in_the_format_version_they_were_generated_in() {
+        let dir = std::env::temp_dir().join(format!("semver-cache-key-{}", std::process::id()));
+        let json_path = dir.join("baseline.json");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            &json_path,
+            format!(
+                r#"{{"root": "0:0", "crate_version": "1.0.0", "includes_private": false,
+                "index": {{}}, "paths": {{}}, "external_crates": {{}}, "format_version": {}}}"#,
+                FORMAT_VERSION,
+            ),
+        )
+        .unwrap();
+        let key = CacheKey {
+            registry: None,
+            crate_name: "foo".to_string(),
+            version: "1.0.0".to_string(),
+            features: vec![],
+            target: "x86_64-unknown-linux-gnu".to_string(),
+            build_flags: vec![],
+            toolchain: "rustc 1.66.0-nightly (b8c35ca26 2022-10-15)".to_string(),
+        };
+        let cache = BaselineCache::new(dir.join("cache"));
+        assert!(cache.load(&key).unwrap().is_none());
+
+        cache.store(&key, FORMAT_VERSION + 1, &json_path).unwrap();
+        assert!(dir
+            .join("cache")
+            .join(key.relative_path(FORMAT_VERSION + 1))
+            .is_file());
+        let cached = cache
+            .load(&key)
+            .unwrap()
+            .expect("cached baseline not found");
+        assert_eq!(Some("1.0.0"), cached.crate_version.as_deref());
+
+        // Output of a different toolchain isn't reused.
+        let other_toolchain = CacheKey {
+            toolchain: "rustc 1.67.0-nightly (01af5040f 2022-10-31)".to_string(),
+            ..key
+        };
+        assert!(cache.load(&other_toolchain).unwrap().is_none());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn parsed_baselines_are_reused_for_the_same_contents() {
+        let dir = std::env::temp_dir().join(format!("semver-parsed-{}", std::process::id()));
+        let json_path = dir.join("baseline.json");
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(
+            &json_path,
+            format!(
+                r#"{{"root": "0:0", "crate_version": "1.0.0", "includes_private": false,
+                "index": {{}}, "paths": {{}}, "external_crates": {{}}, "format_version": {}}}"#,
+                FORMAT_VERSION,
+            ),
+        )
+        .unwrap();
+        let cache = BaselineCache::new(dir.join("cache"));
+
+        let parsed = cache.load_rustdoc(&json_path).unwrap();
+        assert_eq!(Some("1.0.0"), parsed.crate_version.as_deref());
+        let parsed_paths: Vec<PathBuf> = std::fs::read_dir(dir.join("cache/parsed"))
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .collect();
+        assert_eq!(1, parsed_paths.len());
+
+        // What was saved is what's loaded, without parsing the JSON again.
+        let saved = Crate {
+            crate_version: Some("2.0.0".to_string()),
+            ..parsed
+        };
+        std::fs::write(&parsed_paths[0], rmp_serde::to_vec_named(&saved).unwrap()).unwrap();
+        let reused = cache.load_rustdoc(&json_path).unwrap();
+        assert_eq!(Some("2.0.0"), reused.crate_version.as_deref());
+
+        // If the saved form can't be read, the JSON is parsed again.
+        std::fs::write(&parsed_paths[0], b"not valid").unwrap();
+        let reparsed = cache.load_rustdoc(&json_path).unwrap();
+        assert_eq!(Some("1.0.0"), reparsed.crate_version.as_deref());
+
+        // Without the parsed cache, nothing is saved.
+        let uncached = BaselineCache {
+            keeps_parsed: false,
+            ..BaselineCache::new(dir.join("uncached"))
+        };
+        let parsed = uncached.load_rustdoc(&json_path).unwrap();
+        assert_eq!(Some("1.0.0"), parsed.crate_version.as_deref());
+        assert!(!dir.join("uncached").exists());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /// A crate with an item of every kind, whose types and generics use every kind of type,
+    /// generic parameter, bound and predicate.
+    fn crate_with_every_kind_of_item() -> Crate {
+        let id = |id: &str| Id(id.to_string());
+        let primitive = |name: &str| Type::Primitive(name.to_string());
+        let header = Header {
+            const_: false,
+            unsafe_: true,
+            async_: false,
+            abi: Abi::C { unwind: true },
+        };
+        let decl = FnDecl {
+            inputs: vec![
+                ("value".to_string(), Type::Generic("T".to_string())),
+                ("items".to_string(), Type::Slice(Box::new(primitive("u8")))),
+            ],
+            output: Some(Type::Tuple(vec![])),
+            c_variadic: false,
+        };
+        let trait_bound = GenericBound::TraitBound {
+            trait_: Type::ResolvedPath {
+                name: "Example".to_string(),
+                id: id("0:9"),
+                args: Some(Box::new(GenericArgs::AngleBracketed {
+                    args: vec![
+                        GenericArg::Lifetime("'a".to_string()),
+                        GenericArg::Type(primitive("i64")),
+                        GenericArg::Infer,
+                    ],
+                    bindings: vec![TypeBinding {
+                        name: "Item".to_string(),
+                        args: GenericArgs::Parenthesized {
+                            inputs: vec![],
+                            output: None,
+                        },
+                        binding: TypeBindingKind::Equality(Term::Type(Type::Infer)),
+                    }],
+                })),
+                param_names: vec![],
+            },
+            generic_params: vec![],
+            modifier: TraitBoundModifier::Maybe,
+        };
+        let constant = Constant {
+            type_: Type::Array {
+                type_: Box::new(primitive("u8")),
+                len: "4".to_string(),
+            },
+            expr: "[0; 4]".to_string(),
+            value: None,
+            is_literal: false,
+        };
+        let generics = Generics {
+            params: vec![
+                GenericParamDef {
+                    name: "'a".to_string(),
+                    kind: GenericParamDefKind::Lifetime {
+                        outlives: vec!["'static".to_string()],
+                    },
+                },
+                GenericParamDef {
+                    name: "T".to_string(),
+                    kind: GenericParamDefKind::Type {
+                        bounds: vec![
+                            trait_bound.clone(),
+                            GenericBound::Outlives("'a".to_string()),
+                        ],
+                        default: Some(Type::ImplTrait(vec![trait_bound.clone()])),
+                        synthetic: false,
+                    },
+                },
+                GenericParamDef {
+                    name: "N".to_string(),
+                    kind: GenericParamDefKind::Const {
+                        type_: primitive("usize"),
+                        default: Some("3".to_string()),
+                    },
+                },
+            ],
+            where_predicates: vec![
+                WherePredicate::BoundPredicate {
+                    type_: Type::BorrowedRef {
+                        lifetime: Some("'a".to_string()),
+                        mutable: true,
+                        type_: Box::new(Type::Generic("T".to_string())),
+                    },
+                    bounds: vec![trait_bound.clone()],
+                    generic_params: vec![],
+                },
+                WherePredicate::RegionPredicate {
+                    lifetime: "'a".to_string(),
+                    bounds: vec![GenericBound::Outlives("'static".to_string())],
+                },
+                WherePredicate::EqPredicate {
+                    lhs: Type::QualifiedPath {
+                        name: "Item".to_string(),
+                        args: Box::new(GenericArgs::AngleBracketed {
+                            args: vec![GenericArg::Const(constant.clone())],
+                            bindings: vec![],
+                        }),
+                        self_type: Box::new(Type::Generic("T".to_string())),
+                        trait_: Box::new(Type::FunctionPointer(Box::new(FunctionPointer {
+                            decl: decl.clone(),
+                            generic_params: vec![],
+                            header: header.clone(),
+                        }))),
+                    },
+                    rhs: Term::Constant(constant.clone()),
+                },
+            ],
+        };
+        let kinds = vec![
+            ItemEnum::Module(Module {
+                is_crate: true,
+                items: (1..23).map(|number| id(&format!("0:{number}"))).collect(),
+            }),
+            ItemEnum::ExternCrate {
+                name: "other".to_string(),
+                rename: Some("renamed".to_string()),
+            },
+            ItemEnum::Import(Import {
+                source: "other::Thing".to_string(),
+                name: "Thing".to_string(),
+                id: Some(id("1:0")),
+                glob: false,
+            }),
+            ItemEnum::Union(Union {
+                generics: generics.clone(),
+                fields_stripped: true,
+                fields: vec![id("0:5")],
+                impls: vec![],
+            }),
+            ItemEnum::Struct(Struct {
+                struct_type: StructType::Tuple,
+                generics: generics.clone(),
+                fields_stripped: false,
+                fields: vec![id("0:5")],
+                impls: vec![id("0:12")],
+            }),
+            ItemEnum::StructField(Type::RawPointer {
+                mutable: false,
+                type_: Box::new(primitive("u8")),
+            }),
+            ItemEnum::Enum(Enum {
+                generics: Generics::default(),
+                variants_stripped: false,
+                variants: vec![id("0:7")],
+                impls: vec![],
+            }),
+            ItemEnum::Variant(Variant::Tuple(vec![primitive("bool")])),
+            ItemEnum::Function(Function {
+                decl: decl.clone(),
+                generics: generics.clone(),
+                header: Header {
+                    abi: Abi::Rust,
+                    ..header.clone()
+                },
+            }),
+            ItemEnum::Trait(Trait {
+                is_auto: false,
+                is_unsafe: true,
+                items: vec![id("0:11")],
+                generics: generics.clone(),
+                bounds: vec![trait_bound.clone()],
+                implementations: vec![id("0:12")],
+            }),
+            ItemEnum::TraitAlias(TraitAlias {
+                generics: Generics::default(),
+                params: vec![trait_bound.clone()],
+            }),
+            ItemEnum::Method(Method {
+                decl: decl.clone(),
+                generics: Generics::default(),
+                header: Header {
+                    abi: Abi::Other("\"efiapi\"".to_string()),
+                    ..header
+                },
+                has_body: false,
+            }),
+            ItemEnum::Impl(Impl {
+                is_unsafe: false,
+                generics: generics.clone(),
+                provided_trait_methods: vec!["provided".to_string()],
+                trait_: Some(Type::ResolvedPath {
+                    name: "Example".to_string(),
+                    id: id("0:9"),
+                    args: None,
+                    param_names: vec![],
+                }),
+                for_: Type::Generic("T".to_string()),
+                items: vec![],
+                negative: false,
+                synthetic: true,
+                blanket_impl: Some(Type::Generic("T".to_string())),
+            }),
+            ItemEnum::Typedef(Typedef {
+                type_: primitive("u32"),
+                generics: Generics::default(),
+            }),
+            ItemEnum::OpaqueTy(OpaqueTy {
+                bounds: vec![trait_bound],
+                generics: Generics::default(),
+            }),
+            ItemEnum::Constant(constant),
+            ItemEnum::Static(Static {
+                type_: primitive("str"),
+                mutable: true,
+                expr: "\"ünïcode with \\\"quotes\\\"\"".to_string(),
+            }),
+            ItemEnum::ForeignType,
+            ItemEnum::Macro("macro_rules! example { () => {}; }".to_string()),
+            ItemEnum::ProcMacro(ProcMacro {
+                kind: MacroKind::Derive,
+                helpers: vec!["example".to_string()],
+            }),
+            ItemEnum::PrimitiveType("u8".to_string()),
+            ItemEnum::AssocConst {
+                type_: primitive("i8"),
+                default: Some("-1".to_string()),
+            },
+            ItemEnum::AssocType {
+                generics,
+                bounds: vec![],
+                default: Some(Type::Infer),
+            },
+        ];
+        let index = kinds
+            .into_iter()
+            .enumerate()
+            .map(|(number, inner)| {
+                let item = Item {
+                    id: id(&format!("0:{number}")),
+                    crate_id: 0,
+                    name: Some(format!("item{number}")),
+                    span: Some(Span {
+                        filename: "src/lib.rs".into(),
+                        begin: (number + 1, 0),
+                        end: (number + 2, 1),
+                    }),
+                    visibility: match number % 4 {
+                        0 => Visibility::Public,
+                        1 => Visibility::Default,
+                        2 => Visibility::Crate,
+                        _ => Visibility::Restricted {
+                            parent: id("0:0"),
+                            path: "crate::inner".to_string(),
+                        },
+                    },
+                    docs: Some("Docs with \"quotes\" and ünïcode.".to_string()),
+                    links: HashMap::from([("Example".to_string(), id("0:9"))]),
+                    attrs: vec!["#[must_use]".to_string()],
+                    deprecation: Some(Deprecation {
+                        since: Some("1.0.0".to_string()),
+                        note: None,
+                    }),
+                    inner,
+                };
+                (item.id.clone(), item)
+            })
+            .collect();
+        Crate {
+            root: id("0:0"),
+            crate_version: Some("1.2.3".to_string()),
+            includes_private: false,
+            index,
+            paths: HashMap::from([(
+                id("1:0"),
+                ItemSummary {
+                    crate_id: 1,
+                    path: vec!["other".to_string(), "Thing".to_string()],
+                    kind: rustdoc_types::ItemKind::Struct,
+                },
+            )]),
+            external_crates: HashMap::from([(
+                1,
+                ExternalCrate {
+                    name: "other".to_string(),
+                    html_root_url: None,
+                },
+            )]),
+            format_version: FORMAT_VERSION,
+        }
+    }
+
+    #[test]
+    fn parsed_baselines_round_trip_every_kind_of_item() {
+        let crate_ = crate_with_every_kind_of_item();
+        // Listing every kind of item here makes a new kind fail to compile until it's added.
+        let mut kinds: Vec<&str> = crate_
+            .index
+            .values()
+            .map(|item| match &item.inner {
+                ItemEnum::Module(_) => "module",
+                ItemEnum::ExternCrate { .. } => "extern_crate",
+                ItemEnum::Import(_) => "import",
+                ItemEnum::Union(_) => "union",
+                ItemEnum::Struct(_) => "struct",
+                ItemEnum::StructField(_) => "struct_field",
+                ItemEnum::Enum(_) => "enum",
+                ItemEnum::Variant(_) => "variant",
+                ItemEnum::Function(_) => "function",
+                ItemEnum::Trait(_) => "trait",
+                ItemEnum::TraitAlias(_) => "trait_alias",
+                ItemEnum::Method(_) => "method",
+                ItemEnum::Impl(_) => "impl",
+                ItemEnum::Typedef(_) => "typedef",
+                ItemEnum::OpaqueTy(_) => "opaque_ty",
+                ItemEnum::Constant(_) => "constant",
+                ItemEnum::Static(_) => "static",
+                ItemEnum::ForeignType => "foreign_type",
+                ItemEnum::Macro(_) => "macro",
+                ItemEnum::ProcMacro(_) => "proc_macro",
+                ItemEnum::PrimitiveType(_) => "primitive_type",
+                ItemEnum::AssocConst { .. } => "assoc_const",
+                ItemEnum::AssocType { .. } => "assoc_type",
+            })
+            .collect();
+        kinds.sort_unstable();
+        kinds.dedup();
+        assert_eq!(23, kinds.len());
+
+        let encoded = rmp_serde::to_vec_named(&crate_).unwrap();
+        let decoded: Crate = rmp_serde::from_slice(&encoded).unwrap();
+        assert_eq!(crate_, decoded);
+        assert!(rmp_serde::from_slice::<Crate>(&encoded[..encoded.len() - 1]).is_err());
+
+        // The crate reads the same from rustdoc JSON, which is what's parsed before saving it.
+        let json = serde_json::to_string(&crate_).unwrap();
+        assert_eq!(crate_, crate::util::parse_rustdoc(&json).unwrap());
+    }
+}
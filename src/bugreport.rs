@@ -0,0 +1,255 @@
+//! The `bugreport` subcommand, which gathers the diagnostics needed to investigate a bug.
+
+use std::{
+    fmt::Write as _,
+    io::Write as _,
+    path::{Path, PathBuf},
+    process::{Command, Stdio},
+};
+
+use anyhow::Context;
+use termcolor::Color;
+
+use crate::{
+    rustdoc_gen::{load_package_info, locate_manifest, resolve_toolchain},
+    util::{read_format_version, SUPPORTED_FORMAT_VERSIONS},
+    GlobalConfig,
+};
+
+/// How many lines of the reproduced command's output are included, from the end.
+const OUTPUT_LINES: usize = 100;
+
+/// Print a report with the versions of the tools involved and, if `command` isn't empty,
+/// the result of running `cargo semver-checks` with those arguments: its command line,
+/// the rustdoc JSON it read, and the end of its output.
+pub(crate) fn run_bugreport(
+    mut config: GlobalConfig,
+    manifest_path: Option<&str>,
+    command: &[String],
+) -> anyhow::Result<()> {
+    let mut report = String::from("## cargo-semver-checks bug report\n\n");
+    write_versions(&mut report);
+
+    if !command.is_empty() {
+        let mut args = vec![];
+        if let Some(manifest_path) = manifest_path {
+            args.extend(["--manifest-path".to_string(), manifest_path.to_string()]);
+        }
+        args.extend(command.iter().cloned());
+        let command_line = format!("cargo semver-checks {}", shell_words(&args));
+        config
+            .status_printer()
+            .print(Color::Green, "Running", format_args!("`{command_line}`"));
+        let exe =
+            std::env::current_exe().context("Failed to find the cargo-semver-checks executable")?;
+        let output = Command::new(exe)
+            .arg("semver-checks")
+            .args(&args)
+            // Escape codes would make the report hard to read.
+            .env("CARGO_SEMVER_CHECKS_COLOR", "never")
+            .stdin(Stdio::null())
+            .output()
+            .with_context(|| format!("Failed to run `{command_line}`"))?;
+
+        write!(report, "\n#### Command\n\n```\n{command_line}\n```\n").expect("write failed");
+        write_inputs(&mut report, &args);
+        let exit = match output.status.code() {
+            Some(code) => format!("exit code {code}"),
+            None => "terminated by a signal".to_string(),
+        };
+        write!(report, "\n#### Output ({exit})\n").expect("write failed");
+        for (stream, contents) in [("stderr", &output.stderr), ("stdout", &output.stdout)] {
+            let contents = String::from_utf8_lossy(contents);
+            if contents.trim().is_empty() {
+                continue;
+            }
+            write!(
+                report,
+                "\n{stream}:\n```text\n{}\n```\n",
+                last_lines(contents.trim_end(), OUTPUT_LINES)
+            )
+            .expect("write failed");
+        }
+    }
+
+    write!(config.output_writer, "{report}").expect("print failed");
+    Ok(())
+}
+
+fn write_versions(report: &mut String) {
+    writeln!(report, "#### Versions\n").expect("write failed");
+    writeln!(
+        report,
+        "- cargo-semver-checks: {} (rustdoc JSON format versions {}-{})",
+        env!("CARGO_PKG_VERSION"),
+        SUPPORTED_FORMAT_VERSIONS.start(),
+        SUPPORTED_FORMAT_VERSIONS.end(),
+    )
+    .expect("write failed");
+    writeln!(report, "- cargo: {}", tool_version("cargo", &["--version"])).expect("write failed");
+    writeln!(report, "- rustc: {}", tool_version("rustc", &["--version"])).expect("write failed");
+    let nightly = match resolve_toolchain(None) {
+        Ok(toolchain) => tool_version("rustc", &[&format!("+{toolchain}"), "--version"]),
+        Err(e) => format!("unavailable: {e}"),
+    };
+    writeln!(report, "- nightly rustc: {nightly}").expect("write failed");
+    writeln!(
+        report,
+        "- platform: {}-{}",
+        std::env::consts::ARCH,
+        std::env::consts::OS
+    )
+    .expect("write failed");
+}
+
+/// The first line of the tool's output, or why it couldn't be run.
+fn tool_version(program: &str, args: &[&str]) -> String {
+    match Command::new(program).args(args).output() {
+        Ok(output) if output.status.success() => String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string(),
+        Ok(output) => format!(
+            "unavailable: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        ),
+        Err(e) => format!("unavailable: {e}"),
+    }
+}
+
+/// Lists the rustdoc JSON files the command read, and the format version of each.
+fn write_inputs(report: &mut String, args: &[String]) {
+    let inputs = rustdoc_inputs(args);
+    if inputs.is_empty() {
+        return;
+    }
+    writeln!(report, "\n#### Rustdoc JSON inputs\n").expect("write failed");
+    for (path, source) in inputs {
+        let version = match read_format_version(&path) {
+            Ok(version) => format!("format version {version}"),
+            Err(e) => format!("unreadable: {e:#}"),
+        };
+        writeln!(report, "- {} ({source}): {version}", path.display()).expect("write failed");
+    }
+}
+
+/// The rustdoc JSON files given on the command line, and those generated for the current
+/// crate in its target directory, with where each came from.
+fn rustdoc_inputs(args: &[String]) -> Vec<(PathBuf, &'static str)> {
+    let mut inputs = vec![];
+    let matches = match crate::cli::try_parse_args(
+        ["cargo-semver-checks", "semver-checks"]
+            .into_iter()
+            .map(String::from)
+            .chain(args.iter().cloned()),
+    ) {
+        Ok(matches) => matches,
+        // Reproducing a command line that can't be parsed is a bug report too.
+        Err(_) => return inputs,
+    };
+    let semver_check = matches
+        .subcommand_matches("semver-checks")
+        .expect("semver-checks is missing");
+    if let Some((_, subcommand)) = semver_check.subcommand() {
+        for (id, source) in [
+            ("current_rustdoc_path", "--current"),
+            ("baseline_rustdoc_path", "--baseline"),
+        ] {
+            if let Ok(Some(path)) = subcommand.try_get_one::<String>(id) {
+                inputs.push((PathBuf::from(path), source));
+            }
+        }
+        for (id, source) in [
+            ("current_dependency", "--current-dependency"),
+            ("baseline_dependency", "--baseline-dependency"),
+        ] {
+            if let Ok(Some(paths)) = subcommand.try_get_many::<String>(id) {
+                inputs.extend(paths.map(|path| (PathBuf::from(path), source)));
+            }
+        }
+    }
+
+    let manifest_path = match semver_check.get_one::<String>("manifest_path") {
+        Some(manifest_path) => PathBuf::from(manifest_path),
+        None => locate_manifest(),
+    };
+    if let Ok(package) = load_package_info(&manifest_path) {
+        inputs.extend(
+            generated_json_files(&package.target_directory)
+                .into_iter()
+                .map(|path| (path, "generated")),
+        );
+    }
+    inputs
+}
+
+/// The rustdoc JSON files in the target directory, for the host and for other targets.
+fn generated_json_files(target_directory: &Path) -> Vec<PathBuf> {
+    let mut doc_dirs = vec![target_directory.join("doc")];
+    if let Ok(entries) = std::fs::read_dir(target_directory) {
+        doc_dirs.extend(entries.flatten().map(|entry| entry.path().join("doc")));
+    }
+    let mut files: Vec<_> = doc_dirs
+        .iter()
+        .filter_map(|dir| std::fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten().map(|entry| entry.path()))
+        .filter(|path| {
+            path.extension()
+                .is_some_and(|extension| extension == "json")
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// The last `count` lines of the text, noting how many were left out.
+fn last_lines(text: &str, count: usize) -> String {
+    let lines: Vec<_> = text.lines().collect();
+    if lines.len() <= count {
+        return text.to_string();
+    }
+    format!(
+        "({} earlier lines omitted)\n{}",
+        lines.len() - count,
+        lines[lines.len() - count..].join("\n")
+    )
+}
+
+/// The arguments as they would be typed in a shell, quoting those that need it.
+fn shell_words(args: &[String]) -> String {
+    args.iter()
+        .map(|arg| {
+            if !arg.is_empty()
+                && arg
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "-_./=,:+@".contains(c))
+            {
+                arg.clone()
+            } else {
+                format!("'{}'", arg.replace('\'', r"'\''"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{last_lines, shell_words};
+
+    #[test]
+    fn reports_are_paste_ready() {
+        assert_eq!("a\nb", last_lines("a\nb", 2));
+        assert_eq!(
+            "(2 earlier lines omitted)\nc\nd",
+            last_lines("a\nb\nc\nd", 2)
+        );
+
+        let args = ["check-release", "--rustflags", "--cfg foo", "it's"].map(String::from);
+        assert_eq!(
+            r"check-release --rustflags '--cfg foo' 'it'\''s'",
+            shell_words(&args)
+        );
+    }
+}
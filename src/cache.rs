@@ -0,0 +1,176 @@
+//! The `cache` subcommand, for inspecting and pruning cached baselines and baseline builds.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use termcolor::Color;
+use termcolor_output::{colored, colored_ln};
+
+use crate::{baseline::BaselineLoader, GlobalConfig};
+
+/// What the `cache` subcommand should do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CacheAction {
+    /// Print the cache directory.
+    Path,
+    /// Print the cached baselines, and the disk space used by the cache and by builds.
+    Stats,
+    /// Remove the cached baselines, and all downloaded crates and build outputs.
+    Clean,
+}
+
+pub(crate) fn run_cache_action(
+    mut config: GlobalConfig,
+    loader: &BaselineLoader,
+    action: CacheAction,
+) -> anyhow::Result<()> {
+    match action {
+        CacheAction::Path => {
+            colored_ln(&mut config.output_writer, |w| {
+                colored!(w, "{}", loader.cache_dir().display())
+            })
+            .expect("print failed");
+        }
+        CacheAction::Stats => print_stats(&mut config, loader)?,
+        CacheAction::Clean => {
+            let freed = dir_size(loader.cache_dir())? + build_size(loader)?;
+            loader.clean()?;
+            colored_ln(&mut config.output_writer, |w| {
+                colored!(
+                    w,
+                    "{}{}{:>12}{} {} and {}, freeing {}",
+                    fg!(Some(Color::Green)),
+                    bold!(true),
+                    "Removed",
+                    reset!(),
+                    loader.cache_dir().display(),
+                    loader.work_dir().display(),
+                    format_size(freed),
+                )
+            })
+            .expect("print failed");
+        }
+    }
+    Ok(())
+}
+
+fn print_stats(config: &mut GlobalConfig, loader: &BaselineLoader) -> anyhow::Result<()> {
+    let cache_dir = loader.cache_dir();
+    let cached_baselines = files_in(cache_dir)?;
+    let cache_size: u64 = cached_baselines.iter().map(|(_, size)| size).sum();
+    colored_ln(&mut config.output_writer, |w| {
+        colored!(
+            w,
+            "{}{}{:>12}{} {} cached baselines, {}, in {}",
+            fg!(Some(Color::Cyan)),
+            bold!(true),
+            "Cache",
+            reset!(),
+            cached_baselines
+                .iter()
+                .filter(|(path, _)| path.extension() == Some("json".as_ref()))
+                .count(),
+            format_size(cache_size),
+            cache_dir.display(),
+        )
+    })
+    .expect("print failed");
+    for (path, size) in &cached_baselines {
+        let relative_path = path.strip_prefix(cache_dir).unwrap_or(path);
+        colored_ln(&mut config.output_writer, |w| {
+            colored!(
+                w,
+                "{:>12} {:>10}  {}",
+                "",
+                format_size(*size),
+                relative_path.display()
+            )
+        })
+        .expect("print failed");
+    }
+
+    let work_dir = loader.work_dir();
+    let build_size = build_size(loader)?;
+    colored_ln(&mut config.output_writer, |w| {
+        colored!(
+            w,
+            "{}{}{:>12}{} {} of downloaded crates and build outputs in {}",
+            fg!(Some(Color::Cyan)),
+            bold!(true),
+            "Builds",
+            reset!(),
+            format_size(build_size),
+            work_dir.display(),
+        )
+    })
+    .expect("print failed");
+
+    Ok(())
+}
+
+/// Every file in the directory and its subdirectories, with its size in bytes, sorted by path.
+/// If the directory doesn't exist, it has no files.
+fn files_in(dir: &Path) -> anyhow::Result<Vec<(PathBuf, u64)>> {
+    let mut files = vec![];
+    let mut dirs = vec![dir.to_path_buf()];
+    while let Some(dir) = dirs.pop() {
+        if !dir.is_dir() {
+            continue;
+        }
+        let entries = std::fs::read_dir(&dir)
+            .with_context(|| format!("Failed to read directory {:?}", dir))?;
+        for entry in entries {
+            let entry = entry?;
+            let metadata = entry.metadata()?;
+            if metadata.is_dir() {
+                dirs.push(entry.path());
+            } else {
+                files.push((entry.path(), metadata.len()));
+            }
+        }
+    }
+    files.sort();
+    Ok(files)
+}
+
+/// The size of everything in the working directory, except the cache if it's there too.
+fn build_size(loader: &BaselineLoader) -> anyhow::Result<u64> {
+    Ok(files_in(loader.work_dir())?
+        .iter()
+        .filter(|(path, _)| !path.starts_with(loader.cache_dir()))
+        .map(|(_, size)| size)
+        .sum())
+}
+
+fn dir_size(dir: &Path) -> anyhow::Result<u64> {
+    Ok(files_in(dir)?.iter().map(|(_, size)| size).sum())
+}
+
+fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if bytes < 1024 {
+        return format!("{bytes} B");
+    }
+    let mut size = bytes as f64;
+    let mut unit = "B";
+    for next_unit in UNITS {
+        if size < 1024.0 {
+            break;
+        }
+        size /= 1024.0;
+        unit = next_unit;
+    }
+    format!("{size:.1} {unit}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::format_size;
+
+    #[test]
+    fn sizes_are_human_readable() {
+        assert_eq!("512 B", format_size(512));
+        assert_eq!("1.5 KiB", format_size(1536));
+        assert_eq!("3.0 MiB", format_size(3 * 1024 * 1024));
+    }
+}
@@ -0,0 +1,411 @@
+//! Telling apart the items that are the same in the baseline and the current version
+//! from those that changed, so that `--incremental` runs only check the latter.
+
+use std::collections::{HashMap, HashSet};
+
+use rustdoc_types::{Crate, Id, ItemEnum};
+use serde_json::Value;
+use sha2::{Digest as _, Sha256};
+
+use crate::importable_paths::{public_item_paths, CrateGraph};
+
+/// A SHA-256 hash of an item's contents. Items with the same digest are taken to be the
+/// same, so it's long enough that different contents never share one in practice.
+type Digest = [u8; 32];
+
+/// The items of each version of the crate that are the same in the other version:
+/// importable at the same paths, with the same contents, and referring only to items
+/// that are the same too.
+///
+/// Checks report what changed between the versions, so they find nothing in these items,
+/// and needn't look at them. Items re-exported from other crates are never among them.
+#[derive(Debug, Default)]
+pub(crate) struct UnchangedItems<'a> {
+    pub(crate) baseline: HashSet<&'a Id>,
+    pub(crate) current: HashSet<&'a Id>,
+}
+
+impl<'a> UnchangedItems<'a> {
+    pub(crate) fn between(baseline: CrateGraph<'a>, current: CrateGraph<'a>) -> Self {
+        let baseline_items = digested_items(baseline);
+        let current_items = digested_items(current);
+
+        let mut unchanged = Self {
+            baseline: baseline_items.iter().map(|item| item.id).collect(),
+            current: current_items.iter().map(|item| item.id).collect(),
+        };
+        // Items stop being unchanged when their counterpart or an item they refer to does,
+        // so keep going until neither side loses any more of them.
+        loop {
+            let before = unchanged.baseline.len() + unchanged.current.len();
+            unchanged.baseline = unchanged_of(
+                &baseline_items,
+                &unchanged.baseline,
+                &current_items,
+                &unchanged.current,
+            );
+            unchanged.current = unchanged_of(
+                &current_items,
+                &unchanged.current,
+                &baseline_items,
+                &unchanged.baseline,
+            );
+            if unchanged.baseline.len() + unchanged.current.len() == before {
+                return unchanged;
+            }
+        }
+    }
+}
+
+/// An importable item of the main crate, summarized to compare it across versions.
+struct DigestedItem<'a> {
+    id: &'a Id,
+    /// The sorted paths at which the item is importable, and a hash of its contents.
+    key: (Vec<Vec<&'a str>>, Digest),
+    /// The other items of the crate that the item or its fields, variants, or impls refer to.
+    references: Vec<&'a Id>,
+}
+
+/// The items of `items` that are still unchanged: those with a counterpart in `other_items`
+/// that's unchanged, which refer only to unchanged items.
+fn unchanged_of<'a>(
+    items: &[DigestedItem<'a>],
+    unchanged: &HashSet<&'a Id>,
+    other_items: &[DigestedItem<'a>],
+    other_unchanged: &HashSet<&'a Id>,
+) -> HashSet<&'a Id> {
+    let counterparts: HashSet<&(Vec<Vec<&str>>, Digest)> = other_items
+        .iter()
+        .filter(|item| other_unchanged.contains(item.id))
+        .map(|item| &item.key)
+        .collect();
+    items
+        .iter()
+        .filter(|item| {
+            unchanged.contains(item.id)
+                && counterparts.contains(&item.key)
+                && item.references.iter().all(|id| unchanged.contains(id))
+        })
+        .map(|item| item.id)
+        .collect()
+}
+
+fn digested_items(graph: CrateGraph<'_>) -> Vec<DigestedItem<'_>> {
+    let mut digests = Digests {
+        crate_: graph.main,
+        digests: HashMap::new(),
+        in_progress: HashSet::new(),
+    };
+    public_item_paths(graph)
+        .into_iter()
+        .filter(|(crate_, item, paths)| {
+            std::ptr::eq(*crate_, graph.main)
+                && !paths.is_empty()
+                && !matches!(item.inner, ItemEnum::Module(_) | ItemEnum::Import(_))
+        })
+        .map(|(_, item, mut paths)| {
+            paths.sort();
+            let (digest, references) = digests.digest(&item.id);
+            DigestedItem {
+                id: &item.id,
+                key: (paths, digest),
+                references,
+            }
+        })
+        .collect()
+}
+
+/// Hashes of the contents of the items of a crate, which don't depend on their ids or spans,
+/// since those change with unrelated edits to the crate.
+struct Digests<'a> {
+    crate_: &'a Crate,
+    digests: HashMap<&'a Id, (Digest, Vec<&'a Id>)>,
+    /// The items being hashed, to stop at items that contain themselves.
+    in_progress: HashSet<&'a Id>,
+}
+
+impl<'a> Digests<'a> {
+    /// The hash of the item's contents, including those of its fields, variants, impls and
+    /// their items, along with the other items they refer to.
+    fn digest(&mut self, id: &Id) -> (Digest, Vec<&'a Id>) {
+        let (id, item) = match self.crate_.index.get_key_value(id) {
+            Some(item) => item,
+            None => return (Digest::default(), vec![]),
+        };
+        if let Some(digest) = self.digests.get(id) {
+            return digest.clone();
+        }
+        if !self.in_progress.insert(id) {
+            return (Digest::default(), vec![]);
+        }
+
+        let mut value = serde_json::to_value(item).expect("item failed to serialize");
+        if let Value::Object(fields) = &mut value {
+            fields.remove("id");
+            fields.remove("span");
+            fields.remove("links");
+        }
+        let mut references = vec![];
+        self.normalize(&mut value, &mut references);
+        references.sort_by(|a, b| a.0.cmp(&b.0));
+        references.dedup();
+        let digest = (Sha256::digest(value.to_string()).into(), references);
+
+        self.in_progress.remove(id);
+        self.digests.insert(id, digest.clone());
+        digest
+    }
+
+    /// Replace the ids in the value: those of other items it refers to by their paths,
+    /// and those of the items it contains by the hashes of their contents.
+    fn normalize(&mut self, value: &mut Value, references: &mut Vec<&'a Id>) {
+        match value {
+            Value::Object(fields) => {
+                for (key, value) in fields.iter_mut() {
+                    match value {
+                        Value::String(id) if key == "id" => {
+                            *value = Value::String(self.reference(id, references));
+                        }
+                        _ => self.normalize(value, references),
+                    }
+                }
+            }
+            Value::Array(values) => {
+                for value in values {
+                    self.normalize(value, references);
+                }
+            }
+            Value::String(id) => {
+                let id = Id(id.clone());
+                if self.crate_.index.contains_key(&id) {
+                    let (digest, child_references) = self.digest(&id);
+                    references.extend(child_references);
+                    *value =
+                        Value::String(digest.iter().map(|byte| format!("{byte:02x}")).collect());
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// The path of the item that an id refers to, if it has one.
+    fn reference(&self, id: &str, references: &mut Vec<&'a Id>) -> String {
+        let id = Id(id.to_string());
+        if let Some((id, _)) = self.crate_.index.get_key_value(&id) {
+            references.push(id);
+        }
+        match self.crate_.paths.get(&id) {
+            Some(summary) => format!("{:?} {}", summary.kind, summary.path.join("::")),
+            None => String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustdoc_types::{Crate, Id, Item, ItemEnum, FORMAT_VERSION};
+    use serde_json::{json, Value};
+
+    use super::UnchangedItems;
+    use crate::importable_paths::CrateGraph;
+
+    fn item(id: &str, name: Option<&str>, kind: &str, inner: Value) -> Value {
+        json!({
+            "id": id, "crate_id": 0, "name": name, "span": null, "visibility": "public",
+            "docs": null, "links": {}, "attrs": [], "deprecation": null, "kind": kind, "inner": inner,
+        })
+    }
+
+    /// A crate with a struct `Kept`, a struct `Config` whose field has the given type,
+    /// and a function returning `Config`, with the ids shifted by `offset` as if other
+    /// items had been added to the crate.
+    fn example_crate(field_type: &str, offset: usize) -> Crate {
+        let id = |number: usize| format!("0:{}", number + offset);
+        let generics = json!({ "params": [], "where_predicates": [] });
+        let unit_struct = json!({
+            "struct_type": "unit", "generics": generics, "fields_stripped": false,
+            "fields": [], "impls": [],
+        });
+        let config = json!({ "kind": "resolved_path", "inner": {
+            "name": "Config", "id": id(2), "args": null, "param_names": [],
+        } });
+        let items = [
+            item(
+                "0:0",
+                Some("example"),
+                "module",
+                json!({ "is_crate": true, "items": [id(1), id(2), id(4)] }),
+            ),
+            item(&id(1), Some("Kept"), "struct", unit_struct),
+            item(
+                &id(2),
+                Some("Config"),
+                "struct",
+                json!({
+                    "struct_type": "plain", "generics": generics, "fields_stripped": false,
+                    "fields": [id(3)], "impls": [],
+                }),
+            ),
+            item(
+                &id(3),
+                Some("value"),
+                "struct_field",
+                json!({ "kind": "primitive", "inner": field_type }),
+            ),
+            item(
+                &id(4),
+                Some("config"),
+                "function",
+                json!({
+                    "decl": { "inputs": [], "output": config, "c_variadic": false },
+                    "generics": generics,
+                    "header": { "const": false, "unsafe": false, "async": false, "abi": "Rust" },
+                }),
+            ),
+        ];
+        let paths: serde_json::Map<String, Value> =
+            [(1, "Kept", "struct"), (2, "Config", "struct")]
+                .into_iter()
+                .map(|(number, name, kind)| {
+                    let summary = json!({ "crate_id": 0, "path": ["example", name], "kind": kind });
+                    (id(number), summary)
+                })
+                .collect();
+        serde_json::from_value(json!({
+            "root": "0:0",
+            "crate_version": "1.0.0",
+            "includes_private": false,
+            "index": items
+                .into_iter()
+                .map(|item| (item["id"].as_str().unwrap().to_string(), item))
+                .collect::<serde_json::Map<_, _>>(),
+            "paths": paths,
+            "external_crates": {},
+            "format_version": FORMAT_VERSION,
+        }))
+        .unwrap()
+    }
+
+    fn graph(crate_: &Crate) -> CrateGraph<'_> {
+        CrateGraph {
+            main: crate_,
+            dependencies: &[],
+        }
+    }
+
+    fn names<'a>(crate_: &'a Crate, ids: &std::collections::HashSet<&Id>) -> Vec<&'a str> {
+        let mut names: Vec<&str> = ids
+            .iter()
+            .map(|id| crate_.index[*id].name.as_deref().unwrap())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn changed_items_and_those_referring_to_them_are_not_unchanged() {
+        let baseline = example_crate("u8", 0);
+
+        // Ids change with unrelated edits, and don't make items differ.
+        let same = example_crate("u8", 10);
+        let unchanged = UnchangedItems::between(graph(&baseline), graph(&same));
+        assert_eq!(
+            vec!["Config", "Kept", "config"],
+            names(&baseline, &unchanged.baseline)
+        );
+        assert_eq!(
+            vec!["Config", "Kept", "config"],
+            names(&same, &unchanged.current)
+        );
+
+        // `config` is the same, but returns `Config`, which changed.
+        let changed = example_crate("u16", 10);
+        let unchanged = UnchangedItems::between(graph(&baseline), graph(&changed));
+        assert_eq!(vec!["Kept"], names(&baseline, &unchanged.baseline));
+        assert_eq!(vec!["Kept"], names(&changed, &unchanged.current));
+    }
+
+    /// Add a struct `Wrapper` with a field of type `Config`, and a function returning
+    /// `Wrapper`, so the function refers to `Config` only through `Wrapper`.
+    fn with_wrapper(mut crate_: Crate) -> Crate {
+        let config_id = crate_
+            .index
+            .values()
+            .find(|item| item.name.as_deref() == Some("Config"))
+            .unwrap()
+            .id
+            .0
+            .clone();
+        let generics = json!({ "params": [], "where_predicates": [] });
+        let resolved_path = |name: &str, id: &str| {
+            json!({ "kind": "resolved_path", "inner": {
+                "name": name, "id": id, "args": null, "param_names": [],
+            } })
+        };
+        let items = [
+            item(
+                "1:0",
+                Some("Wrapper"),
+                "struct",
+                json!({
+                    "struct_type": "plain", "generics": generics, "fields_stripped": false,
+                    "fields": ["1:1"], "impls": [],
+                }),
+            ),
+            item(
+                "1:1",
+                Some("inner"),
+                "struct_field",
+                resolved_path("Config", &config_id),
+            ),
+            item(
+                "1:2",
+                Some("wrapper"),
+                "function",
+                json!({
+                    "decl": {
+                        "inputs": [], "output": resolved_path("Wrapper", "1:0"), "c_variadic": false,
+                    },
+                    "generics": generics,
+                    "header": { "const": false, "unsafe": false, "async": false, "abi": "Rust" },
+                }),
+            ),
+        ];
+        for item in items {
+            let item: Item = serde_json::from_value(item).unwrap();
+            crate_.index.insert(item.id.clone(), item);
+        }
+        crate_.paths.insert(
+            Id("1:0".to_string()),
+            serde_json::from_value(
+                json!({ "crate_id": 0, "path": ["example", "Wrapper"], "kind": "struct" }),
+            )
+            .unwrap(),
+        );
+        let root = crate_.root.clone();
+        if let ItemEnum::Module(module) = &mut crate_.index.get_mut(&root).unwrap().inner {
+            module
+                .items
+                .extend([Id("1:0".to_string()), Id("1:2".to_string())]);
+        }
+        crate_
+    }
+
+    #[test]
+    fn items_referring_to_changed_items_through_others_are_not_unchanged() {
+        let baseline = with_wrapper(example_crate("u8", 0));
+
+        let same = with_wrapper(example_crate("u8", 10));
+        let unchanged = UnchangedItems::between(graph(&baseline), graph(&same));
+        assert_eq!(
+            vec!["Config", "Kept", "Wrapper", "config", "wrapper"],
+            names(&baseline, &unchanged.baseline)
+        );
+
+        // `wrapper` and `Wrapper` are the same, but `Wrapper` contains `Config`, which changed.
+        let changed = with_wrapper(example_crate("u16", 10));
+        let unchanged = UnchangedItems::between(graph(&baseline), graph(&changed));
+        assert_eq!(vec!["Kept"], names(&baseline, &unchanged.baseline));
+        assert_eq!(vec!["Kept"], names(&changed, &unchanged.current));
+    }
+}
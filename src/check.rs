@@ -0,0 +1,485 @@
+//! The library API: checking a release from rustdoc JSON that has already been generated,
+//! without going through the command line.
+
+use std::path::PathBuf;
+
+use anyhow::Context;
+use rustdoc_types::Crate;
+
+use crate::{
+    baseline::BaselineUnavailable,
+    check_release::{run_check_release, CheckReleaseOptions, CrateComparison, SemverReport},
+    formats,
+    observer::{CheckObserver, SilentObserver},
+    query::{ActualSemverUpdate, LintLevel, QueryRegistry, QuerySelection, RequiredSemverUpdate},
+    snapshot::load_snapshot,
+    util::load_rustdoc_from_file,
+};
+
+/// The rustdoc JSON of one version of a crate, as generated by
+/// `cargo +nightly rustdoc -- -Zunstable-options --output-format json`.
+#[derive(Debug, Clone)]
+pub struct Rustdoc {
+    source: RustdocSource,
+}
+
+#[derive(Debug, Clone)]
+enum RustdocSource {
+    Path(PathBuf),
+    Snapshot(PathBuf),
+    Crate(Box<Crate>),
+}
+
+impl Rustdoc {
+    /// The rustdoc JSON in the given file, which is read when the check runs.
+    pub fn from_path(path: impl Into<PathBuf>) -> Self {
+        Self {
+            source: RustdocSource::Path(path.into()),
+        }
+    }
+
+    /// The API snapshot written by `cargo semver-checks dump-api` in the given file,
+    /// which is read when the check runs.
+    pub fn from_snapshot(path: impl Into<PathBuf>) -> Self {
+        Self {
+            source: RustdocSource::Snapshot(path.into()),
+        }
+    }
+
+    /// Rustdoc JSON that has already been parsed.
+    pub fn from_crate(crate_: Crate) -> Self {
+        Self {
+            source: RustdocSource::Crate(Box::new(crate_)),
+        }
+    }
+
+    fn load(self) -> anyhow::Result<Crate> {
+        match self.source {
+            RustdocSource::Path(path) => load_rustdoc_from_file(
+                path.to_str()
+                    .with_context(|| format!("Non-UTF-8 rustdoc JSON path {:?}", path))?,
+            ),
+            RustdocSource::Snapshot(path) => load_snapshot(&path),
+            RustdocSource::Crate(crate_) => Ok(*crate_),
+        }
+    }
+}
+
+/// A semver check of a new version of a crate against a baseline version,
+/// running the same checks as `cargo semver-checks check-release`:
+///
+/// ```no_run
+/// use cargo_semver_checks::{Check, Rustdoc};
+///
+/// let report = Check::new()
+///     .with_current(Rustdoc::from_path("target/doc/my_crate.json"))
+///     .with_baseline(Rustdoc::from_path("baseline/my_crate.json"))
+///     .run()?;
+/// for failure in report.failures() {
+///     println!("{}: {} violations", failure.id, failure.violations.len());
+/// }
+/// # Ok::<(), anyhow::Error>(())
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Check {
+    current: Option<Rustdoc>,
+    baseline: Option<Rustdoc>,
+    release_type: Option<ActualSemverUpdate>,
+    queries: Option<QueryRegistry>,
+}
+
+impl Check {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The version of the crate to check.
+    pub fn with_current(mut self, current: Rustdoc) -> Self {
+        self.current = Some(current);
+        self
+    }
+
+    /// The version of the crate to check against, usually its latest release.
+    pub fn with_baseline(mut self, baseline: Rustdoc) -> Self {
+        self.baseline = Some(baseline);
+        self
+    }
+
+    /// The kind of release to check for, instead of the version change between
+    /// the baseline and current crates. Checks for changes the release allows are skipped.
+    pub fn with_release_type(mut self, release_type: ActualSemverUpdate) -> Self {
+        self.release_type = Some(release_type);
+        self
+    }
+
+    /// The checks to run, instead of just the built-in ones.
+    pub fn with_queries(mut self, queries: QueryRegistry) -> Self {
+        self.queries = Some(queries);
+        self
+    }
+
+    /// Run the checks without printing anything. Failing checks aren't errors: they're
+    /// reported in the result, and errors are for failing to load the rustdoc JSON or to
+    /// run the checks. Errors loading the baseline carry the same context as in the
+    /// command line tool.
+    pub fn run(self) -> anyhow::Result<Report> {
+        self.run_with_observer(&mut SilentObserver)
+    }
+
+    /// Run the checks like [`Check::run`], telling the observer about their progress.
+    pub fn run_with_observer(self, observer: &mut dyn CheckObserver) -> anyhow::Result<Report> {
+        let current = self
+            .current
+            .context("No current version of the crate to check: set one with `with_current`")?
+            .load()?;
+        let baseline = self
+            .baseline
+            .context("No baseline to check against: set one with `with_baseline`")
+            .and_then(Rustdoc::load)
+            .context(BaselineUnavailable)?;
+
+        let comparisons = [CrateComparison {
+            target: None,
+            current: current.into(),
+            baseline: baseline.into(),
+            dependencies: Default::default(),
+            baseline_docs: None,
+        }];
+        let options = CheckReleaseOptions {
+            release_type: self.release_type,
+            selection: QuerySelection::new(
+                self.queries.unwrap_or_default().into_queries(),
+                vec![],
+                vec![],
+            )?,
+            ..Default::default()
+        };
+        let report = run_check_release(observer, &options, &comparisons)?;
+        Ok(Report::from(&report))
+    }
+}
+
+/// The outcome of a [`Check`].
+#[derive(Debug, Clone)]
+pub struct Report {
+    /// The checks that ran, ordered by id.
+    pub checks: Vec<CheckResult>,
+
+    /// The ids of the checks that were skipped, because the release
+    /// already allows the changes they look for.
+    pub skipped: Vec<String>,
+}
+
+impl Report {
+    /// Whether no check found any violations. Warnings don't count.
+    pub fn success(&self) -> bool {
+        self.failures().next().is_none()
+    }
+
+    pub fn failures(&self) -> impl Iterator<Item = &CheckResult> {
+        self.checks
+            .iter()
+            .filter(|check| !check.violations.is_empty() && !check.lint_level.is_warning())
+    }
+
+    /// The checks at the `warn` lint level that found something.
+    pub fn warnings(&self) -> impl Iterator<Item = &CheckResult> {
+        self.checks
+            .iter()
+            .filter(|check| !check.violations.is_empty() && check.lint_level.is_warning())
+    }
+
+    /// The smallest version update that allows all the violations that were found,
+    /// or `None` if no violations were found.
+    pub fn required_update(&self) -> Option<RequiredSemverUpdate> {
+        self.failures()
+            .map(|check| check.required_update)
+            .max_by_key(|update| matches!(update, RequiredSemverUpdate::Major))
+    }
+}
+
+/// The outcome of one check.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct CheckResult {
+    pub id: String,
+    pub name: String,
+
+    /// What kind of violation the check looks for.
+    pub description: String,
+
+    /// The version update that a violation requires.
+    pub required_update: RequiredSemverUpdate,
+
+    /// Whether the violations fail the check, or are only warnings.
+    pub lint_level: LintLevel,
+
+    /// Ordered by the path of the affected item, then by location and message,
+    /// so that the same crates always give the same report.
+    pub violations: Vec<Violation>,
+}
+
+/// A single semver violation found by a check.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Violation {
+    /// Human-readable description of this particular violation.
+    pub message: String,
+
+    /// The file and line of the item responsible for the violation, as `file:line`, if known.
+    pub location: Option<String>,
+
+    /// Rust code that compiles against the baseline version of the crate,
+    /// but not against the current one, if the check can produce it.
+    pub witness: Option<String>,
+
+    /// The docs.rs page of the affected item in the baseline, if the baseline was published.
+    pub baseline_docs_url: Option<String>,
+
+    /// A rough estimate of the violation's impact: how many other public items refer to the
+    /// affected item in their fields, signatures, or bounds, if it's known which item that is.
+    pub impact: Option<usize>,
+}
+
+impl From<&SemverReport<'_>> for Report {
+    fn from(report: &SemverReport<'_>) -> Self {
+        Self {
+            checks: report
+                .checks
+                .outcomes
+                .iter()
+                .map(|outcome| CheckResult {
+                    id: outcome.query.id.clone(),
+                    name: outcome.query.human_readable_name.clone(),
+                    description: outcome.query.error_message.clone(),
+                    required_update: outcome.query.required_update,
+                    lint_level: outcome.query.lint_level,
+                    violations: outcome.violations.iter().map(Violation::from).collect(),
+                })
+                .collect(),
+            skipped: report
+                .checks
+                .skipped
+                .iter()
+                .map(|query| query.id.clone())
+                .collect(),
+        }
+    }
+}
+
+impl From<&formats::Violation> for Violation {
+    fn from(violation: &formats::Violation) -> Self {
+        Self {
+            message: violation.message.clone(),
+            location: violation.location(),
+            witness: violation.witness.clone(),
+            baseline_docs_url: violation.baseline_docs_url.clone(),
+            impact: violation.impact,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::HashMap, time::Duration};
+
+    use rustdoc_types::{
+        Crate, Generics, Id, Item, ItemEnum, ItemKind, ItemSummary, Module, Struct, StructType,
+        Visibility, FORMAT_VERSION,
+    };
+
+    use super::{Check, Rustdoc, Violation};
+    use crate::{
+        observer::{CheckInfo, CheckObserver},
+        query::{LintLevel, QueryRegistry, RequiredSemverUpdate},
+    };
+
+    /// Records the events of a run as strings, with violations by the first part of their message.
+    #[derive(Default)]
+    struct RecordingObserver {
+        events: Vec<String>,
+    }
+
+    impl CheckObserver for RecordingObserver {
+        fn result_found(&mut self, check: &CheckInfo, violation: &Violation) {
+            self.events.push(format!(
+                "found by {}: {}",
+                check.id,
+                violation.message.split(',').next().unwrap_or_default()
+            ));
+        }
+
+        fn check_failed(&mut self, check: &CheckInfo, _duration: Duration, violations: usize) {
+            self.events
+                .push(format!("failed {} with {violations}", check.id));
+        }
+
+        fn violation_found(&mut self, check: &CheckInfo, violation: &Violation) {
+            self.events.push(format!(
+                "{}: {}",
+                check.id,
+                violation.message.split(',').next().unwrap_or_default()
+            ));
+        }
+    }
+
+    /// Version `version` of a crate `example` with a unit struct by each of the given names.
+    fn crate_with_structs(version: &str, names: &[&str]) -> Crate {
+        let item = |id: &str, name: &str, inner: ItemEnum| Item {
+            id: Id(id.to_string()),
+            crate_id: 0,
+            name: Some(name.to_string()),
+            span: None,
+            visibility: Visibility::Public,
+            docs: None,
+            links: HashMap::new(),
+            attrs: vec![],
+            deprecation: None,
+            inner,
+        };
+        let structs: Vec<Item> = names
+            .iter()
+            .enumerate()
+            .map(|(i, name)| {
+                item(
+                    &format!("0:{}", i + 1),
+                    name,
+                    ItemEnum::Struct(Struct {
+                        struct_type: StructType::Unit,
+                        generics: Generics {
+                            params: vec![],
+                            where_predicates: vec![],
+                        },
+                        fields_stripped: false,
+                        fields: vec![],
+                        impls: vec![],
+                    }),
+                )
+            })
+            .collect();
+        let root = item(
+            "0:0",
+            "example",
+            ItemEnum::Module(Module {
+                is_crate: true,
+                items: structs.iter().map(|item| item.id.clone()).collect(),
+            }),
+        );
+        let paths = structs
+            .iter()
+            .map(|item| {
+                let summary = ItemSummary {
+                    crate_id: 0,
+                    path: vec!["example".to_string(), item.name.clone().unwrap()],
+                    kind: ItemKind::Struct,
+                };
+                (item.id.clone(), summary)
+            })
+            .collect();
+        Crate {
+            root: root.id.clone(),
+            crate_version: Some(version.to_string()),
+            includes_private: false,
+            index: structs
+                .into_iter()
+                .chain([root])
+                .map(|item| (item.id.clone(), item))
+                .collect(),
+            paths,
+            external_crates: HashMap::new(),
+            format_version: FORMAT_VERSION,
+        }
+    }
+
+    #[test]
+    fn check_reports_violations_without_printing_them() {
+        let report = Check::new()
+            .with_current(Rustdoc::from_crate(crate_with_structs("1.0.1", &["Foo"])))
+            .with_baseline(Rustdoc::from_crate(crate_with_structs(
+                "1.0.0",
+                &["Foo", "Bar"],
+            )))
+            .run()
+            .expect("check failed to run");
+
+        assert!(!report.success());
+        assert_eq!(Some(RequiredSemverUpdate::Major), report.required_update());
+        let failures: Vec<_> = report
+            .failures()
+            .map(|check| (check.id.as_str(), check.violations.len()))
+            .collect();
+        assert_eq!(vec![("struct_missing", 1)], failures);
+
+        let missing_baseline = Check::new()
+            .with_current(Rustdoc::from_crate(crate_with_structs("1.0.1", &["Foo"])))
+            .run()
+            .expect_err("checked without a baseline");
+        assert!(missing_baseline
+            .downcast_ref::<crate::baseline::BaselineUnavailable>()
+            .is_some());
+    }
+
+    #[test]
+    fn warnings_do_not_fail_the_check() {
+        let mut queries = QueryRegistry::new();
+        queries
+            .set_lint_level("struct_missing", LintLevel::Warn)
+            .expect("check exists");
+        let report = Check::new()
+            .with_current(Rustdoc::from_crate(crate_with_structs("1.0.1", &["Foo"])))
+            .with_baseline(Rustdoc::from_crate(crate_with_structs(
+                "1.0.0",
+                &["Foo", "Bar"],
+            )))
+            .with_queries(queries)
+            .run()
+            .expect("check failed to run");
+
+        assert!(report.success());
+        assert_eq!(None, report.required_update());
+        let warnings: Vec<_> = report
+            .warnings()
+            .map(|check| (check.id.as_str(), check.violations.len()))
+            .collect();
+        assert_eq!(vec![("struct_missing", 1)], warnings);
+    }
+
+    #[test]
+    fn violations_are_sorted_by_item_path() {
+        let report = Check::new()
+            .with_current(Rustdoc::from_crate(crate_with_structs("1.0.1", &[])))
+            .with_baseline(Rustdoc::from_crate(crate_with_structs(
+                "1.0.0",
+                &["Zed", "Alpha", "Mid"],
+            )))
+            .run()
+            .expect("check failed to run");
+
+        let messages: Vec<_> = report
+            .checks
+            .iter()
+            .flat_map(|check| &check.violations)
+            .map(|violation| violation.message.split(',').next().unwrap_or_default())
+            .collect();
+        assert_eq!(vec!["struct Alpha", "struct Mid", "struct Zed"], messages);
+    }
+
+    #[test]
+    fn observer_is_told_about_failures_and_violations() {
+        let mut observer = RecordingObserver::default();
+        Check::new()
+            .with_current(Rustdoc::from_crate(crate_with_structs("1.0.1", &[])))
+            .with_baseline(Rustdoc::from_crate(crate_with_structs("1.0.0", &["Foo"])))
+            .run_with_observer(&mut observer)
+            .expect("check failed to run");
+
+        assert_eq!(
+            vec![
+                "found by struct_missing: struct Foo".to_string(),
+                "failed struct_missing with 1".to_string(),
+                "struct_missing: struct Foo".to_string(),
+            ],
+            observer.events,
+        );
+    }
+}
@@ -1,43 +1,330 @@
 use std::{
-    cell::RefCell, collections::BTreeMap, env, io::Write, iter::Peekable, rc::Rc, sync::Arc,
+    cell::RefCell,
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet, HashMap},
+    hash::{Hash, Hasher},
+    io::Write,
+    path::{Path, PathBuf},
+    rc::Rc,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc, Condvar, Mutex,
+    },
     time::Duration,
 };
 
 use anyhow::Context;
-use clap::crate_version;
 use handlebars::Handlebars;
 use rustdoc_types::Crate;
 use termcolor::Color;
 use termcolor_output::{colored, colored_ln};
+use tracing::{debug, debug_span};
 use trustfall_core::{
-    frontend::parse,
     interpreter::execution::interpret_ir,
     ir::{FieldValue, TransparentValue},
     schema::Schema,
 };
 
 use crate::{
-    adapter::RustdocAdapter,
-    query::{ActualSemverUpdate, RequiredSemverUpdate, SemverQuery},
-    GlobalConfig,
+    adapter::{IndexUsage, RustdocAdapter},
+    api_surface::{self, ApiSurface},
+    changed_items::UnchangedItems,
+    docs_rs::DocsRsRelease,
+    exceptions::{item_path, SemverExceptions},
+    formats::{
+        write_emitted, write_report, CheckOutcome, CheckReport, Emit, OutputFormat, RunEnvironment,
+        Violation,
+    },
+    hyperlink,
+    impact::ImpactEstimator,
+    importable_paths::CrateGraph,
+    manifest::set_package_version,
+    observer::{CheckInfo, CheckObserver, ComparisonInfo, RunInfo},
+    query::{ActualSemverUpdate, LintLevel, QuerySelection, RequiredSemverUpdate, SemverQuery},
+    rustdoc_gen::{load_package_info, BuildEnvironment, BuildFlags},
+    signature_diff::word_diff,
+    stats::{RunStats, StatsFormat},
+    templates::template_registry,
+    GlobalConfig, Verbosity,
 };
 
 type QueryResultItem = BTreeMap<Arc<str>, FieldValue>;
 
-struct QueryWithResults<'a> {
-    name: &'a str,
-    results: Peekable<Box<dyn Iterator<Item = QueryResultItem> + 'a>>,
+/// A pair of rustdoc outputs to be checked against each other,
+/// along with the target triple they were generated for, if any.
+///
+/// The rustdoc outputs are shared rather than copied when several comparisons have them
+/// in common, like the current crate checked against several baselines, since the rustdoc
+/// of a large crate can take gigabytes of memory.
+pub(crate) struct CrateComparison {
+    pub(crate) target: Option<String>,
+    pub(crate) current: Arc<Crate>,
+    pub(crate) baseline: Arc<Crate>,
+    pub(crate) dependencies: DependencyRustdocs,
+    /// The published release the baseline is, to link to its documentation.
+    pub(crate) baseline_docs: Option<DocsRsRelease>,
 }
 
-impl<'a> QueryWithResults<'a> {
-    fn new(
-        name: &'a str,
-        results: Peekable<Box<dyn Iterator<Item = QueryResultItem> + 'a>>,
-    ) -> Self {
-        Self { name, results }
+/// The rustdoc outputs of other crates whose items the current and baseline crates re-export.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DependencyRustdocs {
+    pub(crate) current: Arc<[Crate]>,
+    pub(crate) baseline: Arc<[Crate]>,
+}
+
+impl CrateComparison {
+    /// Identifies the comparison when several are made: by its target triple, and by its
+    /// baseline version if the crate is checked against more than one baseline.
+    fn label(&self, multiple_baselines: bool) -> Option<String> {
+        let baseline_version = self.baseline.crate_version.as_deref();
+        match (self.target.as_deref(), multiple_baselines) {
+            (target, true) => Some(format!(
+                "{}{}",
+                baseline_version.unwrap_or("unknown"),
+                target
+                    .map(|target| format!(" on {target}"))
+                    .unwrap_or_default(),
+            )),
+            (Some(target), false) => Some(target.to_string()),
+            (None, false) => None,
+        }
+    }
+}
+
+/// Options controlling how the release is checked.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CheckReleaseOptions {
+    /// The kind of release the user intends to make. If set, it's used instead of
+    /// the version change between the baseline and current crates.
+    pub(crate) release_type: Option<ActualSemverUpdate>,
+
+    /// The machine-readable format in which to report results, if any.
+    pub(crate) output_format: OutputFormat,
+
+    /// Where to write the machine-readable report. Defaults to stdout.
+    pub(crate) output_path: Option<PathBuf>,
+
+    /// A document to write to stdout from the findings, like a changelog snippet.
+    pub(crate) emit: Option<Emit>,
+
+    /// Semver violations that have been accepted, and should not fail the check.
+    pub(crate) exceptions: SemverExceptions,
+
+    /// The checks to run.
+    pub(crate) selection: QuerySelection,
+
+    /// Whether to print, for each violation, code that demonstrates it.
+    pub(crate) show_witnesses: bool,
+
+    /// The most violations of each check to print. Machine-readable reports include all of them.
+    pub(crate) max_results_per_check: Option<usize>,
+
+    /// Whether to stop running checks after the first failure of the given kind.
+    pub(crate) fail_fast: Option<FailFast>,
+
+    /// How many checks to run at once. Defaults to the number of CPU cores.
+    pub(crate) jobs: Option<usize>,
+
+    /// Whether to only check the items that changed since the baseline,
+    /// and the items that refer to them.
+    pub(crate) incremental: bool,
+
+    /// How to print performance statistics at the end of the run, if at all.
+    pub(crate) stats_format: Option<StatsFormat>,
+
+    /// How long generating or loading the rustdoc JSON of the compared crates took, if known.
+    pub(crate) rustdoc_duration: Option<Duration>,
+
+    /// The compiler flags and profile with which rustdoc JSON was generated.
+    pub(crate) build_flags: BuildFlags,
+
+    /// The features, toolchain and host with which rustdoc JSON was generated.
+    pub(crate) build_environment: BuildEnvironment,
+
+    /// How to organize the failures in the human-readable output.
+    pub(crate) group_by: GroupBy,
+
+    /// A Handlebars template that replaces the summary at the end of the report.
+    pub(crate) summary_template: Option<String>,
+}
+
+/// Which failures stop the run early, with `--fail-fast`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FailFast {
+    /// Stop after the first failing check.
+    Any,
+    /// Stop after the first failing check that requires a new major version.
+    Major,
+}
+
+impl FailFast {
+    /// The names of the modes, as accepted by `--fail-fast`.
+    pub(crate) const NAMES: [&'static str; 2] = ["any", "major"];
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "any" => Some(Self::Any),
+            "major" => Some(Self::Major),
+            _ => None,
+        }
+    }
+
+    fn stops_at(self, required_update: RequiredSemverUpdate) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Major => matches!(required_update, RequiredSemverUpdate::Major),
+        }
     }
 }
 
+/// How to organize the failures in the human-readable output, with `--group-by`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum GroupBy {
+    /// Each failing check with all of its violations.
+    #[default]
+    Check,
+    /// The violations of all checks under the module of the affected item.
+    Module,
+    /// The failing checks under the version update they require, major first.
+    Severity,
+}
+
+impl GroupBy {
+    /// The names of the groupings, as accepted by `--group-by`.
+    pub(crate) const NAMES: [&'static str; 3] = ["check", "module", "severity"];
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "check" => Some(Self::Check),
+            "module" => Some(Self::Module),
+            "severity" => Some(Self::Severity),
+            _ => None,
+        }
+    }
+}
+
+/// The results of a failing query, merged as they're found: across all the targets that
+/// were checked, recording the targets on which each was observed, and across the public
+/// paths of the same item, e.g. because it's re-exported.
+///
+/// Only the merged results are kept, so memory grows with the number of violations rather
+/// than with the number of results, which is larger for items importable at many paths.
+#[derive(Default)]
+struct QueryWithResults {
+    /// Each result without its `path` output, the paths it was found at, and its targets.
+    /// Results whose `path` isn't a list of strings keep it, and have no paths.
+    results: Vec<(QueryResultItem, Vec<Vec<String>>, Vec<String>)>,
+    /// The indexes in `results` of the results with each hash of their outputs, and of
+    /// whether they had a path to take out, so a result is only compared with those that
+    /// are likely the same.
+    by_hash: HashMap<u64, Vec<usize>>,
+}
+
+impl QueryWithResults {
+    fn add_result(&mut self, target: Option<&str>, mut result: QueryResultItem) {
+        let path = take_import_path(&mut result);
+        let hash = result_hash(&result, path.is_some());
+        let candidates = self.by_hash.entry(hash).or_default();
+        let existing = candidates.iter().copied().find(|&index| {
+            let (existing, paths, _) = &self.results[index];
+            paths.is_empty() == path.is_none() && existing == &result
+        });
+        let index = match existing {
+            Some(index) => index,
+            None => {
+                candidates.push(self.results.len());
+                self.results.push((result, vec![], vec![]));
+                self.results.len() - 1
+            }
+        };
+        let (_, paths, targets) = &mut self.results[index];
+        if let Some(path) = path {
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+        if let Some(target) = target {
+            if !targets.iter().any(|existing| existing == target) {
+                targets.push(target.to_string());
+            }
+        }
+    }
+
+    /// The merged results, with their targets. Results found at several paths list all of
+    /// them in a `paths` output, and keep the shortest one as their `path`.
+    fn into_results(self) -> Vec<(QueryResultItem, Vec<String>)> {
+        let path_key: Arc<str> = Arc::from("path");
+        self.results
+            .into_iter()
+            .map(|(mut result, mut paths, targets)| {
+                if !paths.is_empty() {
+                    paths.sort_by(|a, b| (a.len(), a).cmp(&(b.len(), b)));
+                    result.insert(
+                        path_key.clone(),
+                        FieldValue::List(
+                            paths[0].iter().cloned().map(FieldValue::String).collect(),
+                        ),
+                    );
+                    result.insert(
+                        Arc::from("paths"),
+                        FieldValue::List(
+                            paths
+                                .iter()
+                                .map(|path| FieldValue::String(path.join("::")))
+                                .collect(),
+                        ),
+                    );
+                }
+                (result, targets)
+            })
+            .collect()
+    }
+}
+
+/// A hash of the result's outputs, and of whether its path was taken out of it.
+fn result_hash(result: &QueryResultItem, had_path: bool) -> u64 {
+    fn hash_value(value: &FieldValue, hasher: &mut DefaultHasher) {
+        std::mem::discriminant(value).hash(hasher);
+        match value {
+            FieldValue::Null => {}
+            FieldValue::Int64(value) => value.hash(hasher),
+            FieldValue::Uint64(value) => value.hash(hasher),
+            FieldValue::Float64(value) => value.to_bits().hash(hasher),
+            FieldValue::String(value) | FieldValue::Enum(value) => value.hash(hasher),
+            FieldValue::Boolean(value) => value.hash(hasher),
+            FieldValue::DateTimeUtc(value) => value.hash(hasher),
+            FieldValue::List(values) => {
+                values.len().hash(hasher);
+                values.iter().for_each(|value| hash_value(value, hasher));
+            }
+        }
+    }
+
+    let mut hasher = DefaultHasher::new();
+    had_path.hash(&mut hasher);
+    for (name, value) in result {
+        name.hash(&mut hasher);
+        hash_value(value, &mut hasher);
+    }
+    hasher.finish()
+}
+
+/// Remove the result's `path` output and return it, if it's a list of strings.
+/// Paths of other kinds are left as they are.
+fn take_import_path(result: &mut QueryResultItem) -> Option<Vec<String>> {
+    let path = match result.get("path")? {
+        FieldValue::List(components) => components
+            .iter()
+            .map(|component| match component {
+                FieldValue::String(component) => Some(component.clone()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?,
+        _ => return None,
+    };
+    result.remove("path");
+    Some(path)
+}
+
 fn get_semver_version_change(
     current_version: Option<&str>,
     baseline_version: Option<&str>,
@@ -86,9 +373,8 @@ fn make_result_iter<'a>(
     schema: &Schema,
     adapter: Rc<RefCell<RustdocAdapter<'a>>>,
     semver_query: &SemverQuery,
-) -> anyhow::Result<Peekable<Box<dyn Iterator<Item = QueryResultItem> + 'a>>> {
-    let parsed_query = parse(schema, &semver_query.query)
-        .expect("not a valid query, should have been caught in tests");
+) -> anyhow::Result<Box<dyn Iterator<Item = QueryResultItem> + 'a>> {
+    let parsed_query = semver_query.compile(schema)?;
     let args = Arc::new(
         semver_query
             .arguments
@@ -96,318 +382,1941 @@ fn make_result_iter<'a>(
             .map(|(k, v)| (Arc::from(k.clone()), v.clone().into()))
             .collect(),
     );
-    let results_iter = interpret_ir(adapter.clone(), parsed_query, args)
-        .with_context(|| "Query execution error.")?
-        .peekable();
+    let results_iter =
+        interpret_ir(adapter, parsed_query, args).with_context(|| "Query execution error.")?;
 
     Ok(results_iter)
 }
 
-pub(super) fn run_check_release(
-    mut config: GlobalConfig,
-    current_crate: Crate,
-    baseline_crate: Crate,
-) -> anyhow::Result<()> {
-    let current_version = current_crate.crate_version.as_deref();
-    let baseline_version = baseline_crate.crate_version.as_deref();
+/// What running one check on one comparison found. The results themselves are reported
+/// as they're found, and not kept.
+struct CheckRun {
+    /// How many results aren't accepted as semver exceptions.
+    found: usize,
+    /// How many results were accepted as semver exceptions.
+    suppressed: usize,
+    duration: Duration,
+}
 
-    let version_change = get_semver_version_change(current_version, baseline_version)
-        .unwrap_or_else(|| {
-            colored_ln(&mut config.output_writer, |w| {
-                colored!(
-                    w,
-                    "{}{}{:>12}{} Could not determine whether crate version changed. Assuming no change.",
-                    fg!(Some(Color::Yellow)),
-                    bold!(true),
-                    "Warning",
-                    reset!(),
-                )
-            }).expect("print failed");
-            ActualSemverUpdate::NotChanged
-        });
-    let change = match version_change {
-        ActualSemverUpdate::Major => "major",
-        ActualSemverUpdate::Minor => "minor",
-        ActualSemverUpdate::Patch => "patch",
-        ActualSemverUpdate::NotChanged => "no",
-    };
+impl CheckRun {
+    /// Whether the check's failure stops the run, with `--fail-fast`.
+    fn stops_run(&self, options: &CheckReleaseOptions, semver_query: &SemverQuery) -> bool {
+        self.found > 0 && failure_stops_run(options, semver_query)
+    }
+}
 
-    let queries = SemverQuery::all_queries();
+/// Whether a failure of the check stops the run, with `--fail-fast`.
+fn failure_stops_run(options: &CheckReleaseOptions, semver_query: &SemverQuery) -> bool {
+    !semver_query.lint_level.is_warning()
+        && options
+            .fail_fast
+            .is_some_and(|fail_fast| fail_fast.stops_at(semver_query.required_update))
+}
 
-    let schema = RustdocAdapter::schema();
-    let adapter = Rc::new(RefCell::new(RustdocAdapter::new(
-        &current_crate,
-        Some(&baseline_crate),
-    )));
-    let mut queries_with_errors: Vec<QueryWithResults> = vec![];
+/// The progress of a check, as reported by `run_checks`.
+enum CheckEvent<'r> {
+    Started,
+    /// A result that isn't accepted as a semver exception.
+    Found(QueryResultItem),
+    Finished(&'r CheckRun),
+}
 
-    let queries_to_run: Vec<_> = queries
-        .iter()
-        .filter(|(_, query)| !version_change.supports_requirement(query.required_update))
-        .collect();
-    let skipped_queries = queries.len().saturating_sub(queries_to_run.len());
+/// What a thread running checks sends back about the check at an index of the queries.
+enum CheckMessage {
+    Found(usize, QueryResultItem),
+    Finished(usize, anyhow::Result<CheckRun>),
+}
 
-    if skipped_queries > 0 {
-        colored_ln(&mut config.output_writer, |w| {
-            colored!(
-                w,
-                "{}{}{:>12}{} {}{}{} checks ({} checks skipped), version {} -> {} ({} change)",
-                fg!(Some(Color::Green)),
-                bold!(true),
-                "Starting",
-                reset!(),
-                bold!(true),
-                queries_to_run.len(),
-                reset!(),
-                skipped_queries,
-                baseline_version.unwrap_or("unknown"),
-                current_version.unwrap_or("unknown"),
-                change
-            )
-        })
-        .expect("print failed");
+/// How many messages the threads running checks can send ahead of the reporting thread.
+const MESSAGE_BUFFER: usize = 1024;
+
+/// The index of the check being reported, which threads running later checks wait for
+/// before sending what they find, so nothing is held back on the reporting thread.
+struct ReportedCheck {
+    index: Mutex<usize>,
+    changed: Condvar,
+}
+
+impl ReportedCheck {
+    /// Wait until the check at `index` is reported, returning whether it is: checks after
+    /// the one that stops the run are never reported.
+    fn wait_for(&self, index: usize, stop_index: &AtomicUsize) -> bool {
+        let reported = self.index.lock().expect("lock poisoned");
+        let reported = self
+            .changed
+            .wait_while(reported, |reported| {
+                *reported < index && index <= stop_index.load(Ordering::SeqCst)
+            })
+            .expect("lock poisoned");
+        *reported == index
+    }
+
+    /// Let threads waiting for checks up to `index` go on.
+    fn advance(&self, index: usize) {
+        *self.index.lock().expect("lock poisoned") = index;
+        self.changed.notify_all();
+    }
+}
+
+/// The number of checks to run at once when `--jobs` isn't given: one per CPU core.
+fn default_jobs() -> usize {
+    std::thread::available_parallelism().map_or(1, usize::from)
+}
+
+/// Run the checks on the comparison on up to `--jobs` threads, returning what each found,
+/// in the order of `queries`. The query engine needs an adapter it can borrow mutably,
+/// so each thread has its own clone of one adapter, which share the indexes they build.
+///
+/// Results are passed to `on_event` as the checks find them, on the calling thread and in
+/// the order of `queries`: the earliest unfinished check is reported as it runs, while the
+/// threads running later checks wait for the checks before them to finish before sending
+/// their first result. Memory therefore doesn't grow with the number of results found.
+///
+/// Once a check fails in a way that stops the run with `--fail-fast`, later checks aren't
+/// started, and the returned runs end with that check. They're returned with how the checks
+/// used the adapter's indexes.
+fn run_checks(
+    schema: &Schema,
+    comparison: &CrateComparison,
+    queries: &[(&&str, &&SemverQuery)],
+    options: &CheckReleaseOptions,
+    current_semver: Option<&semver::Version>,
+    on_event: &mut dyn FnMut(usize, CheckEvent),
+) -> anyhow::Result<(Vec<CheckRun>, BTreeMap<&'static str, IndexUsage>)> {
+    let jobs = options
+        .jobs
+        .unwrap_or_else(default_jobs)
+        .clamp(1, queries.len().max(1));
+    let next_index = &AtomicUsize::new(0);
+    // The index of the earliest check known to stop the run.
+    let stop_index = &AtomicUsize::new(usize::MAX);
+    let (sender, receiver) = mpsc::sync_channel(MESSAGE_BUFFER);
+    let reported = &ReportedCheck {
+        index: Mutex::new(0),
+        changed: Condvar::new(),
+    };
+
+    let unchanged = if options.incremental {
+        let start_instant = std::time::Instant::now();
+        let unchanged = UnchangedItems::between(
+            CrateGraph {
+                main: &comparison.baseline,
+                dependencies: &comparison.dependencies.baseline,
+            },
+            CrateGraph {
+                main: &comparison.current,
+                dependencies: &comparison.dependencies.current,
+            },
+        );
+        debug!(
+            "{} baseline and {} current items are unchanged, found in {:.3}s",
+            unchanged.baseline.len(),
+            unchanged.current.len(),
+            start_instant.elapsed().as_secs_f32(),
+        );
+        Some(unchanged)
     } else {
-        colored_ln(&mut config.output_writer, |w| {
-            colored!(
-                w,
-                "{}{}{:>12}{} {}{}{} checks, version {} -> {} ({} change)",
-                fg!(Some(Color::Green)),
-                bold!(true),
-                "Starting",
-                reset!(),
-                bold!(true),
-                queries_to_run.len(),
-                reset!(),
-                baseline_version.unwrap_or("unknown"),
-                current_version.unwrap_or("unknown"),
-                change,
-            )
-        })
-        .expect("print failed");
+        None
+    };
+    let mut adapter = RustdocAdapter::new(&comparison.current, Some(&comparison.baseline))
+        .with_dependencies(
+            &comparison.dependencies.current,
+            &comparison.dependencies.baseline,
+        );
+    if let Some(unchanged) = &unchanged {
+        adapter = adapter.with_unchanged_items(unchanged);
     }
-    let mut total_duration = Duration::default();
+    let adapter = &adapter;
 
-    for (query_id, semver_query) in queries_to_run.iter().copied() {
-        let category = match semver_query.required_update {
-            RequiredSemverUpdate::Major => "major",
-            RequiredSemverUpdate::Minor => "minor",
-        };
-        if config.printing_to_terminal {
-            colored!(
-                config.output_writer,
-                "{}{}{:>12}{} [{:9}] {:^18} {}",
-                fg!(Some(Color::Cyan)),
-                bold!(true),
-                "Running",
-                reset!(),
-                "",
-                category,
-                query_id,
-            )
-            .expect("print failed");
-            config.output_writer.flush().expect("flush failed");
+    std::thread::scope(|scope| {
+        for _ in 0..jobs {
+            let sender = sender.clone();
+            scope.spawn(move || {
+                let adapter = Rc::new(RefCell::new(adapter.clone()));
+                loop {
+                    let index = next_index.fetch_add(1, Ordering::SeqCst);
+                    if index >= queries.len() || index > stop_index.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let (query_id, semver_query) = queries[index];
+                    let _check = debug_span!("check", id = %query_id).entered();
+                    let start_instant = std::time::Instant::now();
+                    let run = make_result_iter(schema, adapter.clone(), semver_query).map(
+                        |results_iter| {
+                            let mut found = 0;
+                            let mut suppressed = 0;
+                            let mut reporting = false;
+                            for result in results_iter {
+                                if options.exceptions.is_suppressed(
+                                    query_id,
+                                    &result,
+                                    current_semver,
+                                ) {
+                                    suppressed += 1;
+                                } else {
+                                    found += 1;
+                                    if !reporting {
+                                        reporting = reported.wait_for(index, stop_index);
+                                    }
+                                    // Results of checks that won't be reported are only counted.
+                                    if reporting {
+                                        // The receiver only hangs up after an error in
+                                        // another check.
+                                        let _ = sender.send(CheckMessage::Found(index, result));
+                                    }
+                                }
+                            }
+                            let duration = start_instant.elapsed();
+                            debug!(
+                                "found {} results, {} of them suppressed, in {:.3}s",
+                                found + suppressed,
+                                suppressed,
+                                duration.as_secs_f32(),
+                            );
+                            CheckRun {
+                                found,
+                                suppressed,
+                                duration,
+                            }
+                        },
+                    );
+                    if matches!(&run, Ok(run) if run.stops_run(options, semver_query)) {
+                        stop_index.fetch_min(index, Ordering::SeqCst);
+                    }
+                    let _ = sender.send(CheckMessage::Finished(index, run));
+                }
+            });
         }
+        drop(sender);
 
-        let start_instant = std::time::Instant::now();
-        let mut results_iter = make_result_iter(&schema, adapter.clone(), semver_query)?;
-        let peeked = results_iter.peek();
-        let end_instant = std::time::Instant::now();
-        let time_to_decide = end_instant - start_instant;
-        total_duration += time_to_decide;
-
-        if peeked.is_none() {
-            if config.printing_to_terminal {
-                write!(config.output_writer, "\r").expect("print failed");
+        let mut reporter = InOrderReporter {
+            options,
+            queries,
+            on_event,
+            reported,
+            finished: queries.iter().map(|_| None).collect(),
+            runs: vec![],
+            done: queries.is_empty(),
+        };
+        if !reporter.done {
+            (reporter.on_event)(0, CheckEvent::Started);
+        }
+        let mut error = None;
+        for message in receiver {
+            if reporter.done {
+                continue;
             }
-            colored_ln(&mut config.output_writer, |w| {
-                colored!(
-                    w,
-                    "{}{}{:>12}{} [{:>8.3}s] {:^18} {}",
-                    fg!(Some(Color::Green)),
-                    bold!(true),
-                    "PASS",
-                    reset!(),
-                    time_to_decide.as_secs_f32(),
-                    category,
-                    query_id,
-                )
-            })
-            .expect("print failed");
-        } else {
-            queries_with_errors.push(QueryWithResults::new(query_id.as_str(), results_iter));
-
-            if config.printing_to_terminal {
-                write!(config.output_writer, "\r").expect("print failed");
+            match message {
+                CheckMessage::Found(index, result) => reporter.found(index, result),
+                CheckMessage::Finished(index, run) => {
+                    if let Err(err) = reporter.finished(index, run) {
+                        // Stop the other threads, and report the error once they're done.
+                        stop_index.fetch_min(0, Ordering::SeqCst);
+                        error = Some(err);
+                    }
+                }
+            }
+            if reporter.done {
+                reporter.reported.advance(usize::MAX);
             }
-            colored_ln(&mut config.output_writer, |w| {
-                colored!(
-                    w,
-                    "{}{}{:>12}{} [{:>8.3}s] {:^18} {}",
-                    fg!(Some(Color::Red)),
-                    bold!(true),
-                    "FAIL",
-                    reset!(),
-                    time_to_decide.as_secs_f32(),
-                    category,
-                    query_id,
-                )
-            })
-            .expect("print failed");
         }
-    }
-
-    if !queries_with_errors.is_empty() {
-        colored_ln(&mut config.output_writer, |w| {
-            colored!(
-                w,
-                "{}{}{:>12}{} [{:>8.3}s] {} checks run: {} passed, {} failed, {} skipped",
-                fg!(Some(Color::Red)),
-                bold!(true),
-                "Summary",
-                reset!(),
-                total_duration.as_secs_f32(),
-                queries_to_run.len(),
-                queries_to_run.len() - queries_with_errors.len(),
-                queries_with_errors.len(),
-                skipped_queries,
-            )
-        })
-        .expect("print failed");
+        match error {
+            Some(err) => Err(err),
+            None => Ok((reporter.runs, adapter.index_usage())),
+        }
+    })
+}
 
-        let mut required_versions = vec![];
+/// Reports what the concurrently running checks find in the order of the checks.
+struct InOrderReporter<'a, 'q> {
+    options: &'a CheckReleaseOptions,
+    queries: &'a [(&'q &'q str, &'q &'q SemverQuery)],
+    on_event: &'a mut dyn FnMut(usize, CheckEvent),
+    /// The index of the check being reported, shared with the threads running checks.
+    reported: &'a ReportedCheck,
+    finished: Vec<Option<anyhow::Result<CheckRun>>>,
+    /// The finished checks that were reported. The next check is the one being reported.
+    runs: Vec<CheckRun>,
+    /// Whether every check that's part of the run was reported.
+    done: bool,
+}
 
-        for query_with_results in queries_with_errors {
-            let semver_query = &queries[query_with_results.name];
-            required_versions.push(semver_query.required_update);
-            colored_ln(&mut config.output_writer, |w| {
-                colored!(
-                    w,
-                    "\n--- failure {}: {} ---\n",
-                    &semver_query.id,
-                    &semver_query.human_readable_name,
-                )
-            })
-            .expect("print failed");
+impl InOrderReporter<'_, '_> {
+    fn found(&mut self, index: usize, result: QueryResultItem) {
+        // Only the check being reported sends its results.
+        (self.on_event)(index, CheckEvent::Found(result));
+    }
 
-            if let Some(ref_link) = semver_query.reference_link.as_deref() {
-                colored_ln(&mut config.output_writer, |w| {
-                    colored!(
-                        w,
-                        "{}Description:{}\n{}\n{:>12} {}\n{:>12} {}\n",
-                        bold!(true),
-                        reset!(),
-                        &semver_query.error_message,
-                        "ref:",
-                        ref_link,
-                        "impl:",
-                        format!(
-                            "https://github.com/obi1kenobi/cargo-semver-check/tree/v{}/src/queries/{}.ron",
-                            crate_version!(),
-                            semver_query.id,
-                        )
-                    )
-                })
-                .expect("print failed");
-            } else {
-                colored_ln(&mut config.output_writer, |w| {
-                    colored!(
-                        w,
-                        "{}Description:{}\n{}\n{:>12} {}\n",
-                        bold!(true),
-                        reset!(),
-                        &semver_query.error_message,
-                        "impl:",
-                        format!(
-                            "https://github.com/obi1kenobi/cargo-semver-check/tree/v{}/src/queries/{}.ron",
-                            crate_version!(),
-                            semver_query.id,
-                        )
-                    )
-                })
-                .expect("print failed");
+    fn finished(&mut self, index: usize, run: anyhow::Result<CheckRun>) -> anyhow::Result<()> {
+        self.finished[index] = Some(run);
+        // Report every check that finished in a row, starting from the next one.
+        while !self.done {
+            let index = self.runs.len();
+            let run = match self.finished[index].take() {
+                Some(run) => run.inspect_err(|_| self.done = true)?,
+                None => break,
+            };
+            (self.on_event)(index, CheckEvent::Finished(&run));
+            let (_, semver_query) = self.queries[index];
+            self.done =
+                run.stops_run(self.options, semver_query) || index + 1 == self.queries.len();
+            self.runs.push(run);
+            if !self.done {
+                (self.on_event)(index + 1, CheckEvent::Started);
+                self.reported.advance(index + 1);
             }
+        }
+        Ok(())
+    }
+}
 
-            colored_ln(&mut config.output_writer, |w| {
-                colored!(w, "{}Failed in:{}", bold!(true), reset!(),)
-            })
-            .expect("print failed");
+/// Run every query regardless of the declared version change, and determine
+/// the smallest version update that the changes require, if any.
+///
+/// As with `check-release`, warnings and results accepted as semver exceptions
+/// don't require an update.
+fn find_required_update(
+    selection: &QuerySelection,
+    exceptions: &SemverExceptions,
+    comparisons: &[CrateComparison],
+) -> anyhow::Result<Option<RequiredSemverUpdate>> {
+    let queries = selection.queries();
+    let schema = RustdocAdapter::schema();
+    let current_semver = comparisons
+        .first()
+        .and_then(|comparison| comparison.current.crate_version.as_deref())
+        .and_then(|version| semver::Version::parse(version).ok());
 
-            let reg = Handlebars::new();
-            let start_instant = std::time::Instant::now();
-            for semver_violation_result in query_with_results.results {
-                let pretty_result: BTreeMap<Arc<str>, TransparentValue> = semver_violation_result
-                    .into_iter()
-                    .map(|(k, v)| (k, v.into()))
-                    .collect();
+    let mut required_update: Option<RequiredSemverUpdate> = None;
+    for comparison in comparisons {
+        let adapter = Rc::new(RefCell::new(
+            RustdocAdapter::new(&comparison.current, Some(&comparison.baseline)).with_dependencies(
+                &comparison.dependencies.current,
+                &comparison.dependencies.baseline,
+            ),
+        ));
 
-                if let Some(template) = semver_query.per_result_error_template.as_deref() {
-                    colored_ln(&mut config.output_writer, |w| {
-                        colored!(
-                            w,
-                            "  {}",
-                            reg.render_template(template, &pretty_result)
-                                .with_context(|| "Error instantiating semver query template.")
-                                .expect("could not materialize template"),
-                        )
-                    })
-                    .expect("print failed");
-                } else {
-                    colored_ln(&mut config.output_writer, |w| {
-                        colored!(
-                            w,
-                            "{}\n",
-                            serde_json::to_string_pretty(&pretty_result).expect("serde failed"),
-                        )
-                    })
-                    .expect("print failed");
+        for (query_id, semver_query) in &queries {
+            if semver_query.lint_level.is_warning()
+                || matches!(required_update, Some(RequiredSemverUpdate::Major))
+                || required_update == Some(semver_query.required_update)
+            {
+                // This query can't make the required update any larger.
+                continue;
+            }
+
+            if make_result_iter(&schema, adapter.clone(), semver_query)?
+                .any(|result| !exceptions.is_suppressed(query_id, &result, current_semver.as_ref()))
+            {
+                required_update = Some(semver_query.required_update);
+                if semver_query.required_update == RequiredSemverUpdate::Major {
+                    return Ok(required_update);
                 }
             }
-            let end_instant = std::time::Instant::now();
-            total_duration += end_instant - start_instant;
         }
+    }
 
-        let required_bump = if required_versions.contains(&RequiredSemverUpdate::Major) {
-            "major"
-        } else if required_versions.contains(&RequiredSemverUpdate::Minor) {
-            "minor"
-        } else {
-            unreachable!("{:?}", required_versions)
-        };
+    Ok(required_update)
+}
+
+/// The smallest version after `baseline` that allows the given update,
+/// following Cargo's conventions for `0.y.z` versions.
+fn next_version(
+    baseline: &semver::Version,
+    required_update: Option<RequiredSemverUpdate>,
+) -> semver::Version {
+    let (major, minor, patch) = (baseline.major, baseline.minor, baseline.patch);
+    let (major, minor, patch) = match required_update {
+        Some(RequiredSemverUpdate::Major) if major > 0 => (major + 1, 0, 0),
+        Some(RequiredSemverUpdate::Major) if minor > 0 => (0, minor + 1, 0),
+        Some(RequiredSemverUpdate::Minor) if major > 0 => (major, minor + 1, 0),
+        _ => (major, minor, patch + 1),
+    };
+    semver::Version::new(major, minor, patch)
+}
+
+/// Run every query regardless of the declared version change, and print only
+/// the kind of release the changes require: `major`, `minor`, or `patch`.
+pub(super) fn print_required_bump(
+    mut config: GlobalConfig,
+    selection: &QuerySelection,
+    exceptions: &SemverExceptions,
+    comparisons: Vec<CrateComparison>,
+) -> anyhow::Result<()> {
+    let required_bump = match find_required_update(selection, exceptions, &comparisons)? {
+        Some(RequiredSemverUpdate::Major) => "major",
+        Some(RequiredSemverUpdate::Minor) => "minor",
+        None => "patch",
+    };
+
+    writeln!(config.output_writer, "{required_bump}").expect("print failed");
+    Ok(())
+}
+
+/// Update the version in the current crate's manifest, if necessary, to the smallest
+/// version after the baseline that is compatible with the changes between them.
+pub(super) fn fix_version(
+    mut config: GlobalConfig,
+    manifest_path: &Path,
+    selection: &QuerySelection,
+    exceptions: &SemverExceptions,
+    comparisons: Vec<CrateComparison>,
+) -> anyhow::Result<()> {
+    let first_comparison = comparisons.first().expect("no crates to compare");
+    let baseline_version = first_comparison.baseline.crate_version.as_deref().context(
+        "The baseline crate version is unknown, so the next version cannot be determined.",
+    )?;
+    let baseline_version = semver::Version::parse(baseline_version)
+        .with_context(|| format!("Baseline version {baseline_version} is not a valid version"))?;
+    let current_version = load_package_info(manifest_path)?.version;
+    let current_version = semver::Version::parse(&current_version)
+        .with_context(|| format!("Current version {current_version} is not a valid version"))?;
 
+    let required_update = find_required_update(selection, exceptions, &comparisons)?;
+    let new_version = next_version(&baseline_version, required_update);
+
+    if current_version >= new_version {
         colored_ln(&mut config.output_writer, |w| {
             colored!(
                 w,
-                "\n{}{}{:>12}{} [{:>8.3}s] semver requires new {} version: {} major and {} minor checks failed",
-                fg!(Some(Color::Red)),
+                "{}{}{:>12}{} version {} is already semver-compatible with baseline version {}",
+                fg!(Some(Color::Green)),
                 bold!(true),
-                "Final",
+                "Unchanged",
                 reset!(),
-                total_duration.as_secs_f32(),
-                required_bump,
-                required_versions.iter().filter(|x| *x == &RequiredSemverUpdate::Major).count(),
-                required_versions.iter().filter(|x| *x == &RequiredSemverUpdate::Minor).count(),
+                current_version,
+                baseline_version,
             )
         })
         .expect("print failed");
-
-        std::process::exit(1);
+        return Ok(());
     }
 
+    set_package_version(manifest_path, &new_version)?;
     colored_ln(&mut config.output_writer, |w| {
         colored!(
             w,
-            "{}{}{:>12}{} [{:>8.3}s] {} checks run: {} passed, {} skipped",
+            "{}{}{:>12}{} version {} -> {} in {}",
             fg!(Some(Color::Green)),
             bold!(true),
-            "Summary",
+            "Updated",
             reset!(),
-            total_duration.as_secs_f32(),
-            queries_to_run.len(),
-            queries_to_run.len(),
-            skipped_queries,
+            current_version,
+            new_version,
+            manifest_path.display(),
         )
     })
     .expect("print failed");
 
     Ok(())
 }
+
+/// Whether a release passed its semver checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ReleaseCheckResult {
+    Passed,
+    ViolationsFound,
+}
+
+/// Everything a `check-release` run found, for the caller to print and act on.
+#[derive(Debug, Clone)]
+pub(crate) struct SemverReport<'a> {
+    /// The checks that ran, with the violations each of them found, and those that were skipped.
+    pub(crate) checks: CheckReport<'a>,
+
+    /// How many of the checks to run didn't get to, because of `--fail-fast`.
+    pub(crate) not_run: usize,
+
+    /// The time spent running the checks and describing their results.
+    pub(crate) duration: Duration,
+
+    pub(crate) stats: RunStats<'a>,
+
+    /// The size of the public API of the baseline and of the current version,
+    /// in the first comparison.
+    pub(crate) baseline_surface: ApiSurface,
+    pub(crate) current_surface: ApiSurface,
+
+    /// Whether several targets or baselines were compared, and which of the two,
+    /// so violations can say which comparisons they were found in.
+    multiple_comparisons: bool,
+    multiple_baselines: bool,
+}
+
+impl<'a> SemverReport<'a> {
+    pub(crate) fn result(&self) -> ReleaseCheckResult {
+        if self.checks.failures().next().is_some() {
+            ReleaseCheckResult::ViolationsFound
+        } else {
+            ReleaseCheckResult::Passed
+        }
+    }
+}
+
+/// Run the selected checks on each comparison, telling the observer about their progress.
+pub(crate) fn run_check_release<'a>(
+    observer: &mut dyn CheckObserver,
+    options: &'a CheckReleaseOptions,
+    comparisons: &'a [CrateComparison],
+) -> anyhow::Result<SemverReport<'a>> {
+    let first_comparison = comparisons.first().expect("no crates to compare");
+    let current_version = first_comparison.current.crate_version.as_deref();
+    let baseline_version = first_comparison.baseline.crate_version.as_deref();
+    let current_semver = current_version.and_then(|version| semver::Version::parse(version).ok());
+
+    let version_change = options
+        .release_type
+        .or_else(|| get_semver_version_change(current_version, baseline_version))
+        .unwrap_or_else(|| {
+            observer.version_change_unknown();
+            ActualSemverUpdate::NotChanged
+        });
+    let change = match version_change {
+        ActualSemverUpdate::Major => "major",
+        ActualSemverUpdate::Minor => "minor",
+        ActualSemverUpdate::Patch => "patch",
+        ActualSemverUpdate::NotChanged => "no",
+    };
+    let change = if options.release_type.is_some() {
+        format!("{change} change, per --release-type")
+    } else {
+        format!("{change} change")
+    };
+
+    let queries = options.selection.queries();
+
+    let schema = RustdocAdapter::schema();
+    let mut queries_with_errors: BTreeMap<&str, QueryWithResults> = BTreeMap::new();
+
+    let queries_to_run: Vec<_> = queries
+        .iter()
+        .filter(|(_, query)| !query.allowed_by(version_change))
+        .collect();
+
+    let environment = run_environment(options, comparisons);
+    observer.run_started(&RunInfo {
+        baseline_version,
+        current_version,
+        change: &change,
+        checks: queries_to_run.len(),
+        skipped: queries.len().saturating_sub(queries_to_run.len()),
+        build_flags: options.build_flags.describe(),
+        features: &environment.features,
+        targets: &environment.targets,
+        toolchain: environment.toolchain.as_deref(),
+        baseline_format_version: environment.baseline_format_version,
+        current_format_version: environment.current_format_version,
+    });
+    for semver_query in queries
+        .values()
+        .filter(|query| query.allowed_by(version_change))
+    {
+        observer.check_skipped(&check_info(semver_query));
+    }
+
+    let reg = template_registry();
+    let mut total_duration = Duration::default();
+    let mut suppressed_results = 0;
+    let mut stats = RunStats::new(options.rustdoc_duration);
+
+    // When checking against several baselines, report which of them each violation regresses against.
+    let multiple_baselines = comparisons
+        .iter()
+        .map(|comparison| comparison.baseline.crate_version.as_deref())
+        .collect::<BTreeSet<_>>()
+        .len()
+        > 1;
+
+    // With `--fail-fast`, checks that didn't get to run are left out of the report.
+    let mut ran_queries: BTreeSet<&str> = BTreeSet::new();
+    let mut stopped_early = false;
+
+    'comparisons: for comparison in comparisons {
+        let target_start_instant = std::time::Instant::now();
+        let label = comparison.label(multiple_baselines);
+        if let Some(label) = label.as_deref() {
+            observer.comparison_started(&ComparisonInfo {
+                label,
+                is_baseline: multiple_baselines,
+            });
+        }
+
+        // The checks run concurrently, and the observer hears about them in order, so the log
+        // reads the same as if they had run one after another.
+        let start_instant = std::time::Instant::now();
+        let (runs, index_usage) = run_checks(
+            &schema,
+            comparison,
+            &queries_to_run,
+            options,
+            current_semver.as_ref(),
+            &mut |index, event| {
+                let (query_id, semver_query) = queries_to_run[index];
+                let check = check_info(semver_query);
+                match event {
+                    CheckEvent::Started => observer.check_started(&check),
+                    CheckEvent::Found(result) => {
+                        let violation = make_violation(
+                            &reg,
+                            semver_query,
+                            result.clone(),
+                            label.iter().cloned().collect(),
+                        );
+                        observer.result_found(&check, &(&violation).into());
+                        queries_with_errors
+                            .entry(query_id)
+                            .or_default()
+                            .add_result(label.as_deref(), result);
+                    }
+                    CheckEvent::Finished(run) => {
+                        suppressed_results += run.suppressed;
+                        stats.record(query_id, run.duration, run.found, run.suppressed);
+                        ran_queries.insert(query_id);
+                        if run.found == 0 {
+                            observer.check_passed(&check, run.duration);
+                        } else {
+                            observer.check_failed(&check, run.duration, run.found);
+                        }
+                    }
+                }
+            },
+        )?;
+        total_duration += start_instant.elapsed();
+        stats.record_index_usage(index_usage);
+
+        if queries_to_run
+            .iter()
+            .zip(&runs)
+            .any(|((_, semver_query), run)| run.stops_run(options, semver_query))
+        {
+            stopped_early = true;
+            break 'comparisons;
+        }
+
+        observer.comparison_finished(label.as_deref(), target_start_instant.elapsed());
+    }
+
+    let start_instant = std::time::Instant::now();
+    let impact = ImpactEstimator::new(
+        &first_comparison.current,
+        &first_comparison.dependencies.current,
+        &first_comparison.baseline,
+        &first_comparison.dependencies.baseline,
+    );
+    let outcomes: Vec<CheckOutcome> = queries_to_run
+        .iter()
+        .filter(|(query_id, _)| ran_queries.contains(*query_id))
+        .map(|(query_id, semver_query)| {
+            let check = check_info(semver_query);
+            let mut violations: Vec<_> = queries_with_errors
+                .remove(*query_id)
+                .map(QueryWithResults::into_results)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(result, targets)| {
+                    let path = item_path(&result);
+                    let mut violation = make_violation(&reg, semver_query, result, targets);
+                    violation.baseline_docs_url =
+                        baseline_docs_url(comparisons, multiple_baselines, &violation);
+                    violation.impact = impact.estimate(&violation);
+                    (path, violation)
+                })
+                .collect();
+            // Results come in the adapter's iteration order, which can differ between runs,
+            // so sort them to keep the output stable and diffable.
+            violations.sort_by(|(path, violation), (other_path, other)| {
+                (path, violation.span(), &violation.message).cmp(&(
+                    other_path,
+                    other.span(),
+                    &other.message,
+                ))
+            });
+            let violations = violations
+                .into_iter()
+                .map(|(_, violation)| {
+                    observer.violation_found(&check, &(&violation).into());
+                    violation
+                })
+                .collect();
+            CheckOutcome {
+                query: semver_query,
+                violations,
+            }
+        })
+        .collect();
+    let report = CheckReport {
+        baseline_version,
+        current_version,
+        outcomes,
+        skipped: queries
+            .values()
+            .copied()
+            .filter(|query| query.allowed_by(version_change))
+            .collect(),
+        suppressed: suppressed_results,
+        environment,
+    };
+    let describing_duration = start_instant.elapsed();
+    total_duration += describing_duration;
+    observer.results_described(describing_duration);
+
+    Ok(SemverReport {
+        checks: report,
+        not_run: if stopped_early {
+            queries_to_run.len() - ran_queries.len()
+        } else {
+            0
+        },
+        duration: total_duration,
+        stats,
+        baseline_surface: ApiSurface::of(
+            &first_comparison.baseline,
+            &first_comparison.dependencies.baseline,
+        ),
+        current_surface: ApiSurface::of(
+            &first_comparison.current,
+            &first_comparison.dependencies.current,
+        ),
+        multiple_comparisons: comparisons.len() > 1,
+        multiple_baselines,
+    })
+}
+
+/// What the compared crates were generated from. Comparisons without a target are on the host,
+/// if rustdoc JSON was generated on it.
+fn run_environment(
+    options: &CheckReleaseOptions,
+    comparisons: &[CrateComparison],
+) -> RunEnvironment {
+    let build = &options.build_environment;
+    let mut targets: Vec<String> = vec![];
+    for target in comparisons
+        .iter()
+        .filter_map(|comparison| comparison.target.as_ref().or(build.host_target.as_ref()))
+    {
+        if !targets.contains(target) {
+            targets.push(target.clone());
+        }
+    }
+    let first_comparison = comparisons.first().expect("no crates to compare");
+    RunEnvironment {
+        features: build.features.clone(),
+        targets,
+        toolchain: build.toolchain.clone(),
+        baseline_format_version: first_comparison.baseline.format_version,
+        current_format_version: first_comparison.current.format_version,
+    }
+}
+
+/// A link to the baseline documentation of the item responsible for the violation, if the
+/// baseline it regresses against was published. With several baselines, the first one is used.
+fn baseline_docs_url(
+    comparisons: &[CrateComparison],
+    multiple_baselines: bool,
+    violation: &Violation,
+) -> Option<String> {
+    let comparison = comparisons
+        .iter()
+        .filter(|comparison| comparison.baseline_docs.is_some())
+        .find(|comparison| match comparison.label(multiple_baselines) {
+            Some(label) => violation.targets.contains(&label),
+            None => true,
+        })?;
+    let path = match violation.data.get("path")? {
+        TransparentValue::List(path) => path
+            .iter()
+            .map(|component| match component {
+                TransparentValue::String(component) => Some(component.as_str()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>()?,
+        _ => return None,
+    };
+    let anchor = match (
+        violation.data.get("variant_name"),
+        violation.data.get("field_name"),
+    ) {
+        (Some(TransparentValue::String(variant)), _) => Some(format!("variant.{variant}")),
+        (_, Some(TransparentValue::String(field))) => Some(format!("structfield.{field}")),
+        _ => None,
+    };
+    comparison
+        .baseline_docs
+        .as_ref()?
+        .item_url(&comparison.baseline, &path, anchor.as_deref())
+}
+
+fn check_info(semver_query: &SemverQuery) -> CheckInfo<'_> {
+    CheckInfo {
+        id: &semver_query.id,
+        name: &semver_query.human_readable_name,
+        required_update: semver_query.required_update,
+        lint_level: semver_query.lint_level,
+    }
+}
+
+/// Write the report in the requested formats, and print the summary of the run
+/// and the violations that were found.
+pub(super) fn print_report(
+    config: &mut GlobalConfig,
+    options: &CheckReleaseOptions,
+    semver_report: &SemverReport,
+) -> anyhow::Result<()> {
+    let report = &semver_report.checks;
+    let total_duration = semver_report.duration;
+
+    if options.output_format != OutputFormat::Human {
+        write_report_output(options, report)?;
+    }
+    if let Some(emit) = options.emit {
+        write_emitted(emit, report, &mut std::io::stdout().lock())?;
+    }
+    if let Some(stats_format) = options.stats_format {
+        semver_report.stats.print(config, stats_format);
+    }
+
+    if config.verbosity >= Verbosity::Normal {
+        let current_semver = report
+            .current_version
+            .and_then(|version| semver::Version::parse(version).ok());
+        for exception in options.exceptions.expired(current_semver.as_ref()) {
+            colored_ln(&mut config.output_writer, |w| {
+                colored!(
+                    w,
+                    "{}{}{:>12}{} exception for {} at {} expired in version {}, and can be removed",
+                    fg!(Some(Color::Yellow)),
+                    bold!(true),
+                    "Warning",
+                    reset!(),
+                    exception.check,
+                    exception.path,
+                    exception
+                        .expires
+                        .as_ref()
+                        .expect("expired exceptions have a version"),
+                )
+            })
+            .expect("print failed");
+        }
+    }
+    let suppressed_note = if report.suppressed > 0 {
+        format!(", {} results suppressed by exceptions", report.suppressed)
+    } else {
+        String::new()
+    };
+
+    let not_run_note = if semver_report.not_run > 0 {
+        format!(", {} not run due to --fail-fast", semver_report.not_run)
+    } else {
+        String::new()
+    };
+
+    let failures: Vec<_> = report.failures().collect();
+    let warnings: Vec<_> = report.warnings().collect();
+    let warnings_note = if !warnings.is_empty() {
+        format!(", {} with warnings", warnings.len())
+    } else {
+        String::new()
+    };
+    let passed = report.outcomes.len() - failures.len() - warnings.len();
+
+    if !failures.is_empty() {
+        if config.verbosity >= Verbosity::Normal && options.summary_template.is_none() {
+            colored_ln(&mut config.output_writer, |w| {
+                colored!(
+                    w,
+                    "{}{}{:>12}{} [{:>8.3}s] {} checks run: {} passed, {} failed{}, {} skipped{}{}",
+                    fg!(Some(Color::Red)),
+                    bold!(true),
+                    "Summary",
+                    reset!(),
+                    total_duration.as_secs_f32(),
+                    report.outcomes.len(),
+                    passed,
+                    failures.len(),
+                    warnings_note,
+                    report.skipped.len(),
+                    not_run_note,
+                    suppressed_note,
+                )
+            })
+            .expect("print failed");
+        }
+
+        match options.group_by {
+            GroupBy::Check => {
+                for outcome in &failures {
+                    print_check_failure(config, options, semver_report, outcome);
+                }
+            }
+            GroupBy::Severity => {
+                for required_update in [RequiredSemverUpdate::Major, RequiredSemverUpdate::Minor] {
+                    let group: Vec<_> = failures
+                        .iter()
+                        .filter(|outcome| outcome.query.required_update == required_update)
+                        .collect();
+                    if group.is_empty() {
+                        continue;
+                    }
+                    colored_ln(&mut config.output_writer, |w| {
+                        colored!(
+                            w,
+                            "\n{}=== requires new {} version: {} checks failed ==={}",
+                            bold!(true),
+                            required_update_name(required_update),
+                            group.len(),
+                            reset!(),
+                        )
+                    })
+                    .expect("print failed");
+                    for outcome in group {
+                        print_check_failure(config, options, semver_report, outcome);
+                    }
+                }
+            }
+            GroupBy::Module => print_failures_by_module(config, options, semver_report, &failures),
+        }
+        if config.verbosity >= Verbosity::Normal {
+            for outcome in &warnings {
+                print_check_failure(config, options, semver_report, outcome);
+            }
+        }
+
+        let required_bump = match report.required_update() {
+            Some(RequiredSemverUpdate::Major) => "major",
+            Some(RequiredSemverUpdate::Minor) => "minor",
+            None => unreachable!("failing checks require an update"),
+        };
+        let count_failures = |required_update| {
+            failures
+                .iter()
+                .filter(|outcome| outcome.query.required_update == required_update)
+                .count()
+        };
+
+        if let Some(template) = options.summary_template.as_deref() {
+            let summary = render_summary(template, semver_report)?;
+            writeln!(config.output_writer, "\n{summary}").expect("print failed");
+            return Ok(());
+        }
+
+        if config.verbosity >= Verbosity::Normal {
+            print_api_surface(config, semver_report, true);
+        }
+        colored_ln(&mut config.output_writer, |w| {
+            colored!(
+                w,
+                "\n{}{}{:>12}{} [{:>8.3}s] semver requires new {} version: {} major and {} minor checks failed",
+                fg!(Some(Color::Red)),
+                bold!(true),
+                "Final",
+                reset!(),
+                total_duration.as_secs_f32(),
+                required_bump,
+                count_failures(RequiredSemverUpdate::Major),
+                count_failures(RequiredSemverUpdate::Minor),
+            )
+        })
+        .expect("print failed");
+
+        return Ok(());
+    }
+
+    // A passing run prints nothing with `--quiet`, for use in pre-commit hooks.
+    if config.verbosity < Verbosity::Normal {
+        return Ok(());
+    }
+
+    // Warnings don't fail the run, but are shown even when everything else passed.
+    for outcome in &warnings {
+        print_check_failure(config, options, semver_report, outcome);
+    }
+    if !warnings.is_empty() {
+        writeln!(config.output_writer).expect("print failed");
+    }
+
+    if let Some(template) = options.summary_template.as_deref() {
+        let summary = render_summary(template, semver_report)?;
+        writeln!(config.output_writer, "{summary}").expect("print failed");
+        return Ok(());
+    }
+
+    if config.verbosity >= Verbosity::Normal {
+        print_api_surface(config, semver_report, false);
+    }
+    colored_ln(&mut config.output_writer, |w| {
+        colored!(
+            w,
+            "{}{}{:>12}{} [{:>8.3}s] {} checks run: {} passed{}, {} skipped{}",
+            fg!(Some(Color::Green)),
+            bold!(true),
+            "Summary",
+            reset!(),
+            total_duration.as_secs_f32(),
+            report.outcomes.len(),
+            passed,
+            warnings_note,
+            report.skipped.len(),
+            suppressed_note,
+        )
+    })
+    .expect("print failed");
+
+    Ok(())
+}
+
+/// Print the baseline and current declarations of an item one above the other,
+/// with the words that differ highlighted.
+fn print_signature_diff(config: &mut GlobalConfig, baseline: &str, current: &str) {
+    let (baseline, current) = word_diff(baseline, current);
+    for (label, segments, color) in [
+        ("baseline:", baseline, Color::Red),
+        ("current:", current, Color::Green),
+    ] {
+        colored_ln(&mut config.output_writer, |w| {
+            colored!(w, "    {:<10}", label)?;
+            for segment in &segments {
+                if segment.changed {
+                    colored!(
+                        w,
+                        "{}{}{}{}",
+                        fg!(Some(color)),
+                        bold!(true),
+                        segment.text,
+                        reset!()
+                    )?;
+                } else {
+                    colored!(w, "{}", segment.text)?;
+                }
+            }
+            Ok(())
+        })
+        .expect("print failed");
+    }
+}
+
+/// Print the number of public items of each kind in the baseline and the current version,
+/// as a sanity check that the size of the API changed as much as intended, after a blank
+/// line if `blank_line_before`. Nothing is printed if neither version has public items.
+fn print_api_surface(
+    config: &mut GlobalConfig,
+    semver_report: &SemverReport,
+    blank_line_before: bool,
+) {
+    let counts = api_surface::compare(
+        &semver_report.baseline_surface,
+        &semver_report.current_surface,
+    );
+    if counts.is_empty() {
+        return;
+    }
+    if blank_line_before {
+        writeln!(config.output_writer).expect("print failed");
+    }
+    let report = &semver_report.checks;
+    colored_ln(&mut config.output_writer, |w| {
+        colored!(
+            w,
+            "{}{}{:>12}{} public items in {} -> {}:",
+            fg!(Some(Color::Cyan)),
+            bold!(true),
+            "API",
+            reset!(),
+            report.baseline_version.unwrap_or("baseline"),
+            report.current_version.unwrap_or("current"),
+        )
+    })
+    .expect("print failed");
+    for (kind, baseline, current) in counts {
+        let (color, change) = match current.cmp(&baseline) {
+            std::cmp::Ordering::Less => (Some(Color::Yellow), format!("-{}", baseline - current)),
+            std::cmp::Ordering::Greater => (None, format!("+{}", current - baseline)),
+            std::cmp::Ordering::Equal => (None, String::new()),
+        };
+        let counts = if change.is_empty() {
+            format!("{baseline:>6} -> {current}")
+        } else {
+            format!("{baseline:>6} -> {current:<6} ")
+        };
+        colored_ln(&mut config.output_writer, |w| {
+            colored!(
+                w,
+                "{:>12} {:<14} {}{}{}{}",
+                "",
+                kind.plural_name(),
+                counts,
+                fg!(color),
+                change,
+                reset!(),
+            )
+        })
+        .expect("print failed");
+    }
+}
+
+/// Print a failing check, or a check that found something to warn about:
+/// its description, followed by its violations.
+fn print_check_failure(
+    config: &mut GlobalConfig,
+    options: &CheckReleaseOptions,
+    semver_report: &SemverReport,
+    outcome: &CheckOutcome,
+) {
+    let semver_query = outcome.query;
+    let check_id = check_id_link(config, semver_query);
+    let (kind, found_in) = match semver_query.lint_level {
+        LintLevel::Deny => ("failure", "Failed in:"),
+        LintLevel::Warn => ("warning", "Found in:"),
+    };
+    colored_ln(&mut config.output_writer, |w| {
+        colored!(
+            w,
+            "\n--- {} {}: {} ---\n",
+            kind,
+            check_id,
+            &semver_query.human_readable_name,
+        )
+    })
+    .expect("print failed");
+
+    if let Some(ref_link) = semver_query.reference_link.as_deref() {
+        colored_ln(&mut config.output_writer, |w| {
+            colored!(
+                w,
+                "{}Description:{}\n{}\n{:>12} {}\n{:>12} {}\n",
+                bold!(true),
+                reset!(),
+                &semver_query.error_message,
+                "ref:",
+                ref_link,
+                "impl:",
+                semver_query.implementation_link(),
+            )
+        })
+        .expect("print failed");
+    } else {
+        colored_ln(&mut config.output_writer, |w| {
+            colored!(
+                w,
+                "{}Description:{}\n{}\n{:>12} {}\n",
+                bold!(true),
+                reset!(),
+                &semver_query.error_message,
+                "impl:",
+                semver_query.implementation_link(),
+            )
+        })
+        .expect("print failed");
+    }
+
+    colored_ln(&mut config.output_writer, |w| {
+        colored!(w, "{}{}{}", bold!(true), found_in, reset!(),)
+    })
+    .expect("print failed");
+
+    let violations: Vec<_> = outcome.violations.iter().collect();
+    print_violations(config, options, semver_report, semver_query, &violations);
+}
+
+/// Print the violations of every failing check under the module of the affected item,
+/// so that the changes to each module can be reviewed together.
+fn print_failures_by_module(
+    config: &mut GlobalConfig,
+    options: &CheckReleaseOptions,
+    semver_report: &SemverReport,
+    failures: &[&CheckOutcome],
+) {
+    let mut modules: BTreeMap<String, BTreeMap<&str, (&SemverQuery, Vec<&Violation>)>> =
+        BTreeMap::new();
+    for outcome in failures {
+        for violation in &outcome.violations {
+            modules
+                .entry(
+                    violation
+                        .module()
+                        .unwrap_or_else(|| "<unknown module>".to_string()),
+                )
+                .or_default()
+                .entry(outcome.query.id.as_str())
+                .or_insert_with(|| (outcome.query, vec![]))
+                .1
+                .push(violation);
+        }
+    }
+
+    for (module, checks) in modules {
+        colored_ln(&mut config.output_writer, |w| {
+            colored!(w, "\n--- module {} ---\n", module)
+        })
+        .expect("print failed");
+        for (semver_query, violations) in checks.values() {
+            let check_id = check_id_link(config, semver_query);
+            colored_ln(&mut config.output_writer, |w| {
+                colored!(
+                    w,
+                    "{}{}: {}{}",
+                    bold!(true),
+                    check_id,
+                    semver_query.human_readable_name,
+                    reset!(),
+                )
+            })
+            .expect("print failed");
+            print_violations(config, options, semver_report, semver_query, violations);
+        }
+    }
+}
+
+/// The id of the check, linking to the reference for the rule it enforces,
+/// or else to its implementation, where the terminal supports it.
+fn check_id_link(config: &GlobalConfig, semver_query: &SemverQuery) -> String {
+    let url = match (&semver_query.reference_link, &semver_query.source_path) {
+        (Some(reference_link), _) => Some(reference_link.clone()),
+        // User-supplied checks link to their query file.
+        (None, Some(path)) => std::fs::canonicalize(path)
+            .ok()
+            .map(|path| format!("file://{}", path.display())),
+        (None, None) if semver_query.is_builtin => Some(semver_query.implementation_link()),
+        (None, None) => None,
+    };
+    match url {
+        Some(url) => config.link(&semver_query.id, &url),
+        None => semver_query.id.clone(),
+    }
+}
+
+/// Print violations of a check, as many as `--max-results-per-check` allows.
+fn print_violations(
+    config: &mut GlobalConfig,
+    options: &CheckReleaseOptions,
+    semver_report: &SemverReport,
+    semver_query: &SemverQuery,
+    violations: &[&Violation],
+) {
+    let shown_violations = options
+        .max_results_per_check
+        .unwrap_or(usize::MAX)
+        .min(violations.len());
+    for violation in &violations[..shown_violations] {
+        // When making multiple comparisons, note which of them are affected by the violation.
+        let targets_note = match (
+            semver_report.multiple_comparisons,
+            semver_report.multiple_baselines,
+        ) {
+            (true, true) => {
+                format!(" (regresses against: {})", violation.targets.join(", "))
+            }
+            (true, false) => format!(" (targets: {})", violation.targets.join(", ")),
+            (false, _) => String::new(),
+        };
+
+        // Make the location of the item open the file, where the terminal supports it.
+        let message = match (violation.location(), violation.span()) {
+            (Some(location), Some((filename, line))) => {
+                match hyperlink::file_url(Path::new(filename), line) {
+                    Some(url) => {
+                        violation
+                            .message
+                            .replacen(&location, &config.link(&location, &url), 1)
+                    }
+                    None => violation.message.clone(),
+                }
+            }
+            _ => violation.message.clone(),
+        };
+        if semver_query.per_result_error_template.is_some() {
+            colored_ln(&mut config.output_writer, |w| {
+                colored!(w, "  {}{}", message, targets_note)
+            })
+            .expect("print failed");
+        } else {
+            colored_ln(&mut config.output_writer, |w| {
+                colored!(w, "{}{}\n", message, targets_note)
+            })
+            .expect("print failed");
+        }
+
+        if let Some(url) = &violation.baseline_docs_url {
+            colored_ln(&mut config.output_writer, |w| {
+                colored!(w, "    baseline docs: {}", url)
+            })
+            .expect("print failed");
+        }
+        if let Some((baseline, current)) = violation.signatures() {
+            print_signature_diff(config, baseline, current);
+        }
+        if let Some(impact @ 1..) = violation.impact {
+            colored_ln(&mut config.output_writer, |w| {
+                colored!(
+                    w,
+                    "    impact: used by {} other public {}",
+                    impact,
+                    if impact == 1 { "item" } else { "items" },
+                )
+            })
+            .expect("print failed");
+        }
+
+        if let (true, Some(witness)) = (options.show_witnesses, &violation.witness) {
+            let witness: Vec<_> = witness
+                .trim()
+                .lines()
+                .map(|line| format!("      {line}"))
+                .collect();
+            colored_ln(&mut config.output_writer, |w| {
+                colored!(
+                    w,
+                    "    {}Compiles against the baseline, but not the current version:{}\n{}\n",
+                    bold!(true),
+                    reset!(),
+                    witness.join("\n"),
+                )
+            })
+            .expect("print failed");
+        }
+    }
+    if shown_violations < violations.len() {
+        colored_ln(&mut config.output_writer, |w| {
+            colored!(
+                w,
+                "  ... and {} more\n",
+                violations.len() - shown_violations,
+            )
+        })
+        .expect("print failed");
+    }
+}
+
+/// Render a user-supplied summary template with the outcome of the run.
+fn render_summary(template: &str, semver_report: &SemverReport) -> anyhow::Result<String> {
+    let report = &semver_report.checks;
+    let failures: Vec<_> = report
+        .failures()
+        .map(|outcome| {
+            serde_json::json!({
+                "id": outcome.query.id,
+                "name": outcome.query.human_readable_name,
+                "required_update": required_update_name(outcome.query.required_update),
+                "violations": outcome.violations.len(),
+            })
+        })
+        .collect();
+    let warnings: Vec<_> = report
+        .warnings()
+        .map(|outcome| {
+            serde_json::json!({
+                "id": outcome.query.id,
+                "name": outcome.query.human_readable_name,
+                "violations": outcome.violations.len(),
+            })
+        })
+        .collect();
+    let api_surface: serde_json::Map<String, serde_json::Value> = api_surface::compare(
+        &semver_report.baseline_surface,
+        &semver_report.current_surface,
+    )
+    .into_iter()
+    .map(|(kind, baseline, current)| {
+        (
+            kind.plural_name().replace(' ', "_"),
+            serde_json::json!({ "baseline": baseline, "current": current }),
+        )
+    })
+    .collect();
+    let count_failures = |required_update| {
+        report
+            .failures()
+            .filter(|outcome| outcome.query.required_update == required_update)
+            .count()
+    };
+    let data = serde_json::json!({
+        "passed": failures.is_empty(),
+        "required_update": report.required_update().map(required_update_name),
+        "baseline_version": report.baseline_version,
+        "current_version": report.current_version,
+        "duration": format!("{:.3}", semver_report.duration.as_secs_f32()),
+        "checks_run": report.outcomes.len(),
+        "checks_passed": report.outcomes.len() - failures.len() - warnings.len(),
+        "checks_failed": failures.len(),
+        "checks_warned": warnings.len(),
+        "checks_skipped": report.skipped.len(),
+        "checks_not_run": semver_report.not_run,
+        "major_failures": count_failures(RequiredSemverUpdate::Major),
+        "minor_failures": count_failures(RequiredSemverUpdate::Minor),
+        "results_suppressed": report.suppressed,
+        "failures": failures,
+        "warnings": warnings,
+        "api_surface": api_surface,
+        "features": report.environment.features,
+        "targets": report.environment.targets,
+        "toolchain": report.environment.toolchain,
+        "baseline_format_version": report.environment.baseline_format_version,
+        "current_format_version": report.environment.current_format_version,
+    });
+    template_registry()
+        .render_template(template, &data)
+        .context("Failed to render the summary template")
+}
+
+fn required_update_name(required_update: RequiredSemverUpdate) -> &'static str {
+    match required_update {
+        RequiredSemverUpdate::Major => "major",
+        RequiredSemverUpdate::Minor => "minor",
+    }
+}
+
+/// Describe a single query result in human-readable form, using the query's
+/// per-result template if it has one.
+fn make_violation(
+    reg: &Handlebars,
+    semver_query: &SemverQuery,
+    result: QueryResultItem,
+    targets: Vec<String>,
+) -> Violation {
+    let pretty_result: BTreeMap<Arc<str>, TransparentValue> =
+        result.into_iter().map(|(k, v)| (k, v.into())).collect();
+
+    let message = if let Some(template) = semver_query.per_result_error_template.as_deref() {
+        reg.render_template(template, &pretty_result)
+            .with_context(|| "Error instantiating semver query template.")
+            .expect("could not materialize template")
+    } else {
+        serde_json::to_string_pretty(&pretty_result).expect("serde failed")
+    };
+    let witness = semver_query.witness_template.as_deref().map(|template| {
+        reg.render_template(template, &pretty_result)
+            .with_context(|| "Error instantiating semver query witness template.")
+            .expect("could not materialize witness template")
+    });
+
+    let mut violation = Violation {
+        message,
+        witness,
+        data: pretty_result,
+        targets,
+        baseline_docs_url: None,
+        impact: None,
+    };
+    // An item reachable through several paths is reported once, listing all of them.
+    if let (Some(template), Some(TransparentValue::List(paths))) = (
+        semver_query.per_result_error_template.as_deref(),
+        violation.data.get("paths"),
+    ) {
+        if paths.len() > 1 && !template.contains("paths") {
+            let paths: Vec<&str> = paths
+                .iter()
+                .filter_map(|path| match path {
+                    TransparentValue::String(path) => Some(path.as_str()),
+                    _ => None,
+                })
+                .collect();
+            violation.message =
+                format!("{}, importable as {}", violation.message, paths.join(", "));
+        }
+    }
+    // Point at the offending item even if the template doesn't say where it is.
+    if let (Some(template), Some(location)) = (
+        semver_query.per_result_error_template.as_deref(),
+        violation.location(),
+    ) {
+        if !template.contains("span_filename") {
+            violation.message = format!("{} in {location}", violation.message);
+        }
+    }
+    violation
+}
+
+/// Write the machine-readable report to the output path, or to stdout if none was given.
+fn write_report_output(options: &CheckReleaseOptions, report: &CheckReport) -> anyhow::Result<()> {
+    match options.output_path.as_deref() {
+        Some(path) => {
+            let mut file = std::fs::File::create(path)
+                .with_context(|| format!("Failed to create output file {:?}", path))?;
+            write_report(options.output_format, report, &mut file)
+                .with_context(|| format!("Failed to write output file {:?}", path))
+        }
+        None => write_report(options.output_format, report, &mut std::io::stdout().lock()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use rustdoc_types::{Crate, FORMAT_VERSION};
+    use semver::Version;
+    use serde_json::json;
+    use trustfall_core::ir::FieldValue;
+
+    use crate::{
+        adapter::RustdocAdapter,
+        exceptions::SemverExceptions,
+        query::{set_lint_levels, QuerySelection, RequiredSemverUpdate, SemverQuery},
+    };
+
+    use crate::templates::template_registry;
+
+    use super::{
+        find_required_update, make_violation, next_version, run_checks, CheckEvent,
+        CheckReleaseOptions, CrateComparison, DependencyRustdocs, FailFast, QueryWithResults,
+    };
+
+    #[test]
+    fn witnesses_are_rendered_as_rust_code() {
+        let queries = SemverQuery::all_queries();
+        let mut result = BTreeMap::new();
+        result.insert(
+            Arc::from("path"),
+            FieldValue::List(vec![
+                FieldValue::String("my_crate".to_string()),
+                FieldValue::String("Config".to_string()),
+            ]),
+        );
+        result.insert(
+            Arc::from("struct_name"),
+            FieldValue::String("Config".to_string()),
+        );
+        result.insert(
+            Arc::from("field_name"),
+            FieldValue::String("quiet".to_string()),
+        );
+
+        let violation = make_violation(
+            &template_registry(),
+            &queries["struct_pub_field_missing"],
+            result,
+            vec![],
+        );
+        assert_eq!(
+            Some("fn witness(value: &my_crate::Config) {\n    let _ = &value.quiet;\n}"),
+            violation.witness.as_deref().map(str::trim),
+        );
+        // `--group-by module` lists the field under the module of its struct.
+        assert_eq!(Some("my_crate".to_string()), violation.module());
+    }
+
+    #[test]
+    fn violations_include_the_location_of_the_item() {
+        let mut query = SemverQuery::all_queries()["struct_missing"].clone();
+        let mut result = BTreeMap::new();
+        result.insert(Arc::from("name"), FieldValue::String("Config".to_string()));
+        result.insert(
+            Arc::from("span_filename"),
+            FieldValue::String("src/lib.rs".to_string()),
+        );
+        result.insert(Arc::from("span_begin_line"), FieldValue::Uint64(12));
+
+        let violation = make_violation(&template_registry(), &query, result.clone(), vec![]);
+        assert_eq!(
+            "struct Config, previously in file src/lib.rs:12",
+            violation.message
+        );
+
+        query.per_result_error_template = Some("struct {{name}}".to_string());
+        let violation = make_violation(&template_registry(), &query, result, vec![]);
+        assert_eq!("struct Config in src/lib.rs:12", violation.message);
+        assert_eq!(Some("src/lib.rs:12".to_string()), violation.location());
+    }
+
+    #[test]
+    fn items_reachable_through_several_paths_are_reported_once() {
+        let result = |path: &[&str], name: &str| {
+            let mut result = BTreeMap::new();
+            result.insert(
+                Arc::from("path"),
+                FieldValue::List(
+                    path.iter()
+                        .map(|component| FieldValue::String(component.to_string()))
+                        .collect(),
+                ),
+            );
+            result.insert(Arc::from("name"), FieldValue::String(name.to_string()));
+            result
+        };
+        let mut results = QueryWithResults::default();
+        results.add_result(None, result(&["my_crate", "config", "Config"], "Config"));
+        results.add_result(None, result(&["my_crate", "Config"], "Config"));
+        results.add_result(None, result(&["my_crate", "Other"], "Other"));
+
+        let merged = results.into_results();
+        assert_eq!(2, merged.len());
+        assert_eq!(
+            result(&["my_crate", "Config"], "Config")["path"],
+            merged[0].0["path"]
+        );
+        assert_eq!(
+            FieldValue::List(vec![
+                FieldValue::String("my_crate::Config".to_string()),
+                FieldValue::String("my_crate::config::Config".to_string()),
+            ]),
+            merged[0].0["paths"],
+        );
+
+        let query = &SemverQuery::all_queries()["struct_missing"];
+        let violation = make_violation(&template_registry(), query, merged[0].0.clone(), vec![]);
+        assert!(violation
+            .message
+            .ends_with(", importable as my_crate::Config, my_crate::config::Config"));
+        let violation = make_violation(&template_registry(), query, merged[1].0.clone(), vec![]);
+        assert!(!violation.message.contains("importable as"));
+    }
+
+    #[test]
+    fn paths_that_are_not_lists_of_strings_are_kept() {
+        let result = |path: FieldValue, name: &str| {
+            let mut result = BTreeMap::new();
+            result.insert(Arc::from("path"), path);
+            result.insert(Arc::from("name"), FieldValue::String(name.to_string()));
+            result
+        };
+        let unmergeable = [
+            FieldValue::List(vec![
+                FieldValue::String("my_crate".to_string()),
+                FieldValue::Null,
+            ]),
+            FieldValue::String("my_crate::Config".to_string()),
+        ];
+
+        for path in unmergeable {
+            let mut results = QueryWithResults::default();
+            results.add_result(None, result(path.clone(), "Config"));
+            results.add_result(None, result(path.clone(), "Other"));
+            let merged = results.into_results();
+            assert_eq!(2, merged.len());
+            for (result, _) in merged {
+                assert_eq!(Some(&path), result.get("path"));
+                assert!(!result.contains_key("paths"));
+            }
+        }
+    }
+
+    #[test]
+    fn results_are_merged_across_targets_as_they_are_found() {
+        let result = |path: &[&str]| {
+            let mut result = BTreeMap::new();
+            result.insert(
+                Arc::from("path"),
+                FieldValue::List(
+                    path.iter()
+                        .map(|component| FieldValue::String(component.to_string()))
+                        .collect(),
+                ),
+            );
+            result
+        };
+        let mut results = QueryWithResults::default();
+        for target in ["x86_64-unknown-linux-gnu", "wasm32-unknown-unknown"] {
+            results.add_result(Some(target), result(&["my_crate", "Config"]));
+            results.add_result(Some(target), result(&["my_crate", "config", "Config"]));
+        }
+        assert_eq!(1, results.results.len());
+
+        let merged = results.into_results();
+        assert_eq!(
+            vec!["x86_64-unknown-linux-gnu", "wasm32-unknown-unknown"],
+            merged[0].1
+        );
+        assert_eq!(result(&["my_crate", "Config"])["path"], merged[0].0["path"]);
+    }
+
+    #[test]
+    fn only_results_with_the_same_outputs_are_merged() {
+        let result = |name: &str, line: u64| {
+            let mut result = BTreeMap::new();
+            result.insert(Arc::from("name"), FieldValue::String(name.to_string()));
+            result.insert(Arc::from("span_begin_line"), FieldValue::Uint64(line));
+            result
+        };
+        let mut results = QueryWithResults::default();
+        for line in 0..1000 {
+            results.add_result(None, result("Config", line));
+        }
+        results.add_result(None, result("Config", 7));
+        results.add_result(None, result("Other", 7));
+        // The same line as an `Int64` is a different output.
+        let mut signed = result("Config", 7);
+        signed.insert(Arc::from("span_begin_line"), FieldValue::Int64(7));
+        results.add_result(None, signed);
+
+        let merged = results.into_results();
+        assert_eq!(1002, merged.len());
+        assert_eq!(result("Config", 999), merged[999].0);
+        assert_eq!(result("Other", 7), merged[1000].0);
+    }
+
+    #[test]
+    fn next_version_follows_cargo_conventions() {
+        let cases = [
+            ("1.2.3", Some(RequiredSemverUpdate::Major), "2.0.0"),
+            ("1.2.3", Some(RequiredSemverUpdate::Minor), "1.3.0"),
+            ("1.2.3", None, "1.2.4"),
+            ("0.2.3", Some(RequiredSemverUpdate::Major), "0.3.0"),
+            ("0.2.3", Some(RequiredSemverUpdate::Minor), "0.2.4"),
+            ("0.2.3", None, "0.2.4"),
+            ("0.0.3", Some(RequiredSemverUpdate::Major), "0.0.4"),
+            ("0.0.3", Some(RequiredSemverUpdate::Minor), "0.0.4"),
+            ("1.2.3-alpha.1", None, "1.2.4"),
+        ];
+        for (baseline, required_update, expected) in cases {
+            let baseline = Version::parse(baseline).unwrap();
+            assert_eq!(
+                Version::parse(expected).unwrap(),
+                next_version(&baseline, required_update),
+                "{baseline} {required_update:?}",
+            );
+        }
+    }
+
+    /// A crate with a unit struct `Foo` and a function `bar` if `with_items`, or with neither.
+    fn example_crate(with_items: bool) -> Crate {
+        let item = |id: &str, name: &str, kind: &str, inner: serde_json::Value| {
+            json!({
+                "id": id, "crate_id": 0, "name": name, "span": null, "visibility": "public",
+                "docs": null, "links": {}, "attrs": [], "deprecation": null, "kind": kind, "inner": inner,
+            })
+        };
+        let generics = json!({ "params": [], "where_predicates": [] });
+        let mut index = json!({});
+        let mut root_items = vec![];
+        if with_items {
+            root_items = vec!["0:1", "0:2"];
+            index["0:1"] = item(
+                "0:1",
+                "Foo",
+                "struct",
+                json!({
+                    "struct_type": "unit", "generics": generics, "fields_stripped": false,
+                    "fields": [], "impls": [],
+                }),
+            );
+            index["0:2"] = item(
+                "0:2",
+                "bar",
+                "function",
+                json!({
+                    "decl": { "inputs": [], "output": null, "c_variadic": false },
+                    "generics": generics,
+                    "header": { "const": false, "unsafe": false, "async": false, "abi": "Rust" },
+                }),
+            );
+        }
+        index["0:0"] = item(
+            "0:0",
+            "demo",
+            "module",
+            json!({ "is_crate": true, "items": root_items }),
+        );
+        serde_json::from_value(json!({
+            "root": "0:0",
+            "crate_version": "1.0.0",
+            "includes_private": false,
+            "index": index,
+            "paths": {},
+            "external_crates": {},
+            "format_version": FORMAT_VERSION,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn incremental_checks_find_the_same_results() {
+        let comparison = CrateComparison {
+            target: None,
+            current: example_crate(false).into(),
+            baseline: example_crate(true).into(),
+            dependencies: DependencyRustdocs::default(),
+            baseline_docs: None,
+        };
+        let queries = SemverQuery::all_queries();
+        let ids: Vec<&str> = queries.keys().map(String::as_str).collect();
+        let checks: Vec<(&str, &SemverQuery)> = ids.iter().copied().zip(queries.values()).collect();
+        let queries_to_run: Vec<_> = checks.iter().map(|(id, query)| (id, query)).collect();
+        let schema = RustdocAdapter::schema();
+        let results = |incremental| -> Vec<usize> {
+            let options = CheckReleaseOptions {
+                incremental,
+                ..Default::default()
+            };
+            run_checks(
+                &schema,
+                &comparison,
+                &queries_to_run,
+                &options,
+                None,
+                &mut |_, _| {},
+            )
+            .unwrap()
+            .0
+            .iter()
+            .map(|run| run.found)
+            .collect()
+        };
+
+        assert_eq!(results(false), results(true));
+    }
+
+    #[test]
+    fn parallel_checks_find_the_same_results_in_the_same_order() {
+        let comparison = CrateComparison {
+            target: None,
+            current: example_crate(false).into(),
+            baseline: example_crate(true).into(),
+            dependencies: DependencyRustdocs::default(),
+            baseline_docs: None,
+        };
+        let queries = SemverQuery::all_queries();
+        let ids: Vec<&str> = queries.keys().map(String::as_str).collect();
+        let checks: Vec<(&str, &SemverQuery)> = ids.iter().copied().zip(queries.values()).collect();
+        let queries_to_run: Vec<_> = checks.iter().map(|(id, query)| (id, query)).collect();
+        let schema = RustdocAdapter::schema();
+        let results = |jobs, fail_fast| -> Vec<usize> {
+            let options = CheckReleaseOptions {
+                jobs: Some(jobs),
+                fail_fast,
+                ..Default::default()
+            };
+            run_checks(
+                &schema,
+                &comparison,
+                &queries_to_run,
+                &options,
+                None,
+                &mut |_, _| {},
+            )
+            .unwrap()
+            .0
+            .iter()
+            .map(|run| run.found)
+            .collect()
+        };
+
+        let sequential = results(1, None);
+        assert_eq!(ids.len(), sequential.len());
+        assert_eq!(1, sequential[ids.binary_search(&"struct_missing").unwrap()]);
+        assert_eq!(
+            1,
+            sequential[ids.binary_search(&"function_missing").unwrap()]
+        );
+        assert_eq!(sequential, results(4, None));
+
+        // `function_missing` is the first check to fail, so the rest are left out.
+        let stopped = results(4, Some(FailFast::Any));
+        assert_eq!(
+            ids.binary_search(&"function_missing").unwrap() + 1,
+            stopped.len()
+        );
+        assert_eq!(sequential[..stopped.len()], stopped[..]);
+
+        // The threads share the indexes, which are each built once for each crate.
+        let options = CheckReleaseOptions {
+            jobs: Some(4),
+            ..Default::default()
+        };
+        let (_, index_usage) = run_checks(
+            &schema,
+            &comparison,
+            &queries_to_run,
+            &options,
+            None,
+            &mut |_, _| {},
+        )
+        .unwrap();
+        assert!(index_usage["items_by_name"].hits > 0);
+        for (name, usage) in index_usage {
+            assert!(
+                usage.uses - usage.hits <= 2,
+                "{name} was built more than twice"
+            );
+        }
+    }
+
+    #[test]
+    fn parallel_checks_report_results_between_their_start_and_finish() {
+        let comparison = CrateComparison {
+            target: None,
+            current: example_crate(false).into(),
+            baseline: example_crate(true).into(),
+            dependencies: DependencyRustdocs::default(),
+            baseline_docs: None,
+        };
+        let queries = SemverQuery::all_queries();
+        let ids: Vec<&str> = queries.keys().map(String::as_str).collect();
+        let checks: Vec<(&str, &SemverQuery)> = ids.iter().copied().zip(queries.values()).collect();
+        let queries_to_run: Vec<_> = checks.iter().map(|(id, query)| (id, query)).collect();
+        let options = CheckReleaseOptions {
+            jobs: Some(4),
+            ..Default::default()
+        };
+        let mut events = vec![];
+        run_checks(
+            &RustdocAdapter::schema(),
+            &comparison,
+            &queries_to_run,
+            &options,
+            None,
+            &mut |index, event| {
+                events.push(match event {
+                    CheckEvent::Started => (index, "started"),
+                    CheckEvent::Found(_) => (index, "found"),
+                    CheckEvent::Finished(_) => (index, "finished"),
+                })
+            },
+        )
+        .unwrap();
+
+        let mut expected = vec![];
+        for (index, id) in ids.iter().enumerate() {
+            expected.push((index, "started"));
+            if ["function_missing", "struct_missing"].contains(id) {
+                expected.push((index, "found"));
+            }
+            expected.push((index, "finished"));
+        }
+        assert_eq!(expected, events);
+    }
+
+    #[test]
+    fn required_update_ignores_warnings_and_exceptions() {
+        let comparisons = [CrateComparison {
+            target: None,
+            current: example_crate(false).into(),
+            baseline: example_crate(true).into(),
+            dependencies: DependencyRustdocs::default(),
+            baseline_docs: None,
+        }];
+        let required_update = |warn: &[&str], exceptions: &str| {
+            let mut queries = SemverQuery::all_queries();
+            let warn: Vec<String> = warn.iter().map(ToString::to_string).collect();
+            set_lint_levels(&mut queries, &warn, &[]).unwrap();
+            let selection = QuerySelection::new(queries, vec![], vec![]).unwrap();
+            let exceptions = SemverExceptions::parse(exceptions).unwrap();
+            find_required_update(&selection, &exceptions, &comparisons).unwrap()
+        };
+
+        // Both `demo::Foo` and `demo::bar` were removed.
+        assert_eq!(Some(RequiredSemverUpdate::Major), required_update(&[], ""));
+        assert_eq!(
+            Some(RequiredSemverUpdate::Major),
+            required_update(&["struct_missing"], ""),
+        );
+        assert_eq!(
+            None,
+            required_update(&["struct_missing", "function_missing"], "")
+        );
+        assert_eq!(
+            None,
+            required_update(
+                &["function_missing"],
+                "[[exception]]\ncheck = \"struct_missing\"\npath = \"demo::Foo\"\n",
+            ),
+        );
+        // Exceptions that expired by the current version no longer apply.
+        assert_eq!(
+            Some(RequiredSemverUpdate::Major),
+            required_update(
+                &["function_missing"],
+                "[[exception]]\ncheck = \"struct_missing\"\npath = \"demo::Foo\"\nexpires = \"1.0.0\"\n",
+            ),
+        );
+    }
+}
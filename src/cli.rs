@@ -0,0 +1,2277 @@
+//! The `cargo semver-checks` command line interface.
+
+use std::{
+    collections::BTreeMap,
+    env,
+    ffi::{OsStr, OsString},
+    io::Write,
+    path::{Path, PathBuf},
+    process::ExitCode,
+    sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Context;
+use clap::{
+    crate_version, value_parser, AppSettings, Arg, ArgGroup, ArgMatches, Command, ValueSource,
+};
+use clap_complete::Shell;
+use rustdoc_types::Crate;
+use termcolor::Color;
+use termcolor_output::{colored, colored_ln};
+
+use crate::{
+    adapter::{SCHEMA_TEXT, SCHEMA_VERSION},
+    baseline::{BaselineLoader, BaselineSource, BaselineUnavailable},
+    bugreport::run_bugreport,
+    cache::{run_cache_action, CacheAction},
+    check_release::{
+        fix_version, print_report, print_required_bump, run_check_release, CheckReleaseOptions,
+        CrateComparison, DependencyRustdocs, FailFast, GroupBy, ReleaseCheckResult,
+    },
+    exceptions::{SemverExceptions, DEFAULT_EXCEPTIONS_FILE},
+    explain::explain,
+    formats::{Emit, OutputFormat},
+    init::{run_init, split_features, BaselineStrategy, InitOptions},
+    interrupt,
+    lint_pack::load_lint_packs,
+    list_checks::list_checks,
+    logging,
+    observer::TerminalObserver,
+    query::{add_queries, set_lint_levels, ActualSemverUpdate, QuerySelection, SemverQuery},
+    rustdoc_gen::{
+        host_triple, load_package_info, load_workspace, locate_manifest, resolve_toolchain,
+        toolchain_version, BuildEnvironment, BuildFlags, CargoFlags, PackageInfo, RustdocCommand,
+        Workspace,
+    },
+    snapshot::write_snapshot,
+    stats::StatsFormat,
+    templates::OutputTemplates,
+    util::load_rustdoc_from_file,
+    watch::SourceWatcher,
+    ColorWhen, GlobalConfig, StatusPrinter, Verbosity,
+};
+
+/// Exit code when semver violations were found.
+const EXIT_SEMVER_VIOLATIONS: u8 = 1;
+/// Exit code for errors other than an unavailable baseline, including invalid arguments.
+const EXIT_ERROR: u8 = 2;
+/// Exit code when the baseline could not be generated, downloaded, or loaded.
+const EXIT_BASELINE_UNAVAILABLE: u8 = 3;
+
+pub(crate) fn main() -> ExitCode {
+    let matches = try_parse_args(env::args_os()).unwrap_or_else(|e| e.exit());
+
+    // Descend one level: from `cargo semver-checks` to just `semver-checks`.
+    let semver_check = matches
+        .subcommand_matches("semver-checks")
+        .expect("semver-checks is missing");
+    let color =
+        ColorWhen::from_setting(semver_check.get_one::<String>("color").map(String::as_str));
+    logging::init(color);
+
+    match run(semver_check, color) {
+        Ok(exit_code) => exit_code,
+        Err(e) => {
+            StatusPrinter::new(color).error(&e);
+            if e.downcast_ref::<BaselineUnavailable>().is_some() {
+                ExitCode::from(EXIT_BASELINE_UNAVAILABLE)
+            } else {
+                ExitCode::from(EXIT_ERROR)
+            }
+        }
+    }
+}
+
+/// Run the checks, then print what they found.
+fn check_and_report(
+    mut config: GlobalConfig,
+    options: CheckReleaseOptions,
+    comparisons: Vec<CrateComparison>,
+) -> anyhow::Result<ReleaseCheckResult> {
+    let report = run_check_release(
+        &mut TerminalObserver::new(&mut config),
+        &options,
+        &comparisons,
+    )?;
+    print_report(&mut config, &options, &report)?;
+    Ok(report.result())
+}
+
+fn check_exit_code(outcome: ReleaseCheckResult) -> ExitCode {
+    match outcome {
+        ReleaseCheckResult::Passed => ExitCode::SUCCESS,
+        ReleaseCheckResult::ViolationsFound => ExitCode::from(EXIT_SEMVER_VIOLATIONS),
+    }
+}
+
+/// The prefix of the environment variables that can be used instead of command-line options.
+const ENV_VAR_PREFIX: &str = "CARGO_SEMVER_CHECKS_";
+
+/// The environment variable that sets the option with the given long name,
+/// e.g. `CARGO_SEMVER_CHECKS_BASELINE_VERSION` for `--baseline-version`.
+fn env_var_name(long: &str) -> String {
+    format!("{ENV_VAR_PREFIX}{}", long.to_uppercase().replace('-', "_"))
+}
+
+/// The subcommands that read the options they aren't given from the
+/// `[package.metadata.semver-checks]` table of the crate's manifest.
+const CONFIGURED_COMMANDS: [&str; 4] = ["check-release", "watch", "dump-api", "publish"];
+
+/// The options whose values are paths, which are relative to the manifest's directory when
+/// set in its `[package.metadata.semver-checks]` table.
+const PATH_OPTIONS: [&str; 12] = [
+    "current_rustdoc_path",
+    "baseline_rustdoc_path",
+    "baseline_snapshot",
+    "current_dependency",
+    "baseline_dependency",
+    "baseline_root",
+    "output_path",
+    "exceptions_path",
+    "cache_dir",
+    "build_dir",
+    "extra_checks",
+    "templates",
+];
+
+const ENV_VARS_HELP: &str = "Options not given on the command line are read from environment variables named after them, e.g. CARGO_SEMVER_CHECKS_BASELINE_VERSION for --baseline-version.";
+
+const ENV_VARS_AND_CONFIG_HELP: &str = "Options not given on the command line are read from environment variables named after them, e.g. CARGO_SEMVER_CHECKS_BASELINE_VERSION for --baseline-version, and then from the [package.metadata.semver-checks] table of the crate's Cargo.toml, e.g. baseline-version = \"1.2.0\".";
+
+/// Where the options not given on the command line are read from, in order of precedence.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum OptionSource {
+    Env,
+    Config,
+}
+
+/// Parse the command line, with the options it doesn't give set by their environment
+/// variables, and then by the `[package.metadata.semver-checks]` table of the crate's
+/// manifest, except for options that conflict with those set by a source that takes
+/// precedence.
+pub(crate) fn try_parse_args<I, T>(args: I) -> clap::Result<ArgMatches>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString>,
+{
+    let env = env::vars_os()
+        .filter_map(|(name, value)| Some((name.into_string().ok()?, value)))
+        .filter(|(name, _)| name.starts_with(ENV_VAR_PREFIX))
+        .collect();
+    parse_args(args.into_iter().map(Into::into).collect(), &env)
+}
+
+/// Parse the command line, reading options from the given environment variables.
+fn parse_args(
+    mut args: Vec<OsString>,
+    env: &BTreeMap<String, OsString>,
+) -> clap::Result<ArgMatches> {
+    let cmd = commands(&[]);
+    // Options set in the environment or the manifest are passed on as if given on the command
+    // line, so that clap checks them like those, so find the options given there first.
+    // If that fails, the command line is wrong regardless, and parsing it again says how.
+    let given = match cmd.clone().ignore_errors(true).try_get_matches_from(&args) {
+        Ok(given) => given,
+        Err(_) => return cmd.try_get_matches_from(args),
+    };
+
+    let mut set_args = vec![];
+    let mut command = &cmd;
+    let mut matches = &given;
+    let mut config = None;
+    loop {
+        let mut set = given_args(command, matches);
+        for source in [OptionSource::Env, OptionSource::Config] {
+            let mut set_by_source = vec![];
+            for arg in command.get_arguments() {
+                let long = match arg.get_long() {
+                    Some(long) if !["help", "version"].contains(&arg.get_id()) => long,
+                    _ => continue,
+                };
+                let conflicts = set.iter().any(|set_arg| {
+                    command.get_arg_conflicts_with(arg).contains(set_arg)
+                        || command.get_arg_conflicts_with(set_arg).contains(&arg)
+                });
+                if set.iter().any(|set_arg| set_arg.get_id() == arg.get_id()) || conflicts {
+                    continue;
+                }
+                let values = match source {
+                    OptionSource::Env => match env.get(&env_var_name(long)) {
+                        Some(value) if arg.is_takes_value_set() => vec![value.clone()],
+                        Some(value) if is_truthy(value) => vec![],
+                        _ => continue,
+                    },
+                    OptionSource::Config => {
+                        if !CONFIGURED_COMMANDS.contains(&matches_name(&given, matches)) {
+                            continue;
+                        }
+                        let config = config.get_or_insert_with(|| load_config(&given, env));
+                        match config_values(config, arg)? {
+                            Some(values) => values,
+                            None => continue,
+                        }
+                    }
+                };
+                set_by_source.push(arg);
+                if values.is_empty() {
+                    set_args.push(OsString::from(format!("--{long}")));
+                }
+                for value in values {
+                    let mut set_arg = OsString::from(format!("--{long}="));
+                    set_arg.push(value);
+                    set_args.push(set_arg);
+                }
+            }
+            set.extend(set_by_source);
+        }
+        match matches.subcommand() {
+            Some((name, subcommand_matches)) => {
+                command = command
+                    .find_subcommand(name)
+                    .expect("matched subcommand not found");
+                matches = subcommand_matches;
+            }
+            None => break,
+        }
+    }
+
+    // Options go before any arguments that are passed on, after `--`.
+    let end = args
+        .iter()
+        .position(|arg| arg == "--")
+        .unwrap_or(args.len());
+    args.splice(end..end, set_args);
+    cmd.try_get_matches_from(args)
+}
+
+/// The options of the command given on the command line.
+fn given_args<'c, 'a>(command: &'c Command<'a>, matches: &ArgMatches) -> Vec<&'c Arg<'a>> {
+    // Clap removes its own `--version` from commands without a version, once it's built.
+    command
+        .get_arguments()
+        .filter(|arg| !["help", "version"].contains(&arg.get_id()))
+        .filter(|arg| matches.value_source(arg.get_id()) == Some(ValueSource::CommandLine))
+        .collect()
+}
+
+/// The name of the subcommand that `matches` are the matches of.
+fn matches_name<'a>(given: &'a ArgMatches, matches: &ArgMatches) -> &'a str {
+    let mut current = given;
+    let mut name = "";
+    while !std::ptr::eq(current, matches) {
+        match current.subcommand() {
+            Some((subcommand, subcommand_matches)) => {
+                name = subcommand;
+                current = subcommand_matches;
+            }
+            None => break,
+        }
+    }
+    name
+}
+
+/// Whether an environment variable enables a flag: any value other than those that clap
+/// considers false does.
+fn is_truthy(value: &OsStr) -> bool {
+    let value = value.to_string_lossy().to_lowercase();
+    !["", "0", "false", "no", "off", "n", "f"].contains(&value.as_str())
+}
+
+/// The manifest's `[package.metadata.semver-checks]` table, and the manifest's directory.
+/// The manifest is the one given with `--manifest-path`, or else the nearest `Cargo.toml`.
+/// Manifests that can't be read have no options set in them: checking the crate fails later.
+fn load_config(
+    given: &ArgMatches,
+    env: &BTreeMap<String, OsString>,
+) -> (toml::value::Table, PathBuf) {
+    let manifest_path = given
+        .subcommand_matches("semver-checks")
+        .and_then(|semver_check| semver_check.get_one::<String>("manifest_path"))
+        .map(PathBuf::from)
+        .or_else(|| env.get(&env_var_name("manifest-path")).map(PathBuf::from))
+        .or_else(|| {
+            let current_dir = env::current_dir().ok()?;
+            current_dir
+                .ancestors()
+                .map(|dir| dir.join("Cargo.toml"))
+                .find(|path| path.is_file())
+        })
+        .unwrap_or_else(|| PathBuf::from("Cargo.toml"));
+    let manifest_dir = manifest_path
+        .parent()
+        .map(Path::to_path_buf)
+        .unwrap_or_default();
+    let table = std::fs::read_to_string(&manifest_path)
+        .ok()
+        .and_then(|contents| toml::from_str::<toml::Value>(&contents).ok())
+        .and_then(|manifest| {
+            manifest
+                .get("package")?
+                .get("metadata")?
+                .get("semver-checks")?
+                .as_table()
+                .cloned()
+        })
+        .unwrap_or_default();
+    (table, manifest_dir)
+}
+
+/// The values that the manifest's `[package.metadata.semver-checks]` table sets the option
+/// to, if it sets it: none for a flag that's enabled, and strings or lists of them for
+/// other options. Relative paths are relative to the manifest's directory.
+fn config_values(
+    (table, manifest_dir): &(toml::value::Table, PathBuf),
+    arg: &Arg,
+) -> clap::Result<Option<Vec<OsString>>> {
+    let long = arg.get_long().expect("option has no long name");
+    if arg.get_id() == "manifest_path" {
+        return Ok(None);
+    }
+    let invalid = |expected: &str| {
+        clap::Error::raw(
+            clap::ErrorKind::InvalidValue,
+            format!(
+                "Invalid `{long}` in the [package.metadata.semver-checks] table: expected {expected}\n"
+            ),
+        )
+    };
+    let value = match table.get(long) {
+        Some(value) => value,
+        None => return Ok(None),
+    };
+    if !arg.is_takes_value_set() {
+        return match value {
+            toml::Value::Boolean(true) => Ok(Some(vec![])),
+            toml::Value::Boolean(false) => Ok(None),
+            _ => Err(invalid("true or false")),
+        };
+    }
+    let values = match value {
+        toml::Value::Array(values) if arg.is_multiple_occurrences_set() => values.as_slice(),
+        toml::Value::Array(_) => return Err(invalid("a single value")),
+        value => std::slice::from_ref(value),
+    };
+    values
+        .iter()
+        .map(|value| {
+            let value = match value {
+                toml::Value::String(value) => value.clone(),
+                toml::Value::Integer(value) => value.to_string(),
+                toml::Value::Boolean(value) => value.to_string(),
+                _ => return Err(invalid("a string, a number, or a list of them")),
+            };
+            Ok(if PATH_OPTIONS.contains(&arg.get_id()) {
+                manifest_dir.join(value).into_os_string()
+            } else {
+                OsString::from(value)
+            })
+        })
+        .collect::<clap::Result<_>>()
+        .map(Some)
+}
+
+/// The command-line interface. For shell completions, `check_ids` are the ids
+/// that `--only` and `--skip` complete to; otherwise, they may be empty.
+pub(crate) fn cli<'a>(check_ids: &[&'a str]) -> Command<'a> {
+    commands(check_ids)
+}
+
+/// Describe where the options of the command and its subcommands are read from,
+/// besides the command line.
+fn with_option_sources_help(mut cmd: Command<'_>) -> Command<'_> {
+    let has_options = cmd
+        .get_arguments()
+        .any(|arg| arg.get_long().is_some() && !["help", "version"].contains(&arg.get_id()));
+    if has_options {
+        let help = if CONFIGURED_COMMANDS.contains(&cmd.get_name()) {
+            ENV_VARS_AND_CONFIG_HELP
+        } else {
+            ENV_VARS_HELP
+        };
+        cmd = cmd.after_help(help);
+    }
+    for subcommand in cmd.get_subcommands_mut() {
+        *subcommand = with_option_sources_help(std::mem::take(subcommand));
+    }
+    cmd
+}
+
+/// The command-line interface.
+fn commands<'a>(check_ids: &[&'a str]) -> Command<'a> {
+    // `watch` accepts the same options as `check-release`. Commands are built separately
+    // rather than cloned and renamed, since clap matches subcommands by their original name.
+    let check_release = |name| {
+        Command::new(name)
+            .version(crate_version!())
+            .setting(AppSettings::ArgRequiredElseHelp)
+            .arg(
+                Arg::with_name("current_rustdoc_path")
+                    .short('c')
+                    .long("current")
+                    .value_name("CURRENT_RUSTDOC_JSON")
+                    .help("The current rustdoc json output to test for semver violations. If not set, rustdoc json is generated for the crate in the current directory.")
+                    .takes_value(true)
+            )
+            .arg(
+                Arg::with_name("baseline_rustdoc_path")
+                    .short('b')
+                    .long("baseline")
+                    .value_name("BASELINE_RUSTDOC_JSON")
+                    .help("The rustdoc json file to use as a semver baseline.")
+                    .takes_value(true)
+                    .required_unless_present_any(["baseline_snapshot", "baseline_root", "baseline_version", "all_compatible_releases"])
+                    .conflicts_with_all(&["baseline_snapshot", "baseline_root", "baseline_version", "all_compatible_releases"])
+            )
+            .arg(
+                Arg::with_name("baseline_snapshot")
+                    .long("baseline-snapshot")
+                    .value_name("SNAPSHOT")
+                    .help("An API snapshot written by `dump-api` to use as a semver baseline, instead of rustdoc json.")
+                    .takes_value(true)
+                    .conflicts_with_all(&["baseline_root", "baseline_version", "all_compatible_releases"])
+            )
+            .args(dependency_args())
+            .arg(
+                Arg::with_name("baseline_root")
+                    .long("baseline-root")
+                    .value_name("MANIFEST_ROOT")
+                    .help("Directory containing the baseline version of the crate, for which rustdoc json is generated.")
+                    .takes_value(true)
+                    .conflicts_with_all(&["baseline_version", "all_compatible_releases"])
+            )
+            .arg(
+                Arg::with_name("baseline_version")
+                    .long("baseline-version")
+                    .value_name("VERSION")
+                    .help("Version of the crate to download from its registry and use as a semver baseline.")
+                    .takes_value(true)
+            )
+            .arg(
+                Arg::with_name("all_compatible_releases")
+                    .long("all-compatible-releases")
+                    .help("Use every published release that is semver-compatible with the current version as a baseline, and report which releases each violation regresses against.")
+                    .conflicts_with("baseline_version")
+            )
+            .arg(
+                Arg::with_name("lib")
+                    .long("lib")
+                    .help("Check the package's library target. This is the default, and the only kind of target that is checked: binaries, examples, and tests have no API that other crates can use.")
+            )
+            .arg(
+                Arg::with_name("workspace")
+                    .long("workspace")
+                    .help("Check every package in the workspace that has a library target, except those with `publish = false`. This is the default when run on a virtual workspace manifest.")
+                    .conflicts_with_all(&["current_rustdoc_path", "baseline_rustdoc_path", "baseline_snapshot"])
+            )
+            .arg(
+                Arg::with_name("registry")
+                    .long("registry")
+                    .value_name("NAME")
+                    .help("Name of the registry, as configured in .cargo/config.toml, from which to download the baseline. Defaults to the registry the crate is published to, or crates.io.")
+                    .takes_value(true)
+                    .requires("registry_baseline")
+            )
+            .group(
+                ArgGroup::new("registry_baseline")
+                    .args(&["baseline_version", "all_compatible_releases"])
+            )
+            .arg(toolchain_arg())
+            .arg(targets_arg().conflicts_with_all(&["current_rustdoc_path", "baseline_rustdoc_path", "baseline_snapshot"]))
+            .arg(
+                Arg::with_name("release_type")
+                    .long("release-type")
+                    .value_name("TYPE")
+                    .help("The kind of release being made. Overrides the version change detected between the baseline and current crate versions.")
+                    .takes_value(true)
+                    .possible_values(["major", "minor", "patch"])
+            )
+            .arg(
+                Arg::with_name("print_required_bump")
+                    .long("print-required-bump")
+                    .help("Run all checks regardless of the version change, and only print the required kind of release: major, minor, or patch.")
+                    .conflicts_with("release_type")
+            )
+            .arg(
+                Arg::with_name("fix")
+                    .long("fix")
+                    .help("Instead of reporting semver violations, update the version in Cargo.toml to the smallest version that is compatible with the changes since the baseline.")
+                    .conflicts_with_all(&["release_type", "print_required_bump", "current_rustdoc_path"])
+            )
+            .arg(
+                Arg::with_name("output_format")
+                    .long("output-format")
+                    .value_name("FORMAT")
+                    .help("Also report results in the given machine-readable format. If no output path is set, the report is written to stdout and human-readable output goes to stderr.")
+                    .takes_value(true)
+                    .possible_values(OutputFormat::NAMES)
+                    .default_value("human")
+            )
+            .arg(
+                Arg::with_name("output_path")
+                    .long("output-path")
+                    .value_name("FILE")
+                    .help("File to which the machine-readable report is written.")
+                    .takes_value(true)
+            )
+            .arg(
+                Arg::with_name("emit")
+                    .long("emit")
+                    .value_name("DOCUMENT")
+                    .help("Also write a document generated from the findings to stdout, with human-readable output going to stderr. `changelog` is Markdown sections of breaking changes, deprecations and other changes, to paste into CHANGELOG.md.")
+                    .takes_value(true)
+                    .possible_values(Emit::NAMES)
+            )
+            .arg(
+                Arg::with_name("only")
+                    .long("only")
+                    .value_name("CHECK_ID")
+                    .help("Only run the given checks. May be specified multiple times, or as a comma-separated list.")
+                    .takes_value(true)
+                    .multiple_occurrences(true)
+                    .use_value_delimiter(true)
+                    .possible_values(check_ids.iter().copied())
+                    .hide_possible_values(true)
+            )
+            .arg(
+                Arg::with_name("skip")
+                    .long("skip")
+                    .value_name("CHECK_ID")
+                    .help("Do not run the given checks. May be specified multiple times, or as a comma-separated list.")
+                    .takes_value(true)
+                    .multiple_occurrences(true)
+                    .use_value_delimiter(true)
+                    .possible_values(check_ids.iter().copied())
+                    .hide_possible_values(true)
+            )
+            .arg(
+                Arg::with_name("warn")
+                    .long("warn")
+                    .value_name("CHECK_ID")
+                    .help("Report what the given checks find as warnings, which don't fail the run. May be specified multiple times, or as a comma-separated list.")
+                    .takes_value(true)
+                    .multiple_occurrences(true)
+                    .use_value_delimiter(true)
+                    .possible_values(check_ids.iter().copied())
+                    .hide_possible_values(true)
+            )
+            .arg(
+                Arg::with_name("deny")
+                    .long("deny")
+                    .value_name("CHECK_ID")
+                    .help("Fail the run if the given checks find anything, including checks that only warn by default. May be specified multiple times, or as a comma-separated list.")
+                    .takes_value(true)
+                    .multiple_occurrences(true)
+                    .use_value_delimiter(true)
+                    .possible_values(check_ids.iter().copied())
+                    .hide_possible_values(true)
+            )
+            .arg(
+                Arg::with_name("witnesses")
+                    .long("witnesses")
+                    .help("For each violation, print downstream code that compiles against the baseline but not the current version, demonstrating the violation.")
+            )
+            .arg(
+                Arg::with_name("max_results_per_check")
+                    .long("max-results-per-check")
+                    .value_name("N")
+                    .help("Print at most N violations of each failing check, followed by the number of violations left out. Machine-readable reports still include every violation.")
+                    .takes_value(true)
+                    .value_parser(value_parser!(u64).range(1..))
+            )
+            .arg(
+                Arg::with_name("incremental")
+                    .long("incremental")
+                    .help("Only check the items that changed since the baseline, and the items that refer to them. Items that are the same in both versions are skipped, which makes checking a large crate after a small change much faster.")
+            )
+            .arg(
+                Arg::with_name("group_by")
+                    .long("group-by")
+                    .value_name("GROUPING")
+                    .help("How to organize the failures: by check, by the module of the affected item, or by severity, i.e. the version update the checks require.")
+                    .takes_value(true)
+                    .possible_values(GroupBy::NAMES)
+                    .default_value("check")
+            )
+            .arg(
+                Arg::with_name("fail_fast")
+                    .long("fail-fast")
+                    .value_name("WHEN")
+                    .help("Stop running checks after the first one that fails, or with --fail-fast=major, after the first one that requires a new major version.")
+                    .takes_value(true)
+                    .min_values(0)
+                    .require_equals(true)
+                    .default_missing_value("any")
+                    .possible_values(FailFast::NAMES)
+            )
+            .arg(
+                Arg::with_name("stats")
+                    .long("stats")
+                    .value_name("FORMAT")
+                    .help("At the end of the run, print how long each check and rustdoc generation took, how many results each check found, and how often checks reused the indexes they query.")
+                    .takes_value(true)
+                    .min_values(0)
+                    .require_equals(true)
+                    .default_missing_value("human")
+                    .possible_values(StatsFormat::NAMES)
+            )
+            .arg(
+                Arg::with_name("jobs")
+                    .short('j')
+                    .long("jobs")
+                    .value_name("N")
+                    .help("Number of checks to run in parallel. Defaults to the number of CPU cores.")
+                    .takes_value(true)
+                    .value_parser(value_parser!(u64).range(1..))
+            )
+            .arg(exceptions_arg())
+            .arg(features_arg())
+            .args(baseline_args())
+            .args(build_args())
+    };
+
+    let cli = Command::new("cargo-semver-checks")
+        .bin_name("cargo")
+        .version(crate_version!())
+        .subcommand(
+            Command::new("semver-checks")
+                .version(crate_version!())
+                .about("Check your crate for semver violations.")
+                .arg(
+                    Arg::with_name("quiet")
+                        .short('q')
+                        .long("quiet")
+                        .help("Print nothing if the checks pass, and otherwise only the failures and the final result.")
+                        .global(true)
+                        .conflicts_with("verbose")
+                )
+                .arg(
+                    Arg::with_name("verbose")
+                        .short('v')
+                        .long("verbose")
+                        .help("Print more details: -v for skipped checks and rustdoc commands, -vv for cargo and rustdoc invocations and timings.")
+                        .global(true)
+                        .multiple_occurrences(true)
+                )
+                .arg(
+                    Arg::with_name("color")
+                        .long("color")
+                        .value_name("WHEN")
+                        .help("Whether to color the output. Defaults to the CARGO_TERM_COLOR environment variable, or to auto.")
+                        .takes_value(true)
+                        .possible_values(["auto", "always", "never"])
+                        .global(true)
+                )
+                .arg(
+                    Arg::with_name("manifest_path")
+                        .long("manifest-path")
+                        .value_name("PATH")
+                        .help("Path to the Cargo.toml of the crate to check. Defaults to the one in the current directory or its nearest ancestor, as with cargo.")
+                        .takes_value(true)
+                        .global(true)
+                )
+                .arg(
+                    Arg::with_name("extra_checks")
+                        .long("extra-checks")
+                        .value_name("DIR")
+                        .help("Directory of additional checks, as .ron query files, to use along with the built-in checks.")
+                        .takes_value(true)
+                        .global(true)
+                )
+                .arg(
+                    Arg::with_name("templates")
+                        .long("templates")
+                        .value_name("DIR")
+                        .help("Directory of Handlebars templates that replace the messages of checks, as <check id>.hbs files, and the summary of the report, as summary.hbs.")
+                        .takes_value(true)
+                        .global(true)
+                )
+                .subcommand(
+                    Command::new("diff-files")
+                        .version(crate_version!())
+                        .setting(AppSettings::ArgRequiredElseHelp)
+                        .arg(
+                            Arg::with_name("current_rustdoc_path")
+                                .short('c')
+                                .long("current")
+                                .value_name("CURRENT_RUSTDOC_JSON")
+                                .help("The current rustdoc json output to test for semver violations. Required.")
+                                .takes_value(true)
+                                .required(true)
+                        )
+                        .arg(
+                            Arg::with_name("baseline_rustdoc_path")
+                                .short('b')
+                                .long("baseline")
+                                .value_name("BASELINE_RUSTDOC_JSON")
+                                .help("The rustdoc json file to use as a semver baseline. Required.")
+                                .takes_value(true)
+                                .required(true)
+                        )
+                        .args(dependency_args())
+                )
+                .subcommand(
+                    Command::new("list-checks")
+                        .version(crate_version!())
+                        .about("List every available check, with its id, required version bump, and description.")
+                        .arg(
+                            Arg::with_name("json")
+                                .long("json")
+                                .help("Print the checks as a JSON array.")
+                        )
+                )
+                .subcommand(
+                    Command::new("bugreport")
+                        .version(crate_version!())
+                        .about("Print a report for filing a bug: the versions of cargo-semver-checks and Rust, and the result of reproducing the failing command given after `--`.")
+                        .arg(
+                            Arg::with_name("command")
+                                .value_name("ARGS")
+                                .help("The arguments to cargo semver-checks that fail, e.g. `-- check-release --baseline-version 1.0.0`.")
+                                .multiple_values(true)
+                                .last(true)
+                        )
+                )
+                .subcommand(
+                    Command::new("explain")
+                        .version(crate_version!())
+                        .about("Explain what a check looks for, with an example of code it reports.")
+                        .setting(AppSettings::ArgRequiredElseHelp)
+                        .arg(
+                            Arg::with_name("check_id")
+                                .value_name("CHECK_ID")
+                                .help("The id of the check to explain, as shown by list-checks.")
+                                .required(true)
+                                .possible_values(check_ids.iter().copied())
+                                .hide_possible_values(true)
+                        )
+                )
+                .subcommand(
+                    Command::new("schema")
+                        .version(crate_version!())
+                        .about("Print the schema that checks query, in GraphQL SDL, as of this version of cargo-semver-checks.")
+                )
+                .subcommand(
+                    Command::new("dump-api")
+                        .version(crate_version!())
+                        .about("Write a snapshot of the crate's public API, to commit to the repository and use as the baseline with `check-release --baseline-snapshot`.")
+                        .arg(
+                            Arg::with_name("current_rustdoc_path")
+                                .short('c')
+                                .long("current")
+                                .value_name("CURRENT_RUSTDOC_JSON")
+                                .help("The rustdoc json to snapshot. If not set, rustdoc json is generated for the crate in the current directory.")
+                                .takes_value(true)
+                        )
+                        .arg(
+                            Arg::with_name("output_path")
+                                .short('o')
+                                .long("output")
+                                .value_name("FILE")
+                                .help("File to write the snapshot to. Defaults to stdout.")
+                                .takes_value(true)
+                        )
+                        .arg(toolchain_arg())
+                        .arg(
+                            Arg::with_name("target")
+                                .long("target")
+                                .value_name("TRIPLE")
+                                .help("Snapshot the crate's API on the given target.")
+                                .takes_value(true)
+                        )
+                        .arg(features_arg())
+                        .args(build_args())
+                        .mut_arg("offline", |arg| arg.help("Run cargo without accessing the network."))
+                )
+                .subcommand(
+                    Command::new("init")
+                        .version(crate_version!())
+                        .about("Set up the crate for semver checking: add a [package.metadata.semver-checks] table to its Cargo.toml, and optionally a GitHub Actions workflow. Choices not made with options are prompted for.")
+                        .arg(
+                            Arg::with_name("all_compatible_releases")
+                                .long("all-compatible-releases")
+                                .help("In CI, check against every published release that is semver-compatible with the current version. This is the default.")
+                        )
+                        .arg(
+                            Arg::with_name("baseline_branch")
+                                .long("baseline-branch")
+                                .value_name("BRANCH")
+                                .help("In CI, check against the crate's code on the given git branch.")
+                                .takes_value(true)
+                        )
+                        .arg(
+                            Arg::with_name("baseline_version")
+                                .long("baseline-version")
+                                .value_name("VERSION")
+                                .help("In CI, check against the given published version.")
+                                .takes_value(true)
+                        )
+                        .group(
+                            ArgGroup::new("baseline")
+                                .args(&["all_compatible_releases", "baseline_branch", "baseline_version"])
+                        )
+                        .arg(
+                            Arg::with_name("features")
+                                .long("features")
+                                .value_name("FEATURES")
+                                .help("In CI, enable the given features, separated by commas.")
+                                .takes_value(true)
+                        )
+                        .arg(
+                            Arg::with_name("github_actions")
+                                .long("github-actions")
+                                .help("Write a GitHub Actions workflow that runs the checks.")
+                        )
+                        .arg(
+                            Arg::with_name("no_github_actions")
+                                .long("no-github-actions")
+                                .help("Don't write a GitHub Actions workflow.")
+                                .conflicts_with("github_actions")
+                        )
+                )
+                .subcommand(
+                    Command::new("cache")
+                        .version(crate_version!())
+                        .about("Inspect or clean up cached baselines and baseline builds.")
+                        .setting(AppSettings::SubcommandRequiredElseHelp)
+                        .arg(cache_dir_arg().global(true))
+                        .arg(build_dir_arg().global(true))
+                        .subcommand(
+                            Command::new("path")
+                                .about("Print the directory in which baselines are cached.")
+                        )
+                        .subcommand(
+                            Command::new("stats")
+                                .about("List the cached baselines, and the disk space used by the cache and by baseline builds.")
+                        )
+                        .subcommand(
+                            Command::new("clean")
+                                .about("Remove every cached baseline, and all downloaded crates and build outputs.")
+                        )
+                )
+                .subcommand(
+                    Command::new("completions")
+                        .version(crate_version!())
+                        .about("Print a shell completion script for cargo-semver-checks.")
+                        .setting(AppSettings::ArgRequiredElseHelp)
+                        .arg(
+                            Arg::with_name("shell")
+                                .value_name("SHELL")
+                                .help("The shell to generate completions for.")
+                                .required(true)
+                                .value_parser(value_parser!(Shell))
+                        )
+                )
+                .subcommand(check_release("check-release"))
+                .subcommand(
+                    check_release("watch")
+                        .about("Check the crate whenever its files change, reusing the baselines, until interrupted. Accepts the same options as check-release.")
+                        .arg(
+                            Arg::with_name("poll_interval")
+                                .long("poll-interval")
+                                .value_name("MILLISECONDS")
+                                .help("How often to look for changed files.")
+                                .takes_value(true)
+                                .value_parser(value_parser!(u64).range(1..))
+                                .default_value("500")
+                        )
+                )
+                .subcommand(
+                    Command::new("diff-published")
+                        .version(crate_version!())
+                        .about("Check two published versions of a crate against each other for semver violations.")
+                        .setting(AppSettings::ArgRequiredElseHelp)
+                        .arg(
+                            Arg::with_name("crate_name")
+                                .value_name("CRATE")
+                                .help("Name of the crate to check.")
+                                .required(true)
+                        )
+                        .arg(
+                            Arg::with_name("baseline_version")
+                                .value_name("BASELINE_VERSION")
+                                .help("The published version to use as a semver baseline.")
+                                .required(true)
+                        )
+                        .arg(
+                            Arg::with_name("current_version")
+                                .value_name("CURRENT_VERSION")
+                                .help("The published version to check for semver violations.")
+                                .required(true)
+                        )
+                        .arg(
+                            Arg::with_name("registry")
+                                .long("registry")
+                                .value_name("NAME")
+                                .help("Name of the registry, as configured in .cargo/config.toml, from which to download the crate. Defaults to crates.io.")
+                                .takes_value(true)
+                        )
+                        .arg(toolchain_arg())
+                        .arg(targets_arg())
+                        .arg(features_arg())
+                        .args(baseline_args())
+                        .mut_arg("cache_dir", |arg| arg.help("Directory in which generated rustdoc json is cached. Defaults to a subdirectory of the system's temporary directory."))
+                        .args(build_args())
+                )
+                .subcommand(
+                    Command::new("publish")
+                        .version(crate_version!())
+                        .about("Check the crate for semver violations against its latest published version, and if there are none, publish it with `cargo publish`.")
+                        .arg(
+                            Arg::with_name("registry")
+                                .long("registry")
+                                .value_name("NAME")
+                                .help("Name of the registry to check against and publish to. Defaults to the registry the crate is published to, or crates.io.")
+                                .takes_value(true)
+                        )
+                        .arg(toolchain_arg())
+                        .arg(targets_arg())
+                        .arg(features_arg())
+                        .arg(exceptions_arg())
+                        .args(baseline_args())
+                        .args(build_args())
+                        .arg(
+                            Arg::with_name("publish_args")
+                                .value_name("CARGO_PUBLISH_ARGS")
+                                .help("Arguments passed on to `cargo publish`, after `--`.")
+                                .multiple_values(true)
+                                .last(true)
+                        )
+                )
+        );
+    with_option_sources_help(cli)
+}
+
+/// `--toolchain`, for the commands that generate rustdoc json.
+fn toolchain_arg<'a>() -> Arg<'a> {
+    Arg::with_name("toolchain")
+        .long("toolchain")
+        .value_name("TOOLCHAIN")
+        .help("The nightly rustup toolchain used to generate rustdoc json, e.g. nightly-2022-07-20. Defaults to an installed nightly toolchain.")
+        .takes_value(true)
+}
+
+/// `--target`, for the commands that check the crate on each of several targets.
+fn targets_arg<'a>() -> Arg<'a> {
+    Arg::with_name("target")
+        .long("target")
+        .value_name("TRIPLE")
+        .help("Check the crate's API on the given target. May be specified multiple times, in which case each target is checked and the results are merged.")
+        .takes_value(true)
+        .multiple_occurrences(true)
+}
+
+/// `--features`, for the commands that generate rustdoc json.
+fn features_arg<'a>() -> Arg<'a> {
+    Arg::with_name("features")
+        .long("features")
+        .value_name("FEATURES")
+        .help("Space or comma separated list of crate features to enable when generating rustdoc json.")
+        .takes_value(true)
+        .multiple_occurrences(true)
+}
+
+/// `--exceptions`, for the commands that fail on semver violations.
+fn exceptions_arg<'a>() -> Arg<'a> {
+    Arg::with_name("exceptions_path")
+        .long("exceptions")
+        .value_name("FILE")
+        .help("File listing accepted semver violations that should not fail the check. Defaults to semver-exceptions.toml next to the crate's Cargo.toml, if it exists.")
+        .takes_value(true)
+}
+
+/// The rustdoc json of the crates that the compared crates re-export items from.
+fn dependency_args<'a>() -> [Arg<'a>; 2] {
+    [
+        Arg::with_name("current_dependency")
+            .long("current-dependency")
+            .value_name("RUSTDOC_JSON")
+            .help("The rustdoc json of a crate whose items the current crate re-exports. May be given multiple times.")
+            .takes_value(true)
+            .multiple_occurrences(true),
+        Arg::with_name("baseline_dependency")
+            .long("baseline-dependency")
+            .value_name("RUSTDOC_JSON")
+            .help("The rustdoc json of a crate whose items the baseline re-exports. May be given multiple times.")
+            .takes_value(true)
+            .multiple_occurrences(true),
+    ]
+}
+
+fn cache_dir_arg<'a>() -> Arg<'a> {
+    Arg::with_name("cache_dir")
+        .long("cache-dir")
+        .value_name("DIR")
+        .help("Directory in which generated baseline rustdoc json is cached. Defaults to a subdirectory of the build directory.")
+        .takes_value(true)
+}
+
+fn build_dir_arg<'a>() -> Arg<'a> {
+    Arg::with_name("build_dir")
+        .long("build-dir")
+        .value_name("DIR")
+        .help("Directory in which baselines are built, separately from the crate's own build. Defaults to target/semver-checks in the crate's target directory.")
+        .takes_value(true)
+}
+
+/// Where baselines are built and cached, for the commands that obtain them.
+fn baseline_args<'a>() -> [Arg<'a>; 3] {
+    [
+        cache_dir_arg(),
+        build_dir_arg(),
+        Arg::with_name("no_docs_rs")
+            .long("no-docs-rs")
+            .help("Always generate the baseline rustdoc json locally, instead of downloading it from docs.rs when available."),
+    ]
+}
+
+/// How cargo builds the crates, for the commands that generate rustdoc json.
+fn build_args<'a>() -> [Arg<'a>; 6] {
+    [
+        Arg::with_name("offline")
+            .long("offline")
+            .help("Run cargo without accessing the network. Baselines not already cached are built from locally-available crates."),
+        Arg::with_name("locked")
+            .long("locked")
+            .help("Run cargo with --locked, requiring Cargo.lock to be up to date."),
+        Arg::with_name("frozen")
+            .long("frozen")
+            .help("Run cargo with --frozen: equivalent to --locked and --offline."),
+        Arg::with_name("rustflags")
+            .long("rustflags")
+            .value_name("FLAGS")
+            .help("Compiler flags to build with, instead of those in the RUSTFLAGS environment variable. Any --cfg flags also apply when documenting the crate.")
+            .takes_value(true)
+            .allow_hyphen_values(true),
+        Arg::with_name("rustdocflags")
+            .long("rustdocflags")
+            .value_name("FLAGS")
+            .help("Rustdoc flags to document with, instead of those in the RUSTDOCFLAGS environment variable.")
+            .takes_value(true)
+            .allow_hyphen_values(true),
+        Arg::with_name("profile")
+            .long("profile")
+            .value_name("NAME")
+            .help("Build with the given cargo profile, instead of the dev profile.")
+            .takes_value(true),
+    ]
+}
+
+fn run(semver_check: &ArgMatches, color: ColorWhen) -> anyhow::Result<ExitCode> {
+    interrupt::install_handler()?;
+
+    let verbosity = Verbosity::from_flags(
+        semver_check.contains_id("quiet"),
+        semver_check.occurrences_of("verbose"),
+    );
+    let config = GlobalConfig::new(verbosity, color);
+    let current_manifest = match semver_check.get_one::<String>("manifest_path") {
+        Some(manifest_path) => PathBuf::from(manifest_path),
+        None => locate_manifest(),
+    };
+    // The schema doesn't depend on the checks, so it's printed even if they fail to load.
+    if semver_check.subcommand_matches("schema").is_some() {
+        let mut config = config;
+        writeln!(config.output_writer, "# schema version {SCHEMA_VERSION}").expect("print failed");
+        write!(config.output_writer, "{SCHEMA_TEXT}").expect("print failed");
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(dump_api_matches) = semver_check.subcommand_matches("dump-api") {
+        dump_api(config, dump_api_matches, &current_manifest)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let mut builtin_queries = SemverQuery::all_queries_with_extra(
+        semver_check
+            .get_one::<String>("extra_checks")
+            .map(Path::new),
+    )?;
+    output_templates(semver_check)?.override_checks(&mut builtin_queries)?;
+    let mut queries = builtin_queries.clone();
+    // Lint packs are declared in the manifest of the crate being checked, if there is one.
+    if let Ok(package) = load_package_info(&current_manifest) {
+        let work_dir = BaselineLoader::package_work_dir(&package);
+        add_queries(
+            &mut queries,
+            load_lint_packs(&package, &current_manifest, &work_dir)?,
+        )?;
+    }
+
+    if let Some(completions) = semver_check.subcommand_matches("completions") {
+        let shell = *completions
+            .get_one::<Shell>("shell")
+            .expect("shell is required but was not present");
+        let check_ids: Vec<&str> = queries.keys().map(String::as_str).collect();
+        clap_complete::generate(
+            shell,
+            &mut cli(&check_ids),
+            "cargo-semver-checks",
+            &mut std::io::stdout(),
+        );
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(list_checks_matches) = semver_check.subcommand_matches("list-checks") {
+        list_checks(config, &queries, list_checks_matches.contains_id("json"))?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(init_matches) = semver_check.subcommand_matches("init") {
+        let package = load_package_info(&current_manifest).context(
+            "Failed to find the package to set up. \
+            Run this from the package's directory, or pass --manifest-path.",
+        )?;
+        let workspace = load_workspace(&current_manifest)?;
+        let baseline = if init_matches.contains_id("all_compatible_releases") {
+            Some(BaselineStrategy::AllCompatibleReleases)
+        } else if let Some(branch) = init_matches.get_one::<String>("baseline_branch") {
+            Some(BaselineStrategy::Branch(branch.clone()))
+        } else {
+            init_matches
+                .get_one::<String>("baseline_version")
+                .map(|version| BaselineStrategy::Version(version.clone()))
+        };
+        let github_actions = if init_matches.contains_id("github_actions") {
+            Some(true)
+        } else if init_matches.contains_id("no_github_actions") {
+            Some(false)
+        } else {
+            None
+        };
+        let options = InitOptions {
+            baseline,
+            features: init_matches
+                .get_one::<String>("features")
+                .map(|features| split_features(features)),
+            github_actions,
+            interactive: atty::is(atty::Stream::Stdin),
+        };
+        run_init(&config, &package.manifest_path, &workspace, options)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(cache_matches) = semver_check.subcommand_matches("cache") {
+        let (action, action_matches) = match cache_matches.subcommand() {
+            Some(("path", matches)) => (CacheAction::Path, matches),
+            Some(("stats", matches)) => (CacheAction::Stats, matches),
+            Some(("clean", matches)) => (CacheAction::Clean, matches),
+            _ => unreachable!("a cache action is required but was not present"),
+        };
+        let work_dir = match action_matches.get_one::<String>("build_dir") {
+            Some(build_dir) => PathBuf::from(build_dir),
+            None => BaselineLoader::default_work_dir(&current_manifest).context(
+                "Failed to find the crate's build directory. \
+                Run this from the crate's directory, or pass --build-dir.",
+            )?,
+        };
+        let loader = BaselineLoader::new(
+            &work_dir,
+            action_matches
+                .get_one::<String>("cache_dir")
+                .map(PathBuf::from),
+            CargoFlags::default(),
+            false,
+            config.status_printer(),
+        );
+        run_cache_action(config, &loader, action)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(bugreport_matches) = semver_check.subcommand_matches("bugreport") {
+        let command: Vec<String> = bugreport_matches
+            .get_many::<String>("command")
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect();
+        run_bugreport(
+            config,
+            semver_check
+                .get_one::<String>("manifest_path")
+                .map(String::as_str),
+            &command,
+        )?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(explain_matches) = semver_check.subcommand_matches("explain") {
+        let check_id = explain_matches
+            .get_one::<String>("check_id")
+            .expect("check_id is required but was not present");
+        explain(config, &queries, check_id)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    if let Some(diff_files) = semver_check.subcommand_matches("diff-files") {
+        let current_rustdoc_path: &str = diff_files
+            .get_one::<String>("current_rustdoc_path")
+            .expect("current_rustdoc_path is required but was not present")
+            .as_str();
+        let baseline_rustdoc_path: &str = diff_files
+            .get_one::<String>("baseline_rustdoc_path")
+            .expect("baseline_rustdoc_path is required but was not present")
+            .as_str();
+
+        let current_crate = load_rustdoc_from_file(current_rustdoc_path)?;
+        let baseline_crate =
+            load_rustdoc_from_file(baseline_rustdoc_path).context(BaselineUnavailable)?;
+
+        return check_and_report(
+            config,
+            CheckReleaseOptions {
+                selection: QuerySelection::new(queries, vec![], vec![])?,
+                summary_template: output_templates(diff_files)?.summary,
+                ..Default::default()
+            },
+            vec![CrateComparison {
+                target: None,
+                current: current_crate.into(),
+                baseline: baseline_crate.into(),
+                dependencies: load_dependency_rustdocs(diff_files)?,
+                baseline_docs: None,
+            }],
+        )
+        .map(check_exit_code);
+    } else if let Some(check_release) = semver_check.subcommand_matches("check-release") {
+        if check_release.contains_id("workspace") {
+            let workspace = load_workspace(&current_manifest)?;
+            return check_release_workspace(
+                check_release,
+                &workspace,
+                &builtin_queries,
+                verbosity,
+                color,
+            );
+        }
+        if !check_release.contains_id("current_rustdoc_path") {
+            // Like cargo, check every package in the workspace when run on a virtual manifest.
+            if let Ok(workspace) = load_workspace(&current_manifest) {
+                if workspace.is_virtual {
+                    return check_release_workspace(
+                        check_release,
+                        &workspace,
+                        &builtin_queries,
+                        verbosity,
+                        color,
+                    );
+                }
+            }
+        }
+        let baseline_root = check_release
+            .get_one::<String>("baseline_root")
+            .map(PathBuf::from);
+        return check_release_package(
+            config,
+            check_release,
+            queries,
+            &current_manifest,
+            baseline_root,
+        );
+    } else if let Some(watch) = semver_check.subcommand_matches("watch") {
+        return watch_package(config, watch, queries, &current_manifest);
+    } else if let Some(diff_published) = semver_check.subcommand_matches("diff-published") {
+        let crate_name = diff_published
+            .get_one::<String>("crate_name")
+            .expect("crate_name is required but was not present");
+        let registry = diff_published.get_one::<String>("registry").cloned();
+        let [baseline_source, current_source] =
+            ["baseline_version", "current_version"].map(|version_arg| BaselineSource::Registry {
+                crate_name: crate_name.clone(),
+                version: diff_published
+                    .get_one::<String>(version_arg)
+                    .expect("versions are required but were not present")
+                    .clone(),
+                registry: registry.clone(),
+            });
+
+        let rustdoc_options = RustdocOptions::from_matches(diff_published);
+        // Neither version is local, so there's no target directory to work in.
+        let loader = rustdoc_options.baseline_loader(
+            &config,
+            &env::temp_dir().join(format!("cargo-semver-checks-{crate_name}")),
+        );
+        let toolchain = resolve_toolchain(rustdoc_options.toolchain)?;
+
+        let mut comparisons = vec![];
+        for target in &rustdoc_options.targets {
+            let rustdoc_cmd =
+                rustdoc_options.rustdoc_command(&config, Some(toolchain.clone()), target.clone());
+            comparisons.push(CrateComparison {
+                target: target.clone(),
+                current: loader.load(&current_source, &rustdoc_cmd)?.into(),
+                baseline: loader
+                    .load(&baseline_source, &rustdoc_cmd)
+                    .context(BaselineUnavailable)?
+                    .into(),
+                dependencies: DependencyRustdocs::default(),
+                baseline_docs: baseline_source.docs_rs_release(),
+            });
+        }
+
+        return check_and_report(
+            config,
+            CheckReleaseOptions {
+                selection: QuerySelection::new(queries, vec![], vec![])?,
+                build_flags: rustdoc_options.build_flags.clone(),
+                build_environment: rustdoc_options.build_environment(None, Some(&toolchain)),
+                summary_template: output_templates(diff_published)?.summary,
+                ..Default::default()
+            },
+            comparisons,
+        )
+        .map(check_exit_code);
+    } else if let Some(publish) = semver_check.subcommand_matches("publish") {
+        let package = load_package_info(&current_manifest)?;
+        let registry = package_registry(publish, &package);
+        let rustdoc_options = RustdocOptions::from_matches(publish);
+        let baseline_loader =
+            rustdoc_options.baseline_loader(&config, &BaselineLoader::package_work_dir(&package));
+
+        match baseline_loader
+            .find_latest_version(registry.as_deref(), &package.name, &package.version)
+            .context(BaselineUnavailable)?
+        {
+            Some(baseline_version) => {
+                let baseline_source = BaselineSource::Registry {
+                    crate_name: package.name.clone(),
+                    version: baseline_version,
+                    registry: registry.clone(),
+                };
+                let (comparisons, build_environment) = load_comparisons(
+                    &config,
+                    &rustdoc_options,
+                    &baseline_loader,
+                    &current_manifest,
+                    None,
+                    &[baseline_source],
+                    &DependencyRustdocs::default(),
+                )?;
+                let options = CheckReleaseOptions {
+                    exceptions: load_exceptions(publish, &current_manifest)?,
+                    selection: QuerySelection::new(queries, vec![], vec![])?,
+                    build_flags: rustdoc_options.build_flags.clone(),
+                    build_environment,
+                    summary_template: output_templates(publish)?.summary,
+                    ..Default::default()
+                };
+                if check_and_report(config, options, comparisons)? != ReleaseCheckResult::Passed {
+                    return Ok(ExitCode::from(EXIT_SEMVER_VIOLATIONS));
+                }
+            }
+            None => {
+                let mut config = config;
+                colored_ln(&mut config.output_writer, |w| {
+                    colored!(
+                        w,
+                        "{}{}{:>12}{} semver checks: no version of {} earlier than {} has been published",
+                        fg!(Some(Color::Yellow)),
+                        bold!(true),
+                        "Skipping",
+                        reset!(),
+                        package.name,
+                        package.version,
+                    )
+                })
+                .expect("print failed");
+            }
+        }
+
+        let mut cmd = std::process::Command::new("cargo");
+        cmd.arg("publish").args(rustdoc_options.cargo_flags.args());
+        if let Some(manifest_path) = semver_check.get_one::<String>("manifest_path") {
+            cmd.args(["--manifest-path", manifest_path]);
+        }
+        if let Some(registry) = publish.get_one::<String>("registry") {
+            cmd.args(["--registry", registry]);
+        }
+        cmd.args(
+            publish
+                .get_many::<String>("publish_args")
+                .into_iter()
+                .flatten(),
+        );
+        let status = cmd
+            .status()
+            .with_context(|| "Failed to run `cargo publish`")?;
+        if !status.success() {
+            let code = status
+                .code()
+                .and_then(|code| u8::try_from(code).ok())
+                .unwrap_or(EXIT_ERROR);
+            return Ok(ExitCode::from(code));
+        }
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    unreachable!("no commands matched")
+}
+
+/// The crate features to enable, from `--features`.
+fn features(matches: &ArgMatches) -> Vec<String> {
+    matches
+        .get_many::<String>("features")
+        .into_iter()
+        .flatten()
+        .flat_map(|features| features.split(|c: char| c == ',' || c.is_whitespace()))
+        .filter(|feature| !feature.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+fn cargo_flags(matches: &ArgMatches) -> CargoFlags {
+    CargoFlags {
+        locked: matches.contains_id("locked") || matches.contains_id("frozen"),
+        offline: matches.contains_id("offline") || matches.contains_id("frozen"),
+    }
+}
+
+fn build_flags(matches: &ArgMatches) -> BuildFlags {
+    BuildFlags {
+        rustflags: matches.get_one::<String>("rustflags").cloned(),
+        rustdocflags: matches.get_one::<String>("rustdocflags").cloned(),
+        profile: matches.get_one::<String>("profile").cloned(),
+    }
+}
+
+/// Write a snapshot of the API of the crate, or of the given rustdoc json.
+fn dump_api(
+    config: GlobalConfig,
+    matches: &ArgMatches,
+    current_manifest: &Path,
+) -> anyhow::Result<()> {
+    let crate_ = match matches.get_one::<String>("current_rustdoc_path") {
+        Some(path) => load_rustdoc_from_file(path)?,
+        None => {
+            let toolchain =
+                resolve_toolchain(matches.get_one::<String>("toolchain").map(String::as_str))?;
+            RustdocCommand::new()
+                .verbosity(config.verbosity)
+                .status_printer(config.status_printer())
+                .toolchain(Some(toolchain))
+                .target(matches.get_one::<String>("target").cloned())
+                .features(features(matches))
+                .cargo_flags(cargo_flags(matches))
+                .build_flags(build_flags(matches))
+                .generate(current_manifest)?
+        }
+    };
+
+    match matches.get_one::<String>("output_path") {
+        Some(path) => {
+            let mut file = std::fs::File::create(path)
+                .with_context(|| format!("Failed to create API snapshot {:?}", path))?;
+            write_snapshot(&crate_, &mut file)
+                .with_context(|| format!("Failed to write API snapshot {:?}", path))?;
+            config.status_printer().print(
+                Color::Green,
+                "Wrote",
+                format_args!("API snapshot to {path}"),
+            );
+            Ok(())
+        }
+        None => {
+            let mut config = config;
+            write_snapshot(&crate_, &mut config.output_writer)
+        }
+    }
+}
+
+/// How to generate rustdoc json for the crates being compared.
+struct RustdocOptions<'a> {
+    toolchain: Option<&'a str>,
+    targets: Vec<Option<String>>,
+    features: Vec<String>,
+    cache_dir: Option<PathBuf>,
+    build_dir: Option<PathBuf>,
+    cargo_flags: CargoFlags,
+    build_flags: BuildFlags,
+    use_docs_rs: bool,
+}
+
+impl<'a> RustdocOptions<'a> {
+    fn from_matches(matches: &'a ArgMatches) -> Self {
+        let targets = match matches.get_many::<String>("target") {
+            Some(targets) => targets.cloned().map(Some).collect(),
+            None => vec![None],
+        };
+        Self {
+            toolchain: matches.get_one::<String>("toolchain").map(String::as_str),
+            targets,
+            features: features(matches),
+            cache_dir: matches.get_one::<String>("cache_dir").map(PathBuf::from),
+            build_dir: matches.get_one::<String>("build_dir").map(PathBuf::from),
+            cargo_flags: cargo_flags(matches),
+            build_flags: build_flags(matches),
+            use_docs_rs: !matches.contains_id("no_docs_rs"),
+        }
+    }
+
+    /// A command generating rustdoc json for the given target with these options.
+    fn rustdoc_command(
+        &self,
+        config: &GlobalConfig,
+        toolchain: Option<String>,
+        target: Option<String>,
+    ) -> RustdocCommand {
+        RustdocCommand::new()
+            .verbosity(config.verbosity)
+            .status_printer(config.status_printer())
+            .toolchain(toolchain)
+            .target(target)
+            .features(self.features.clone())
+            .cargo_flags(self.cargo_flags)
+            .build_flags(self.build_flags.clone())
+    }
+
+    /// What rustdoc json is generated with by the given toolchain, if it's generated at all,
+    /// for the package if it's a local one.
+    fn build_environment(
+        &self,
+        package: Option<&PackageInfo>,
+        toolchain: Option<&str>,
+    ) -> BuildEnvironment {
+        let features = match package {
+            Some(package) => package.resolved_features(&self.features),
+            None => {
+                let mut features = self.features.clone();
+                features.sort_unstable();
+                features.dedup();
+                features
+            }
+        };
+        BuildEnvironment {
+            features,
+            toolchain: toolchain
+                .map(|toolchain| toolchain_version(toolchain).unwrap_or_else(|| toolchain.into())),
+            host_target: toolchain.and_then(|_| host_triple().ok()),
+        }
+    }
+
+    /// A loader working in the `--build-dir` directory if set, or in `default_work_dir` otherwise.
+    fn baseline_loader(&self, config: &GlobalConfig, default_work_dir: &Path) -> BaselineLoader {
+        BaselineLoader::new(
+            self.build_dir.as_deref().unwrap_or(default_work_dir),
+            self.cache_dir.clone(),
+            self.cargo_flags,
+            self.use_docs_rs,
+            config.status_printer(),
+        )
+    }
+
+    /// A loader for baselines given as files outside of a package, where there's no working
+    /// directory: parsed baselines are only cached if `--build-dir` or `--cache-dir` is set,
+    /// rather than in the current directory.
+    fn baseline_loader_outside_package(&self, config: &GlobalConfig) -> BaselineLoader {
+        let loader = self.baseline_loader(config, Path::new(""));
+        if self.build_dir.is_none() && self.cache_dir.is_none() {
+            loader.without_parsed_cache()
+        } else {
+            loader
+        }
+    }
+}
+
+/// Check the package defined by `current_manifest`, against the baseline in `baseline_root`
+/// if a baseline directory is used.
+fn check_release_package(
+    config: GlobalConfig,
+    check_release: &ArgMatches,
+    queries: BTreeMap<String, SemverQuery>,
+    current_manifest: &Path,
+    baseline_root: Option<PathBuf>,
+) -> anyhow::Result<ExitCode> {
+    let verbosity = config.verbosity;
+    let color = config.color;
+    let current_rustdoc_path = check_release
+        .get_one::<String>("current_rustdoc_path")
+        .map(String::as_str);
+    if current_rustdoc_path.is_none() {
+        load_library_package(current_manifest)?;
+    }
+    let rustdoc_options = RustdocOptions::from_matches(check_release);
+    let selection = query_selection(check_release, queries)?;
+    let (baseline_loader, baseline_sources) = resolve_baselines(
+        &config,
+        check_release,
+        &rustdoc_options,
+        current_manifest,
+        baseline_root,
+    )?;
+    let rustdoc_start_instant = std::time::Instant::now();
+    let (comparisons, build_environment) = load_comparisons(
+        &config,
+        &rustdoc_options,
+        &baseline_loader,
+        current_manifest,
+        current_rustdoc_path,
+        &baseline_sources,
+        &load_dependency_rustdocs(check_release)?,
+    )?;
+    let rustdoc_duration = rustdoc_start_instant.elapsed();
+
+    if check_release.contains_id("fix") {
+        let exceptions = load_exceptions(check_release, current_manifest)?;
+        fix_version(
+            config,
+            current_manifest,
+            &selection,
+            &exceptions,
+            comparisons,
+        )?;
+        return Ok(ExitCode::SUCCESS);
+    }
+    if check_release.contains_id("print_required_bump") {
+        let exceptions = load_exceptions(check_release, current_manifest)?;
+        print_required_bump(config, &selection, &exceptions, comparisons)?;
+        return Ok(ExitCode::SUCCESS);
+    }
+
+    let mut options =
+        check_release_options(check_release, current_manifest, selection, &rustdoc_options)?;
+    options.rustdoc_duration = Some(rustdoc_duration);
+    options.build_environment = build_environment;
+    // Keep stdout clean for the machine-readable report.
+    let config = if (options.output_format != OutputFormat::Human && options.output_path.is_none())
+        || options.emit.is_some()
+    {
+        GlobalConfig::with_output_stream(atty::Stream::Stderr, verbosity, color)
+    } else {
+        config
+    };
+
+    check_and_report(config, options, comparisons).map(check_exit_code)
+}
+
+/// Check the package every time its files change, until interrupted.
+///
+/// The baselines don't change while watching, so they are only loaded once:
+/// each check only regenerates the rustdoc json of the current crate.
+fn watch_package(
+    config: GlobalConfig,
+    watch: &ArgMatches,
+    queries: BTreeMap<String, SemverQuery>,
+    current_manifest: &Path,
+) -> anyhow::Result<ExitCode> {
+    for (id, option) in [
+        ("current_rustdoc_path", "--current"),
+        ("fix", "--fix"),
+        ("print_required_bump", "--print-required-bump"),
+        ("output_path", "--output-path"),
+        ("emit", "--emit"),
+        ("workspace", "--workspace"),
+    ] {
+        if watch.contains_id(id) {
+            anyhow::bail!("{option} cannot be used with `watch`");
+        }
+    }
+    if watch.get_one::<String>("output_format").map(String::as_str) != Some("human") {
+        anyhow::bail!("--output-format cannot be used with `watch`");
+    }
+
+    let package = load_library_package(current_manifest)?;
+    let rustdoc_options = RustdocOptions::from_matches(watch);
+    let (baseline_loader, baseline_sources) = resolve_baselines(
+        &config,
+        watch,
+        &rustdoc_options,
+        current_manifest,
+        watch.get_one::<String>("baseline_root").map(PathBuf::from),
+    )?;
+    let dependencies = load_dependency_rustdocs(watch)?;
+    let toolchain = resolve_toolchain(rustdoc_options.toolchain)?;
+    let build_environment = rustdoc_options.build_environment(Some(&package), Some(&toolchain));
+    let mut baselines = vec![];
+    for target in &rustdoc_options.targets {
+        let rustdoc_cmd =
+            rustdoc_options.rustdoc_command(&config, Some(toolchain.clone()), target.clone());
+        let crates = baseline_sources
+            .iter()
+            .map(|source| baseline_loader.load(source, &rustdoc_cmd).map(Arc::new))
+            .collect::<anyhow::Result<Vec<_>>>()
+            .context(BaselineUnavailable)?;
+        baselines.push((target.clone(), rustdoc_cmd, crates));
+    }
+
+    let package_dir = current_manifest
+        .parent()
+        .expect("manifest path has no parent");
+    let poll_interval = *watch
+        .get_one::<u64>("poll_interval")
+        .expect("poll_interval has a default value");
+    let mut watcher = SourceWatcher::new(
+        package_dir,
+        vec![package.target_directory.clone()],
+        Duration::from_millis(poll_interval),
+    );
+    let (verbosity, color) = (config.verbosity, config.color);
+    let mut config = config;
+    loop {
+        let status = config.status_printer();
+        let result = (|| {
+            let mut comparisons = vec![];
+            for (target, rustdoc_cmd, baseline_crates) in &baselines {
+                let current_crate = Arc::new(rustdoc_cmd.generate(current_manifest)?);
+                for (baseline_crate, baseline_source) in
+                    baseline_crates.iter().zip(&baseline_sources)
+                {
+                    comparisons.push(CrateComparison {
+                        target: target.clone(),
+                        current: current_crate.clone(),
+                        baseline: baseline_crate.clone(),
+                        dependencies: dependencies.clone(),
+                        baseline_docs: baseline_source.docs_rs_release(),
+                    });
+                }
+            }
+            let selection = query_selection(watch, queries.clone())?;
+            let mut options =
+                check_release_options(watch, current_manifest, selection, &rustdoc_options)?;
+            options.build_environment = build_environment.clone();
+            check_and_report(config, options, comparisons)
+        })();
+        // Problems like compilation errors are reported, and fixing them is just another change.
+        if let Err(e) = result {
+            status.error(&e);
+        }
+        status.print(
+            Color::Cyan,
+            "Watching",
+            format_args!("{} for changes", package_dir.display()),
+        );
+        watcher.wait_for_change();
+        config = GlobalConfig::new(verbosity, color);
+    }
+}
+
+/// The package defined by the manifest, which must have a library target to be checked.
+fn load_library_package(manifest_path: &Path) -> anyhow::Result<PackageInfo> {
+    let package = load_package_info(manifest_path)?;
+    // Workspace members without a library are skipped before getting here,
+    // so the user must have asked for this package specifically.
+    if !package.has_library {
+        anyhow::bail!(
+            "Package {} has no library target, so it has no public API to check. \
+            Only library targets are checked: `pub` items in binary targets \
+            cannot be used by other crates.",
+            package.name
+        );
+    }
+    Ok(package)
+}
+
+/// The checks selected with `--only` and `--skip`.
+fn query_selection(
+    matches: &ArgMatches,
+    mut queries: BTreeMap<String, SemverQuery>,
+) -> anyhow::Result<QuerySelection> {
+    let ids = |name| -> Vec<String> {
+        matches
+            .get_many::<String>(name)
+            .into_iter()
+            .flatten()
+            .cloned()
+            .collect()
+    };
+    set_lint_levels(&mut queries, &ids("warn"), &ids("deny"))?;
+    QuerySelection::new(queries, ids("only"), ids("skip"))
+}
+
+/// The baselines to check against, and the loader to load them with.
+fn resolve_baselines(
+    config: &GlobalConfig,
+    matches: &ArgMatches,
+    rustdoc_options: &RustdocOptions,
+    current_manifest: &Path,
+    baseline_root: Option<PathBuf>,
+) -> anyhow::Result<(BaselineLoader, Vec<BaselineSource>)> {
+    let baseline_source = if let Some(path) = matches.get_one::<String>("baseline_rustdoc_path") {
+        BaselineSource::RustdocFile(PathBuf::from(path))
+    } else if let Some(path) = matches.get_one::<String>("baseline_snapshot") {
+        BaselineSource::Snapshot(PathBuf::from(path))
+    } else if let Some(root) = baseline_root {
+        BaselineSource::Root(root)
+    } else if let Some(version) = matches.get_one::<String>("baseline_version") {
+        let package = load_package_info(current_manifest)?;
+        let registry = package_registry(matches, &package);
+        BaselineSource::Registry {
+            crate_name: package.name,
+            version: version.clone(),
+            registry,
+        }
+    } else if matches.contains_id("all_compatible_releases") {
+        // Replaced by one source per compatible release, once they are known.
+        let package = load_package_info(current_manifest)?;
+        let registry = package_registry(matches, &package);
+        BaselineSource::Registry {
+            crate_name: package.name,
+            version: package.version,
+            registry,
+        }
+    } else {
+        unreachable!("a baseline is required but was not present")
+    };
+
+    let work_dir = match &baseline_source {
+        BaselineSource::Root(root) => Some(
+            BaselineLoader::default_work_dir(current_manifest)
+                .or_else(|_| BaselineLoader::default_work_dir(&root.join("Cargo.toml")))?,
+        ),
+        BaselineSource::RustdocFile(..)
+        | BaselineSource::Snapshot(..)
+        | BaselineSource::Registry { .. } => {
+            BaselineLoader::default_work_dir(current_manifest).ok()
+        }
+    };
+    let baseline_loader = match &work_dir {
+        Some(work_dir) => rustdoc_options.baseline_loader(config, work_dir),
+        None => rustdoc_options.baseline_loader_outside_package(config),
+    };
+    let baseline_sources = match baseline_source {
+        BaselineSource::Registry {
+            crate_name,
+            version,
+            registry,
+        } if matches.contains_id("all_compatible_releases") => {
+            let current_version = semver::Version::parse(&version)
+                .with_context(|| format!("Invalid crate version {version}"))?;
+            let versions = baseline_loader
+                .find_compatible_versions(registry.as_deref(), &crate_name, &current_version)
+                .context(BaselineUnavailable)?;
+            if versions.is_empty() {
+                return Err(anyhow::anyhow!(
+                    "No published version of {crate_name} is semver-compatible with, and earlier than, {version}"
+                )
+                .context(BaselineUnavailable));
+            }
+            versions
+                .into_iter()
+                .map(|version| BaselineSource::Registry {
+                    crate_name: crate_name.clone(),
+                    version,
+                    registry: registry.clone(),
+                })
+                .collect()
+        }
+        baseline_source => vec![baseline_source],
+    };
+    Ok((baseline_loader, baseline_sources))
+}
+
+/// How to check and report on the release, as set by the command-line options.
+fn check_release_options(
+    matches: &ArgMatches,
+    current_manifest: &Path,
+    selection: QuerySelection,
+    rustdoc_options: &RustdocOptions,
+) -> anyhow::Result<CheckReleaseOptions> {
+    let output_path = matches.get_one::<String>("output_path").map(PathBuf::from);
+    let emit = matches
+        .get_one::<String>("emit")
+        .map(|name| Emit::from_name(name).expect("unexpected document to emit"));
+    if emit.is_some()
+        && output_path.is_none()
+        && matches
+            .get_one::<String>("output_format")
+            .map(String::as_str)
+            != Some("human")
+    {
+        anyhow::bail!("--emit writes to stdout, so a machine-readable report needs --output-path");
+    }
+    Ok(CheckReleaseOptions {
+        release_type: matches
+            .get_one::<String>("release_type")
+            .map(|release_type| match release_type.as_str() {
+                "major" => ActualSemverUpdate::Major,
+                "minor" => ActualSemverUpdate::Minor,
+                "patch" => ActualSemverUpdate::Patch,
+                _ => unreachable!("unexpected release type {release_type}"),
+            }),
+        output_format: OutputFormat::from_name(
+            matches
+                .get_one::<String>("output_format")
+                .expect("output_format has a default value"),
+        )
+        .expect("unexpected output format"),
+        output_path,
+        emit,
+        exceptions: load_exceptions(matches, current_manifest)?,
+        selection,
+        show_witnesses: matches.contains_id("witnesses"),
+        max_results_per_check: matches
+            .get_one::<u64>("max_results_per_check")
+            .map(|max| usize::try_from(*max).unwrap_or(usize::MAX)),
+        group_by: GroupBy::from_name(
+            matches
+                .get_one::<String>("group_by")
+                .expect("group_by has a default value"),
+        )
+        .expect("unexpected grouping"),
+        fail_fast: matches
+            .get_one::<String>("fail_fast")
+            .map(|name| FailFast::from_name(name).expect("unexpected fail-fast mode")),
+        jobs: matches
+            .get_one::<u64>("jobs")
+            .map(|jobs| usize::try_from(*jobs).unwrap_or(usize::MAX)),
+        incremental: matches.contains_id("incremental"),
+        stats_format: matches
+            .get_one::<String>("stats")
+            .map(|name| StatsFormat::from_name(name).expect("unexpected stats format")),
+        rustdoc_duration: None,
+        build_flags: rustdoc_options.build_flags.clone(),
+        build_environment: BuildEnvironment::default(),
+        summary_template: output_templates(matches)?.summary,
+    })
+}
+
+/// The templates in the `--templates` directory, if one was given.
+fn output_templates(matches: &ArgMatches) -> anyhow::Result<OutputTemplates> {
+    match matches.get_one::<String>("templates") {
+        Some(dir) => OutputTemplates::load(Path::new(dir)),
+        None => Ok(OutputTemplates::default()),
+    }
+}
+
+/// Check every package in the workspace that has a library target and may be published,
+/// failing if any of them fails.
+fn check_release_workspace(
+    check_release: &ArgMatches,
+    workspace: &Workspace,
+    builtin_queries: &BTreeMap<String, SemverQuery>,
+    verbosity: Verbosity,
+    color: ColorWhen,
+) -> anyhow::Result<ExitCode> {
+    let mut exit_code = ExitCode::SUCCESS;
+    for package in &workspace.members {
+        let config = GlobalConfig::new(verbosity, color);
+        let skip_reason = if !package.has_library {
+            Some("it has no library target, so it has no public API to check")
+        } else if package.publish.as_deref() == Some(&[]) {
+            Some("it is not published (`publish = false`)")
+        } else {
+            None
+        };
+        if let Some(skip_reason) = skip_reason {
+            config.status_printer().print(
+                Color::Yellow,
+                "Skipping",
+                format_args!("{}: {}", package.name, skip_reason),
+            );
+            continue;
+        }
+        config.status_printer().print(
+            Color::Green,
+            "Checking",
+            format_args!("package {} v{}", package.name, package.version),
+        );
+
+        let mut queries = builtin_queries.clone();
+        add_queries(
+            &mut queries,
+            load_lint_packs(
+                package,
+                &package.manifest_path,
+                &BaselineLoader::package_work_dir(package),
+            )?,
+        )?;
+        // The baseline of each package is at the same place within the baseline workspace.
+        let baseline_root = check_release
+            .get_one::<String>("baseline_root")
+            .map(|root| {
+                let package_dir = package
+                    .manifest_path
+                    .parent()
+                    .expect("manifest path has no parent");
+                Path::new(root).join(
+                    package_dir
+                        .strip_prefix(&workspace.root)
+                        .unwrap_or(Path::new("")),
+                )
+            });
+        let package_exit_code = check_release_package(
+            config,
+            check_release,
+            queries,
+            &package.manifest_path,
+            baseline_root,
+        )?;
+        if exit_code == ExitCode::SUCCESS {
+            exit_code = package_exit_code;
+        }
+    }
+    Ok(exit_code)
+}
+
+/// Generate or load the rustdoc json of the current crate and of each baseline, for each target,
+/// along with what any generated rustdoc json was generated with.
+fn load_comparisons(
+    config: &GlobalConfig,
+    rustdoc_options: &RustdocOptions,
+    baseline_loader: &BaselineLoader,
+    current_manifest: &Path,
+    current_rustdoc_path: Option<&str>,
+    baseline_sources: &[BaselineSource],
+    dependencies: &DependencyRustdocs,
+) -> anyhow::Result<(Vec<CrateComparison>, BuildEnvironment)> {
+    // Only look for a toolchain if we might need to generate rustdoc json.
+    let generates_rustdoc = current_rustdoc_path.is_none()
+        || baseline_sources.iter().any(|source| {
+            !matches!(
+                source,
+                BaselineSource::RustdocFile(..) | BaselineSource::Snapshot(..)
+            )
+        });
+    let toolchain = if generates_rustdoc {
+        Some(resolve_toolchain(rustdoc_options.toolchain)?)
+    } else {
+        None
+    };
+    let package = if generates_rustdoc {
+        load_package_info(current_manifest).ok()
+    } else {
+        None
+    };
+    let build_environment =
+        rustdoc_options.build_environment(package.as_ref(), toolchain.as_deref());
+
+    let mut comparisons = vec![];
+    for target in &rustdoc_options.targets {
+        let rustdoc_cmd =
+            rustdoc_options.rustdoc_command(config, toolchain.clone(), target.clone());
+
+        let current_crate = Arc::new(match current_rustdoc_path {
+            Some(path) => load_rustdoc_from_file(path)?,
+            None => rustdoc_cmd.generate(current_manifest)?,
+        });
+        for baseline_source in baseline_sources {
+            comparisons.push(CrateComparison {
+                target: target.clone(),
+                current: current_crate.clone(),
+                baseline: baseline_loader
+                    .load(baseline_source, &rustdoc_cmd)
+                    .context(BaselineUnavailable)?
+                    .into(),
+                dependencies: dependencies.clone(),
+                baseline_docs: baseline_source.docs_rs_release(),
+            });
+        }
+    }
+    Ok((comparisons, build_environment))
+}
+
+/// Load the rustdoc json given by `--current-dependency` and `--baseline-dependency`.
+fn load_dependency_rustdocs(matches: &ArgMatches) -> anyhow::Result<DependencyRustdocs> {
+    let load = |id| -> anyhow::Result<Arc<[Crate]>> {
+        matches
+            .get_many::<String>(id)
+            .into_iter()
+            .flatten()
+            .map(|path| load_rustdoc_from_file(path))
+            .collect()
+    };
+    Ok(DependencyRustdocs {
+        current: load("current_dependency")?,
+        baseline: load("baseline_dependency").context(BaselineUnavailable)?,
+    })
+}
+
+/// The registry in which the package is published: the one given by `--registry`, or if
+/// the package may only be published to one registry, that one. `None` means crates.io.
+fn package_registry(matches: &ArgMatches, package: &PackageInfo) -> Option<String> {
+    match matches.get_one::<String>("registry") {
+        Some(registry) => Some(registry.clone()),
+        None => match package.publish.as_deref() {
+            Some([registry]) => Some(registry.clone()),
+            _ => None,
+        },
+    }
+}
+
+/// Load the accepted semver violations from the file given by `--exceptions`,
+/// or from the default file next to the manifest if it exists.
+fn load_exceptions(matches: &ArgMatches, manifest_path: &Path) -> anyhow::Result<SemverExceptions> {
+    let exceptions_path = match matches.get_one::<String>("exceptions_path") {
+        Some(path) => Some(PathBuf::from(path)),
+        None => Some(manifest_path.with_file_name(DEFAULT_EXCEPTIONS_FILE))
+            .filter(|path| path.is_file()),
+    };
+    match exceptions_path {
+        Some(path) => SemverExceptions::load(&path),
+        None => Ok(SemverExceptions::default()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, ffi::OsString};
+
+    use clap::ArgMatches;
+
+    use super::{cli, parse_args};
+
+    /// The `check-release` matches of its arguments, with the given environment variables.
+    fn parse_check_release(args: &[&str], env: &[(&str, &str)]) -> clap::Result<ArgMatches> {
+        let env: BTreeMap<String, OsString> = env
+            .iter()
+            .map(|(name, value)| (name.to_string(), OsString::from(value)))
+            .collect();
+        let args = ["cargo-semver-checks", "semver-checks", "check-release"]
+            .iter()
+            .chain(args)
+            .map(OsString::from)
+            .collect();
+        let matches = parse_args(args, &env)?;
+        Ok(matches
+            .subcommand_matches("semver-checks")
+            .and_then(|semver_checks| semver_checks.subcommand_matches("check-release"))
+            .expect("check-release subcommand not found")
+            .clone())
+    }
+
+    fn value(matches: &ArgMatches, id: &str) -> Option<String> {
+        matches.get_one::<String>(id).cloned()
+    }
+
+    #[test]
+    fn options_can_be_set_by_env_vars_named_after_them() {
+        cli(&[]).debug_assert();
+        let matches = parse_check_release(
+            &[],
+            &[
+                ("CARGO_SEMVER_CHECKS_BASELINE_VERSION", "1.0.0"),
+                ("CARGO_SEMVER_CHECKS_OFFLINE", "yes"),
+                ("CARGO_SEMVER_CHECKS_LOCKED", "false"),
+            ],
+        )
+        .expect("arguments failed to parse");
+        assert_eq!(
+            Some("1.0.0".to_string()),
+            value(&matches, "baseline_version")
+        );
+        assert!(matches.contains_id("offline"));
+        assert!(!matches.contains_id("locked"));
+    }
+
+    #[test]
+    fn env_vars_conflicting_with_the_command_line_are_ignored() {
+        let env = [("CARGO_SEMVER_CHECKS_BASELINE_VERSION", "1.0.0")];
+        let parse = |args: &[&str]| {
+            let matches = parse_check_release(args, &env).expect("arguments failed to parse");
+            (
+                value(&matches, "baseline_version"),
+                value(&matches, "baseline_root"),
+            )
+        };
+
+        assert_eq!(
+            (Some("1.0.0".to_string()), None),
+            parse(&["--manifest-path", "Cargo.toml"])
+        );
+        assert_eq!(
+            (None, Some("../old".to_string())),
+            parse(&["--baseline-root", "../old"])
+        );
+        // Options given on the command line still conflict with each other,
+        // and so do those set in the environment.
+        assert!(parse_check_release(
+            &["--baseline-root", "../old", "--baseline-version", "1.0.0"],
+            &[],
+        )
+        .is_err());
+        assert!(parse_check_release(
+            &[],
+            &[
+                ("CARGO_SEMVER_CHECKS_BASELINE_VERSION", "1.0.0"),
+                ("CARGO_SEMVER_CHECKS_BASELINE_ROOT", "../old"),
+            ],
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn options_can_be_set_in_the_manifest_below_env_vars() {
+        let dir = std::env::temp_dir().join(format!("semver-config-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let manifest_path = dir.join("Cargo.toml");
+        let write_manifest = |table: &str| {
+            let manifest = format!(
+                "[package]\nname = \"example\"\nversion = \"1.0.0\"\n\n\
+                [package.metadata.semver-checks]\n{table}"
+            );
+            std::fs::write(&manifest_path, manifest).unwrap();
+        };
+        let manifest_arg = manifest_path.to_str().unwrap();
+        write_manifest(
+            r#"baseline-version = "1.0.0"
+features = ["alloc", "serde"]
+offline = true
+locked = false
+exceptions = "exceptions.toml"
+jobs = 2
+
+[package.metadata.semver-checks.lint-packs]
+"#,
+        );
+
+        let matches = parse_check_release(&["--manifest-path", manifest_arg], &[])
+            .expect("arguments failed to parse");
+        assert_eq!(
+            Some("1.0.0".to_string()),
+            value(&matches, "baseline_version")
+        );
+        assert_eq!(
+            vec!["alloc", "serde"],
+            matches
+                .get_many::<String>("features")
+                .unwrap()
+                .collect::<Vec<_>>()
+        );
+        assert!(matches.contains_id("offline"));
+        assert!(!matches.contains_id("locked"));
+        assert_eq!(Some(&2), matches.get_one::<u64>("jobs"));
+        // Paths are relative to the manifest.
+        assert_eq!(
+            Some(dir.join("exceptions.toml")),
+            value(&matches, "exceptions_path").map(Into::into)
+        );
+
+        // Environment variables and the command line take precedence over the manifest,
+        // including with options that conflict with those set in the manifest.
+        let matches = parse_check_release(
+            &["--manifest-path", manifest_arg, "--features", "std"],
+            &[("CARGO_SEMVER_CHECKS_BASELINE_VERSION", "2.0.0")],
+        )
+        .expect("arguments failed to parse");
+        assert_eq!(
+            Some("2.0.0".to_string()),
+            value(&matches, "baseline_version")
+        );
+        assert_eq!(
+            vec!["std"],
+            matches
+                .get_many::<String>("features")
+                .unwrap()
+                .collect::<Vec<_>>()
+        );
+        for (args, env) in [
+            (vec!["--baseline-root", "../old"], vec![]),
+            (
+                vec![],
+                vec![("CARGO_SEMVER_CHECKS_BASELINE_ROOT", "../old")],
+            ),
+        ] {
+            let args: Vec<&str> = ["--manifest-path", manifest_arg]
+                .into_iter()
+                .chain(args)
+                .collect();
+            let matches = parse_check_release(&args, &env).expect("arguments failed to parse");
+            assert_eq!(None, value(&matches, "baseline_version"));
+            assert_eq!(Some("../old".to_string()), value(&matches, "baseline_root"));
+        }
+
+        write_manifest("offline = \"yes\"\n");
+        assert!(parse_check_release(&["--manifest-path", manifest_arg], &[]).is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
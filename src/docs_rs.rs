@@ -0,0 +1,205 @@
+//! Downloading prebuilt rustdoc JSON for crates published on crates.io from docs.rs,
+//! and linking to the documentation of their items.
+
+use std::{fs::File, path::Path};
+
+use anyhow::Context;
+use flate2::read::GzDecoder;
+use rustdoc_types::{Crate, ItemKind};
+
+use crate::interrupt::TempPath;
+
+const DOCS_RS_URL: &str = "https://docs.rs";
+
+/// The URL of the gzip-compressed rustdoc JSON that docs.rs built for the given crate version
+/// and target, in the rustdoc JSON format version that we are able to parse.
+fn rustdoc_json_url(crate_name: &str, version: &str, target: &str) -> String {
+    format!(
+        "{DOCS_RS_URL}/crate/{crate_name}/{version}/{target}/json/{}.gz",
+        rustdoc_types::FORMAT_VERSION
+    )
+}
+
+/// A version of a crate published on crates.io, whose documentation docs.rs hosts.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DocsRsRelease {
+    pub(crate) crate_name: String,
+    pub(crate) version: String,
+}
+
+impl DocsRsRelease {
+    /// The docs.rs page of the item at `path` in this release, whose rustdoc JSON is `crate_`,
+    /// at the given anchor within the page. Items that aren't documented at that path,
+    /// like items re-exported from a private module, get a search for their name instead.
+    pub(crate) fn item_url(
+        &self,
+        crate_: &Crate,
+        path: &[&str],
+        anchor: Option<&str>,
+    ) -> Option<String> {
+        let kind = crate_
+            .paths
+            .values()
+            .find(|summary| summary.crate_id == 0 && summary.path == path)
+            .and_then(|summary| page_kind(&summary.kind));
+        let url = match kind {
+            Some(kind) => item_page_url(&self.crate_name, &self.version, path, kind)?,
+            None => {
+                let (crate_root, name) = (path.first()?, path.last()?);
+                format!(
+                    "{DOCS_RS_URL}/{}/{}/{crate_root}/?search={name}",
+                    self.crate_name, self.version
+                )
+            }
+        };
+        Some(match anchor {
+            Some(anchor) => format!("{url}#{anchor}"),
+            None => url,
+        })
+    }
+}
+
+/// The docs.rs page of the item at `path`, whose kind is given as it appears in the names
+/// of rustdoc pages, like `struct` or `fn`. `None` if the path doesn't name an item in a crate.
+pub(crate) fn item_page_url(
+    crate_name: &str,
+    version: &str,
+    path: &[&str],
+    kind: &str,
+) -> Option<String> {
+    let (name, modules) = path.split_last()?;
+    if modules.is_empty() {
+        return None;
+    }
+    let page = if kind == "mod" {
+        format!("{name}/index.html")
+    } else {
+        format!("{kind}.{name}.html")
+    };
+    Some(format!(
+        "{DOCS_RS_URL}/{crate_name}/{version}/{}/{page}",
+        modules.join("/")
+    ))
+}
+
+/// How rustdoc names the pages of items of the given kind, for the kinds that have pages.
+fn page_kind(kind: &ItemKind) -> Option<&'static str> {
+    Some(match kind {
+        ItemKind::Module => "mod",
+        ItemKind::Struct => "struct",
+        ItemKind::Union => "union",
+        ItemKind::Enum => "enum",
+        ItemKind::Function => "fn",
+        ItemKind::Typedef => "type",
+        ItemKind::Constant => "constant",
+        ItemKind::Static => "static",
+        ItemKind::Trait => "trait",
+        ItemKind::TraitAlias => "traitalias",
+        ItemKind::Macro => "macro",
+        ItemKind::ProcAttribute => "attr",
+        ItemKind::ProcDerive => "derive",
+        _ => return None,
+    })
+}
+
+/// Download the rustdoc JSON that docs.rs built for the given crate version and target,
+/// and write it to `destination`.
+///
+/// Returns `false` if docs.rs doesn't have rustdoc JSON for that crate version, target,
+/// and format version, in which case it has to be generated locally instead.
+pub(crate) fn download_rustdoc_json(
+    crate_name: &str,
+    version: &str,
+    target: &str,
+    destination: &Path,
+) -> anyhow::Result<bool> {
+    let url = rustdoc_json_url(crate_name, version, target);
+    let response = match ureq::get(&url)
+        .set(
+            "User-Agent",
+            concat!("cargo-semver-checks/", env!("CARGO_PKG_VERSION")),
+        )
+        .call()
+    {
+        Ok(response) => response,
+        Err(ureq::Error::Status(404, _)) => return Ok(false),
+        Err(e) => return Err(e).with_context(|| format!("Failed to download {url}")),
+    };
+
+    if let Some(parent) = destination.parent() {
+        std::fs::create_dir_all(parent)
+            .with_context(|| format!("Failed to create directory {:?}", parent))?;
+    }
+    // Download to a temporary file, so an interrupted download doesn't leave a truncated file.
+    let temp = TempPath::next_to(destination);
+    let mut file =
+        File::create(temp.path()).with_context(|| format!("Failed to create {:?}", temp.path()))?;
+    std::io::copy(&mut GzDecoder::new(response.into_reader()), &mut file)
+        .with_context(|| format!("Failed to download and decompress {url}"))?;
+    temp.persist(destination)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rustdoc_types::{Crate, Id, ItemKind, ItemSummary, FORMAT_VERSION};
+
+    use super::{rustdoc_json_url, DocsRsRelease};
+
+    #[test]
+    fn url_requests_supported_format_version() {
+        assert_eq!(
+            format!(
+                "https://docs.rs/crate/itoa/1.0.2/x86_64-unknown-linux-gnu/json/{}.gz",
+                rustdoc_types::FORMAT_VERSION
+            ),
+            rustdoc_json_url("itoa", "1.0.2", "x86_64-unknown-linux-gnu"),
+        );
+    }
+
+    #[test]
+    fn item_urls_use_the_kind_of_the_item() {
+        let summary = ItemSummary {
+            crate_id: 0,
+            path: vec![
+                "my_crate".to_string(),
+                "config".to_string(),
+                "Mode".to_string(),
+            ],
+            kind: ItemKind::Enum,
+        };
+        let crate_ = Crate {
+            root: Id("0:0".to_string()),
+            crate_version: Some("1.2.0".to_string()),
+            includes_private: false,
+            index: HashMap::new(),
+            paths: HashMap::from([(Id("0:1".to_string()), summary)]),
+            external_crates: HashMap::new(),
+            format_version: FORMAT_VERSION,
+        };
+        let release = DocsRsRelease {
+            crate_name: "my-crate".to_string(),
+            version: "1.2.0".to_string(),
+        };
+
+        assert_eq!(
+            Some("https://docs.rs/my-crate/1.2.0/my_crate/config/enum.Mode.html#variant.Fast"),
+            release
+                .item_url(
+                    &crate_,
+                    &["my_crate", "config", "Mode"],
+                    Some("variant.Fast")
+                )
+                .as_deref(),
+        );
+        // Re-exports aren't documented at their path, so they're searched for.
+        assert_eq!(
+            Some("https://docs.rs/my-crate/1.2.0/my_crate/?search=Mode"),
+            release
+                .item_url(&crate_, &["my_crate", "Mode"], None)
+                .as_deref(),
+        );
+    }
+}
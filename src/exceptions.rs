@@ -0,0 +1,189 @@
+use std::{collections::BTreeMap, path::Path, sync::Arc};
+
+use anyhow::Context;
+use serde::Deserialize;
+use trustfall_core::ir::FieldValue;
+
+/// The name of the file, next to the crate's `Cargo.toml`, listing accepted semver violations.
+pub(crate) const DEFAULT_EXCEPTIONS_FILE: &str = "semver-exceptions.toml";
+
+/// Semver violations that have been deliberately accepted, and should not fail the check.
+///
+/// ```toml
+/// [[exception]]
+/// check = "struct_missing"
+/// path = "my_crate::OldStruct"
+/// expires = "2.0.0"
+/// reason = "OldStruct was replaced by NewStruct."
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct SemverExceptions {
+    #[serde(default, rename = "exception")]
+    exceptions: Vec<SemverException>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct SemverException {
+    /// The id of the check whose result is accepted, e.g. `struct_missing`.
+    pub(crate) check: String,
+
+    /// The importable path of the affected item, e.g. `my_crate::module::Item`.
+    /// For struct fields and enum variants, the field or variant name is the last component.
+    pub(crate) path: String,
+
+    /// The crate version from which the exception no longer applies.
+    /// If not set, the exception never expires.
+    pub(crate) expires: Option<semver::Version>,
+
+    /// Why the violation was accepted. Not used, except to document the exception.
+    #[allow(dead_code)]
+    pub(crate) reason: Option<String>,
+}
+
+impl SemverException {
+    /// Whether the exception no longer applies to the given version of the crate.
+    /// If the version is unknown, the exception is assumed to still apply.
+    pub(crate) fn is_expired(&self, current_version: Option<&semver::Version>) -> bool {
+        match (&self.expires, current_version) {
+            (Some(expires), Some(current)) => current >= expires,
+            _ => false,
+        }
+    }
+}
+
+impl SemverExceptions {
+    pub(crate) fn load(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read semver exceptions file {:?}", path))?;
+        Self::parse(&contents)
+            .with_context(|| format!("Failed to parse semver exceptions file {:?}", path))
+    }
+
+    pub(crate) fn parse(contents: &str) -> anyhow::Result<Self> {
+        Ok(toml::from_str(contents)?)
+    }
+
+    /// The exceptions that no longer apply to the given version of the crate.
+    pub(crate) fn expired<'a>(
+        &'a self,
+        current_version: Option<&'a semver::Version>,
+    ) -> impl Iterator<Item = &'a SemverException> {
+        self.exceptions
+            .iter()
+            .filter(move |exception| exception.is_expired(current_version))
+    }
+
+    /// Whether a result of the given check is covered by an exception
+    /// that applies to the given version of the crate.
+    pub(crate) fn is_suppressed(
+        &self,
+        check_id: &str,
+        result: &BTreeMap<Arc<str>, FieldValue>,
+        current_version: Option<&semver::Version>,
+    ) -> bool {
+        let path = match item_path(result) {
+            Some(path) => path,
+            None => return false,
+        };
+        self.exceptions.iter().any(|exception| {
+            exception.check == check_id
+                && exception.path == path
+                && !exception.is_expired(current_version)
+        })
+    }
+}
+
+/// The path of the item a query result is about, built from the query's `path` output,
+/// plus the `variant_name` or `field_name` output for enum variants and struct fields.
+pub(crate) fn item_path(result: &BTreeMap<Arc<str>, FieldValue>) -> Option<String> {
+    let mut components: Vec<&str> = match result.get("path")? {
+        FieldValue::List(components) => components
+            .iter()
+            .map(|component| match component {
+                FieldValue::String(component) => Some(component.as_str()),
+                _ => None,
+            })
+            .collect::<Option<_>>()?,
+        _ => return None,
+    };
+    for member in ["variant_name", "field_name"] {
+        if let Some(FieldValue::String(name)) = result.get(member) {
+            components.push(name);
+        }
+    }
+    Some(components.join("::"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use trustfall_core::ir::FieldValue;
+
+    use super::SemverExceptions;
+
+    fn result(path: &[&str], field_name: Option<&str>) -> BTreeMap<Arc<str>, FieldValue> {
+        let mut result = BTreeMap::new();
+        result.insert(
+            Arc::from("path"),
+            FieldValue::List(
+                path.iter()
+                    .map(|component| FieldValue::String(component.to_string()))
+                    .collect(),
+            ),
+        );
+        if let Some(field_name) = field_name {
+            result.insert(
+                Arc::from("field_name"),
+                FieldValue::String(field_name.to_string()),
+            );
+        }
+        result
+    }
+
+    #[test]
+    fn exceptions_match_check_path_and_version() {
+        let exceptions = SemverExceptions::parse(
+            r#"
+[[exception]]
+check = "struct_missing"
+path = "foo::Bar"
+expires = "2.0.0"
+reason = "Bar was never meant to be public."
+
+[[exception]]
+check = "struct_pub_field_missing"
+path = "foo::Baz::qux"
+"#,
+        )
+        .expect("failed to parse");
+
+        let v1 = semver::Version::new(1, 4, 0);
+        let v2 = semver::Version::new(2, 0, 0);
+        let bar = result(&["foo", "Bar"], None);
+        assert!(exceptions.is_suppressed("struct_missing", &bar, Some(&v1)));
+        assert!(exceptions.is_suppressed("struct_missing", &bar, None));
+        assert!(!exceptions.is_suppressed("struct_missing", &bar, Some(&v2)));
+        assert!(!exceptions.is_suppressed("enum_missing", &bar, Some(&v1)));
+        assert_eq!(1, exceptions.expired(Some(&v2)).count());
+
+        let qux = result(&["foo", "Baz"], Some("qux"));
+        assert!(exceptions.is_suppressed("struct_pub_field_missing", &qux, Some(&v2)));
+        let baz = result(&["foo", "Baz"], None);
+        assert!(!exceptions.is_suppressed("struct_pub_field_missing", &baz, Some(&v2)));
+    }
+
+    #[test]
+    fn unknown_fields_are_rejected() {
+        assert!(SemverExceptions::parse(
+            "[[exception]]\ncheck = \"a\"\npath = \"b\"\nuntil = \"1.0.0\"\n"
+        )
+        .is_err());
+        assert!(SemverExceptions::parse("")
+            .expect("failed to parse")
+            .exceptions
+            .is_empty());
+    }
+}
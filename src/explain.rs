@@ -0,0 +1,98 @@
+use std::collections::BTreeMap;
+
+use termcolor_output::{colored, colored_ln};
+
+use crate::{
+    query::{unknown_check_id, LintLevel, RequiredSemverUpdate, SemverQuery},
+    GlobalConfig,
+};
+
+/// Re-indent `text` by a fixed amount, regardless of its original indentation,
+/// so that multi-line sections stand out from their headings.
+fn indent(text: &str) -> String {
+    let text = text.trim_matches('\n').trim_end();
+    let common_indent = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or_default();
+    text.lines()
+        .map(|line| {
+            if line.trim().is_empty() {
+                String::new()
+            } else {
+                format!("    {}", &line[common_indent..])
+            }
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Print everything there is to know about the check with the given id.
+pub(super) fn explain(
+    mut config: GlobalConfig,
+    queries: &BTreeMap<String, SemverQuery>,
+    check_id: &str,
+) -> anyhow::Result<()> {
+    let query = queries
+        .get(check_id)
+        .ok_or_else(|| unknown_check_id(check_id, queries))?;
+
+    let required_bump = match query.required_update {
+        RequiredSemverUpdate::Major => "major",
+        RequiredSemverUpdate::Minor => "minor",
+    };
+    let lint_level_note = match query.lint_level {
+        LintLevel::Deny => "",
+        LintLevel::Warn => {
+            " Only reported as a warning, which doesn't fail the run, unless denied with --deny."
+        }
+    };
+    colored_ln(&mut config.output_writer, |w| {
+        colored!(
+            w,
+            "{}{}: {}{}\nRequires a new {} version.{}\n",
+            bold!(true),
+            query.id,
+            query.human_readable_name,
+            reset!(),
+            required_bump,
+            lint_level_note,
+        )
+    })
+    .expect("print failed");
+
+    let mut sections = vec![("Description", indent(&query.description))];
+    if let Some(reference_link) = query.reference_link.as_deref() {
+        sections.push(("Reference", indent(reference_link)));
+    }
+    if let Some(example) = query.example.as_deref() {
+        sections.push(("Example", indent(example)));
+    }
+    sections.push(("Query", indent(&query.query)));
+    sections.push(("Implementation", indent(&query.implementation_link())));
+
+    for (heading, contents) in sections {
+        colored_ln(&mut config.output_writer, |w| {
+            colored!(w, "{}{}:{}\n{}\n", bold!(true), heading, reset!(), contents)
+        })
+        .expect("print failed");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::indent;
+
+    #[test]
+    fn indentation_is_normalized() {
+        assert_eq!(
+            "    {\n        foo\n\n    }",
+            indent("\n        {\n            foo\n  \n        }\n    "),
+        );
+        assert_eq!("    one line", indent("one line"));
+    }
+}
@@ -0,0 +1,169 @@
+//! A Markdown snippet for the changelog, listing the API changes that the checks found
+//! by the affected item rather than by check.
+
+use std::{fmt::Write as _, io::Write};
+
+use trustfall_core::ir::TransparentValue;
+
+use crate::query::{LintLevel, RequiredSemverUpdate, SemverQuery};
+
+use super::{CheckReport, Violation};
+
+/// Whether the check looks for newly deprecated items.
+fn is_deprecation(query: &SemverQuery) -> bool {
+    query.id.contains("deprecated")
+}
+
+fn string<'a>(violation: &'a Violation, key: &str) -> Option<&'a str> {
+    match violation.data.get(key)? {
+        TransparentValue::String(value) => Some(value.as_str()),
+        _ => None,
+    }
+}
+
+fn write_entry(md: &mut String, query: &SemverQuery, violation: &Violation) -> std::fmt::Result {
+    match violation.item_path() {
+        Some(path) => write!(md, "- `{path}`: {}", query.human_readable_name)?,
+        None => write!(md, "- {}", violation.message)?,
+    }
+    if let Some(note) = string(violation, "deprecation_note") {
+        write!(md, ": {note}")?;
+    }
+    writeln!(md)
+}
+
+fn write_section(
+    md: &mut String,
+    heading: &str,
+    entries: &[(&SemverQuery, &Violation)],
+) -> std::fmt::Result {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    writeln!(md, "### {heading}\n")?;
+    for (query, violation) in entries {
+        write_entry(md, query, violation)?;
+    }
+    writeln!(md)
+}
+
+/// Write "Breaking changes", "Deprecations" and "Other changes" sections, leaving out
+/// those with nothing in them. Nothing is written if no changes were found.
+pub(super) fn write(report: &CheckReport, writer: &mut dyn Write) -> anyhow::Result<()> {
+    let mut breaking = vec![];
+    let mut deprecations = vec![];
+    let mut other = vec![];
+    for outcome in &report.outcomes {
+        let query = outcome.query;
+        let section = if is_deprecation(query) {
+            &mut deprecations
+        } else if query.lint_level == LintLevel::Deny
+            && query.required_update == RequiredSemverUpdate::Major
+        {
+            &mut breaking
+        } else {
+            &mut other
+        };
+        section.extend(
+            outcome
+                .violations
+                .iter()
+                .map(|violation| (query, violation)),
+        );
+    }
+
+    let mut md = String::new();
+    write_section(&mut md, "Breaking changes", &breaking)?;
+    write_section(&mut md, "Deprecations", &deprecations)?;
+    write_section(&mut md, "Other changes", &other)?;
+    writer.write_all(md.trim_end().as_bytes())?;
+    if !md.is_empty() {
+        writeln!(writer)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use trustfall_core::ir::TransparentValue;
+
+    use crate::{
+        formats::{CheckOutcome, CheckReport, Violation},
+        query::SemverQuery,
+    };
+
+    fn violation(path: &[&str], extra: &[(&str, &str)]) -> Violation {
+        let mut data = BTreeMap::new();
+        data.insert(
+            Arc::from("path"),
+            TransparentValue::List(
+                path.iter()
+                    .map(|component| TransparentValue::String(component.to_string()))
+                    .collect(),
+            ),
+        );
+        for (key, value) in extra {
+            data.insert(Arc::from(*key), TransparentValue::String(value.to_string()));
+        }
+        Violation {
+            message: "message".to_string(),
+            witness: None,
+            data,
+            targets: vec![],
+            baseline_docs_url: None,
+            impact: None,
+        }
+    }
+
+    #[test]
+    fn changelog_lists_changes_by_item() {
+        let queries = SemverQuery::all_queries();
+        let report = CheckReport {
+            baseline_version: Some("1.0.0"),
+            current_version: Some("1.1.0"),
+            outcomes: vec![
+                CheckOutcome {
+                    query: &queries["enum_variant_missing"],
+                    violations: vec![violation(&["demo", "Mode"], &[("variant_name", "Fast")])],
+                },
+                CheckOutcome {
+                    query: &queries["function_now_deprecated"],
+                    violations: vec![violation(
+                        &["demo", "parse"],
+                        &[("deprecation_note", "use `parse_checked` instead")],
+                    )],
+                },
+                CheckOutcome {
+                    query: &queries["struct_missing"],
+                    violations: vec![],
+                },
+            ],
+            skipped: vec![],
+            suppressed: 0,
+            environment: Default::default(),
+        };
+
+        let mut changelog = vec![];
+        super::write(&report, &mut changelog).unwrap();
+        assert_eq!(
+            "### Breaking changes\n\n\
+             - `demo::Mode::Fast`: pub enum variant removed or renamed\n\n\
+             ### Deprecations\n\n\
+             - `demo::parse`: pub fn #[deprecated] added: use `parse_checked` instead\n",
+            String::from_utf8(changelog).unwrap(),
+        );
+
+        let mut empty = vec![];
+        super::write(
+            &CheckReport {
+                outcomes: vec![],
+                ..report
+            },
+            &mut empty,
+        )
+        .unwrap();
+        assert!(empty.is_empty());
+    }
+}
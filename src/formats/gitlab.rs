@@ -0,0 +1,152 @@
+//! GitLab Code Quality reports, shown inline in merge requests when uploaded as the
+//! `codequality` report artifact of a job.
+
+use std::{collections::HashMap, io::Write};
+
+use serde_json::{json, Value};
+
+use crate::{
+    query::{LintLevel, RequiredSemverUpdate, SemverQuery},
+    util::fnv1a,
+};
+
+use super::{CheckReport, Violation};
+
+/// GitLab requires a location, so violations without a span are reported on the manifest,
+/// which is usually at this path relative to the repository root.
+const FALLBACK_PATH: &str = "Cargo.toml";
+
+/// Violations that require a major version are major, others are minor.
+/// Findings of checks at the `warn` lint level are only informational.
+fn severity(query: &SemverQuery) -> &'static str {
+    match (query.lint_level, query.required_update) {
+        (LintLevel::Warn, _) => "info",
+        (LintLevel::Deny, RequiredSemverUpdate::Major) => "major",
+        (LintLevel::Deny, RequiredSemverUpdate::Minor) => "minor",
+    }
+}
+
+/// A fingerprint of the violation that doesn't depend on where the item is,
+/// so that GitLab recognizes the same violation when code moves around.
+fn fingerprint(query: &SemverQuery, violation: &Violation) -> String {
+    let item = violation
+        .item_path()
+        .unwrap_or_else(|| violation.message.clone());
+    format!(
+        "{:016x}",
+        fnv1a(&format!(
+            "{}\n{}\n{}",
+            query.id,
+            item,
+            violation.targets.join(",")
+        ))
+    )
+}
+
+pub(super) fn write(report: &CheckReport, writer: &mut dyn Write) -> anyhow::Result<()> {
+    // Fingerprints must be unique within the report, so repeats are told apart by a counter.
+    let mut seen: HashMap<String, usize> = HashMap::new();
+    let issues: Vec<Value> = report
+        .outcomes
+        .iter()
+        .flat_map(|outcome| {
+            outcome
+                .violations
+                .iter()
+                .map(move |violation| (outcome.query, violation))
+        })
+        .map(|(query, violation)| {
+            let mut fingerprint = fingerprint(query, violation);
+            let repeats = seen.entry(fingerprint.clone()).or_default();
+            if *repeats > 0 {
+                fingerprint = format!("{fingerprint}-{repeats}");
+            }
+            *repeats += 1;
+
+            let (path, line) = violation.span().unwrap_or((FALLBACK_PATH, 1));
+            json!({
+                "type": "issue",
+                "check_name": query.id,
+                "description": format!("{}: {}", query.human_readable_name, violation.message),
+                "categories": ["Compatibility"],
+                "severity": severity(query),
+                "fingerprint": fingerprint,
+                "location": {
+                    "path": path,
+                    "lines": { "begin": line },
+                },
+            })
+        })
+        .collect();
+
+    serde_json::to_writer_pretty(&mut *writer, &issues)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use trustfall_core::ir::TransparentValue;
+
+    use crate::{
+        formats::{CheckOutcome, CheckReport, Violation},
+        query::SemverQuery,
+    };
+
+    fn violation(name: &str, line: u64) -> Violation {
+        let mut data = BTreeMap::new();
+        data.insert(
+            Arc::from("path"),
+            TransparentValue::List(vec![
+                TransparentValue::String("demo".to_string()),
+                TransparentValue::String(name.to_string()),
+            ]),
+        );
+        data.insert(
+            Arc::from("span_filename"),
+            TransparentValue::String("src/lib.rs".to_string()),
+        );
+        data.insert(Arc::from("span_begin_line"), TransparentValue::Uint64(line));
+        Violation {
+            message: format!("struct {name} removed"),
+            witness: None,
+            data,
+            targets: vec![],
+            baseline_docs_url: None,
+            impact: None,
+        }
+    }
+
+    fn issues(violations: Vec<Violation>) -> Vec<serde_json::Value> {
+        let queries = SemverQuery::all_queries();
+        let report = CheckReport {
+            baseline_version: None,
+            current_version: None,
+            outcomes: vec![CheckOutcome {
+                query: &queries["struct_missing"],
+                violations,
+            }],
+            skipped: vec![],
+            suppressed: 0,
+            environment: Default::default(),
+        };
+        let mut output = vec![];
+        super::write(&report, &mut output).unwrap();
+        serde_json::from_slice(&output).unwrap()
+    }
+
+    #[test]
+    fn fingerprints_are_unique_and_independent_of_lines() {
+        let before = issues(vec![violation("Foo", 3), violation("Bar", 8)]);
+        let after = issues(vec![violation("Foo", 10), violation("Foo", 20)]);
+
+        assert_eq!("major", before[0]["severity"]);
+        assert_eq!("src/lib.rs", before[0]["location"]["path"]);
+        assert_eq!(3, before[0]["location"]["lines"]["begin"]);
+        assert_eq!(before[0]["fingerprint"], after[0]["fingerprint"]);
+        assert_ne!(before[0]["fingerprint"], before[1]["fingerprint"]);
+        assert_ne!(after[0]["fingerprint"], after[1]["fingerprint"]);
+    }
+}
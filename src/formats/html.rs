@@ -0,0 +1,355 @@
+//! A self-contained HTML report, for archiving alongside other release artifacts.
+//! It has no external resources: the styles and the script that filters the table are inline.
+
+use std::{collections::BTreeSet, fmt::Write as _, io::Write, path::Path};
+
+use clap::crate_version;
+
+use crate::{
+    query::{LintLevel, RequiredSemverUpdate, SemverQuery},
+    signature_diff::{word_diff, Segment},
+};
+
+use super::{junit::escape, CheckReport, Violation};
+
+/// How many lines of source to show before and after the line of the affected item.
+const SNIPPET_CONTEXT_LINES: usize = 2;
+
+const STYLE: &str = "
+body { font-family: system-ui, sans-serif; margin: 2em; color: #1f2328; }
+table { border-collapse: collapse; width: 100%; }
+th, td { border: 1px solid #d0d7de; padding: 0.4em 0.6em; text-align: left; vertical-align: top; }
+th { background: #f6f8fa; }
+code, pre { font-family: ui-monospace, monospace; font-size: 0.9em; }
+pre { background: #f6f8fa; padding: 0.5em; overflow-x: auto; }
+dl { display: grid; grid-template-columns: max-content auto; gap: 0.2em 1em; }
+dt { font-weight: bold; }
+dd { margin: 0; }
+.filters { margin: 1em 0; display: flex; gap: 1em; flex-wrap: wrap; }
+.major { color: #cf222e; font-weight: bold; }
+.minor { color: #9a6700; font-weight: bold; }
+.warning { color: #0969da; }
+.highlight { background: #fff8c5; display: block; }
+.signatures { margin: 0.5em 0; width: auto; }
+del { background: #ffebe9; }
+ins { background: #dafbe1; text-decoration: none; }
+";
+
+/// Shows only the rows matching every selected filter.
+const SCRIPT: &str = "
+const filters = document.querySelectorAll('.filters select');
+function applyFilters() {
+  for (const row of document.querySelectorAll('#violations tbody tr')) {
+    let shown = true;
+    for (const filter of filters) {
+      if (filter.value && row.dataset[filter.name] !== filter.value) shown = false;
+    }
+    row.hidden = !shown;
+  }
+}
+for (const filter of filters) filter.addEventListener('change', applyFilters);
+";
+
+fn severity(query: &SemverQuery) -> &'static str {
+    match (query.lint_level, query.required_update) {
+        (LintLevel::Warn, _) => "warning",
+        (LintLevel::Deny, RequiredSemverUpdate::Major) => "major",
+        (LintLevel::Deny, RequiredSemverUpdate::Minor) => "minor",
+    }
+}
+
+/// The lines of source around the affected item, with the item's line highlighted,
+/// if the file is where its span says it is.
+fn snippet(violation: &Violation) -> Option<String> {
+    let (filename, line) = violation.span()?;
+    let line = usize::try_from(line).ok()?.checked_sub(1)?;
+    let contents = std::fs::read_to_string(Path::new(filename)).ok()?;
+    let lines: Vec<&str> = contents.lines().collect();
+    if line >= lines.len() {
+        return None;
+    }
+    let first = line.saturating_sub(SNIPPET_CONTEXT_LINES);
+    let last = (line + SNIPPET_CONTEXT_LINES).min(lines.len() - 1);
+    let mut html = String::new();
+    for (number, text) in lines.iter().enumerate().take(last + 1).skip(first) {
+        let text = format!("{:>5} | {}", number + 1, escape(text));
+        if number == line {
+            html.push_str(&format!(r#"<span class="highlight">{text}</span>"#));
+        } else {
+            html.push_str(&text);
+            html.push('\n');
+        }
+    }
+    Some(html)
+}
+
+/// The baseline and current declarations side by side, with the words that differ marked.
+fn write_signature_diff(html: &mut String, baseline: &str, current: &str) -> std::fmt::Result {
+    let (baseline, current) = word_diff(baseline, current);
+    let render = |segments: &[Segment], tag: &str| -> String {
+        segments
+            .iter()
+            .map(|segment| match segment.changed {
+                true => format!("<{tag}>{}</{tag}>", escape(segment.text)),
+                false => escape(segment.text),
+            })
+            .collect()
+    };
+    write!(
+        html,
+        r#"<table class="signatures"><tr><th>Baseline</th><th>Current</th></tr><tr><td><code>{}</code></td><td><code>{}</code></td></tr></table>"#,
+        render(&baseline, "del"),
+        render(&current, "ins"),
+    )
+}
+
+fn write_filter(
+    html: &mut String,
+    name: &str,
+    label: &str,
+    values: &BTreeSet<&str>,
+) -> std::fmt::Result {
+    writeln!(
+        html,
+        r#"<label>{label} <select name="{name}"><option value="">all</option>"#
+    )?;
+    for value in values {
+        let value = escape(value);
+        writeln!(html, r#"<option value="{value}">{value}</option>"#)?;
+    }
+    writeln!(html, "</select></label>")
+}
+
+fn write_metadata(html: &mut String, report: &CheckReport) -> std::fmt::Result {
+    let failed = report.failures().count();
+    let warned = report.warnings().count();
+    let required_update = match report.required_update() {
+        Some(RequiredSemverUpdate::Major) => "major",
+        Some(RequiredSemverUpdate::Minor) => "minor",
+        None => "none",
+    };
+    writeln!(html, "<dl>")?;
+    for (term, description) in [
+        (
+            "Baseline version",
+            report.baseline_version.unwrap_or("unknown").to_string(),
+        ),
+        (
+            "Current version",
+            report.current_version.unwrap_or("unknown").to_string(),
+        ),
+        ("Required update", required_update.to_string()),
+        (
+            "Checks",
+            format!(
+                "{} run: {} passed, {} failed, {} with warnings, {} skipped",
+                report.outcomes.len(),
+                report.outcomes.len() - failed - warned,
+                failed,
+                warned,
+                report.skipped.len(),
+            ),
+        ),
+        ("Suppressed results", report.suppressed.to_string()),
+    ]
+    .into_iter()
+    .chain(report.environment.describe())
+    .chain([(
+        "Generated by",
+        format!("cargo-semver-checks {}", crate_version!()),
+    )]) {
+        writeln!(html, "<dt>{term}</dt><dd>{}</dd>", escape(&description))?;
+    }
+    writeln!(html, "</dl>")
+}
+
+fn write_violations(html: &mut String, report: &CheckReport) -> std::fmt::Result {
+    let rows: Vec<_> = report
+        .outcomes
+        .iter()
+        .flat_map(|outcome| {
+            outcome
+                .violations
+                .iter()
+                .map(move |violation| (outcome.query, violation, violation.module()))
+        })
+        .collect();
+    if rows.is_empty() {
+        return writeln!(html, "<p>No semver violations found.</p>");
+    }
+
+    writeln!(html, r#"<div class="filters">"#)?;
+    let checks = rows.iter().map(|(query, _, _)| query.id.as_str()).collect();
+    write_filter(html, "check", "Check", &checks)?;
+    let severities = rows.iter().map(|(query, _, _)| severity(query)).collect();
+    write_filter(html, "severity", "Severity", &severities)?;
+    let modules = rows
+        .iter()
+        .filter_map(|(_, _, module)| module.as_deref())
+        .collect();
+    write_filter(html, "module", "Module", &modules)?;
+    writeln!(html, "</div>")?;
+
+    writeln!(html, r#"<table id="violations">"#)?;
+    writeln!(
+        html,
+        "<thead><tr><th>Severity</th><th>Check</th><th>Module</th><th>Violation</th></tr></thead>"
+    )?;
+    writeln!(html, "<tbody>")?;
+    for (query, violation, module) in &rows {
+        let severity = severity(query);
+        let module = escape(module.as_deref().unwrap_or_default());
+        writeln!(
+            html,
+            r#"<tr data-check="{id}" data-severity="{severity}" data-module="{module}">"#,
+            id = escape(&query.id),
+        )?;
+        writeln!(html, r#"<td class="{severity}">{severity}</td>"#)?;
+        match query.reference_link.as_deref() {
+            Some(link) => writeln!(
+                html,
+                r#"<td><a href="{}" title="{}"><code>{}</code></a></td>"#,
+                escape(link),
+                escape(&query.human_readable_name),
+                escape(&query.id),
+            )?,
+            None => writeln!(
+                html,
+                r#"<td><code title="{}">{}</code></td>"#,
+                escape(&query.human_readable_name),
+                escape(&query.id),
+            )?,
+        }
+        writeln!(html, "<td><code>{module}</code></td>")?;
+        write!(html, "<td>{}", escape(&violation.message))?;
+        if !violation.targets.is_empty() {
+            write!(
+                html,
+                " (targets: {})",
+                escape(&violation.targets.join(", "))
+            )?;
+        }
+        if let Some(url) = &violation.baseline_docs_url {
+            write!(html, r#" <a href="{}">baseline docs</a>"#, escape(url))?;
+        }
+        if let Some(impact @ 1..) = violation.impact {
+            write!(html, " (used by {impact} other public items)")?;
+        }
+        if let Some((baseline, current)) = violation.signatures() {
+            write_signature_diff(html, baseline, current)?;
+        }
+        if let Some(snippet) = snippet(violation) {
+            write!(
+                html,
+                "<details><summary>Source</summary><pre>{snippet}</pre></details>"
+            )?;
+        }
+        if let Some(witness) = &violation.witness {
+            write!(
+                html,
+                "<details><summary>Compiles against the baseline, but not the current version</summary><pre>{}</pre></details>",
+                escape(witness.trim()),
+            )?;
+        }
+        writeln!(html, "</td>\n</tr>")?;
+    }
+    writeln!(html, "</tbody>\n</table>")
+}
+
+pub(super) fn write(report: &CheckReport, writer: &mut dyn Write) -> anyhow::Result<()> {
+    let title = format!(
+        "cargo-semver-checks: {} → {}",
+        report.baseline_version.unwrap_or("unknown"),
+        report.current_version.unwrap_or("unknown"),
+    );
+
+    let mut html = String::new();
+    writeln!(html, "<!DOCTYPE html>")?;
+    writeln!(html, r#"<html lang="en">"#)?;
+    writeln!(html, "<head>")?;
+    writeln!(html, r#"<meta charset="utf-8">"#)?;
+    writeln!(html, "<title>{}</title>", escape(&title))?;
+    writeln!(html, "<style>{STYLE}</style>")?;
+    writeln!(html, "</head>")?;
+    writeln!(html, "<body>")?;
+    writeln!(html, "<h1>{}</h1>", escape(&title))?;
+    write_metadata(&mut html, report)?;
+    writeln!(html, "<h2>Violations</h2>")?;
+    write_violations(&mut html, report)?;
+    if !report.skipped.is_empty() {
+        writeln!(html, "<h2>Skipped checks</h2>")?;
+        writeln!(
+            html,
+            "<p>The version change allows the changes these checks look for:</p>\n<ul>"
+        )?;
+        for query in &report.skipped {
+            writeln!(html, "<li><code>{}</code></li>", escape(&query.id))?;
+        }
+        writeln!(html, "</ul>")?;
+    }
+    writeln!(html, "<script>{SCRIPT}</script>")?;
+    writeln!(html, "</body>")?;
+    writeln!(html, "</html>")?;
+
+    writer.write_all(html.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use trustfall_core::ir::TransparentValue;
+
+    use crate::{
+        formats::{CheckOutcome, CheckReport, RunEnvironment, Violation},
+        query::SemverQuery,
+    };
+
+    #[test]
+    fn violations_are_escaped_and_filterable_by_module() {
+        let mut data = BTreeMap::new();
+        data.insert(
+            Arc::from("path"),
+            TransparentValue::List(
+                ["demo", "config", "Foo"]
+                    .iter()
+                    .map(|component| TransparentValue::String(component.to_string()))
+                    .collect(),
+            ),
+        );
+        let queries = SemverQuery::all_queries();
+        let report = CheckReport {
+            baseline_version: Some("1.0.0"),
+            current_version: Some("1.1.0"),
+            outcomes: vec![CheckOutcome {
+                query: &queries["struct_missing"],
+                violations: vec![Violation {
+                    message: "struct Foo<'a> removed".to_string(),
+                    witness: None,
+                    data,
+                    targets: vec![],
+                    baseline_docs_url: None,
+                    impact: None,
+                }],
+            }],
+            skipped: vec![],
+            suppressed: 0,
+            environment: RunEnvironment {
+                features: vec!["default".to_string(), "std".to_string()],
+                ..Default::default()
+            },
+        };
+        let mut output = vec![];
+        super::write(&report, &mut output).unwrap();
+        let html = String::from_utf8(output).unwrap();
+
+        assert!(html.contains("struct Foo&lt;&apos;a&gt; removed"), "{html}");
+        assert!(html.contains(
+            r#"<tr data-check="struct_missing" data-severity="major" data-module="demo::config">"#
+        ));
+        assert!(html.contains(r#"<option value="demo::config">demo::config</option>"#));
+        assert!(html.contains("<dt>Required update</dt><dd>major</dd>"));
+        assert!(html.contains("<dt>Features</dt><dd>default, std</dd>"));
+    }
+}
@@ -0,0 +1,167 @@
+use std::{fmt::Write as _, io::Write};
+
+use crate::query::RequiredSemverUpdate;
+
+use super::{CheckOutcome, CheckReport};
+
+/// Escape text for use in XML and HTML attribute values and element contents.
+pub(super) fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn write_violations(xml: &mut String, outcome: &CheckOutcome) -> std::fmt::Result {
+    for violation in &outcome.violations {
+        let targets_note = if violation.targets.is_empty() {
+            String::new()
+        } else {
+            format!(" (targets: {})", violation.targets.join(", "))
+        };
+        writeln!(
+            xml,
+            "{}{}",
+            escape(&violation.message),
+            escape(&targets_note)
+        )?;
+    }
+    Ok(())
+}
+
+/// What the rustdoc JSON was generated from, as properties of the test suite.
+fn write_properties(xml: &mut String, report: &CheckReport) -> std::fmt::Result {
+    let environment = &report.environment;
+    writeln!(xml, "    <properties>")?;
+    for (name, value) in [
+        ("features", environment.features.join(",")),
+        ("targets", environment.targets.join(",")),
+        (
+            "toolchain",
+            environment.toolchain.clone().unwrap_or_default(),
+        ),
+        (
+            "baseline_format_version",
+            environment.baseline_format_version.to_string(),
+        ),
+        (
+            "current_format_version",
+            environment.current_format_version.to_string(),
+        ),
+    ] {
+        writeln!(
+            xml,
+            r#"      <property name="{name}" value="{}"/>"#,
+            escape(&value)
+        )?;
+    }
+    writeln!(xml, "    </properties>")
+}
+
+pub(super) fn write(report: &CheckReport, writer: &mut dyn Write) -> anyhow::Result<()> {
+    let failures = report.failures().count();
+    let tests = report.outcomes.len() + report.skipped.len();
+
+    let mut xml = String::new();
+    writeln!(xml, r#"<?xml version="1.0" encoding="UTF-8"?>"#)?;
+    writeln!(
+        xml,
+        r#"<testsuites name="cargo-semver-checks" tests="{tests}" failures="{failures}">"#
+    )?;
+    writeln!(
+        xml,
+        r#"  <testsuite name="semver-checks ({} -> {})" tests="{tests}" failures="{failures}" skipped="{}">"#,
+        escape(report.baseline_version.unwrap_or("unknown")),
+        escape(report.current_version.unwrap_or("unknown")),
+        report.skipped.len(),
+    )?;
+    write_properties(&mut xml, report)?;
+
+    for outcome in &report.outcomes {
+        let query = outcome.query;
+        if outcome.passed() {
+            writeln!(
+                xml,
+                r#"    <testcase classname="semver-checks" name="{}"/>"#,
+                escape(&query.id)
+            )?;
+            continue;
+        }
+
+        // Warnings don't fail the run, so their test cases pass, with the findings as output.
+        if query.lint_level.is_warning() {
+            writeln!(
+                xml,
+                r#"    <testcase classname="semver-checks" name="{}">"#,
+                escape(&query.id)
+            )?;
+            writeln!(xml, "      <system-out>")?;
+            writeln!(xml, "warning: {}", escape(&query.error_message))?;
+            write_violations(&mut xml, outcome)?;
+            writeln!(xml, "      </system-out>")?;
+            writeln!(xml, "    </testcase>")?;
+            continue;
+        }
+
+        let failure_type = match query.required_update {
+            RequiredSemverUpdate::Major => "major",
+            RequiredSemverUpdate::Minor => "minor",
+        };
+        writeln!(
+            xml,
+            r#"    <testcase classname="semver-checks" name="{}">"#,
+            escape(&query.id)
+        )?;
+        writeln!(
+            xml,
+            r#"      <failure message="{}" type="{failure_type}">"#,
+            escape(&query.error_message),
+        )?;
+        write_violations(&mut xml, outcome)?;
+        if let Some(reference_link) = query.reference_link.as_deref() {
+            writeln!(xml, "ref: {}", escape(reference_link))?;
+        }
+        writeln!(xml, "      </failure>")?;
+        writeln!(xml, "    </testcase>")?;
+    }
+
+    for query in &report.skipped {
+        writeln!(
+            xml,
+            r#"    <testcase classname="semver-checks" name="{}">"#,
+            escape(&query.id)
+        )?;
+        writeln!(
+            xml,
+            r#"      <skipped message="the version change allows the changes this check looks for"/>"#
+        )?;
+        writeln!(xml, "    </testcase>")?;
+    }
+
+    writeln!(xml, "  </testsuite>")?;
+    writeln!(xml, "</testsuites>")?;
+
+    writer.write_all(xml.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::escape;
+
+    #[test]
+    fn special_characters_are_escaped() {
+        assert_eq!(
+            "struct Foo&lt;&apos;a&gt; &amp; &quot;bar&quot;",
+            escape(r#"struct Foo<'a> & "bar""#)
+        );
+    }
+}
@@ -0,0 +1,135 @@
+use std::{fmt::Write as _, io::Write};
+
+use crate::query::RequiredSemverUpdate;
+
+use super::{CheckOutcome, CheckReport};
+
+/// Escape text so it's displayed literally inside inline code.
+fn inline_code(text: &str) -> String {
+    if text.contains('`') {
+        format!("`` {text} ``")
+    } else {
+        format!("`{text}`")
+    }
+}
+
+fn write_failures(md: &mut String, heading: &str, failures: &[&CheckOutcome]) -> std::fmt::Result {
+    if failures.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(md, "### {heading}\n")?;
+    for outcome in failures {
+        let query = outcome.query;
+        let count = outcome.violations.len();
+        writeln!(md, "<details>")?;
+        writeln!(
+            md,
+            "<summary><b>{}</b>: {} ({} {})</summary>\n",
+            query.id,
+            query.human_readable_name,
+            count,
+            if count == 1 { "item" } else { "items" },
+        )?;
+        write!(md, "{}", query.error_message)?;
+        if let Some(reference_link) = query.reference_link.as_deref() {
+            write!(md, " [Reference]({reference_link})")?;
+        }
+        writeln!(md, "\n")?;
+        for violation in &outcome.violations {
+            write!(md, "- {}", inline_code(&violation.message))?;
+            if !violation.targets.is_empty() {
+                write!(md, " (targets: {})", violation.targets.join(", "))?;
+            }
+            if let Some(url) = &violation.baseline_docs_url {
+                write!(md, " [baseline docs]({url})")?;
+            }
+            if let Some(impact @ 1..) = violation.impact {
+                write!(md, " (used by {impact} other public items)")?;
+            }
+            writeln!(md)?;
+            if let Some((baseline, current)) = violation.signatures() {
+                writeln!(md, "  ```diff\n  - {baseline}\n  + {current}\n  ```")?;
+            }
+        }
+        writeln!(md, "\n</details>\n")?;
+    }
+
+    Ok(())
+}
+
+pub(super) fn write(report: &CheckReport, writer: &mut dyn Write) -> anyhow::Result<()> {
+    let mut md = String::new();
+
+    writeln!(md, "## cargo-semver-checks\n")?;
+    let version_change = format!(
+        "{} → {}",
+        report.baseline_version.unwrap_or("unknown"),
+        report.current_version.unwrap_or("unknown"),
+    );
+    match report.required_update() {
+        Some(required_update) => {
+            let bump = match required_update {
+                RequiredSemverUpdate::Major => "major",
+                RequiredSemverUpdate::Minor => "minor",
+            };
+            writeln!(
+                md,
+                "❌ **Semver violations found**: version {version_change} requires a new **{bump}** version.\n"
+            )?;
+        }
+        None => {
+            writeln!(
+                md,
+                "✅ **No semver violations found** for version {version_change}.\n"
+            )?;
+        }
+    }
+
+    let failed = report.failures().count();
+    let warnings: Vec<_> = report.warnings().collect();
+    writeln!(
+        md,
+        "{} checks run: {} passed, {} failed, {} with warnings, {} skipped.",
+        report.outcomes.len(),
+        report.outcomes.len() - failed - warnings.len(),
+        failed,
+        warnings.len(),
+        report.skipped.len(),
+    )?;
+    if report.suppressed > 0 {
+        writeln!(
+            md,
+            "{} results were suppressed by semver exceptions.",
+            report.suppressed
+        )?;
+    }
+    writeln!(md)?;
+
+    let (major, minor): (Vec<_>, Vec<_>) = report
+        .failures()
+        .partition(|outcome| outcome.query.required_update == RequiredSemverUpdate::Major);
+    write_failures(&mut md, "Major", &major)?;
+    write_failures(&mut md, "Minor", &minor)?;
+    write_failures(&mut md, "Warnings", &warnings)?;
+
+    writeln!(md, "<details>\n<summary>Environment</summary>\n")?;
+    for (label, value) in report.environment.describe() {
+        writeln!(md, "- {label}: {}", inline_code(&value))?;
+    }
+    writeln!(md, "\n</details>")?;
+
+    writer.write_all(md.as_bytes())?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::inline_code;
+
+    #[test]
+    fn backticks_are_preserved_in_inline_code() {
+        assert_eq!("`struct Foo`", inline_code("struct Foo"));
+        assert_eq!("`` fn `r#try` ``", inline_code("fn `r#try`"));
+    }
+}
@@ -0,0 +1,314 @@
+//! Machine-readable reports of the outcome of a `check-release` run,
+//! for consumption by CI systems and other tools.
+
+mod changelog;
+mod gitlab;
+mod html;
+mod junit;
+mod markdown;
+mod sarif;
+
+use std::{collections::BTreeMap, io::Write, sync::Arc};
+
+use trustfall_core::ir::TransparentValue;
+
+use crate::query::{RequiredSemverUpdate, SemverQuery};
+
+/// The format in which to report the outcome of the checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum OutputFormat {
+    /// Colored, human-readable progress and results. This is always printed,
+    /// and other formats are written in addition to it.
+    #[default]
+    Human,
+
+    /// SARIF 2.1.0, for code scanning integrations.
+    Sarif,
+
+    /// JUnit XML, where each check is a test case.
+    Junit,
+
+    /// A compact Markdown summary, suitable for posting as a pull request comment.
+    Markdown,
+
+    /// A GitLab Code Quality report, shown inline in merge requests.
+    Gitlab,
+
+    /// A self-contained HTML page with filterable tables, for archiving.
+    Html,
+}
+
+impl OutputFormat {
+    /// The names of the formats, as accepted by `--output-format`.
+    pub(crate) const NAMES: [&'static str; 6] =
+        ["human", "sarif", "junit", "markdown", "gitlab", "html"];
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "human" => Some(Self::Human),
+            "sarif" => Some(Self::Sarif),
+            "junit" => Some(Self::Junit),
+            "markdown" => Some(Self::Markdown),
+            "gitlab" => Some(Self::Gitlab),
+            "html" => Some(Self::Html),
+            _ => None,
+        }
+    }
+}
+
+/// A document to write from the findings with `--emit`, in addition to the report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Emit {
+    /// Markdown sections of breaking changes, deprecations and other changes, for CHANGELOG.md.
+    Changelog,
+}
+
+impl Emit {
+    /// The names of the documents, as accepted by `--emit`.
+    pub(crate) const NAMES: [&'static str; 1] = ["changelog"];
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "changelog" => Some(Self::Changelog),
+            _ => None,
+        }
+    }
+}
+
+/// A single instance of a semver violation found by a check.
+#[derive(Debug, Clone)]
+pub(crate) struct Violation {
+    /// Human-readable description of this particular violation.
+    pub(crate) message: String,
+
+    /// Rust code that compiles against the baseline version of the crate,
+    /// but not against the current one, if the check can produce it.
+    pub(crate) witness: Option<String>,
+
+    /// The raw query outputs describing the violation.
+    pub(crate) data: BTreeMap<Arc<str>, TransparentValue>,
+
+    /// The targets on which the violation was found, or when checking against several
+    /// baselines, the baseline versions (and targets) it regresses against.
+    /// Empty if neither multiple baselines nor any target was specified.
+    pub(crate) targets: Vec<String>,
+
+    /// The docs.rs page of the affected item in the baseline, if the baseline was published.
+    pub(crate) baseline_docs_url: Option<String>,
+
+    /// How many other public items refer to the affected item, if it's known which item that is.
+    pub(crate) impact: Option<usize>,
+}
+
+impl Violation {
+    /// The file and line of the item responsible for the violation,
+    /// if the query reported them.
+    pub(crate) fn span(&self) -> Option<(&str, u64)> {
+        let filename = match self.data.get("span_filename")? {
+            TransparentValue::String(filename) => filename.as_str(),
+            _ => return None,
+        };
+        let line = match self.data.get("span_begin_line")? {
+            TransparentValue::Uint64(line) => *line,
+            TransparentValue::Int64(line) => u64::try_from(*line).ok()?,
+            _ => return None,
+        };
+        Some((filename, line))
+    }
+
+    /// The module of the item responsible for the violation, like `my_crate::config`,
+    /// if the query reported the item's `path`.
+    pub(crate) fn module(&self) -> Option<String> {
+        match self.data.get("path")? {
+            TransparentValue::List(path) if !path.is_empty() => {
+                let components = path[..path.len() - 1]
+                    .iter()
+                    .map(|component| match component {
+                        TransparentValue::String(component) => Some(component.as_str()),
+                        _ => None,
+                    })
+                    .collect::<Option<Vec<_>>>()?;
+                Some(components.join("::"))
+            }
+            _ => None,
+        }
+    }
+
+    /// The path of the affected item, including the variant or field the violation is about,
+    /// like `my_crate::Config::verbose`, if the query reported the item's `path`.
+    pub(crate) fn item_path(&self) -> Option<String> {
+        let mut path = match self.data.get("path")? {
+            TransparentValue::List(path) => path
+                .iter()
+                .map(|component| match component {
+                    TransparentValue::String(component) => Some(component.as_str()),
+                    _ => None,
+                })
+                .collect::<Option<Vec<_>>>()?
+                .join("::"),
+            _ => return None,
+        };
+        let member =
+            ["variant_name", "field_name"]
+                .into_iter()
+                .find_map(|key| match self.data.get(key) {
+                    Some(TransparentValue::String(member)) => Some(member),
+                    _ => None,
+                });
+        if let Some(member) = member {
+            path = format!("{path}::{member}");
+        }
+        Some(path)
+    }
+
+    /// The baseline and current declarations of the affected item,
+    /// if the check reported them as `baseline_signature` and `current_signature`.
+    pub(crate) fn signatures(&self) -> Option<(&str, &str)> {
+        match (
+            self.data.get("baseline_signature")?,
+            self.data.get("current_signature")?,
+        ) {
+            (TransparentValue::String(baseline), TransparentValue::String(current)) => {
+                Some((baseline.as_str(), current.as_str()))
+            }
+            _ => None,
+        }
+    }
+
+    /// The file and line of the item responsible for the violation, as `file:line`.
+    pub(crate) fn location(&self) -> Option<String> {
+        self.span()
+            .map(|(filename, line)| format!("{filename}:{line}"))
+    }
+}
+
+/// The outcome of running one check: passed if no violations were found.
+#[derive(Debug, Clone)]
+pub(crate) struct CheckOutcome<'a> {
+    pub(crate) query: &'a SemverQuery,
+    pub(crate) violations: Vec<Violation>,
+}
+
+impl<'a> CheckOutcome<'a> {
+    pub(crate) fn passed(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// The outcome of all the checks in a `check-release` run.
+#[derive(Debug, Clone)]
+pub(crate) struct CheckReport<'a> {
+    pub(crate) baseline_version: Option<&'a str>,
+    pub(crate) current_version: Option<&'a str>,
+
+    /// The outcomes of the checks that were run, ordered by check id.
+    pub(crate) outcomes: Vec<CheckOutcome<'a>>,
+
+    /// The checks that were skipped because the version change already allows
+    /// the kinds of changes they look for.
+    pub(crate) skipped: Vec<&'a SemverQuery>,
+
+    /// The number of violations that were not reported, because they were accepted
+    /// in the semver exceptions file.
+    pub(crate) suppressed: usize,
+
+    pub(crate) environment: RunEnvironment,
+}
+
+/// What the compared rustdoc JSON was generated from, so that results are reproducible
+/// and can be compared across runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct RunEnvironment {
+    /// The enabled features of the crate, including default features and the features
+    /// they enable. Only known if rustdoc JSON was generated for a local package.
+    pub(crate) features: Vec<String>,
+
+    /// The target triples the crates were compared on: the requested ones, or the host's.
+    /// Empty if rustdoc JSON was only loaded from files, for an unknown target.
+    pub(crate) targets: Vec<String>,
+
+    /// The version of the toolchain that generated rustdoc JSON, if any was generated.
+    pub(crate) toolchain: Option<String>,
+
+    /// The rustdoc JSON format versions of the baseline and current crates.
+    pub(crate) baseline_format_version: u32,
+    pub(crate) current_format_version: u32,
+}
+
+impl RunEnvironment {
+    /// The parts of the environment that are known, labeled for display.
+    pub(crate) fn describe(&self) -> Vec<(&'static str, String)> {
+        let mut description = vec![];
+        if !self.features.is_empty() {
+            description.push(("Features", self.features.join(", ")));
+        }
+        if !self.targets.is_empty() {
+            description.push(("Targets", self.targets.join(", ")));
+        }
+        if let Some(toolchain) = &self.toolchain {
+            description.push(("Toolchain", toolchain.clone()));
+        }
+        description.push((
+            "Rustdoc format versions",
+            format!(
+                "v{} -> v{}",
+                self.baseline_format_version, self.current_format_version
+            ),
+        ));
+        description
+    }
+}
+
+impl<'a> CheckReport<'a> {
+    /// The checks that found violations which fail the run.
+    pub(crate) fn failures(&self) -> impl Iterator<Item = &CheckOutcome<'a>> {
+        self.outcomes
+            .iter()
+            .filter(|outcome| !outcome.passed() && !outcome.query.lint_level.is_warning())
+    }
+
+    /// The checks at the `warn` lint level that found something. They don't fail the run.
+    pub(crate) fn warnings(&self) -> impl Iterator<Item = &CheckOutcome<'a>> {
+        self.outcomes
+            .iter()
+            .filter(|outcome| !outcome.passed() && outcome.query.lint_level.is_warning())
+    }
+
+    /// The smallest version update that allows all the violations that were found,
+    /// or `None` if no violations were found.
+    pub(crate) fn required_update(&self) -> Option<RequiredSemverUpdate> {
+        self.failures()
+            .map(|outcome| outcome.query.required_update)
+            .max_by_key(|update| match update {
+                RequiredSemverUpdate::Major => 1,
+                RequiredSemverUpdate::Minor => 0,
+            })
+    }
+}
+
+/// Write the report in the given machine-readable format.
+pub(crate) fn write_report(
+    format: OutputFormat,
+    report: &CheckReport,
+    writer: &mut dyn Write,
+) -> anyhow::Result<()> {
+    match format {
+        OutputFormat::Human => unreachable!("human-readable output is printed while checking"),
+        OutputFormat::Sarif => sarif::write(report, writer),
+        OutputFormat::Junit => junit::write(report, writer),
+        OutputFormat::Markdown => markdown::write(report, writer),
+        OutputFormat::Gitlab => gitlab::write(report, writer),
+        OutputFormat::Html => html::write(report, writer),
+    }
+}
+
+/// Write the document selected with `--emit`.
+pub(crate) fn write_emitted(
+    emit: Emit,
+    report: &CheckReport,
+    writer: &mut dyn Write,
+) -> anyhow::Result<()> {
+    match emit {
+        Emit::Changelog => changelog::write(report, writer),
+    }
+}
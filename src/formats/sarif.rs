@@ -0,0 +1,110 @@
+use std::io::Write;
+
+use clap::crate_version;
+use serde_json::{json, Value};
+
+use crate::query::{LintLevel, RequiredSemverUpdate, SemverQuery};
+
+use super::CheckReport;
+
+const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Violations that require a major version are errors, others are warnings.
+/// Findings of checks at the `warn` lint level are only notes, since they never fail the run.
+fn level(query: &SemverQuery) -> &'static str {
+    match (query.lint_level, query.required_update) {
+        (LintLevel::Warn, _) => "note",
+        (LintLevel::Deny, RequiredSemverUpdate::Major) => "error",
+        (LintLevel::Deny, RequiredSemverUpdate::Minor) => "warning",
+    }
+}
+
+pub(super) fn write(report: &CheckReport, writer: &mut dyn Write) -> anyhow::Result<()> {
+    let rules: Vec<Value> = report
+        .outcomes
+        .iter()
+        .map(|outcome| {
+            let query = outcome.query;
+            let mut rule = json!({
+                "id": query.id,
+                "name": query.human_readable_name,
+                "shortDescription": { "text": query.human_readable_name },
+                "fullDescription": { "text": query.description },
+                "help": { "text": query.error_message },
+                "defaultConfiguration": { "level": level(query) },
+            });
+            if let Some(reference_link) = query.reference_link.as_deref() {
+                rule["helpUri"] = json!(reference_link);
+            }
+            rule
+        })
+        .collect();
+
+    let results: Vec<Value> = report
+        .outcomes
+        .iter()
+        .enumerate()
+        .flat_map(|(rule_index, outcome)| {
+            outcome.violations.iter().map(move |violation| {
+                let mut result = json!({
+                    "ruleId": outcome.query.id,
+                    "ruleIndex": rule_index,
+                    "level": level(outcome.query),
+                    "message": { "text": violation.message },
+                });
+                if let Some((filename, line)) = violation.span() {
+                    result["locations"] = json!([{
+                        "physicalLocation": {
+                            "artifactLocation": { "uri": filename },
+                            "region": { "startLine": line },
+                        },
+                    }]);
+                }
+                if !violation.targets.is_empty() {
+                    result["properties"]["targets"] = json!(violation.targets);
+                }
+                if let Some(impact) = violation.impact {
+                    result["properties"]["impact"] = json!(impact);
+                }
+                result
+            })
+        })
+        .collect();
+
+    let sarif = json!({
+        "$schema": SARIF_SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "cargo-semver-checks",
+                    "version": crate_version!(),
+                    "informationUri": "https://github.com/obi1kenobi/cargo-semver-check",
+                    "rules": rules,
+                },
+            },
+            "results": results,
+            "properties": {
+                "baselineVersion": report.baseline_version,
+                "currentVersion": report.current_version,
+                "requiredUpdate": report.required_update().map(|update| match update {
+                    RequiredSemverUpdate::Major => "major",
+                    RequiredSemverUpdate::Minor => "minor",
+                }),
+                "skippedChecks": report.skipped.iter().map(|query| &query.id).collect::<Vec<_>>(),
+                "suppressedResults": report.suppressed,
+                "features": report.environment.features,
+                "targets": report.environment.targets,
+                "toolchain": report.environment.toolchain,
+                "rustdocFormatVersions": {
+                    "baseline": report.environment.baseline_format_version,
+                    "current": report.environment.current_format_version,
+                },
+            },
+        }],
+    });
+
+    serde_json::to_writer_pretty(&mut *writer, &sarif)?;
+    writeln!(writer)?;
+    Ok(())
+}
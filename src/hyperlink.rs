@@ -0,0 +1,90 @@
+//! Clickable links in terminal output, with the OSC 8 escape sequence.
+//! Terminals that don't support it would print it as garbage, so it's only used
+//! on terminals known to support it, unless `FORCE_HYPERLINK` says otherwise.
+
+use std::path::Path;
+
+/// Set to `1` to always print hyperlinks, or to `0` to never print them.
+const FORCE_HYPERLINK_ENV_VAR: &str = "FORCE_HYPERLINK";
+
+/// Whether to print hyperlinks to a terminal, judging by the environment variables
+/// that terminals which support them set.
+pub(crate) fn supported(printing_to_terminal: bool) -> bool {
+    supported_with_env(printing_to_terminal, |name| std::env::var(name).ok())
+}
+
+fn supported_with_env(printing_to_terminal: bool, env: impl Fn(&str) -> Option<String>) -> bool {
+    if let Some(force) = env(FORCE_HYPERLINK_ENV_VAR) {
+        return !force.is_empty() && force != "0";
+    }
+    if !printing_to_terminal {
+        return false;
+    }
+    if ["DOMTERM", "WT_SESSION", "KONSOLE_VERSION"]
+        .into_iter()
+        .any(|name| env(name).is_some())
+    {
+        return true;
+    }
+    if let Some(program) = env("TERM_PROGRAM") {
+        if ["iTerm.app", "WezTerm", "vscode", "ghostty"].contains(&program.as_str()) {
+            return true;
+        }
+    }
+    if let Some(term) = env("TERM") {
+        if ["xterm-kitty", "alacritty", "foot"]
+            .into_iter()
+            .any(|name| term.starts_with(name))
+        {
+            return true;
+        }
+    }
+    // GNOME Terminal and other VTE-based terminals support hyperlinks since VTE 0.50.
+    env("VTE_VERSION")
+        .and_then(|version| version.parse::<u32>().ok())
+        .is_some_and(|version| version >= 5000)
+}
+
+/// The text, linking to the URL.
+pub(crate) fn link(text: &str, url: &str) -> String {
+    format!("\x1b]8;;{url}\x1b\\{text}\x1b]8;;\x1b\\")
+}
+
+/// A `file://` URL for the line of the file, if the file exists.
+/// Relative paths, like those in rustdoc spans, are relative to the current directory.
+pub(crate) fn file_url(path: &Path, line: u64) -> Option<String> {
+    let path = path.canonicalize().ok()?;
+    Some(format!("file://{}#L{line}", path.to_str()?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::{link, supported_with_env};
+
+    #[test]
+    fn hyperlinks_are_only_printed_to_terminals_that_support_them() {
+        let supported = |printing_to_terminal, vars: &[(&str, &str)]| {
+            let vars: HashMap<_, _> = vars.iter().copied().collect();
+            supported_with_env(printing_to_terminal, |name| {
+                vars.get(name).map(|value| value.to_string())
+            })
+        };
+        assert!(supported(true, &[("TERM_PROGRAM", "WezTerm")]));
+        assert!(supported(true, &[("VTE_VERSION", "6003")]));
+        assert!(!supported(true, &[("VTE_VERSION", "4802")]));
+        assert!(!supported(true, &[("TERM", "xterm-256color")]));
+        assert!(!supported(false, &[("TERM_PROGRAM", "WezTerm")]));
+        assert!(supported(false, &[("FORCE_HYPERLINK", "1")]));
+        assert!(!supported(
+            true,
+            &[("FORCE_HYPERLINK", "0"), ("WT_SESSION", "1")]
+        ));
+
+        assert_eq!(
+            "\x1b]8;;https://example.com\x1b\\text\x1b]8;;\x1b\\",
+            link("text", "https://example.com")
+        );
+    }
+}
@@ -0,0 +1,230 @@
+//! A rough estimate of the impact of each violation: how many other public items refer to
+//! the affected item, in their fields, signatures, or bounds. A break in an item that much of
+//! the API is built on is likely to affect more users than one in an item nothing else uses.
+
+use std::collections::{HashMap, HashSet};
+
+use rustdoc_types::{Crate, ItemEnum};
+use serde_json::Value;
+use trustfall_core::ir::TransparentValue;
+
+use crate::{
+    formats::Violation,
+    importable_paths::{externally_visible_items, public_item_paths, CrateGraph},
+};
+
+/// The number of public items referring to each public item of a crate, by item path.
+#[derive(Debug, Clone, Default)]
+struct References {
+    by_path: HashMap<Vec<String>, usize>,
+}
+
+impl References {
+    fn of(crate_: &Crate, dependencies: &[Crate]) -> Self {
+        let graph = CrateGraph {
+            main: crate_,
+            dependencies,
+        };
+        let mut referrers: HashMap<String, usize> = HashMap::new();
+        for (item_crate, id) in externally_visible_items(graph) {
+            if !std::ptr::eq(item_crate.0, crate_) {
+                continue;
+            }
+            let item = match crate_.index.get(id) {
+                Some(item) => item,
+                None => continue,
+            };
+            // Impls and modules contain items, rather than referring to them.
+            if matches!(
+                item.inner,
+                ItemEnum::Impl(_) | ItemEnum::Module(_) | ItemEnum::Import(_)
+            ) {
+                continue;
+            }
+            let inner = serde_json::to_value(&item.inner).expect("serde failed");
+            let mut referenced = HashSet::new();
+            collect_resolved_paths(&inner, &mut referenced);
+            referenced.remove(&id.0);
+            for referenced in referenced {
+                *referrers.entry(referenced).or_default() += 1;
+            }
+        }
+
+        let mut by_path = HashMap::new();
+        for (item_crate, item, paths) in public_item_paths(graph) {
+            if !std::ptr::eq(item_crate, crate_) {
+                continue;
+            }
+            let count = referrers.get(&item.id.0).copied().unwrap_or_default();
+            for path in paths {
+                by_path.insert(path.into_iter().map(String::from).collect(), count);
+            }
+        }
+        Self { by_path }
+    }
+}
+
+/// The ids of the items that the types in the value refer to by path,
+/// like the types of fields and arguments, and the traits in bounds.
+fn collect_resolved_paths(value: &Value, ids: &mut HashSet<String>) {
+    match value {
+        Value::Object(fields) => {
+            if fields.get("kind").and_then(Value::as_str) == Some("resolved_path") {
+                if let Some(id) = fields["inner"].get("id").and_then(Value::as_str) {
+                    ids.insert(id.to_string());
+                }
+            }
+            fields
+                .values()
+                .for_each(|value| collect_resolved_paths(value, ids));
+        }
+        Value::Array(values) => values
+            .iter()
+            .for_each(|value| collect_resolved_paths(value, ids)),
+        _ => {}
+    }
+}
+
+/// Estimates the impact of violations, from the references between the items of the
+/// current version of the crate, or for items it no longer has, of the baseline.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ImpactEstimator {
+    current: References,
+    baseline: References,
+}
+
+impl ImpactEstimator {
+    pub(crate) fn new(
+        current: &Crate,
+        current_dependencies: &[Crate],
+        baseline: &Crate,
+        baseline_dependencies: &[Crate],
+    ) -> Self {
+        Self {
+            current: References::of(current, current_dependencies),
+            baseline: References::of(baseline, baseline_dependencies),
+        }
+    }
+
+    /// How many other public items refer to the item affected by the violation,
+    /// if the violation says which item that is.
+    pub(crate) fn estimate(&self, violation: &Violation) -> Option<usize> {
+        let path: Vec<String> = match violation.data.get("path")? {
+            TransparentValue::List(path) => path
+                .iter()
+                .map(|component| match component {
+                    TransparentValue::String(component) => Some(component.clone()),
+                    _ => None,
+                })
+                .collect::<Option<_>>()?,
+            _ => return None,
+        };
+        self.current
+            .by_path
+            .get(&path)
+            .or_else(|| self.baseline.by_path.get(&path))
+            .copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, sync::Arc};
+
+    use rustdoc_types::{Crate, FORMAT_VERSION};
+    use serde_json::json;
+    use trustfall_core::ir::TransparentValue;
+
+    use super::ImpactEstimator;
+    use crate::formats::Violation;
+
+    /// A crate where `Foo` is returned by `make_foo` and is the type of a field of `Bar`,
+    /// and `Bar` isn't used by anything. Without `make_foo` if `with_function` is false.
+    fn example_crate(with_function: bool) -> Crate {
+        let item = |id: &str, name: &str, kind: &str, inner: serde_json::Value| {
+            json!({
+                "id": id, "crate_id": 0, "name": name, "span": null, "visibility": "public",
+                "docs": null, "links": {}, "attrs": [], "deprecation": null, "kind": kind, "inner": inner,
+            })
+        };
+        let generics = json!({ "params": [], "where_predicates": [] });
+        let foo = json!({ "kind": "resolved_path", "inner": {
+            "name": "Foo", "id": "0:1", "args": null, "param_names": [],
+        } });
+        let mut root_items = vec!["0:1", "0:2"];
+        let mut index = json!({
+            "0:1": item("0:1", "Foo", "struct", json!({
+                "struct_type": "unit", "generics": generics, "fields_stripped": false,
+                "fields": [], "impls": [],
+            })),
+            "0:2": item("0:2", "Bar", "struct", json!({
+                "struct_type": "plain", "generics": generics, "fields_stripped": false,
+                "fields": ["0:3"], "impls": [],
+            })),
+            "0:3": item("0:3", "foo", "struct_field", foo.clone()),
+        });
+        if with_function {
+            root_items.push("0:4");
+            index["0:4"] = item(
+                "0:4",
+                "make_foo",
+                "function",
+                json!({
+                    "decl": { "inputs": [], "output": foo, "c_variadic": false },
+                    "generics": generics,
+                    "header": { "const": false, "unsafe": false, "async": false, "abi": "Rust" },
+                }),
+            );
+        }
+        index["0:0"] = item(
+            "0:0",
+            "demo",
+            "module",
+            json!({ "is_crate": true, "items": root_items }),
+        );
+        serde_json::from_value(json!({
+            "root": "0:0",
+            "crate_version": "1.0.0",
+            "includes_private": false,
+            "index": index,
+            "paths": {},
+            "external_crates": {},
+            "format_version": FORMAT_VERSION,
+        }))
+        .unwrap()
+    }
+
+    fn violation(path: &[&str]) -> Violation {
+        let mut data = BTreeMap::new();
+        data.insert(
+            Arc::from("path"),
+            TransparentValue::List(
+                path.iter()
+                    .map(|component| TransparentValue::String(component.to_string()))
+                    .collect(),
+            ),
+        );
+        Violation {
+            message: "message".to_string(),
+            witness: None,
+            data,
+            targets: vec![],
+            baseline_docs_url: None,
+            impact: None,
+        }
+    }
+
+    #[test]
+    fn impact_counts_public_items_referring_to_the_affected_item() {
+        let estimator = ImpactEstimator::new(&example_crate(false), &[], &example_crate(true), &[]);
+        // In the current version, only the field of `Bar` refers to `Foo`.
+        assert_eq!(Some(1), estimator.estimate(&violation(&["demo", "Foo"])));
+        assert_eq!(Some(0), estimator.estimate(&violation(&["demo", "Bar"])));
+        // The function was removed, so the baseline is used.
+        assert_eq!(
+            Some(0),
+            estimator.estimate(&violation(&["demo", "make_foo"]))
+        );
+        assert_eq!(None, estimator.estimate(&violation(&["demo", "Missing"])));
+    }
+}
@@ -0,0 +1,580 @@
+//! Finding the paths from which code outside a crate can import its items.
+
+use std::{
+    collections::{HashMap, HashSet},
+    hash::{Hash, Hasher},
+};
+
+use rustdoc_types::{Crate, Id, Import, Item, ItemEnum, Variant, Visibility};
+
+use crate::adapter::is_doc_hidden;
+
+/// A crate, and the rustdoc JSON of the crates whose items it re-exports, if available.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CrateGraph<'a> {
+    pub(crate) main: &'a Crate,
+    pub(crate) dependencies: &'a [Crate],
+}
+
+impl<'a> CrateGraph<'a> {
+    /// The item that an import in `crate_` refers to, and the crate it's in. Items from
+    /// other crates are found in the dependencies, if their rustdoc JSON was provided.
+    pub(crate) fn resolve_import(
+        &self,
+        crate_: &'a Crate,
+        import: &Import,
+    ) -> Option<(&'a Crate, &'a Item)> {
+        let id = import.id.as_ref()?;
+        if let Some(item) = crate_.index.get(id) {
+            return Some((crate_, item));
+        }
+
+        // Other crates' items are only summarized, with the path at which they are defined,
+        // which is also where their own crate's rustdoc JSON records them.
+        let summary = crate_.paths.get(id)?;
+        let crate_name = &crate_.external_crates.get(&summary.crate_id)?.name;
+        let dependency = self
+            .dependencies
+            .iter()
+            .find(|dependency| crate_name_of(dependency) == Some(crate_name.as_str()))?;
+        dependency
+            .paths
+            .iter()
+            .find(|(_, item)| item.crate_id == 0 && item.path == summary.path)
+            .and_then(|(id, _)| dependency.index.get(id))
+            .map(|item| (dependency, item))
+    }
+}
+
+/// A crate, compared and hashed by its address, to tell apart the crates of a graph,
+/// whose item ids overlap.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct CrateRef<'a>(pub(crate) &'a Crate);
+
+impl PartialEq for CrateRef<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::eq(self.0, other.0)
+    }
+}
+
+impl Eq for CrateRef<'_> {}
+
+impl Hash for CrateRef<'_> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::ptr::hash(self.0, state)
+    }
+}
+
+/// An item, by the crate whose rustdoc describes it and its id there.
+pub(crate) type ItemKey<'a> = (CrateRef<'a>, &'a Id);
+
+/// The name of the crate, as it's written in paths.
+fn crate_name_of(crate_: &Crate) -> Option<&str> {
+    crate_
+        .index
+        .get(&crate_.root)
+        .and_then(|root| root.name.as_deref())
+}
+
+/// A path from which an item can be imported, like `["my_crate", "module", "Item"]`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ImportablePath<'a> {
+    pub path: Vec<&'a str>,
+    /// Whether the item, a module in the path, or a re-export along the way is
+    /// `#[doc(hidden)]`, which makes the path an implementation detail rather than public API.
+    pub doc_hidden: bool,
+}
+
+/// The paths from which items can be imported, by the crate whose rustdoc describes the item
+/// and its id. Items that have no such paths aren't in it.
+pub(crate) type ImportablePathIndex<'a> = HashMap<ItemKey<'a>, Vec<ImportablePath<'a>>>;
+
+/// Every path from which each item can be imported by users of the graph's main crate,
+/// following `pub use` re-exports, renames, and globs, found in a single walk of the crate.
+///
+/// Items that aren't public, or are only public within private modules, have no such paths.
+/// A path that is reachable both with and without going through hidden items isn't hidden.
+pub(crate) fn importable_path_index(graph: CrateGraph<'_>) -> ImportablePathIndex<'_> {
+    let mut index: ImportablePathIndex = HashMap::new();
+    walk_public_items(graph, &mut |crate_, item, path, doc_hidden| {
+        index
+            .entry((CrateRef(crate_), &item.id))
+            .or_default()
+            .push(ImportablePath {
+                path: path.to_vec(),
+                doc_hidden,
+            });
+    });
+    for paths in index.values_mut() {
+        paths.sort();
+        paths.dedup_by(|hidden, visible| hidden.path == visible.path);
+    }
+    index
+}
+
+/// The items of the dependencies that the main crate re-exports, each once.
+pub(crate) fn reexported_items<'a>(graph: CrateGraph<'a>) -> Vec<(&'a Crate, &'a Item)> {
+    let mut seen = HashSet::new();
+    let mut items = vec![];
+    walk_public_items(graph, &mut |crate_, item, _, _| {
+        if !std::ptr::eq(crate_, graph.main) && seen.insert((CrateRef(crate_), &item.id)) {
+            items.push((crate_, item));
+        }
+    });
+    items
+}
+
+/// The items that users of the main crate can reach: those importable from it, including
+/// at hidden paths, and the public parts of those, like `pub` fields, enum variants,
+/// trait items, and the items of impls that are themselves public.
+pub(crate) fn externally_visible_items<'a>(graph: CrateGraph<'a>) -> HashSet<ItemKey<'a>> {
+    let mut visible = HashSet::new();
+    walk_public_items(graph, &mut |crate_, item, _, _| {
+        add_visible_parts(crate_, item, &mut visible);
+    });
+    visible
+}
+
+/// Add the item, and the parts of it that are visible wherever it is.
+fn add_visible_parts<'a>(crate_: &'a Crate, item: &'a Item, visible: &mut HashSet<ItemKey<'a>>) {
+    if !visible.insert((CrateRef(crate_), &item.id)) {
+        return;
+    }
+    let (parts, only_public): (Vec<&Id>, bool) = match &item.inner {
+        ItemEnum::Struct(struct_) => (struct_.fields.iter().chain(&struct_.impls).collect(), true),
+        ItemEnum::Union(union_) => (union_.fields.iter().chain(&union_.impls).collect(), true),
+        ItemEnum::Enum(enum_) => (enum_.variants.iter().chain(&enum_.impls).collect(), false),
+        ItemEnum::Variant(Variant::Struct(fields)) => (fields.iter().collect(), false),
+        ItemEnum::Trait(trait_) => (trait_.items.iter().collect(), false),
+        ItemEnum::Impl(impl_) => (impl_.items.iter().collect(), impl_.trait_.is_none()),
+        _ => return,
+    };
+    for part in parts.into_iter().filter_map(|id| crate_.index.get(id)) {
+        // Impls are as visible as their type, and what they contain as visible as declared.
+        let is_impl = matches!(part.inner, ItemEnum::Impl(_));
+        if !only_public || is_impl || part.visibility == Visibility::Public {
+            add_visible_parts(crate_, part, visible);
+        }
+    }
+}
+
+/// Every item importable from the main crate, with the crate whose rustdoc describes it
+/// and the paths it's importable at that don't go through hidden items.
+pub(crate) fn public_item_paths<'a>(
+    graph: CrateGraph<'a>,
+) -> Vec<(&'a Crate, &'a Item, Vec<Vec<&'a str>>)> {
+    let mut positions: HashMap<ItemKey<'a>, usize> = HashMap::new();
+    let mut items: Vec<(&'a Crate, &'a Item, Vec<Vec<&'a str>>)> = vec![];
+    walk_public_items(graph, &mut |crate_, item, path, doc_hidden| {
+        let position = *positions
+            .entry((CrateRef(crate_), &item.id))
+            .or_insert_with(|| {
+                items.push((crate_, item, vec![]));
+                items.len() - 1
+            });
+        if !doc_hidden {
+            items[position].2.push(path.to_vec());
+        }
+    });
+    items
+}
+
+/// Called with an item, the crate whose rustdoc describes it, the path it's importable at,
+/// and whether that path goes through hidden items.
+type VisitItem<'a, 'b> = dyn FnMut(&'a Crate, &'a Item, &[&'a str], bool) + 'b;
+
+/// Call `visit` with every item importable from the main crate, the path it's importable
+/// at, and whether that path goes through hidden items. Items importable at several paths
+/// are visited once for each.
+fn walk_public_items<'a>(graph: CrateGraph<'a>, visit: &mut VisitItem<'a, '_>) {
+    let main = graph.main;
+    let root = match main.index.get(&main.root) {
+        Some(root) => root,
+        None => return,
+    };
+    let root_name = match &root.name {
+        Some(name) => name.as_str(),
+        None => return,
+    };
+    let mut walk = PathWalk {
+        graph,
+        visit,
+        modules_in_path: HashSet::new(),
+    };
+    walk.visit_module(main, root, &mut vec![root_name], is_doc_hidden(&root.attrs));
+}
+
+struct PathWalk<'a, 'b> {
+    graph: CrateGraph<'a>,
+    visit: &'b mut VisitItem<'a, 'b>,
+    /// The modules being visited, to stop when re-exports form a cycle.
+    modules_in_path: HashSet<ItemKey<'a>>,
+}
+
+impl<'a, 'b> PathWalk<'a, 'b> {
+    /// Visit the items in the module, at `path`, including those glob-imported into it.
+    /// `hidden` is whether the path to the module goes through hidden items.
+    fn visit_module(
+        &mut self,
+        crate_: &'a Crate,
+        module: &'a Item,
+        path: &mut Vec<&'a str>,
+        hidden: bool,
+    ) {
+        let items = match &module.inner {
+            ItemEnum::Module(module) => &module.items,
+            _ => return,
+        };
+        if !self.modules_in_path.insert((CrateRef(crate_), &module.id)) {
+            return;
+        }
+
+        // Items declared or imported by name shadow those of the same name from globs.
+        let named_items: HashSet<&str> = items
+            .iter()
+            .filter_map(|id| crate_.index.get(id))
+            .filter_map(|item| match &item.inner {
+                ItemEnum::Import(import) if !import.glob => Some(import.name.as_str()),
+                ItemEnum::Import(_) => None,
+                _ => item.name.as_deref(),
+            })
+            .collect();
+
+        for item in items.iter().filter_map(|id| crate_.index.get(id)) {
+            if item.visibility != Visibility::Public {
+                continue;
+            }
+            let hidden = hidden || is_doc_hidden(&item.attrs);
+            match &item.inner {
+                ItemEnum::Import(import) => {
+                    let (imported_crate, imported) = match self.graph.resolve_import(crate_, import)
+                    {
+                        Some(imported) => imported,
+                        None => continue,
+                    };
+                    if import.glob {
+                        self.visit_glob(imported_crate, imported, &named_items, path, hidden);
+                    } else {
+                        self.visit_item(imported_crate, imported, &import.name, path, hidden);
+                    }
+                }
+                _ => {
+                    if let Some(name) = &item.name {
+                        self.visit_item(crate_, item, name, path, hidden);
+                    }
+                }
+            }
+        }
+
+        self.modules_in_path.remove(&(CrateRef(crate_), &module.id));
+    }
+
+    /// Visit an item that is importable as `name` in the module at `path`.
+    fn visit_item(
+        &mut self,
+        crate_: &'a Crate,
+        item: &'a Item,
+        name: &'a str,
+        path: &mut Vec<&'a str>,
+        hidden: bool,
+    ) {
+        let hidden = hidden || is_doc_hidden(&item.attrs);
+        path.push(name);
+        (self.visit)(crate_, item, path, hidden);
+        self.visit_module(crate_, item, path, hidden);
+        path.pop();
+    }
+
+    /// Visit the public items of a glob-imported module, except those that `named_items`
+    /// in the importing module shadow. The module itself isn't in their path, so they aren't
+    /// hidden if it is.
+    fn visit_glob(
+        &mut self,
+        crate_: &'a Crate,
+        module: &'a Item,
+        named_items: &HashSet<&str>,
+        path: &mut Vec<&'a str>,
+        hidden: bool,
+    ) {
+        let items = match &module.inner {
+            ItemEnum::Module(module) => &module.items,
+            _ => return,
+        };
+        if !self.modules_in_path.insert((CrateRef(crate_), &module.id)) {
+            return;
+        }
+        for item in items.iter().filter_map(|id| crate_.index.get(id)) {
+            if item.visibility != Visibility::Public {
+                continue;
+            }
+            let hidden = hidden || is_doc_hidden(&item.attrs);
+            match &item.inner {
+                ItemEnum::Import(import) => {
+                    let (imported_crate, imported) = match self.graph.resolve_import(crate_, import)
+                    {
+                        Some(imported) => imported,
+                        None => continue,
+                    };
+                    if import.glob {
+                        self.visit_glob(imported_crate, imported, named_items, path, hidden);
+                    } else if !named_items.contains(import.name.as_str()) {
+                        self.visit_item(imported_crate, imported, &import.name, path, hidden);
+                    }
+                }
+                _ => match &item.name {
+                    Some(name) if !named_items.contains(name.as_str()) => {
+                        self.visit_item(crate_, item, name, path, hidden)
+                    }
+                    _ => {}
+                },
+            }
+        }
+        self.modules_in_path.remove(&(CrateRef(crate_), &module.id));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rustdoc_types::{
+        Crate, ExternalCrate, Generics, Id, Import, Item, ItemEnum, ItemKind, ItemSummary, Module,
+        Struct, StructType, Type, Visibility, FORMAT_VERSION,
+    };
+
+    use super::{
+        externally_visible_items, importable_path_index, reexported_items, CrateGraph, CrateRef,
+        ImportablePath,
+    };
+
+    fn importable_paths<'a>(
+        graph: CrateGraph<'a>,
+        crate_: &'a Crate,
+        id: &Id,
+    ) -> Vec<ImportablePath<'a>> {
+        importable_path_index(graph)
+            .remove(&(CrateRef(crate_), id))
+            .unwrap_or_default()
+    }
+
+    fn item(id: &str, name: Option<&str>, visibility: Visibility, inner: ItemEnum) -> Item {
+        Item {
+            id: Id(id.to_string()),
+            crate_id: 0,
+            name: name.map(ToString::to_string),
+            span: None,
+            visibility,
+            docs: None,
+            links: HashMap::new(),
+            attrs: vec![],
+            deprecation: None,
+            inner,
+        }
+    }
+
+    fn module(id: &str, name: &str, visibility: Visibility, items: &[&str]) -> Item {
+        item(
+            id,
+            Some(name),
+            visibility,
+            ItemEnum::Module(Module {
+                is_crate: id == "0:0",
+                items: items.iter().map(|id| Id(id.to_string())).collect(),
+            }),
+        )
+    }
+
+    fn unit_struct(id: &str, name: &str) -> Item {
+        item(
+            id,
+            Some(name),
+            Visibility::Public,
+            ItemEnum::Struct(Struct {
+                struct_type: StructType::Unit,
+                generics: Generics {
+                    params: vec![],
+                    where_predicates: vec![],
+                },
+                fields_stripped: false,
+                fields: vec![],
+                impls: vec![],
+            }),
+        )
+    }
+
+    fn import(id: &str, name: &str, target: &str, glob: bool) -> Item {
+        item(
+            id,
+            None,
+            Visibility::Public,
+            ItemEnum::Import(Import {
+                source: String::new(),
+                name: name.to_string(),
+                id: Some(Id(target.to_string())),
+                glob,
+            }),
+        )
+    }
+
+    fn krate(items: Vec<Item>) -> Crate {
+        Crate {
+            root: Id("0:0".to_string()),
+            crate_version: None,
+            includes_private: false,
+            index: items
+                .into_iter()
+                .map(|item| (item.id.clone(), item))
+                .collect(),
+            paths: HashMap::new(),
+            external_crates: HashMap::new(),
+            format_version: FORMAT_VERSION,
+        }
+    }
+
+    #[test]
+    fn re_exports_renames_and_globs_are_followed() {
+        // mod private { pub struct Foo; pub mod inner { pub struct Foo; } }
+        // #[doc(hidden)] pub mod hidden { pub use crate::private::Foo as Bar; pub use crate::private::inner::*; }
+        // pub use hidden::*;
+        let mut hidden = module("0:2", "hidden", Visibility::Public, &["0:5", "0:6"]);
+        hidden.attrs.push("#[doc(hidden)]".to_string());
+        let crate_ = krate(vec![
+            module("0:0", "krate", Visibility::Public, &["0:1", "0:2", "0:7"]),
+            module("0:1", "private", Visibility::Default, &["0:3", "0:4"]),
+            hidden,
+            unit_struct("0:3", "Foo"),
+            module("0:4", "inner", Visibility::Public, &["0:8"]),
+            import("0:5", "Bar", "0:3", false),
+            import("0:6", "inner", "0:4", true),
+            import("0:7", "hidden", "0:2", true),
+            unit_struct("0:8", "Foo"),
+        ]);
+        let graph = CrateGraph {
+            main: &crate_,
+            dependencies: &[],
+        };
+
+        let paths = |id: &str| {
+            importable_paths(graph, &crate_, &Id(id.to_string()))
+                .into_iter()
+                .map(|path| (path.path.join("::"), path.doc_hidden))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            vec![
+                ("krate::Bar".to_string(), false),
+                ("krate::hidden::Bar".to_string(), true)
+            ],
+            paths("0:3")
+        );
+        assert_eq!(
+            vec![
+                ("krate::Foo".to_string(), false),
+                ("krate::hidden::Foo".to_string(), true)
+            ],
+            paths("0:8")
+        );
+        // The module is only reachable through the glob, which imports its items instead.
+        assert!(paths("0:4").is_empty());
+    }
+
+    #[test]
+    fn re_exports_of_other_crates_items_are_found_in_their_rustdoc() {
+        // In `dep`: pub mod inner { pub struct Thing; }
+        // In `krate`: pub use dep::inner::Thing;
+        let mut dependency = krate(vec![
+            module("0:0", "dep", Visibility::Public, &["0:1"]),
+            module("0:1", "inner", Visibility::Public, &["0:2"]),
+            unit_struct("0:2", "Thing"),
+        ]);
+        dependency.paths.insert(
+            Id("0:2".to_string()),
+            ItemSummary {
+                crate_id: 0,
+                path: vec!["dep".to_string(), "inner".to_string(), "Thing".to_string()],
+                kind: ItemKind::Struct,
+            },
+        );
+        let mut facade = krate(vec![
+            module("0:0", "krate", Visibility::Public, &["0:1"]),
+            import("0:1", "Thing", "20:2", false),
+        ]);
+        facade.paths.insert(
+            Id("20:2".to_string()),
+            dependency.paths[&Id("0:2".to_string())].clone(),
+        );
+        facade
+            .paths
+            .get_mut(&Id("20:2".to_string()))
+            .unwrap()
+            .crate_id = 20;
+        facade.external_crates.insert(
+            20,
+            ExternalCrate {
+                name: "dep".to_string(),
+                html_root_url: None,
+            },
+        );
+
+        let without_dependency = CrateGraph {
+            main: &facade,
+            dependencies: &[],
+        };
+        assert!(reexported_items(without_dependency).is_empty());
+
+        let dependencies = [dependency];
+        let graph = CrateGraph {
+            main: &facade,
+            dependencies: &dependencies,
+        };
+        let reexported = reexported_items(graph);
+        assert_eq!(1, reexported.len());
+        let (crate_, thing) = reexported[0];
+        assert_eq!(Some("Thing"), thing.name.as_deref());
+        assert_eq!(
+            vec![vec!["krate", "Thing"]],
+            importable_paths(graph, crate_, &thing.id)
+                .into_iter()
+                .map(|path| path.path)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn public_parts_of_importable_items_are_externally_visible() {
+        // pub struct Outer { pub a: u8, b: u8 }
+        // mod private { pub struct Inner; }
+        let mut outer = unit_struct("0:1", "Outer");
+        if let ItemEnum::Struct(struct_) = &mut outer.inner {
+            struct_.struct_type = StructType::Plain;
+            struct_.fields = vec![Id("0:2".to_string()), Id("0:3".to_string())];
+        }
+        let field = |id: &str, name: &str, visibility| {
+            item(
+                id,
+                Some(name),
+                visibility,
+                ItemEnum::StructField(Type::Primitive("u8".to_string())),
+            )
+        };
+        let crate_ = krate(vec![
+            module("0:0", "krate", Visibility::Public, &["0:1", "0:4"]),
+            outer,
+            field("0:2", "a", Visibility::Public),
+            field("0:3", "b", Visibility::Default),
+            module("0:4", "private", Visibility::Default, &["0:5"]),
+            unit_struct("0:5", "Inner"),
+        ]);
+        let graph = CrateGraph {
+            main: &crate_,
+            dependencies: &[],
+        };
+
+        let visible = externally_visible_items(graph);
+        let is_visible = |id: &str| visible.contains(&(CrateRef(&crate_), &Id(id.to_string())));
+        assert!(is_visible("0:1"));
+        assert!(is_visible("0:2"));
+        assert!(!is_visible("0:3"));
+        assert!(!is_visible("0:5"));
+    }
+}
@@ -0,0 +1,293 @@
+//! The `init` subcommand, which sets up a crate for semver checking.
+
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context};
+use termcolor::Color;
+
+use crate::{rustdoc_gen::Workspace, GlobalConfig};
+
+/// Where the generated GitHub Actions workflow is written, relative to the workspace root.
+const WORKFLOW_PATH: &str = ".github/workflows/semver-checks.yml";
+
+/// Appended to the manifest, for the user to fill in.
+const STARTER_METADATA: &str = r#"
+[package.metadata.semver-checks.lint-packs]
+# Extra checks to run, declared like dependencies. For example:
+# acme-semver-policies = "1.2"
+"#;
+
+/// What the crate is checked against in CI.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum BaselineStrategy {
+    /// Every published release that is semver-compatible with the current version.
+    AllCompatibleReleases,
+    /// The crate's code on a git branch, e.g. the branch pull requests are merged into.
+    Branch(String),
+    /// A specific published version.
+    Version(String),
+}
+
+/// Choices made with command-line options. Anything left unset is prompted for
+/// when running interactively, and defaulted otherwise.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct InitOptions {
+    pub(crate) baseline: Option<BaselineStrategy>,
+    pub(crate) features: Option<Vec<String>>,
+    pub(crate) github_actions: Option<bool>,
+    pub(crate) interactive: bool,
+}
+
+pub(crate) fn run_init(
+    config: &GlobalConfig,
+    manifest_path: &Path,
+    workspace: &Workspace,
+    options: InitOptions,
+) -> anyhow::Result<()> {
+    let status = config.status_printer();
+
+    let contents = std::fs::read_to_string(manifest_path)
+        .with_context(|| format!("Failed to read manifest {:?}", manifest_path))?;
+    if has_semver_checks_metadata(&contents)? {
+        status.print(
+            Color::Yellow,
+            "Skipping",
+            format_args!(
+                "{:?} already has a [package.metadata.semver-checks] table",
+                manifest_path
+            ),
+        );
+    } else {
+        let separator = if contents.ends_with('\n') { "" } else { "\n" };
+        std::fs::write(
+            manifest_path,
+            format!("{contents}{separator}{STARTER_METADATA}"),
+        )
+        .with_context(|| format!("Failed to write manifest {:?}", manifest_path))?;
+        status.print(
+            Color::Green,
+            "Updated",
+            format_args!(
+                "{:?} with a [package.metadata.semver-checks] table",
+                manifest_path
+            ),
+        );
+    }
+
+    let workflow_path = workspace.root.join(WORKFLOW_PATH);
+    let github_actions = match options.github_actions {
+        Some(github_actions) => github_actions,
+        None if options.interactive => prompt_yes_no(&format!(
+            "Write a GitHub Actions workflow to {}?",
+            workflow_path.display()
+        ))?,
+        None => false,
+    };
+    if !github_actions {
+        return Ok(());
+    }
+    if workflow_path.exists() {
+        bail!(
+            "{:?} already exists. Remove it to generate a new workflow.",
+            workflow_path
+        );
+    }
+
+    let baseline = match options.baseline {
+        Some(baseline) => baseline,
+        None if options.interactive => prompt_baseline()?,
+        None => BaselineStrategy::AllCompatibleReleases,
+    };
+    let features = match options.features {
+        Some(features) => features,
+        None if options.interactive => split_features(&prompt(
+            "Features to enable when checking, separated by commas",
+            "",
+        )?),
+        None => vec![],
+    };
+    let relative_manifest_path = manifest_path
+        .strip_prefix(&workspace.root)
+        .map(Path::to_path_buf)
+        .unwrap_or_else(|_| PathBuf::from("Cargo.toml"));
+
+    let parent = workflow_path.parent().expect("workflow path has no parent");
+    std::fs::create_dir_all(parent)
+        .with_context(|| format!("Failed to create directory {:?}", parent))?;
+    std::fs::write(
+        &workflow_path,
+        github_workflow(&baseline, &features, &relative_manifest_path),
+    )
+    .with_context(|| format!("Failed to write workflow {:?}", workflow_path))?;
+    status.print(
+        Color::Green,
+        "Created",
+        format_args!("{}", workflow_path.display()),
+    );
+    Ok(())
+}
+
+fn has_semver_checks_metadata(manifest: &str) -> anyhow::Result<bool> {
+    let manifest: toml::Value = toml::from_str(manifest).context("Failed to parse manifest")?;
+    Ok(manifest
+        .get("package")
+        .and_then(|package| package.get("metadata"))
+        .and_then(|metadata| metadata.get("semver-checks"))
+        .is_some())
+}
+
+pub(crate) fn split_features(features: &str) -> Vec<String> {
+    features
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|feature| !feature.is_empty())
+        .map(ToString::to_string)
+        .collect()
+}
+
+/// A GitHub Actions workflow that checks the crate on every pull request and push.
+fn github_workflow(
+    baseline: &BaselineStrategy,
+    features: &[String],
+    manifest_path: &Path,
+) -> String {
+    let mut checkout_baseline = String::new();
+    let mut args = String::new();
+    if manifest_path != Path::new("Cargo.toml") {
+        args.push_str(&format!(" --manifest-path {}", manifest_path.display()));
+    }
+    args.push_str(" check-release");
+    match baseline {
+        BaselineStrategy::AllCompatibleReleases => args.push_str(" --all-compatible-releases"),
+        BaselineStrategy::Version(version) => {
+            args.push_str(&format!(" --baseline-version {version}"));
+        }
+        BaselineStrategy::Branch(branch) => {
+            checkout_baseline = format!(
+                "      - uses: actions/checkout@v3\n        \
+                with:\n          \
+                ref: {branch}\n          \
+                path: semver-checks-baseline\n"
+            );
+            let baseline_root = Path::new("semver-checks-baseline")
+                .join(manifest_path.parent().unwrap_or_else(|| Path::new("")));
+            args.push_str(&format!(" --baseline-root {}", baseline_root.display()));
+        }
+    }
+    if !features.is_empty() {
+        args.push_str(&format!(" --features {}", features.join(",")));
+    }
+
+    format!(
+        "\
+name: Semver checks
+
+on:
+  pull_request:
+  push:
+
+jobs:
+  semver-checks:
+    runs-on: ubuntu-latest
+    steps:
+      - uses: actions/checkout@v3
+{checkout_baseline}      - name: Install nightly Rust, which is needed to generate rustdoc JSON
+        run: rustup toolchain install nightly --profile minimal
+      - name: Install cargo-semver-checks
+        run: cargo install cargo-semver-checks --locked
+      - name: Check for semver violations
+        run: cargo semver-checks{args}
+"
+    )
+}
+
+fn prompt(question: &str, default: &str) -> anyhow::Result<String> {
+    let mut stderr = std::io::stderr();
+    write!(stderr, "{question} [{default}]: ").context("Failed to print prompt")?;
+    stderr.flush().context("Failed to print prompt")?;
+    let mut answer = String::new();
+    std::io::stdin()
+        .read_line(&mut answer)
+        .context("Failed to read answer")?;
+    let answer = answer.trim();
+    Ok(if answer.is_empty() { default } else { answer }.to_string())
+}
+
+fn prompt_yes_no(question: &str) -> anyhow::Result<bool> {
+    loop {
+        // An empty answer returns the default as shown, which means yes.
+        match prompt(question, "Y/n")?.to_lowercase().as_str() {
+            "y/n" | "y" | "yes" => return Ok(true),
+            "n" | "no" => return Ok(false),
+            _ => continue,
+        }
+    }
+}
+
+fn prompt_baseline() -> anyhow::Result<BaselineStrategy> {
+    eprintln!(
+        "What should the crate be checked against?\n  \
+        1. every published release that is semver-compatible with its current version\n  \
+        2. its code on a git branch, e.g. the branch pull requests are merged into\n  \
+        3. a specific published version"
+    );
+    loop {
+        match prompt("Baseline", "1")?.as_str() {
+            "1" => return Ok(BaselineStrategy::AllCompatibleReleases),
+            "2" => return Ok(BaselineStrategy::Branch(prompt("Branch", "main")?)),
+            "3" => loop {
+                let version = prompt("Version", "")?;
+                if !version.is_empty() {
+                    return Ok(BaselineStrategy::Version(version));
+                }
+            },
+            _ => continue,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{github_workflow, has_semver_checks_metadata, BaselineStrategy};
+
+    #[test]
+    fn workflow_checks_against_the_chosen_baseline() {
+        let workflow = github_workflow(
+            &BaselineStrategy::Branch("main".to_string()),
+            &["serde".to_string(), "std".to_string()],
+            Path::new("crates/foo/Cargo.toml"),
+        );
+        assert!(workflow.contains("ref: main\n"), "{}", workflow);
+        assert!(
+            workflow.contains(
+                "run: cargo semver-checks --manifest-path crates/foo/Cargo.toml check-release \
+                --baseline-root semver-checks-baseline/crates/foo --features serde,std\n"
+            ),
+            "{}",
+            workflow
+        );
+
+        let workflow = github_workflow(
+            &BaselineStrategy::AllCompatibleReleases,
+            &[],
+            Path::new("Cargo.toml"),
+        );
+        assert!(!workflow.contains("ref:"), "{}", workflow);
+        assert!(
+            workflow.contains("run: cargo semver-checks check-release --all-compatible-releases\n"),
+            "{}",
+            workflow
+        );
+
+        assert!(!has_semver_checks_metadata("[package]\nname = \"foo\"\n").unwrap());
+        assert!(has_semver_checks_metadata(&format!(
+            "[package]\nname = \"foo\"\n{}",
+            super::STARTER_METADATA
+        ))
+        .unwrap());
+    }
+}
@@ -0,0 +1,114 @@
+//! Cleaning up partially-written files when the process is interrupted with Ctrl-C.
+
+use std::{
+    path::{Path, PathBuf},
+    sync::{Mutex, MutexGuard},
+};
+
+use anyhow::Context;
+
+/// Exit code when interrupted, following the shell convention of 128 + SIGINT.
+const EXIT_INTERRUPTED: i32 = 130;
+
+/// Temporary files and directories that must be removed if the process is interrupted.
+static PENDING: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+fn pending() -> MutexGuard<'static, Vec<PathBuf>> {
+    // Removing paths can't leave the list in an inconsistent state, so poisoning is harmless.
+    PENDING.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// On Ctrl-C, remove every [`TempPath`] that hasn't been persisted yet, then exit.
+pub(crate) fn install_handler() -> anyhow::Result<()> {
+    ctrlc::set_handler(|| {
+        for path in pending().drain(..) {
+            remove_path(&path);
+        }
+        std::process::exit(EXIT_INTERRUPTED);
+    })
+    .context("Failed to install Ctrl-C handler")
+}
+
+fn remove_path(path: &Path) {
+    // Best effort: there is nothing more to do if the path can't be removed.
+    let _ = if path.is_dir() {
+        std::fs::remove_dir_all(path)
+    } else {
+        std::fs::remove_file(path)
+    };
+}
+
+/// A temporary file or directory, which is removed when dropped or when the process
+/// is interrupted, unless it is first moved to its final location with [`TempPath::persist`].
+///
+/// Writing to a temporary path and then persisting it ensures that the final location
+/// never contains partially-written data.
+#[derive(Debug)]
+pub(crate) struct TempPath {
+    path: PathBuf,
+}
+
+impl TempPath {
+    /// A temporary path next to `destination`, in the same directory so that persisting it
+    /// is an atomic rename.
+    pub(crate) fn next_to(destination: &Path) -> Self {
+        let mut file_name = destination
+            .file_name()
+            .expect("destination has no file name")
+            .to_os_string();
+        file_name.push(format!(".{}.partial", std::process::id()));
+        let path = destination.with_file_name(file_name);
+        pending().push(path.clone());
+        Self { path }
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Move the temporary path to `destination`, replacing anything already there.
+    pub(crate) fn persist(self, destination: &Path) -> anyhow::Result<()> {
+        std::fs::rename(&self.path, destination)
+            .with_context(|| format!("Failed to move {:?} to {:?}", self.path, destination))?;
+        pending().retain(|path| path != &self.path);
+        Ok(())
+    }
+}
+
+impl Drop for TempPath {
+    fn drop(&mut self) {
+        let mut pending = pending();
+        if let Some(index) = pending.iter().position(|path| path == &self.path) {
+            pending.swap_remove(index);
+            remove_path(&self.path);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TempPath;
+
+    #[test]
+    fn temp_paths_are_removed_unless_persisted() {
+        let dir = std::env::temp_dir().join(format!(
+            "cargo-semver-checks-interrupt-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).expect("failed to create directory");
+        let destination = dir.join("baseline.json");
+
+        let temp = TempPath::next_to(&destination);
+        std::fs::write(temp.path(), "{}").expect("failed to write");
+        let temp_path = temp.path().to_path_buf();
+        drop(temp);
+        assert!(!temp_path.exists());
+
+        let temp = TempPath::next_to(&destination);
+        std::fs::write(temp.path(), "{}").expect("failed to write");
+        temp.persist(&destination).expect("failed to persist");
+        assert!(destination.is_file());
+
+        std::fs::remove_dir_all(&dir).expect("failed to remove directory");
+    }
+}
@@ -0,0 +1,233 @@
+//! Scan your Rust crate for semver violations.
+//!
+//! This is the library behind `cargo semver-checks`. Use [`Check`] to run its checks
+//! on rustdoc JSON from release tools and scripts, or [`run_cli`] to run the whole tool.
+
+#![forbid(unsafe_code)]
+
+mod adapter;
+mod api_surface;
+mod baseline;
+mod bugreport;
+mod cache;
+mod changed_items;
+mod check;
+mod check_release;
+mod cli;
+mod docs_rs;
+mod exceptions;
+mod explain;
+mod formats;
+mod hyperlink;
+mod impact;
+mod importable_paths;
+mod init;
+mod interrupt;
+mod lint_pack;
+mod list_checks;
+mod logging;
+mod manifest;
+mod observer;
+mod query;
+mod query_definition;
+mod renames;
+mod rustdoc_gen;
+mod signature_diff;
+mod snapshot;
+mod stats;
+mod templates;
+mod type_display;
+mod type_normalize;
+mod util;
+mod watch;
+
+use std::process::ExitCode;
+
+use termcolor::{Color, ColorChoice, StandardStream};
+use termcolor_output::{colored, colored_ln};
+
+pub use crate::{
+    check::{Check, CheckResult, Report, Rustdoc, Violation},
+    observer::{CheckInfo, CheckObserver, ComparisonInfo, RunInfo, SilentObserver},
+    query::{ActualSemverUpdate, LintLevel, QueryRegistry, RequiredSemverUpdate, SemverQuery},
+};
+
+/// How much human-readable output to print.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default)]
+pub(crate) enum Verbosity {
+    /// Nothing if the checks pass, and otherwise only the failures and the final result.
+    Quiet,
+    /// Progress and results of every check.
+    #[default]
+    Normal,
+    /// Also why checks were skipped, and the commands used to generate rustdoc.
+    Verbose,
+    /// Also the underlying cargo and rustdoc invocations, and timing details.
+    VeryVerbose,
+}
+
+impl Verbosity {
+    fn from_flags(quiet: bool, verbose_count: u64) -> Self {
+        match (quiet, verbose_count) {
+            (true, _) => Self::Quiet,
+            (false, 0) => Self::Normal,
+            (false, 1) => Self::Verbose,
+            (false, _) => Self::VeryVerbose,
+        }
+    }
+}
+
+/// When to color the output: set by `--color`, or else by the `CARGO_TERM_COLOR`
+/// environment variable, as with cargo.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ColorWhen {
+    /// Only when printing to a terminal.
+    Auto,
+    Always,
+    /// Always, using ANSI escape codes even on Windows consoles.
+    AlwaysAnsi,
+    Never,
+}
+
+impl ColorWhen {
+    fn from_setting(color_flag: Option<&str>) -> Self {
+        let setting = match color_flag {
+            Some(color_flag) => Some(color_flag.to_string()),
+            None => std::env::var("CARGO_TERM_COLOR").ok(),
+        };
+        match setting.as_deref() {
+            Some("always") => Self::Always,
+            Some("alwaysansi") => Self::AlwaysAnsi,
+            Some("never") => Self::Never,
+            Some(_) | None => Self::Auto,
+        }
+    }
+
+    /// The color choice for output printed to the given stream.
+    fn color_choice(self, stream: atty::Stream) -> ColorChoice {
+        match self {
+            Self::Auto if atty::is(stream) => ColorChoice::Auto,
+            Self::Auto | Self::Never => ColorChoice::Never,
+            Self::Always => ColorChoice::Always,
+            Self::AlwaysAnsi => ColorChoice::AlwaysAnsi,
+        }
+    }
+}
+
+#[allow(dead_code)]
+pub(crate) struct GlobalConfig {
+    printing_to_terminal: bool,
+    output_writer: StandardStream,
+    verbosity: Verbosity,
+    color: ColorWhen,
+    /// Whether the output goes to a terminal that supports hyperlinks, and may be colored.
+    hyperlinks: bool,
+}
+
+impl GlobalConfig {
+    fn new(verbosity: Verbosity, color: ColorWhen) -> Self {
+        Self::with_output_stream(atty::Stream::Stdout, verbosity, color)
+    }
+
+    /// Print human-readable output to the given stream, which must be stdout or stderr.
+    fn with_output_stream(stream: atty::Stream, verbosity: Verbosity, color: ColorWhen) -> Self {
+        let printing_to_terminal = atty::is(stream);
+
+        let color_choice = color.color_choice(stream);
+        let output_writer = match stream {
+            atty::Stream::Stdout => StandardStream::stdout(color_choice),
+            atty::Stream::Stderr => StandardStream::stderr(color_choice),
+            atty::Stream::Stdin => unreachable!("cannot print to stdin"),
+        };
+
+        Self {
+            printing_to_terminal,
+            output_writer,
+            verbosity,
+            color,
+            hyperlinks: color != ColorWhen::Never && hyperlink::supported(printing_to_terminal),
+        }
+    }
+
+    /// The text as a hyperlink to the URL, or just the text if hyperlinks aren't printed.
+    pub(crate) fn link(&self, text: &str, url: &str) -> String {
+        if self.hyperlinks {
+            hyperlink::link(text, url)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// A printer for status messages on stderr, colored like the rest of the output.
+    /// Status messages aren't printed with `--quiet`, but errors are.
+    pub(crate) fn status_printer(&self) -> StatusPrinter {
+        StatusPrinter {
+            quiet: self.verbosity == Verbosity::Quiet,
+            ..StatusPrinter::new(self.color)
+        }
+    }
+}
+
+/// Prints cargo-style status messages, such as `     Running <command>`, to stderr.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct StatusPrinter {
+    color_choice: ColorChoice,
+    quiet: bool,
+}
+
+impl Default for StatusPrinter {
+    fn default() -> Self {
+        Self::new(ColorWhen::Auto)
+    }
+}
+
+impl StatusPrinter {
+    fn new(color: ColorWhen) -> Self {
+        Self {
+            color_choice: color.color_choice(atty::Stream::Stderr),
+            quiet: false,
+        }
+    }
+
+    /// Print the message after the status, which is right-aligned and colored.
+    pub(crate) fn print(&self, color: Color, status: &str, message: impl std::fmt::Display) {
+        if self.quiet {
+            return;
+        }
+        let mut stderr = StandardStream::stderr(self.color_choice);
+        colored_ln(&mut stderr, |w| {
+            colored!(
+                w,
+                "{}{}{:>12}{} {}",
+                fg!(Some(color)),
+                bold!(true),
+                status,
+                reset!(),
+                message,
+            )
+        })
+        .expect("print failed");
+    }
+
+    /// Print an error, along with the chain of errors that caused it.
+    fn error(&self, error: &anyhow::Error) {
+        let mut stderr = StandardStream::stderr(self.color_choice);
+        colored_ln(&mut stderr, |w| {
+            colored!(
+                w,
+                "{}{}Error:{} {:?}",
+                fg!(Some(Color::Red)),
+                bold!(true),
+                reset!(),
+                error,
+            )
+        })
+        .expect("print failed");
+    }
+}
+
+/// Run `cargo semver-checks` with the arguments the process was started with,
+/// printing its output and returning the exit code it reports.
+pub fn run_cli() -> ExitCode {
+    cli::main()
+}
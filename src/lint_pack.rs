@@ -0,0 +1,224 @@
+//! Lint packs: shareable collections of checks, declared in the checked crate's manifest.
+//!
+//! ```toml
+//! [package.metadata.semver-checks.lint-packs]
+//! acme-semver-policies = "1.2"                        # from crates.io
+//! internal-policies = { version = "0.3", registry = "my-registry" }
+//! shared-policies = { git = "https://example.com/policies.git", branch = "main" }
+//! local-policies = { path = "../semver-policies" }
+//! ```
+//!
+//! Each entry is a dependency specification, as in the `[dependencies]` table.
+//! The root of each lint pack contains a `semver-checks-pack.toml` file:
+//!
+//! ```toml
+//! name = "acme"                  # the namespace of the pack's checks
+//! description = "ACME's API stability policies."
+//! checks = "checks"              # directory of `.ron` query files, relative to the pack root
+//! ```
+//!
+//! The ids of the pack's checks are namespaced by the pack's name, as in `acme::check_id`.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    query::SemverQuery,
+    rustdoc_gen::{find_dependency_root, PackageInfo},
+};
+
+/// The name of the metadata file at the root of every lint pack.
+const LINT_PACK_FILE: &str = "semver-checks-pack.toml";
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct LintPackManifest {
+    /// The namespace of the pack's checks.
+    name: String,
+
+    /// What the pack's checks are for. Not used, except to document the pack.
+    #[allow(dead_code)]
+    description: Option<String>,
+
+    /// The directory containing the pack's `.ron` query files, relative to the pack root.
+    #[serde(default = "default_checks_dir")]
+    checks: PathBuf,
+}
+
+fn default_checks_dir() -> PathBuf {
+    PathBuf::from("checks")
+}
+
+/// The `[package.metadata.semver-checks]` table of the checked crate's manifest.
+/// Its other keys set command-line options, and are read along with the command line.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct SemverChecksMetadata {
+    #[serde(default)]
+    lint_packs: BTreeMap<String, toml::Value>,
+}
+
+/// The manifest of the placeholder crate that depends on all the lint packs
+/// that need to be downloaded, so that Cargo can fetch them for us.
+#[derive(Debug, Serialize)]
+struct PlaceholderManifest<'a> {
+    package: PlaceholderPackage,
+    dependencies: BTreeMap<&'a str, &'a toml::Value>,
+    workspace: BTreeMap<String, toml::Value>,
+}
+
+#[derive(Debug, Serialize)]
+struct PlaceholderPackage {
+    name: &'static str,
+    version: &'static str,
+    edition: &'static str,
+    publish: bool,
+}
+
+/// Load the checks of all the lint packs declared by the package, with namespaced ids.
+/// Lint packs that aren't local paths are downloaded by Cargo into `work_dir`.
+pub(crate) fn load_lint_packs(
+    package: &PackageInfo,
+    manifest_path: &Path,
+    work_dir: &Path,
+) -> anyhow::Result<Vec<SemverQuery>> {
+    let metadata: SemverChecksMetadata = match package.metadata.get("semver-checks") {
+        Some(metadata) => serde_json::from_value(metadata.clone()).with_context(|| {
+            format!(
+                "Invalid [package.metadata.semver-checks] table in {:?}",
+                manifest_path
+            )
+        })?,
+        None => return Ok(vec![]),
+    };
+    if metadata.lint_packs.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let manifest_dir = manifest_path
+        .parent()
+        .expect("manifest path has no parent")
+        .to_path_buf();
+    let mut pack_roots = vec![];
+    let mut downloaded = BTreeMap::new();
+    for (name, spec) in &metadata.lint_packs {
+        match spec.get("path").and_then(toml::Value::as_str) {
+            Some(path) => pack_roots.push(manifest_dir.join(path)),
+            None => {
+                downloaded.insert(name.as_str(), spec);
+            }
+        }
+    }
+
+    if !downloaded.is_empty() {
+        let placeholder_manifest = write_placeholder_manifest(work_dir, &downloaded)?;
+        for (name, spec) in downloaded {
+            // The dependency may be renamed, in which case `package` is its real name.
+            let package_name = spec
+                .get("package")
+                .and_then(toml::Value::as_str)
+                .unwrap_or(name);
+            pack_roots.push(
+                find_dependency_root(&placeholder_manifest, package_name)
+                    .with_context(|| format!("Failed to download lint pack {name}"))?,
+            );
+        }
+    }
+
+    let mut queries = vec![];
+    for pack_root in pack_roots {
+        queries.extend(load_lint_pack(&pack_root)?);
+    }
+    Ok(queries)
+}
+
+/// Load the checks of the lint pack at the given directory, with namespaced ids.
+fn load_lint_pack(pack_root: &Path) -> anyhow::Result<Vec<SemverQuery>> {
+    let manifest_path = pack_root.join(LINT_PACK_FILE);
+    let manifest_contents = std::fs::read_to_string(&manifest_path)
+        .with_context(|| format!("Failed to read lint pack manifest {:?}", manifest_path))?;
+    let manifest: LintPackManifest = toml::from_str(&manifest_contents)
+        .with_context(|| format!("Failed to parse lint pack manifest {:?}", manifest_path))?;
+    if manifest.name.is_empty() || manifest.name.contains("::") {
+        bail!(
+            "Invalid lint pack name {:?} in {:?}",
+            manifest.name,
+            manifest_path
+        );
+    }
+
+    let mut queries = SemverQuery::load_dir(&pack_root.join(&manifest.checks))?;
+    for query in &mut queries {
+        query.id = format!("{}::{}", manifest.name, query.id);
+    }
+    Ok(queries)
+}
+
+fn write_placeholder_manifest(
+    work_dir: &Path,
+    dependencies: &BTreeMap<&str, &toml::Value>,
+) -> anyhow::Result<PathBuf> {
+    let dir = work_dir.join("lint-packs");
+    let src_dir = dir.join("src");
+    std::fs::create_dir_all(&src_dir)
+        .with_context(|| format!("Failed to create directory {:?}", src_dir))?;
+    std::fs::write(src_dir.join("lib.rs"), "")
+        .with_context(|| format!("Failed to write placeholder crate in {:?}", dir))?;
+
+    let manifest = PlaceholderManifest {
+        package: PlaceholderPackage {
+            name: "semver-checks-lint-packs-placeholder",
+            version: "0.0.0",
+            edition: "2021",
+            publish: false,
+        },
+        dependencies: dependencies.clone(),
+        workspace: BTreeMap::new(),
+    };
+    let manifest_path = dir.join("Cargo.toml");
+    std::fs::write(&manifest_path, toml::to_string(&manifest)?)
+        .with_context(|| format!("Failed to write placeholder manifest {:?}", manifest_path))?;
+
+    Ok(manifest_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::load_lint_pack;
+
+    #[test]
+    fn lint_pack_checks_are_namespaced() {
+        let pack_root = std::env::temp_dir().join(format!(
+            "cargo-semver-checks-lint-pack-{}",
+            std::process::id()
+        ));
+        let checks_dir = pack_root.join("policies");
+        std::fs::create_dir_all(&checks_dir).expect("failed to create directory");
+        std::fs::write(
+            pack_root.join("semver-checks-pack.toml"),
+            "name = \"acme\"\nchecks = \"policies\"\n",
+        )
+        .expect("failed to write pack manifest");
+        std::fs::write(
+            checks_dir.join("struct_missing.ron"),
+            include_str!("./queries/struct_missing.ron"),
+        )
+        .expect("failed to write query");
+
+        let queries = load_lint_pack(&pack_root).expect("failed to load lint pack");
+        assert_eq!(
+            vec!["acme::struct_missing"],
+            queries
+                .iter()
+                .map(|query| query.id.as_str())
+                .collect::<Vec<_>>(),
+        );
+
+        std::fs::remove_dir_all(&pack_root).expect("failed to remove directory");
+    }
+}
@@ -0,0 +1,87 @@
+use std::{collections::BTreeMap, io::Write};
+
+use serde_json::json;
+use termcolor::Color;
+use termcolor_output::{colored, colored_ln};
+
+use crate::{
+    query::{LintLevel, RequiredSemverUpdate, SemverQuery},
+    GlobalConfig,
+};
+
+fn required_bump(query: &SemverQuery) -> &'static str {
+    match query.required_update {
+        RequiredSemverUpdate::Major => "major",
+        RequiredSemverUpdate::Minor => "minor",
+    }
+}
+
+/// What the check's findings mean for the run: the version update they require,
+/// or only a warning.
+fn category(query: &SemverQuery) -> &'static str {
+    match query.lint_level {
+        LintLevel::Deny => required_bump(query),
+        LintLevel::Warn => "warning",
+    }
+}
+
+/// Print every available check, either as human-readable text or as a JSON array.
+pub(super) fn list_checks(
+    mut config: GlobalConfig,
+    queries: &BTreeMap<String, SemverQuery>,
+    as_json: bool,
+) -> anyhow::Result<()> {
+    if as_json {
+        let checks: Vec<_> = queries
+            .values()
+            .map(|query| {
+                json!({
+                    "id": query.id,
+                    "requiredUpdate": required_bump(query),
+                    "lintLevel": match query.lint_level {
+                        LintLevel::Deny => "deny",
+                        LintLevel::Warn => "warn",
+                    },
+                    "name": query.human_readable_name,
+                    "description": query.description,
+                    "referenceLink": query.reference_link,
+                })
+            })
+            .collect();
+        serde_json::to_writer_pretty(&mut config.output_writer, &checks)?;
+        writeln!(config.output_writer)?;
+        return Ok(());
+    }
+
+    let id_width = queries.keys().map(String::len).max().unwrap_or_default();
+    for query in queries.values() {
+        colored_ln(&mut config.output_writer, |w| {
+            colored!(
+                w,
+                "{}{}{} {:^7} {}",
+                bold!(true),
+                format!("{:<id_width$}", query.id),
+                reset!(),
+                category(query),
+                query.human_readable_name,
+            )
+        })
+        .expect("print failed");
+        if let Some(reference_link) = query.reference_link.as_deref() {
+            colored_ln(&mut config.output_writer, |w| {
+                colored!(
+                    w,
+                    "{} {:7} {}{}{}",
+                    " ".repeat(id_width),
+                    "",
+                    fg!(Some(Color::Cyan)),
+                    reference_link,
+                    reset!(),
+                )
+            })
+            .expect("print failed");
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,138 @@
+//! Debug output for diagnosing problems, enabled with the `RUST_LOG` environment variable.
+//!
+//! Modules emit `tracing` events, and each check runs in a `check` span, so library users get
+//! the same events in the subscriber they install, or in their `log` logger if they have none.
+//! The command line tool prints them to stderr as cargo-style status lines, like the rest of
+//! its output.
+
+use std::fmt;
+
+use termcolor::{StandardStream, WriteColor};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::{
+    fmt::{format::Writer, FmtContext, FormatEvent, FormatFields, FormattedFields},
+    registry::LookupSpan,
+    EnvFilter,
+};
+
+use crate::ColorWhen;
+
+/// The environment variable with the directives that select the events to print, as parsed
+/// by [`EnvFilter`]: a level like `debug`, or `target=level` for the modules whose path starts
+/// with the target, like `cargo_semver_checks::baseline=trace`.
+const LOG_ENV_VAR: &str = "RUST_LOG";
+
+/// Formats each event like cargo's status lines: its level right-aligned and colored,
+/// then its target, the spans it's in, and its message.
+struct StatusFormat;
+
+impl<S, N> FormatEvent<S, N> for StatusFormat
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+    N: for<'a> FormatFields<'a> + 'static,
+{
+    fn format_event(
+        &self,
+        ctx: &FmtContext<'_, S, N>,
+        mut writer: Writer<'_>,
+        event: &Event<'_>,
+    ) -> fmt::Result {
+        let metadata = event.metadata();
+        let color = match *metadata.level() {
+            Level::ERROR => "31",
+            Level::WARN => "33",
+            Level::INFO => "32",
+            Level::DEBUG => "36",
+            Level::TRACE => "35",
+        };
+        let level = format!("{:>12}", metadata.level());
+        if writer.has_ansi_escapes() {
+            write!(writer, "\x1b[1;{color}m{level}\x1b[0m ")?;
+        } else {
+            write!(writer, "{level} ")?;
+        }
+        write!(writer, "{}: ", metadata.target())?;
+        if let Some(scope) = ctx.event_scope() {
+            for span in scope.from_root() {
+                write!(writer, "{}", span.name())?;
+                if let Some(fields) = span.extensions().get::<FormattedFields<N>>() {
+                    if !fields.is_empty() {
+                        write!(writer, "{{{fields}}}")?;
+                    }
+                }
+                write!(writer, ": ")?;
+            }
+        }
+        ctx.field_format().format_fields(writer.by_ref(), event)?;
+        writeln!(writer)
+    }
+}
+
+/// Print the events that `RUST_LOG` selects, if it's set.
+pub(crate) fn init(color: ColorWhen) {
+    let filter = match EnvFilter::try_from_env(LOG_ENV_VAR) {
+        Ok(filter) => filter,
+        Err(_) => return,
+    };
+    let ansi = StandardStream::stderr(color.color_choice(atty::Stream::Stderr)).supports_color();
+    // Only fails if a subscriber was already installed, which then keeps receiving the events.
+    let _ = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_writer(std::io::stderr)
+        .with_ansi(ansi)
+        .event_format(StatusFormat)
+        .try_init();
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::Write,
+        sync::{Arc, Mutex},
+    };
+
+    use tracing::{debug, debug_span, trace};
+    use tracing_subscriber::EnvFilter;
+
+    use super::StatusFormat;
+
+    /// Collects what's printed, in place of stderr.
+    #[derive(Clone, Default)]
+    struct Output(Arc<Mutex<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn events_are_printed_as_status_lines_with_their_spans() {
+        let output = Output::default();
+        let writer = output.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_env_filter(EnvFilter::new("cargo_semver_checks=debug"))
+            .with_writer(move || writer.clone())
+            .with_ansi(false)
+            .event_format(StatusFormat)
+            .finish();
+
+        tracing::subscriber::with_default(subscriber, || {
+            debug!("loading baseline");
+            let _check = debug_span!("check", id = %"struct_missing").entered();
+            debug!(found = 2, "finished");
+            trace!("not printed");
+        });
+
+        let output = String::from_utf8(output.0.lock().unwrap().clone()).unwrap();
+        assert_eq!(
+            "       DEBUG cargo_semver_checks::logging::tests: loading baseline\n       \
+            DEBUG cargo_semver_checks::logging::tests: check{id=struct_missing}: finished found=2\n",
+            output
+        );
+    }
+}
@@ -0,0 +1,117 @@
+use std::path::Path;
+
+use anyhow::{bail, Context};
+
+/// Set the `version` field in the `[package]` table of the manifest at `manifest_path`.
+///
+/// Only the version string itself is replaced, so the rest of the manifest's
+/// formatting and comments are preserved.
+pub(crate) fn set_package_version(
+    manifest_path: &Path,
+    version: &semver::Version,
+) -> anyhow::Result<()> {
+    let contents = std::fs::read_to_string(manifest_path)
+        .with_context(|| format!("Failed to read manifest {:?}", manifest_path))?;
+    let updated = replace_package_version(&contents, &version.to_string())
+        .with_context(|| format!("Failed to update version in manifest {:?}", manifest_path))?;
+    std::fs::write(manifest_path, updated)
+        .with_context(|| format!("Failed to write manifest {:?}", manifest_path))
+}
+
+fn replace_package_version(contents: &str, version: &str) -> anyhow::Result<String> {
+    let mut output = String::with_capacity(contents.len());
+    let mut in_package_table = false;
+    let mut replaced = false;
+
+    for line in contents.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        if trimmed.starts_with('[') {
+            let header = trimmed
+                .split(']')
+                .next()
+                .expect("split always yields at least one element")
+                .trim_start_matches('[')
+                .trim();
+            in_package_table = header == "package";
+        } else if in_package_table && !replaced {
+            if let Some(rest) = trimmed.strip_prefix("version") {
+                let rest = rest.trim_start();
+                if rest.starts_with('.') {
+                    bail!("the package version is inherited from the workspace, edit the workspace manifest instead");
+                }
+                if let Some(value) = rest.strip_prefix('=') {
+                    let value = value.trim_start();
+                    let quote = value
+                        .chars()
+                        .next()
+                        .filter(|c| *c == '"' || *c == '\'')
+                        .context("the package version is not a string")?;
+                    let quote_start = line.len() - value.len();
+                    let quote_end = line[quote_start + 1..]
+                        .find(quote)
+                        .map(|idx| quote_start + 1 + idx)
+                        .context("the package version string is not terminated")?;
+
+                    output.push_str(&line[..=quote_start]);
+                    output.push_str(version);
+                    output.push_str(&line[quote_end..]);
+                    replaced = true;
+                    continue;
+                }
+            }
+        }
+
+        output.push_str(line);
+    }
+
+    if !replaced {
+        bail!("no version field found in the [package] table");
+    }
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::replace_package_version;
+
+    #[test]
+    fn only_the_package_version_is_replaced() {
+        let manifest = r#"[package]
+name = "foo"   # the name
+version   =  "0.4.2"  # the version
+
+[dependencies]
+bar = { version = "1.0.0" }
+"#;
+        let expected = r#"[package]
+name = "foo"   # the name
+version   =  "0.5.0"  # the version
+
+[dependencies]
+bar = { version = "1.0.0" }
+"#;
+        assert_eq!(
+            expected,
+            replace_package_version(manifest, "0.5.0").expect("failed to replace")
+        );
+    }
+
+    #[test]
+    fn version_outside_package_table_is_ignored() {
+        let manifest =
+            "[workspace.package]\r\nversion = '1.0.0'\r\n\r\n[package]\r\nversion = '1.0.0'\r\n";
+        let expected =
+            "[workspace.package]\r\nversion = '1.0.0'\r\n\r\n[package]\r\nversion = '2.0.0'\r\n";
+        assert_eq!(
+            expected,
+            replace_package_version(manifest, "2.0.0").expect("failed to replace")
+        );
+    }
+
+    #[test]
+    fn inherited_version_is_an_error() {
+        let manifest = "[package]\nname = \"foo\"\nversion.workspace = true\n";
+        assert!(replace_package_version(manifest, "2.0.0").is_err());
+        assert!(replace_package_version("[package]\nname = \"foo\"\n", "2.0.0").is_err());
+    }
+}
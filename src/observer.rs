@@ -0,0 +1,435 @@
+//! Notifications about the progress of a `check-release` run, for rendering it as it happens.
+
+use std::{io::Write, time::Duration};
+
+use termcolor::Color;
+use termcolor_output::{colored, colored_ln};
+
+use crate::{
+    check::Violation,
+    query::{LintLevel, RequiredSemverUpdate},
+    GlobalConfig, Verbosity,
+};
+
+/// What is about to be checked, when a run starts.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct RunInfo<'a> {
+    pub baseline_version: Option<&'a str>,
+    pub current_version: Option<&'a str>,
+
+    /// The version change between the baseline and the current version,
+    /// like `minor change`, or the release type it was overridden with.
+    pub change: &'a str,
+
+    /// How many checks will run, and how many are skipped because the change allows
+    /// the kinds of changes they look for.
+    pub checks: usize,
+    pub skipped: usize,
+
+    /// The compiler flags and profile the rustdoc JSON was generated with, formatted for display.
+    pub build_flags: Vec<String>,
+
+    /// The enabled crate features, including default features and the features they enable.
+    /// Empty if none are, or if rustdoc JSON wasn't generated for a local package.
+    pub features: &'a [String],
+
+    /// The target triples the crates are compared on, if known.
+    pub targets: &'a [String],
+
+    /// The version of the toolchain that generated rustdoc JSON, if any was generated.
+    pub toolchain: Option<&'a str>,
+
+    /// The rustdoc JSON format versions of the baseline and current crates.
+    pub baseline_format_version: u32,
+    pub current_format_version: u32,
+}
+
+/// A check that is run or skipped.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct CheckInfo<'a> {
+    pub id: &'a str,
+    pub name: &'a str,
+    pub required_update: RequiredSemverUpdate,
+    /// Checks at the `warn` level report what they find without failing the run.
+    pub lint_level: LintLevel,
+}
+
+/// One of several comparisons in a run: of a target, or against one of several baselines.
+#[derive(Debug, Clone, Copy)]
+#[non_exhaustive]
+pub struct ComparisonInfo<'a> {
+    /// The target, or the baseline version and target if any.
+    pub label: &'a str,
+    pub is_baseline: bool,
+}
+
+/// Receives the events of a run as they happen. All methods do nothing by default,
+/// so implementations only need the ones they render.
+///
+/// Checks are run once per comparison, so with several targets or baselines,
+/// every check is started, and then passes or fails, once per comparison.
+/// While a check runs, each violation it finds is reported as a result as soon as it's found.
+/// Violations are then merged across comparisons, so they're found after all checks ran.
+pub trait CheckObserver {
+    /// The version change couldn't be determined from the crate versions,
+    /// so checks run as if the version didn't change.
+    fn version_change_unknown(&mut self) {}
+
+    fn run_started(&mut self, _run: &RunInfo) {}
+
+    fn check_skipped(&mut self, _check: &CheckInfo) {}
+
+    fn comparison_started(&mut self, _comparison: &ComparisonInfo) {}
+
+    fn check_started(&mut self, _check: &CheckInfo) {}
+
+    /// The check found a violation in the current comparison while running. Neither its docs
+    /// link nor its impact are known yet, and the same item may be found again in other
+    /// comparisons or at other paths: `violation_found` reports it once it's merged.
+    fn result_found(&mut self, _check: &CheckInfo, _violation: &Violation) {}
+
+    fn check_passed(&mut self, _check: &CheckInfo, _duration: Duration) {}
+
+    fn check_failed(&mut self, _check: &CheckInfo, _duration: Duration, _violations: usize) {}
+
+    /// All checks of a comparison ran. The label is `None` if there's only one comparison.
+    fn comparison_finished(&mut self, _label: Option<&str>, _duration: Duration) {}
+
+    fn violation_found(&mut self, _check: &CheckInfo, _violation: &Violation) {}
+
+    /// All violations were found, and rendered with the checks' templates.
+    fn results_described(&mut self, _duration: Duration) {}
+}
+
+/// Ignores every event.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SilentObserver;
+
+impl CheckObserver for SilentObserver {}
+
+/// Prints the progress of the run as cargo-style colored status lines,
+/// with as much detail as the verbosity asks for.
+pub(crate) struct TerminalObserver<'a> {
+    config: &'a mut GlobalConfig,
+    /// The version change of the run, to explain why checks are skipped.
+    change: String,
+    /// How many violations the running check found so far.
+    found: usize,
+}
+
+impl<'a> TerminalObserver<'a> {
+    pub(crate) fn new(config: &'a mut GlobalConfig) -> Self {
+        Self {
+            config,
+            change: String::new(),
+            found: 0,
+        }
+    }
+
+    /// Print the line shown while the check runs, with how many violations it found so far.
+    fn print_running_line(&mut self, check: &CheckInfo) {
+        let found = if self.found > 0 {
+            format!("{} found", self.found)
+        } else {
+            String::new()
+        };
+        colored!(
+            self.config.output_writer,
+            "{}{}{:>12}{} [{:>9}] {:^18} {}",
+            fg!(Some(Color::Cyan)),
+            bold!(true),
+            "Running",
+            reset!(),
+            found,
+            category(check),
+            check.id,
+        )
+        .expect("print failed");
+        self.config.output_writer.flush().expect("flush failed");
+    }
+
+    /// Return to the start of the line, to overwrite the `Running` line of the check.
+    fn finish_running_line(&mut self) {
+        if self.config.printing_to_terminal {
+            write!(self.config.output_writer, "\r").expect("print failed");
+        }
+    }
+}
+
+fn category(check: &CheckInfo) -> &'static str {
+    match (check.lint_level, check.required_update) {
+        (LintLevel::Warn, _) => "warning",
+        (LintLevel::Deny, RequiredSemverUpdate::Major) => "major",
+        (LintLevel::Deny, RequiredSemverUpdate::Minor) => "minor",
+    }
+}
+
+impl<'a> CheckObserver for TerminalObserver<'a> {
+    fn version_change_unknown(&mut self) {
+        if self.config.verbosity < Verbosity::Normal {
+            return;
+        }
+        colored_ln(&mut self.config.output_writer, |w| {
+            colored!(
+                w,
+                "{}{}{:>12}{} Could not determine whether crate version changed. Assuming no change.",
+                fg!(Some(Color::Yellow)),
+                bold!(true),
+                "Warning",
+                reset!(),
+            )
+        })
+        .expect("print failed");
+    }
+
+    fn run_started(&mut self, run: &RunInfo) {
+        self.change = run.change.to_string();
+        if self.config.verbosity < Verbosity::Normal {
+            return;
+        }
+
+        let skipped_note = if run.skipped > 0 {
+            format!(" ({} checks skipped)", run.skipped)
+        } else {
+            String::new()
+        };
+        colored_ln(&mut self.config.output_writer, |w| {
+            colored!(
+                w,
+                "{}{}{:>12}{} {}{}{} checks{}, version {} -> {} ({})",
+                fg!(Some(Color::Green)),
+                bold!(true),
+                "Starting",
+                reset!(),
+                bold!(true),
+                run.checks,
+                reset!(),
+                skipped_note,
+                run.baseline_version.unwrap_or("unknown"),
+                run.current_version.unwrap_or("unknown"),
+                run.change,
+            )
+        })
+        .expect("print failed");
+
+        // Flags can change the crate's API, so show them to make the run reproducible.
+        if !run.build_flags.is_empty() {
+            colored_ln(&mut self.config.output_writer, |w| {
+                colored!(
+                    w,
+                    "{}{}{:>12}{} built with {}",
+                    fg!(Some(Color::Cyan)),
+                    bold!(true),
+                    "Flags",
+                    reset!(),
+                    run.build_flags.join(", "),
+                )
+            })
+            .expect("print failed");
+        }
+
+        // Like the flags, these decide which API is checked, so results are only
+        // comparable across runs in which they're the same.
+        let mut environment = vec![];
+        if !run.features.is_empty() {
+            environment.push(format!("features {}", run.features.join(", ")));
+        }
+        if !run.targets.is_empty() {
+            environment.push(format!("target {}", run.targets.join(", ")));
+        }
+        if let Some(toolchain) = run.toolchain {
+            environment.push(format!("toolchain {toolchain}"));
+        }
+        environment.push(format!(
+            "rustdoc format v{} -> v{}",
+            run.baseline_format_version, run.current_format_version
+        ));
+        colored_ln(&mut self.config.output_writer, |w| {
+            colored!(
+                w,
+                "{}{}{:>12}{} {}",
+                fg!(Some(Color::Cyan)),
+                bold!(true),
+                "Environment",
+                reset!(),
+                environment.join("; "),
+            )
+        })
+        .expect("print failed");
+    }
+
+    fn check_skipped(&mut self, check: &CheckInfo) {
+        if self.config.verbosity < Verbosity::Verbose {
+            return;
+        }
+        let change = &self.change;
+        colored_ln(&mut self.config.output_writer, |w| {
+            colored!(
+                w,
+                "{}{}{:>12}{} [{:9}] {:^18} {}: allowed by {}",
+                fg!(Some(Color::Yellow)),
+                bold!(true),
+                "SKIP",
+                reset!(),
+                "",
+                category(check),
+                check.id,
+                change,
+            )
+        })
+        .expect("print failed");
+    }
+
+    fn comparison_started(&mut self, comparison: &ComparisonInfo) {
+        if self.config.verbosity < Verbosity::Normal {
+            return;
+        }
+        colored_ln(&mut self.config.output_writer, |w| {
+            colored!(
+                w,
+                "{}{}{:>12}{} {} {}",
+                fg!(Some(Color::Green)),
+                bold!(true),
+                "Checking",
+                reset!(),
+                if comparison.is_baseline {
+                    "against baseline"
+                } else {
+                    "target"
+                },
+                comparison.label,
+            )
+        })
+        .expect("print failed");
+    }
+
+    fn check_started(&mut self, check: &CheckInfo) {
+        self.found = 0;
+        // The line is only useful while the check runs, and is overwritten by its result.
+        if !self.config.printing_to_terminal || self.config.verbosity < Verbosity::Normal {
+            return;
+        }
+        self.print_running_line(check);
+    }
+
+    fn result_found(&mut self, check: &CheckInfo, violation: &Violation) {
+        self.found += 1;
+        if self.config.verbosity < Verbosity::Normal {
+            return;
+        }
+        if self.config.verbosity >= Verbosity::Verbose {
+            // Print the violation in place of the `Running` line, padded to overwrite all of it,
+            // and then the `Running` line again below it.
+            self.finish_running_line();
+            let width = if self.config.printing_to_terminal {
+                check.id.len()
+            } else {
+                0
+            };
+            let message = format!("{:width$}", violation.message);
+            colored_ln(&mut self.config.output_writer, |w| {
+                colored!(
+                    w,
+                    "{}{}{:>12}{} [{:9}] {:^18} {}",
+                    fg!(Some(Color::Yellow)),
+                    bold!(true),
+                    "Found",
+                    reset!(),
+                    "",
+                    category(check),
+                    message,
+                )
+            })
+            .expect("print failed");
+        }
+        if self.config.printing_to_terminal {
+            self.finish_running_line();
+            self.print_running_line(check);
+        }
+    }
+
+    fn check_passed(&mut self, check: &CheckInfo, duration: Duration) {
+        if self.config.verbosity < Verbosity::Normal {
+            return;
+        }
+        self.finish_running_line();
+        colored_ln(&mut self.config.output_writer, |w| {
+            colored!(
+                w,
+                "{}{}{:>12}{} [{:>8.3}s] {:^18} {}",
+                fg!(Some(Color::Green)),
+                bold!(true),
+                "PASS",
+                reset!(),
+                duration.as_secs_f32(),
+                category(check),
+                check.id,
+            )
+        })
+        .expect("print failed");
+    }
+
+    fn check_failed(&mut self, check: &CheckInfo, duration: Duration, _violations: usize) {
+        if self.config.verbosity < Verbosity::Normal {
+            return;
+        }
+        self.finish_running_line();
+        let (color, status) = match check.lint_level {
+            LintLevel::Deny => (Color::Red, "FAIL"),
+            LintLevel::Warn => (Color::Yellow, "WARN"),
+        };
+        colored_ln(&mut self.config.output_writer, |w| {
+            colored!(
+                w,
+                "{}{}{:>12}{} [{:>8.3}s] {:^18} {}",
+                fg!(Some(color)),
+                bold!(true),
+                status,
+                reset!(),
+                duration.as_secs_f32(),
+                category(check),
+                check.id,
+            )
+        })
+        .expect("print failed");
+    }
+
+    fn comparison_finished(&mut self, label: Option<&str>, duration: Duration) {
+        if self.config.verbosity < Verbosity::VeryVerbose {
+            return;
+        }
+        colored_ln(&mut self.config.output_writer, |w| {
+            colored!(
+                w,
+                "{}{}{:>12}{} [{:>8.3}s] all checks for {}",
+                fg!(Some(Color::Cyan)),
+                bold!(true),
+                "Timing",
+                reset!(),
+                duration.as_secs_f32(),
+                label.unwrap_or("host target"),
+            )
+        })
+        .expect("print failed");
+    }
+
+    fn results_described(&mut self, duration: Duration) {
+        if self.config.verbosity < Verbosity::VeryVerbose {
+            return;
+        }
+        colored_ln(&mut self.config.output_writer, |w| {
+            colored!(
+                w,
+                "{}{}{:>12}{} [{:>8.3}s] describing results",
+                fg!(Some(Color::Cyan)),
+                bold!(true),
+                "Timing",
+                reset!(),
+                duration.as_secs_f32(),
+            )
+        })
+        .expect("print failed");
+    }
+}
@@ -1,16 +1,29 @@
-use std::collections::BTreeMap;
+use std::{collections::BTreeMap, ffi::OsStr, path::Path, sync::Arc};
 
-use serde::{Deserialize, Serialize};
-use trustfall_core::ir::TransparentValue;
+use anyhow::{anyhow, bail, Context};
+use clap::crate_version;
+use trustfall_core::{
+    frontend::parse,
+    ir::{indexed::IndexedQuery, IRQuery, TransparentValue},
+    schema::Schema,
+};
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
-pub(crate) enum RequiredSemverUpdate {
-    Major,
-    Minor,
+use crate::{
+    adapter::{RustdocAdapter, MIN_SUPPORTED_SCHEMA_VERSION, SCHEMA_VERSION},
+    templates::template_registry,
+};
+
+pub use crate::query_definition::{LintLevel, RequiredSemverUpdate, SemverQuery};
+
+impl LintLevel {
+    pub(crate) fn is_warning(self) -> bool {
+        self == LintLevel::Warn
+    }
 }
 
+/// The version update a release makes, or is intended to make.
 #[derive(Debug, Clone, Copy)]
-pub(crate) enum ActualSemverUpdate {
+pub enum ActualSemverUpdate {
     Major,
     Minor,
     Patch,
@@ -28,59 +41,341 @@ impl ActualSemverUpdate {
     }
 }
 
-/// A query that can be executed on a pair of rustdoc output files,
-/// returning instances of a particular kind of semver violation.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub(crate) struct SemverQuery {
-    pub(crate) id: String,
+impl SemverQuery {
+    /// The built-in queries, which `build.rs` parses and validates at build time.
+    pub(crate) fn all_queries() -> BTreeMap<String, SemverQuery> {
+        let mut queries = BTreeMap::default();
 
-    pub(crate) human_readable_name: String,
+        let builtin: Vec<SemverQuery> = include!(concat!(env!("OUT_DIR"), "/builtin_queries.rs"));
+        for query in builtin {
+            let id_conflict = queries.insert(query.id.clone(), query);
+            assert!(id_conflict.is_none(), "{:?}", id_conflict);
+        }
 
-    pub(crate) description: String,
+        queries
+    }
 
-    pub(crate) required_update: RequiredSemverUpdate,
+    /// The query, compiled to Trustfall IR.
+    pub(crate) fn compile(&self, schema: &Schema) -> anyhow::Result<Arc<IndexedQuery>> {
+        match self.compiled_query {
+            Some(compiled_query) => {
+                let ir: IRQuery = serde_json::from_str(compiled_query)
+                    .with_context(|| format!("Check {:?} was not compiled correctly", self.id))?;
+                let indexed_query = IndexedQuery::try_from(ir).map_err(|e| {
+                    anyhow!("Check {:?} was not compiled correctly: {e:?}", self.id)
+                })?;
+                Ok(Arc::new(indexed_query))
+            }
+            None => parse(schema, &self.query)
+                .with_context(|| format!("Check {:?} does not contain a valid query", self.id)),
+        }
+    }
 
-    #[serde(default)]
-    pub(crate) reference_link: Option<String>,
+    /// The built-in queries, together with the user-supplied queries
+    /// in the `.ron` files in `extra_dir`, if any.
+    pub(crate) fn all_queries_with_extra(
+        extra_dir: Option<&Path>,
+    ) -> anyhow::Result<BTreeMap<String, SemverQuery>> {
+        let mut queries = Self::all_queries();
+        if let Some(extra_dir) = extra_dir {
+            add_queries(&mut queries, Self::load_dir(extra_dir)?)?;
+        }
+        Ok(queries)
+    }
 
-    pub(crate) query: String,
+    /// Load the user-supplied queries in the `.ron` files in `dir`.
+    pub(crate) fn load_dir(dir: &Path) -> anyhow::Result<Vec<SemverQuery>> {
+        let mut paths = vec![];
+        for entry in std::fs::read_dir(dir)
+            .with_context(|| format!("Failed to read checks directory {:?}", dir))?
+        {
+            let path = entry
+                .with_context(|| format!("Failed to read checks directory {:?}", dir))?
+                .path();
+            if path.is_file() && path.extension() == Some(OsStr::new("ron")) {
+                paths.push(path);
+            }
+        }
+        paths.sort();
 
-    #[serde(default)]
-    pub(crate) arguments: BTreeMap<String, TransparentValue>,
+        let schema = RustdocAdapter::schema();
+        paths
+            .iter()
+            .map(|path| Self::load_from_file(&schema, path))
+            .collect()
+    }
 
-    /// The top-level error describing the semver violation that was detected.
-    /// Even if multiple instances of this semver issue are found, this error
-    /// message is displayed only at most once.
-    pub(crate) error_message: String,
+    /// Parse a check in the same `.ron` format as the built-in ones,
+    /// ensuring its query is valid for the rustdoc schema and its templates are valid.
+    pub fn from_ron_str(query_text: &str) -> anyhow::Result<Self> {
+        Self::parse_and_validate(&RustdocAdapter::schema(), query_text, None)
+    }
 
-    /// Optional template that can be combined with each query output to produce
-    /// a human-readable description of the specific semver violation that was discovered.
-    #[serde(default)]
-    pub(crate) per_result_error_template: Option<String>,
-}
+    /// Load a check from a `.ron` file, like those passed to `--extra-checks`.
+    pub fn from_file(path: &Path) -> anyhow::Result<Self> {
+        Self::load_from_file(&RustdocAdapter::schema(), path)
+    }
 
-impl SemverQuery {
-    pub(crate) fn all_queries() -> BTreeMap<String, SemverQuery> {
-        let mut queries = BTreeMap::default();
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Whether the version change allows what the check looks for, so it needn't run.
+    /// Warnings never fail the run, so they're reported whatever the version change.
+    pub(crate) fn allowed_by(&self, version_change: ActualSemverUpdate) -> bool {
+        !self.lint_level.is_warning() && version_change.supports_requirement(self.required_update)
+    }
+
+    /// Load a user-supplied query, ensuring it's valid for the rustdoc schema.
+    fn load_from_file(schema: &Schema, path: &Path) -> anyhow::Result<Self> {
+        let query_text = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read check file {:?}", path))?;
+        let mut query = Self::parse_and_validate(schema, &query_text, Some(path))?;
+        query.source_path = Some(path.to_path_buf());
+        Ok(query)
+    }
 
-        let query_text_contents = [
-            include_str!("./queries/enum_missing.ron"),
-            include_str!("./queries/enum_variant_added.ron"),
-            include_str!("./queries/enum_variant_missing.ron"),
-            include_str!("./queries/struct_marked_non_exhaustive.ron"),
-            include_str!("./queries/struct_missing.ron"),
-            include_str!("./queries/struct_pub_field_missing.ron"),
-            include_str!("./queries/unit_struct_changed_kind.ron"),
-            include_str!("./queries/variant_marked_non_exhaustive.ron"),
-            include_str!("./queries/function_missing.ron"),
+    /// Parse a user-supplied query, read from `path` if it came from a file.
+    fn parse_and_validate(
+        schema: &Schema,
+        query_text: &str,
+        path: Option<&Path>,
+    ) -> anyhow::Result<Self> {
+        let location = path
+            .map(|path| format!(" in {:?}", path))
+            .unwrap_or_default();
+        let query: SemverQuery = ron::from_str(query_text).with_context(|| match path {
+            Some(path) => format!("Failed to parse check file {:?}", path),
+            None => "Failed to parse check".to_string(),
+        })?;
+
+        if query.min_schema_version > SCHEMA_VERSION {
+            bail!(
+                "Check {:?}{} requires schema version {}, but this version of \
+                cargo-semver-checks only supports up to schema version {}. \
+                Upgrade cargo-semver-checks to run it.",
+                query.id,
+                location,
+                query.min_schema_version,
+                SCHEMA_VERSION,
+            );
+        }
+        if query.min_schema_version < MIN_SUPPORTED_SCHEMA_VERSION {
+            bail!(
+                "Check {:?}{} was written for schema version {}, but this version of \
+                cargo-semver-checks only supports schema versions {} to {}. \
+                Update the check for the current schema, printed by `cargo semver-checks schema`, \
+                and set its min_schema_version.",
+                query.id,
+                location,
+                query.min_schema_version,
+                MIN_SUPPORTED_SCHEMA_VERSION,
+                SCHEMA_VERSION,
+            );
+        }
+        parse(schema, &query.query).with_context(|| {
+            format!(
+                "Check {:?}{} does not contain a valid query for schema version {}",
+                query.id, location, SCHEMA_VERSION,
+            )
+        })?;
+        let templates = [
+            (
+                "per_result_error_template",
+                &query.per_result_error_template,
+            ),
+            ("witness_template", &query.witness_template),
         ];
-        for query_text in query_text_contents {
-            let query: SemverQuery = ron::from_str(query_text).expect("query failed to parse");
-            let id_conflict = queries.insert(query.id.clone(), query);
-            assert!(id_conflict.is_none(), "{:?}", id_conflict);
+        for (name, template) in templates {
+            if let Some(template) = template.as_deref() {
+                template_registry()
+                    .register_template_string(name, template)
+                    .with_context(|| {
+                        format!("Check {:?}{} has an invalid {}", query.id, location, name)
+                    })?;
+            }
         }
 
-        queries
+        Ok(query)
+    }
+
+    /// Where to find the query's implementation: the path of a user-supplied query file,
+    /// or a link to the source of a built-in query.
+    pub(crate) fn implementation_link(&self) -> String {
+        match &self.source_path {
+            Some(path) => path.display().to_string(),
+            None if self.is_builtin => format!(
+                "https://github.com/obi1kenobi/cargo-semver-check/tree/v{}/src/queries/{}.ron",
+                crate_version!(),
+                self.id,
+            ),
+            None => "registered at runtime".to_string(),
+        }
+    }
+}
+
+/// Add user-supplied queries to a set of queries, ensuring their ids are unique.
+pub(crate) fn add_queries(
+    queries: &mut BTreeMap<String, SemverQuery>,
+    new_queries: Vec<SemverQuery>,
+) -> anyhow::Result<()> {
+    for query in new_queries {
+        if let Some(existing) = queries.get(&query.id) {
+            bail!(
+                "Check {:?} in {} has the same id as the check in {}",
+                query.id,
+                query.implementation_link(),
+                existing.implementation_link(),
+            );
+        }
+        queries.insert(query.id.clone(), query);
+    }
+    Ok(())
+}
+
+/// The checks to run: the built-in ones, and those registered at runtime by a program
+/// using the library, like an organization's own policies.
+#[derive(Debug, Clone)]
+pub struct QueryRegistry {
+    queries: BTreeMap<String, SemverQuery>,
+}
+
+impl Default for QueryRegistry {
+    fn default() -> Self {
+        Self {
+            queries: SemverQuery::all_queries(),
+        }
+    }
+}
+
+impl QueryRegistry {
+    /// A registry of the built-in checks.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a check, which must not have the same id as one that's already registered.
+    pub fn register(&mut self, query: SemverQuery) -> anyhow::Result<()> {
+        add_queries(&mut self.queries, vec![query])
+    }
+
+    /// Add a check in the `.ron` format, validating it against the schema.
+    pub fn register_ron_str(&mut self, query_text: &str) -> anyhow::Result<()> {
+        self.register(SemverQuery::from_ron_str(query_text)?)
+    }
+
+    /// Add the checks in the `.ron` files in `dir`, like `--extra-checks` does.
+    pub fn register_dir(&mut self, dir: &Path) -> anyhow::Result<()> {
+        add_queries(&mut self.queries, SemverQuery::load_dir(dir)?)
+    }
+
+    /// Make a check's findings fail the run, or only warn, like `--deny` and `--warn` do.
+    pub fn set_lint_level(&mut self, id: &str, lint_level: LintLevel) -> anyhow::Result<()> {
+        match self.queries.get_mut(id) {
+            Some(query) => query.lint_level = lint_level,
+            None => return Err(unknown_check_id(id, &self.queries)),
+        }
+        Ok(())
+    }
+
+    /// The registered checks, by id.
+    pub fn queries(&self) -> impl Iterator<Item = &SemverQuery> {
+        self.queries.values()
+    }
+
+    pub(crate) fn into_queries(self) -> BTreeMap<String, SemverQuery> {
+        self.queries
+    }
+}
+
+/// The error for a check id that doesn't match any of the given queries.
+pub(crate) fn unknown_check_id(id: &str, queries: &BTreeMap<String, SemverQuery>) -> anyhow::Error {
+    anyhow!(
+        "unknown check id {:?}, expected one of: {}",
+        id,
+        queries.keys().cloned().collect::<Vec<_>>().join(", "),
+    )
+}
+
+/// Change the lint level of the given checks, as with `--warn` and `--deny`.
+/// Checks listed in both are denied.
+pub(crate) fn set_lint_levels(
+    queries: &mut BTreeMap<String, SemverQuery>,
+    warn: &[String],
+    deny: &[String],
+) -> anyhow::Result<()> {
+    let levels = warn
+        .iter()
+        .map(|id| (id, LintLevel::Warn))
+        .chain(deny.iter().map(|id| (id, LintLevel::Deny)));
+    for (id, lint_level) in levels {
+        match queries.get_mut(id) {
+            Some(query) => query.lint_level = lint_level,
+            None => return Err(unknown_check_id(id, queries)),
+        }
+    }
+    Ok(())
+}
+
+/// Which queries to run, as selected with `--only` and `--skip`.
+/// By default, all the built-in queries are selected.
+#[derive(Debug, Clone)]
+pub(crate) struct QuerySelection {
+    queries: BTreeMap<String, SemverQuery>,
+    only: Vec<String>,
+    skip: Vec<String>,
+}
+
+impl Default for QuerySelection {
+    fn default() -> Self {
+        Self {
+            queries: SemverQuery::all_queries(),
+            only: vec![],
+            skip: vec![],
+        }
+    }
+}
+
+impl QuerySelection {
+    /// Select only the queries in `only`, if any are listed, except those in `skip`.
+    /// Unknown query ids are an error, so that a typo doesn't silently change what's checked,
+    /// and so is selecting no queries at all, which would always pass.
+    pub(crate) fn new(
+        queries: BTreeMap<String, SemverQuery>,
+        only: Vec<String>,
+        skip: Vec<String>,
+    ) -> anyhow::Result<Self> {
+        for id in only.iter().chain(skip.iter()) {
+            if !queries.contains_key(id) {
+                return Err(unknown_check_id(id, &queries));
+            }
+        }
+        let selection = Self {
+            queries,
+            only,
+            skip,
+        };
+        if selection.queries().is_empty() {
+            if selection.only.is_empty() && selection.skip.is_empty() {
+                bail!("no checks are selected: there are no checks to run");
+            }
+            bail!("no checks are selected: --only and --skip together exclude every check");
+        }
+        Ok(selection)
+    }
+
+    pub(crate) fn is_selected(&self, query_id: &str) -> bool {
+        (self.only.is_empty() || self.only.iter().any(|id| id == query_id))
+            && !self.skip.iter().any(|id| id == query_id)
+    }
+
+    /// The selected queries, by id.
+    pub(crate) fn queries(&self) -> BTreeMap<&str, &SemverQuery> {
+        self.queries
+            .iter()
+            .filter(|(query_id, _)| self.is_selected(query_id))
+            .map(|(query_id, query)| (query_id.as_str(), query))
+            .collect()
     }
 }
 
@@ -88,15 +383,190 @@ impl SemverQuery {
 mod tests {
     use trustfall_core::frontend::parse;
 
-    use crate::adapter::RustdocAdapter;
+    use crate::adapter::{RustdocAdapter, SCHEMA_VERSION};
 
-    use super::SemverQuery;
+    use super::{set_lint_levels, LintLevel, QueryRegistry, QuerySelection, SemverQuery};
 
     #[test]
     fn all_queries_parse_correctly() {
         let schema = RustdocAdapter::schema();
         for semver_query in SemverQuery::all_queries().into_values() {
+            assert!(semver_query.min_schema_version <= SCHEMA_VERSION);
             let _ = parse(&schema, &semver_query.query).expect("not a valid query");
         }
     }
+
+    #[test]
+    fn compiled_queries_match_their_text() {
+        let schema = RustdocAdapter::schema();
+        for semver_query in SemverQuery::all_queries().into_values() {
+            assert!(semver_query.compiled_query.is_some());
+            assert_eq!(
+                parse(&schema, &semver_query.query).expect("not a valid query"),
+                semver_query
+                    .compile(&schema)
+                    .expect("not compiled correctly"),
+            );
+        }
+    }
+
+    #[test]
+    fn query_selection() {
+        let all = QuerySelection::default();
+        assert_eq!(SemverQuery::all_queries().len(), all.queries().len());
+
+        let only = QuerySelection::new(
+            SemverQuery::all_queries(),
+            vec!["struct_missing".to_string(), "enum_missing".to_string()],
+            vec!["enum_missing".to_string()],
+        )
+        .expect("valid query ids");
+        assert_eq!(
+            vec!["struct_missing"],
+            only.queries().into_keys().collect::<Vec<_>>(),
+        );
+
+        let skip = QuerySelection::new(
+            SemverQuery::all_queries(),
+            vec![],
+            vec!["struct_missing".to_string()],
+        )
+        .expect("valid query ids");
+        assert!(!skip.is_selected("struct_missing"));
+        assert!(skip.is_selected("enum_missing"));
+
+        assert!(QuerySelection::new(
+            SemverQuery::all_queries(),
+            vec!["struct_misssing".to_string()],
+            vec![],
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn empty_query_selection_is_an_error() {
+        let message = QuerySelection::new(
+            SemverQuery::all_queries(),
+            vec!["struct_missing".to_string()],
+            vec!["struct_missing".to_string()],
+        )
+        .expect_err("empty selection accepted")
+        .to_string();
+        assert!(message.contains("no checks are selected"), "{}", message);
+
+        let all_ids: Vec<String> = SemverQuery::all_queries().into_keys().collect();
+        assert!(QuerySelection::new(SemverQuery::all_queries(), vec![], all_ids).is_err());
+    }
+
+    #[test]
+    fn lint_levels_are_overridden() {
+        let mut queries = SemverQuery::all_queries();
+        assert_eq!(LintLevel::Deny, queries["struct_missing"].lint_level);
+        assert_eq!(
+            LintLevel::Warn,
+            queries["function_now_deprecated"].lint_level
+        );
+
+        let ids = |ids: &[&str]| ids.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+        set_lint_levels(
+            &mut queries,
+            &ids(&["struct_missing", "enum_missing"]),
+            &ids(&["function_now_deprecated", "enum_missing"]),
+        )
+        .expect("valid query ids");
+        assert_eq!(LintLevel::Warn, queries["struct_missing"].lint_level);
+        assert_eq!(LintLevel::Deny, queries["enum_missing"].lint_level);
+        assert_eq!(
+            LintLevel::Deny,
+            queries["function_now_deprecated"].lint_level
+        );
+
+        assert!(set_lint_levels(&mut queries, &ids(&["struct_misssing"]), &[]).is_err());
+    }
+
+    #[test]
+    fn extra_queries_are_merged_and_validated() {
+        let dir = std::env::temp_dir().join(format!(
+            "cargo-semver-checks-extra-queries-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).expect("failed to create directory");
+        let builtin = include_str!("./queries/struct_missing.ron");
+
+        std::fs::write(
+            dir.join("custom.ron"),
+            builtin.replace("\"struct_missing\"", "\"custom_struct_missing\""),
+        )
+        .expect("failed to write query");
+        let queries =
+            SemverQuery::all_queries_with_extra(Some(&dir)).expect("failed to load queries");
+        assert_eq!(SemverQuery::all_queries().len() + 1, queries.len());
+        assert_eq!(
+            Some(dir.join("custom.ron")),
+            queries["custom_struct_missing"].source_path
+        );
+
+        // Ids must not collide with the built-in queries.
+        std::fs::write(dir.join("duplicate.ron"), builtin).expect("failed to write query");
+        assert!(SemverQuery::all_queries_with_extra(Some(&dir)).is_err());
+        std::fs::remove_file(dir.join("duplicate.ron")).expect("failed to remove query");
+
+        // Queries must be valid for the schema.
+        std::fs::write(
+            dir.join("invalid.ron"),
+            builtin
+                .replace("\"struct_missing\"", "\"invalid_struct_missing\"")
+                .replace("CrateDiff", "NoSuchVertex"),
+        )
+        .expect("failed to write query");
+        assert!(SemverQuery::all_queries_with_extra(Some(&dir)).is_err());
+        std::fs::remove_file(dir.join("invalid.ron")).expect("failed to remove query");
+
+        // Queries for a newer schema are rejected before their query is parsed.
+        std::fs::write(
+            dir.join("newer.ron"),
+            builtin
+                .replace("\"struct_missing\"", "\"newer_struct_missing\"")
+                .replace(
+                    "min_schema_version: 1,",
+                    &format!("min_schema_version: {},", SCHEMA_VERSION + 1),
+                )
+                .replace("CrateDiff", "FutureVertex"),
+        )
+        .expect("failed to write query");
+        let error = SemverQuery::all_queries_with_extra(Some(&dir))
+            .expect_err("query for a newer schema was loaded");
+        assert!(format!("{error:#}").contains("Upgrade cargo-semver-checks"));
+
+        std::fs::remove_dir_all(&dir).expect("failed to remove directory");
+    }
+
+    #[test]
+    fn queries_registered_at_runtime_are_validated() {
+        let builtin = include_str!("./queries/struct_missing.ron");
+        let mut registry = QueryRegistry::new();
+
+        registry
+            .register_ron_str(&builtin.replace("\"struct_missing\"", "\"org_struct_missing\""))
+            .expect("failed to register query");
+        let registered = registry
+            .queries()
+            .find(|query| query.id() == "org_struct_missing")
+            .expect("registered query is missing");
+        assert_eq!("registered at runtime", registered.implementation_link());
+
+        assert!(registry.register_ron_str(builtin).is_err());
+        let error = registry
+            .register_ron_str(
+                &builtin
+                    .replace("\"struct_missing\"", "\"invalid_struct_missing\"")
+                    .replace("CrateDiff", "NoSuchVertex"),
+            )
+            .expect_err("invalid query was registered");
+        assert!(format!("{error:#}").contains("not contain a valid query"));
+        assert_eq!(
+            SemverQuery::all_queries().len() + 1,
+            registry.queries().count()
+        );
+    }
 }
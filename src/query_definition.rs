@@ -0,0 +1,96 @@
+//! The definitions of checks, as they're written in `.ron` files.
+//!
+//! `build.rs` includes this module too, to parse and validate the built-in checks with
+//! the same definitions that are used at runtime.
+
+use std::{collections::BTreeMap, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+use trustfall_core::ir::TransparentValue;
+
+/// The version update that a semver violation requires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RequiredSemverUpdate {
+    Major,
+    Minor,
+}
+
+/// Whether a check's findings fail the run, or are only reported as advisory warnings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum LintLevel {
+    /// Findings are semver violations: they fail the run unless the version update allows them.
+    #[default]
+    Deny,
+    /// Findings are printed and included in reports, but never fail the run.
+    Warn,
+}
+
+/// A query that can be executed on a pair of rustdoc output files,
+/// returning instances of a particular kind of semver violation.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct SemverQuery {
+    pub(crate) id: String,
+
+    pub(crate) human_readable_name: String,
+
+    pub(crate) description: String,
+
+    pub(crate) required_update: RequiredSemverUpdate,
+
+    /// Whether findings fail the run, or are only warnings. Projects can change it
+    /// with `--warn` and `--deny`.
+    #[serde(default)]
+    pub(crate) lint_level: LintLevel,
+
+    /// The oldest schema version that has everything the query uses.
+    /// Queries written before schema versions existed were written for the first one.
+    #[serde(default = "first_schema_version")]
+    pub(crate) min_schema_version: u32,
+
+    #[serde(default)]
+    pub(crate) reference_link: Option<String>,
+
+    pub(crate) query: String,
+
+    #[serde(default)]
+    pub(crate) arguments: BTreeMap<String, TransparentValue>,
+
+    /// The top-level error describing the semver violation that was detected.
+    /// Even if multiple instances of this semver issue are found, this error
+    /// message is displayed only at most once.
+    pub(crate) error_message: String,
+
+    /// Optional template that can be combined with each query output to produce
+    /// a human-readable description of the specific semver violation that was discovered.
+    #[serde(default)]
+    pub(crate) per_result_error_template: Option<String>,
+
+    /// Optional template that can be combined with each query output to produce
+    /// a "witness": downstream Rust code that compiles against the baseline version
+    /// of the crate, but not against the current one, demonstrating the violation.
+    #[serde(default)]
+    pub(crate) witness_template: Option<String>,
+
+    /// Optional example of a change that this query reports, and of code it breaks.
+    #[serde(default)]
+    pub(crate) example: Option<String>,
+
+    /// The query compiled to Trustfall IR at build time, as JSON, if it's one of the built-in
+    /// queries. Other queries are compiled when they run.
+    #[serde(skip)]
+    pub(crate) compiled_query: Option<&'static str>,
+
+    /// The file from which the query was loaded, if it isn't one of the built-in queries.
+    #[serde(skip)]
+    pub(crate) source_path: Option<PathBuf>,
+
+    /// Whether the query is one of the built-in ones. Queries that aren't, and weren't
+    /// loaded from a file either, were registered by a program using the library.
+    #[serde(skip)]
+    pub(crate) is_builtin: bool,
+}
+
+fn first_schema_version() -> u32 {
+    1
+}
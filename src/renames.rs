@@ -0,0 +1,299 @@
+//! Pairing the items removed since the baseline with the items added in their place
+//! that look like the same item under a different name or module.
+
+use std::collections::{HashMap, HashSet};
+
+use rustdoc_types::{Crate, Generics, Id, Item, ItemEnum};
+
+use crate::{
+    importable_paths::{public_item_paths, CrateGraph, CrateRef, ItemKey},
+    type_normalize::normalized_type_string,
+};
+
+/// The likely renames between the items of two versions of a crate, in both directions.
+#[derive(Debug, Default)]
+pub(crate) struct Renames<'a> {
+    /// For each baseline item that's no longer importable at any of its paths,
+    /// the new current items it may have been renamed to.
+    renamed_to: HashMap<ItemKey<'a>, Vec<(&'a Crate, &'a Item)>>,
+    /// The reverse: for each new current item, the removed baseline items it may replace.
+    renamed_from: HashMap<ItemKey<'a>, Vec<(&'a Crate, &'a Item)>>,
+}
+
+impl<'a> Renames<'a> {
+    /// Pair every removed item of `baseline` with the items added in `current` that are
+    /// of the same kind and have the same shape: the same fields for structs, the same
+    /// variants for enums, and the same signature for functions.
+    pub(crate) fn between(baseline: CrateGraph<'a>, current: CrateGraph<'a>) -> Self {
+        let baseline_items = signed_items(baseline);
+        let current_items = signed_items(current);
+
+        let baseline_paths = importable_at(&baseline_items);
+        let current_paths = importable_at(&current_items);
+
+        let mut added: HashMap<&str, Vec<&SignedItem<'a>>> = HashMap::new();
+        for item in current_items
+            .iter()
+            .filter(|item| item.is_new_in(&baseline_paths))
+        {
+            added.entry(item.signature.as_str()).or_default().push(item);
+        }
+
+        let mut renames = Self::default();
+        for removed in baseline_items
+            .iter()
+            .filter(|item| item.is_new_in(&current_paths))
+        {
+            for candidate in added.get(removed.signature.as_str()).into_iter().flatten() {
+                renames
+                    .renamed_to
+                    .entry(removed.key())
+                    .or_default()
+                    .push((candidate.crate_, candidate.item));
+                renames
+                    .renamed_from
+                    .entry(candidate.key())
+                    .or_default()
+                    .push((removed.crate_, removed.item));
+            }
+        }
+        renames
+    }
+
+    /// The items of the other version that the baseline item was possibly renamed to,
+    /// or that the current item was possibly renamed from.
+    pub(crate) fn candidates(
+        &self,
+        is_baseline: bool,
+        crate_: &'a Crate,
+        id: &'a Id,
+    ) -> &[(&'a Crate, &'a Item)] {
+        let renames = if is_baseline {
+            &self.renamed_to
+        } else {
+            &self.renamed_from
+        };
+        renames
+            .get(&(CrateRef(crate_), id))
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+}
+
+/// A public item whose kind can be matched across renames.
+struct SignedItem<'a> {
+    crate_: &'a Crate,
+    item: &'a Item,
+    /// The kind and shape of the item, without its name.
+    signature: String,
+    paths: Vec<Vec<&'a str>>,
+}
+
+impl<'a> SignedItem<'a> {
+    fn key(&self) -> ItemKey<'a> {
+        (CrateRef(self.crate_), &self.item.id)
+    }
+
+    /// Whether none of the item's paths hold an item of the same kind in `other_paths`.
+    fn is_new_in(&self, other_paths: &HashSet<(&str, &[&str])>) -> bool {
+        let kind = kind_of(&self.signature);
+        !self.paths.is_empty()
+            && self
+                .paths
+                .iter()
+                .all(|path| !other_paths.contains(&(kind, path.as_slice())))
+    }
+}
+
+fn signed_items(graph: CrateGraph<'_>) -> Vec<SignedItem<'_>> {
+    public_item_paths(graph)
+        .into_iter()
+        .filter_map(|(crate_, item, paths)| {
+            Some(SignedItem {
+                crate_,
+                item,
+                signature: signature(crate_, item)?,
+                paths,
+            })
+        })
+        .collect()
+}
+
+/// The kind and path of every importable item, to tell whether an item is still there.
+fn importable_at<'b, 'a>(items: &'b [SignedItem<'a>]) -> HashSet<(&'b str, &'b [&'a str])> {
+    items
+        .iter()
+        .flat_map(|item| {
+            item.paths
+                .iter()
+                .map(move |path| (kind_of(&item.signature), path.as_slice()))
+        })
+        .collect()
+}
+
+fn kind_of(signature: &str) -> &str {
+    signature
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .next()
+        .unwrap_or_default()
+}
+
+/// The kind of the item followed by its shape, e.g. `struct Plain<1> { x: u8 }`,
+/// or `None` for items that aren't matched across renames.
+fn signature(crate_: &Crate, item: &Item) -> Option<String> {
+    match &item.inner {
+        ItemEnum::Struct(struct_) => {
+            let fields: Vec<String> = struct_
+                .fields
+                .iter()
+                .filter_map(|id| crate_.index.get(id))
+                .filter_map(|field| match &field.inner {
+                    ItemEnum::StructField(type_) => Some(format!(
+                        "{}: {}",
+                        field.name.as_deref().unwrap_or_default(),
+                        normalized_type_string(crate_, Some(&struct_.generics), type_),
+                    )),
+                    _ => None,
+                })
+                .collect();
+            Some(format!(
+                "struct {:?}{} {{ {} }}",
+                struct_.struct_type,
+                arity(&struct_.generics),
+                fields.join(", "),
+            ))
+        }
+        ItemEnum::Enum(enum_) => {
+            let variants: Vec<&str> = enum_
+                .variants
+                .iter()
+                .filter_map(|id| crate_.index.get(id)?.name.as_deref())
+                .collect();
+            Some(format!(
+                "enum{} {{ {} }}",
+                arity(&enum_.generics),
+                variants.join(", "),
+            ))
+        }
+        ItemEnum::Function(function) => {
+            let generics = &function.generics;
+            let inputs: Vec<String> = function
+                .decl
+                .inputs
+                .iter()
+                .map(|(_, type_)| normalized_type_string(crate_, Some(generics), type_))
+                .collect();
+            let output = function
+                .decl
+                .output
+                .as_ref()
+                .map(|type_| normalized_type_string(crate_, Some(generics), type_))
+                .unwrap_or_else(|| "()".to_string());
+            Some(format!(
+                "fn{}({}) -> {}",
+                arity(generics),
+                inputs.join(", "),
+                output,
+            ))
+        }
+        _ => None,
+    }
+}
+
+fn arity(generics: &Generics) -> String {
+    format!("<{}>", generics.params.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rustdoc_types::{
+        Crate, Generics, Id, Item, ItemEnum, Module, Struct, StructType, Type, Visibility,
+        FORMAT_VERSION,
+    };
+
+    use super::Renames;
+    use crate::importable_paths::CrateGraph;
+
+    fn item(id: &str, name: &str, inner: ItemEnum) -> Item {
+        Item {
+            id: Id(id.to_string()),
+            crate_id: 0,
+            name: Some(name.to_string()),
+            span: None,
+            visibility: Visibility::Public,
+            docs: None,
+            links: HashMap::new(),
+            attrs: vec![],
+            deprecation: None,
+            inner,
+        }
+    }
+
+    /// A crate with a single struct that has one `u8` field named `x`.
+    fn crate_with_struct(struct_name: &str) -> Crate {
+        let struct_ = item(
+            "0:1",
+            struct_name,
+            ItemEnum::Struct(Struct {
+                struct_type: StructType::Plain,
+                generics: Generics {
+                    params: vec![],
+                    where_predicates: vec![],
+                },
+                fields_stripped: false,
+                fields: vec![Id("0:2".to_string())],
+                impls: vec![],
+            }),
+        );
+        let field = item(
+            "0:2",
+            "x",
+            ItemEnum::StructField(Type::Primitive("u8".to_string())),
+        );
+        let root = item(
+            "0:0",
+            "example",
+            ItemEnum::Module(Module {
+                is_crate: true,
+                items: vec![struct_.id.clone()],
+            }),
+        );
+        Crate {
+            root: root.id.clone(),
+            crate_version: None,
+            includes_private: false,
+            index: [root, struct_, field]
+                .into_iter()
+                .map(|item| (item.id.clone(), item))
+                .collect(),
+            paths: HashMap::new(),
+            external_crates: HashMap::new(),
+            format_version: FORMAT_VERSION,
+        }
+    }
+
+    #[test]
+    fn renamed_struct_with_same_fields_is_paired() {
+        let baseline = crate_with_struct("Foo");
+        let current = crate_with_struct("Bar");
+        let graph = |main| CrateGraph {
+            main,
+            dependencies: &[],
+        };
+
+        let renames = Renames::between(graph(&baseline), graph(&current));
+
+        let id = Id("0:1".to_string());
+        let renamed_to: Vec<_> = renames
+            .candidates(true, &baseline, &id)
+            .iter()
+            .map(|(_, item)| item.name.as_deref())
+            .collect();
+        assert_eq!(renamed_to, vec![Some("Bar")]);
+
+        let unchanged = Renames::between(graph(&baseline), graph(&baseline));
+        assert!(unchanged.candidates(true, &baseline, &id).is_empty());
+    }
+}
@@ -0,0 +1,868 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+use anyhow::{bail, Context};
+use rustdoc_types::Crate;
+use serde::Deserialize;
+use termcolor::Color;
+use tracing::debug;
+
+use crate::{util::load_rustdoc_from_file, StatusPrinter, Verbosity};
+
+/// The subset of `cargo metadata` output that we need in order to
+/// locate the rustdoc JSON file generated for a crate.
+#[derive(Debug, Clone, Deserialize)]
+struct CargoMetadata {
+    packages: Vec<MetadataPackage>,
+    target_directory: PathBuf,
+    workspace_root: PathBuf,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct MetadataPackage {
+    name: String,
+    version: String,
+    manifest_path: PathBuf,
+    publish: Option<Vec<String>>,
+    targets: Vec<MetadataTarget>,
+    #[serde(default)]
+    features: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    metadata: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct MetadataTarget {
+    name: String,
+    kind: Vec<String>,
+}
+
+impl MetadataTarget {
+    /// Whether this is the package's library target, which may be built as any kind of library.
+    /// A package has at most one.
+    fn is_library(&self) -> bool {
+        const LIBRARY_KINDS: [&str; 6] =
+            ["lib", "rlib", "dylib", "cdylib", "staticlib", "proc-macro"];
+        self.kind
+            .iter()
+            .any(|kind| LIBRARY_KINDS.contains(&kind.as_str()))
+    }
+}
+
+/// The subset of a `Cargo.lock` file that we need in order to
+/// find the versions to which dependencies were resolved.
+#[derive(Debug, Clone, Deserialize)]
+struct Lockfile {
+    #[serde(default)]
+    package: Vec<LockedPackage>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: String,
+}
+
+/// Basic information about the package defined by a manifest.
+#[derive(Debug, Clone)]
+pub(crate) struct PackageInfo {
+    pub(crate) name: String,
+    pub(crate) version: String,
+    pub(crate) manifest_path: PathBuf,
+    /// Whether the package has a library target. Packages without one have no API to check.
+    pub(crate) has_library: bool,
+    /// The registries to which the package may be published, if restricted.
+    pub(crate) publish: Option<Vec<String>>,
+    pub(crate) target_directory: PathBuf,
+    /// The contents of the package's `[package.metadata]` table, or null if it has none.
+    pub(crate) metadata: serde_json::Value,
+    /// The package's features, and the features and optional dependencies each one enables.
+    pub(crate) features: BTreeMap<String, Vec<String>>,
+}
+
+impl PackageInfo {
+    /// The features enabled when building the package with the requested ones:
+    /// the requested and default features, and the features these enable, sorted.
+    /// Features of dependencies, like `serde/derive`, are left out.
+    pub(crate) fn resolved_features(&self, requested: &[String]) -> Vec<String> {
+        let mut enabled = BTreeSet::new();
+        let mut pending: Vec<&str> = requested.iter().map(String::as_str).collect();
+        if self.features.contains_key("default") {
+            pending.push("default");
+        }
+        while let Some(feature) = pending.pop() {
+            if !self.features.contains_key(feature) || !enabled.insert(feature.to_string()) {
+                continue;
+            }
+            pending.extend(
+                self.features[feature]
+                    .iter()
+                    .map(String::as_str)
+                    .filter(|enables| !enables.contains('/') && !enables.starts_with("dep:")),
+            );
+        }
+        enabled.into_iter().collect()
+    }
+}
+
+/// What rustdoc JSON was generated with besides the build flags,
+/// so that results can be reproduced and compared across runs.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct BuildEnvironment {
+    /// The enabled features of the current crate, as given by [`PackageInfo::resolved_features`].
+    pub(crate) features: Vec<String>,
+    /// The version of the toolchain, if rustdoc JSON was generated rather than loaded.
+    pub(crate) toolchain: Option<String>,
+    /// The target triple of the host, which rustdoc JSON is generated for without `--target`.
+    pub(crate) host_target: Option<String>,
+}
+
+/// Flags passed through to cargo, restricting whether it may update lockfiles
+/// or access the network, like the cargo flags of the same names.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct CargoFlags {
+    /// Require `Cargo.lock` to be up to date, instead of updating it.
+    pub(crate) locked: bool,
+    /// Only use crates that have already been downloaded.
+    pub(crate) offline: bool,
+}
+
+impl CargoFlags {
+    pub(crate) fn args(&self) -> &'static [&'static str] {
+        match (self.locked, self.offline) {
+            (true, true) => &["--frozen"],
+            (true, false) => &["--locked"],
+            (false, true) => &["--offline"],
+            (false, false) => &[],
+        }
+    }
+}
+
+/// Compiler flags and the cargo profile used when documenting a crate. They can change
+/// the crate's public API, e.g. through `cfg` settings, so they are part of what's checked.
+///
+/// Unless overridden, the `RUSTFLAGS` and `RUSTDOCFLAGS` environment variables apply,
+/// and cargo configuration such as profile settings applies as usual.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub(crate) struct BuildFlags {
+    /// Used instead of the `RUSTFLAGS` environment variable, if set.
+    pub(crate) rustflags: Option<String>,
+    /// Used instead of the `RUSTDOCFLAGS` environment variable, if set.
+    pub(crate) rustdocflags: Option<String>,
+    /// The cargo profile to build with, instead of `dev`.
+    pub(crate) profile: Option<String>,
+}
+
+impl BuildFlags {
+    fn rustflags(&self) -> Option<String> {
+        effective_flags(self.rustflags.as_deref(), "RUSTFLAGS")
+    }
+
+    fn rustdocflags(&self) -> Option<String> {
+        effective_flags(self.rustdocflags.as_deref(), "RUSTDOCFLAGS")
+    }
+
+    /// The `--cfg` settings in `RUSTFLAGS`. Rustdoc doesn't read `RUSTFLAGS`, so these
+    /// have to be passed to it explicitly for the documented API to match the compiled one.
+    fn rustflags_cfgs(&self) -> Vec<String> {
+        let rustflags = self.rustflags().unwrap_or_default();
+        let mut flags = rustflags.split_whitespace();
+        let mut cfgs = vec![];
+        while let Some(flag) = flags.next() {
+            if flag == "--cfg" {
+                cfgs.extend(flags.next().map(ToString::to_string));
+            } else if let Some(cfg) = flag.strip_prefix("--cfg=") {
+                cfgs.push(cfg.to_string());
+            }
+        }
+        cfgs
+    }
+
+    /// The flags in effect, formatted for display, or an empty list if there are none.
+    pub(crate) fn describe(&self) -> Vec<String> {
+        let mut description = vec![];
+        if let Some(rustflags) = self.rustflags() {
+            description.push(format!("RUSTFLAGS={rustflags:?}"));
+        }
+        if let Some(rustdocflags) = self.rustdocflags() {
+            description.push(format!("RUSTDOCFLAGS={rustdocflags:?}"));
+        }
+        if let Some(profile) = self.profile.as_deref() {
+            description.push(format!("--profile {profile}"));
+        }
+        description
+    }
+}
+
+/// The flags from `overridden` if set, or from the environment variable otherwise.
+fn effective_flags(overridden: Option<&str>, env_var: &str) -> Option<String> {
+    overridden
+        .map(ToString::to_string)
+        .or_else(|| std::env::var(env_var).ok())
+        .filter(|flags| !flags.trim().is_empty())
+}
+
+/// Generates rustdoc JSON output for the library target of a crate,
+/// optionally for a specific target triple and set of features.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RustdocCommand {
+    verbosity: Verbosity,
+    toolchain: Option<String>,
+    target: Option<String>,
+    features: Vec<String>,
+    target_dir: Option<PathBuf>,
+    cargo_flags: CargoFlags,
+    build_flags: BuildFlags,
+    status: StatusPrinter,
+}
+
+impl RustdocCommand {
+    pub(crate) fn new() -> Self {
+        Self::default()
+    }
+
+    /// At [`Verbosity::Verbose`] and above, print the commands being run,
+    /// and at [`Verbosity::VeryVerbose`] also the underlying rustdoc invocations.
+    pub(crate) fn verbosity(mut self, verbosity: Verbosity) -> Self {
+        self.verbosity = verbosity;
+        self
+    }
+
+    /// Print status messages with the given printer, so they are colored like other output.
+    pub(crate) fn status_printer(mut self, status: StatusPrinter) -> Self {
+        self.status = status;
+        self
+    }
+
+    /// Use the given rustup toolchain, which should be resolved via [`resolve_toolchain`].
+    /// If not set, the `nightly` toolchain is used.
+    pub(crate) fn toolchain(mut self, toolchain: Option<String>) -> Self {
+        self.toolchain = toolchain;
+        self
+    }
+
+    /// Generate documentation for the given target triple instead of the host.
+    pub(crate) fn target(mut self, target: Option<String>) -> Self {
+        self.target = target;
+        self
+    }
+
+    /// Enable the given crate features, in addition to the default ones.
+    pub(crate) fn features(mut self, mut features: Vec<String>) -> Self {
+        features.sort_unstable();
+        features.dedup();
+        self.features = features;
+        self
+    }
+
+    /// Build in the given directory instead of the crate's own target directory.
+    pub(crate) fn target_dir(mut self, target_dir: Option<PathBuf>) -> Self {
+        self.target_dir = target_dir;
+        self
+    }
+
+    /// Pass `--locked`, `--offline`, or `--frozen` to cargo.
+    pub(crate) fn cargo_flags(mut self, cargo_flags: CargoFlags) -> Self {
+        self.cargo_flags = cargo_flags;
+        self
+    }
+
+    /// Build with the given compiler flags and profile.
+    pub(crate) fn build_flags(mut self, build_flags: BuildFlags) -> Self {
+        self.build_flags = build_flags;
+        self
+    }
+
+    pub(crate) fn active_build_flags(&self) -> &BuildFlags {
+        &self.build_flags
+    }
+
+    pub(crate) fn target_triple(&self) -> Option<&str> {
+        self.target.as_deref()
+    }
+
+    pub(crate) fn active_features(&self) -> &[String] {
+        &self.features
+    }
+
+    /// The rustup toolchain that generates the rustdoc JSON.
+    pub(crate) fn active_toolchain(&self) -> &str {
+        self.toolchain.as_deref().unwrap_or("nightly")
+    }
+
+    /// Run `cargo rustdoc` on the crate whose manifest is at `manifest_path`,
+    /// then load and return the generated rustdoc JSON.
+    pub(crate) fn generate(&self, manifest_path: &Path) -> anyhow::Result<Crate> {
+        let json_path = self.generate_json_file(manifest_path)?;
+        load_rustdoc_from_file(
+            json_path
+                .to_str()
+                .expect("rustdoc JSON path is not valid UTF-8"),
+        )
+    }
+
+    /// Run `cargo rustdoc` on the crate whose manifest is at `manifest_path`,
+    /// returning the path of the generated rustdoc JSON file.
+    pub(crate) fn generate_json_file(&self, manifest_path: &Path) -> anyhow::Result<PathBuf> {
+        let metadata = load_metadata(manifest_path, true, self.cargo_flags)?;
+        let package = find_package(&metadata, manifest_path)?;
+        let lib_name = find_lib_target_name(package)?;
+
+        let target_directory = self
+            .target_dir
+            .as_ref()
+            .unwrap_or(&metadata.target_directory);
+        self.run(
+            manifest_path,
+            None,
+            self.cargo_flags,
+            target_directory,
+            &lib_name,
+        )
+    }
+
+    /// Run `cargo rustdoc` on the given version of a dependency of the crate
+    /// whose manifest is at `manifest_path`, returning the path of the generated
+    /// rustdoc JSON file.
+    pub(crate) fn generate_dependency_json_file(
+        &self,
+        manifest_path: &Path,
+        crate_name: &str,
+        version: &str,
+    ) -> anyhow::Result<PathBuf> {
+        // The manifest's lockfile is created here rather than by the user,
+        // so there is nothing for `--locked` to protect: only restrict network access.
+        let cargo_flags = CargoFlags {
+            locked: false,
+            ..self.cargo_flags
+        };
+        let metadata = load_metadata(manifest_path, false, cargo_flags)?;
+        let package = metadata
+            .packages
+            .iter()
+            .find(|package| package.name == crate_name && package.version == version)
+            .with_context(|| {
+                format!("Dependency {crate_name} v{version} not found in {manifest_path:?}")
+            })?;
+        let lib_name = find_lib_target_name(package)?;
+
+        let target_directory = self
+            .target_dir
+            .as_ref()
+            .unwrap_or(&metadata.target_directory);
+        self.run(
+            manifest_path,
+            Some(&format!("{crate_name}@{version}")),
+            cargo_flags,
+            target_directory,
+            &lib_name,
+        )
+    }
+
+    fn run(
+        &self,
+        manifest_path: &Path,
+        package_spec: Option<&str>,
+        cargo_flags: CargoFlags,
+        target_directory: &Path,
+        lib_name: &str,
+    ) -> anyhow::Result<PathBuf> {
+        let mut cmd = Command::new("cargo");
+        cmd.arg(format!("+{}", self.active_toolchain()))
+            .arg("rustdoc")
+            .arg("--lib")
+            .arg("--manifest-path")
+            .arg(manifest_path);
+        if let Some(package_spec) = package_spec {
+            cmd.arg("--package").arg(package_spec);
+        }
+        if let Some(target) = self.target.as_deref() {
+            cmd.arg("--target").arg(target);
+        }
+        if let Some(target_dir) = self.target_dir.as_deref() {
+            cmd.arg("--target-dir").arg(target_dir);
+        }
+        if !self.features.is_empty() {
+            cmd.arg("--features").arg(self.features.join(","));
+        }
+        cmd.args(cargo_flags.args());
+        if let Some(profile) = self.build_flags.profile.as_deref() {
+            cmd.arg("--profile").arg(profile);
+        }
+        if let Some(rustflags) = self.build_flags.rustflags.as_deref() {
+            cmd.env("RUSTFLAGS", rustflags);
+        }
+        if let Some(rustdocflags) = self.build_flags.rustdocflags.as_deref() {
+            cmd.env("RUSTDOCFLAGS", rustdocflags);
+        }
+        if self.verbosity >= Verbosity::VeryVerbose {
+            cmd.arg("--verbose");
+        }
+        cmd.args(["--", "-Zunstable-options", "--output-format", "json"]);
+        for cfg in self.build_flags.rustflags_cfgs() {
+            cmd.arg("--cfg").arg(cfg);
+        }
+
+        if self.verbosity >= Verbosity::Verbose {
+            self.status.print(
+                Color::Green,
+                "Running",
+                format_args!("`{}`", display_command(&cmd)),
+            );
+        }
+        debug!("running `{}`", display_command(&cmd));
+        let start_instant = std::time::Instant::now();
+        let status = cmd
+            .status()
+            .with_context(|| format!("Failed to run `cargo rustdoc` for {:?}", manifest_path))?;
+        if !status.success() {
+            bail!(
+                "`cargo rustdoc` failed for {:?}, exit status: {}",
+                manifest_path,
+                status
+            );
+        }
+
+        if self.verbosity >= Verbosity::VeryVerbose {
+            self.status.print(
+                Color::Green,
+                "Generated",
+                format_args!(
+                    "rustdoc for {} in {:.3}s",
+                    package_spec.unwrap_or(lib_name),
+                    start_instant.elapsed().as_secs_f32(),
+                ),
+            );
+        }
+
+        let mut json_path = target_directory.to_path_buf();
+        if let Some(target) = self.target.as_deref() {
+            json_path.push(target);
+        }
+        json_path.push("doc");
+        json_path.push(format!("{}.json", lib_name.replace('-', "_")));
+
+        Ok(json_path)
+    }
+}
+
+/// Format a command the way it would be typed into a shell, for display purposes.
+fn display_command(cmd: &Command) -> String {
+    let env = cmd.get_envs().filter_map(|(key, value)| {
+        value.map(|value| format!("{}={:?}", key.to_string_lossy(), value))
+    });
+    env.chain(
+        std::iter::once(cmd.get_program())
+            .chain(cmd.get_args())
+            .map(|arg| arg.to_string_lossy().into_owned()),
+    )
+    .collect::<Vec<_>>()
+    .join(" ")
+}
+
+/// The packages of a workspace.
+#[derive(Debug, Clone)]
+pub(crate) struct Workspace {
+    pub(crate) root: PathBuf,
+    /// Every package in the workspace, sorted by name.
+    pub(crate) members: Vec<PackageInfo>,
+    /// Whether the manifest only defines the workspace, without a package of its own.
+    pub(crate) is_virtual: bool,
+}
+
+pub(crate) fn load_package_info(manifest_path: &Path) -> anyhow::Result<PackageInfo> {
+    // Without dependencies, cargo neither resolves nor downloads anything.
+    let metadata = load_metadata(manifest_path, true, CargoFlags::default())?;
+    let package = find_package(&metadata, manifest_path)?;
+    Ok(package_info(&metadata, package))
+}
+
+/// Load the workspace that the manifest belongs to, or that it defines.
+pub(crate) fn load_workspace(manifest_path: &Path) -> anyhow::Result<Workspace> {
+    // With `--no-deps`, the only packages listed are the workspace members.
+    let metadata = load_metadata(manifest_path, true, CargoFlags::default())?;
+    let mut members: Vec<_> = metadata
+        .packages
+        .iter()
+        .map(|package| package_info(&metadata, package))
+        .collect();
+    members.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(Workspace {
+        root: metadata.workspace_root.clone(),
+        members,
+        is_virtual: find_package(&metadata, manifest_path).is_err(),
+    })
+}
+
+fn package_info(metadata: &CargoMetadata, package: &MetadataPackage) -> PackageInfo {
+    PackageInfo {
+        name: package.name.clone(),
+        version: package.version.clone(),
+        manifest_path: package.manifest_path.clone(),
+        has_library: package.targets.iter().any(MetadataTarget::is_library),
+        publish: package.publish.clone(),
+        target_directory: metadata.target_directory.clone(),
+        metadata: package.metadata.clone(),
+        features: package.features.clone(),
+    }
+}
+
+/// The version to which Cargo resolves a dependency of the package defined by
+/// the given manifest, or `None` if no published version matches its requirement.
+///
+/// The dependency is resolved from scratch, using only the registry index:
+/// it isn't downloaded, and any existing lockfile is replaced.
+/// When `offline`, only the locally-cached copy of the registry index is used.
+pub(crate) fn resolve_dependency_version(
+    manifest_path: &Path,
+    dependency_name: &str,
+    offline: bool,
+) -> anyhow::Result<Option<String>> {
+    let mut cmd = Command::new("cargo");
+    cmd.arg("generate-lockfile")
+        .arg("--manifest-path")
+        .arg(manifest_path);
+    if offline {
+        cmd.arg("--offline");
+    }
+    let output = cmd.output().with_context(|| {
+        format!("Failed to run `cargo generate-lockfile` for {manifest_path:?}")
+    })?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("failed to select a version")
+            || stderr.contains("no matching package named")
+        {
+            return Ok(None);
+        }
+        bail!("`cargo generate-lockfile` failed for {manifest_path:?}:\n{stderr}");
+    }
+
+    let lockfile_path = manifest_path.with_file_name("Cargo.lock");
+    let lockfile: Lockfile = toml::from_str(
+        &std::fs::read_to_string(&lockfile_path)
+            .with_context(|| format!("Failed to read lockfile {lockfile_path:?}"))?,
+    )
+    .with_context(|| format!("Failed to parse lockfile {lockfile_path:?}"))?;
+    lockfile
+        .package
+        .into_iter()
+        .find(|package| package.name == dependency_name)
+        .map(|package| Some(package.version))
+        .with_context(|| format!("Dependency {dependency_name} not found in {lockfile_path:?}"))
+}
+
+/// The directory containing the source of a dependency of the package defined by
+/// the given manifest, downloading it if necessary.
+pub(crate) fn find_dependency_root(
+    manifest_path: &Path,
+    dependency_name: &str,
+) -> anyhow::Result<PathBuf> {
+    let metadata = load_metadata(manifest_path, false, CargoFlags::default())?;
+    let package = metadata
+        .packages
+        .iter()
+        .find(|package| package.name == dependency_name)
+        .with_context(|| format!("Dependency {dependency_name} not found in {manifest_path:?}"))?;
+    Ok(package
+        .manifest_path
+        .parent()
+        .expect("manifest path has no parent")
+        .to_path_buf())
+}
+
+/// The manifest of the package to check when `--manifest-path` isn't specified.
+///
+/// Like Cargo, this is the `Cargo.toml` in the current directory or in its nearest ancestor
+/// that has one. Outside of any package, this is `Cargo.toml` in the current directory,
+/// so that commands that don't need a package still work.
+pub(crate) fn locate_manifest() -> PathBuf {
+    Command::new("cargo")
+        .args(["locate-project", "--message-format", "plain"])
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|path| PathBuf::from(path.trim_end()))
+        .unwrap_or_else(|| PathBuf::from("Cargo.toml"))
+}
+
+/// The target triple of the host, as reported by `rustc`.
+pub(crate) fn host_triple() -> anyhow::Result<String> {
+    let output = Command::new("rustc")
+        .arg("-vV")
+        .output()
+        .with_context(|| "Failed to run `rustc -vV`")?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find_map(|line| line.strip_prefix("host: "))
+        .map(ToString::to_string)
+        .with_context(|| "Could not determine host target triple from `rustc -vV` output")
+}
+
+/// Rustdoc JSON output is unstable, and requires a nightly toolchain.
+///
+/// If a toolchain was explicitly requested, ensure it's installed. Otherwise, find
+/// an installed nightly toolchain, preferring the plain `nightly` channel
+/// over date-pinned nightly toolchains.
+pub(crate) fn resolve_toolchain(requested: Option<&str>) -> anyhow::Result<String> {
+    let output = Command::new("rustup")
+        .args(["toolchain", "list"])
+        .output()
+        .map_err(|_| {
+            anyhow::anyhow!(
+                "Failed to run `rustup`, which is required to find a nightly Rust toolchain. \
+                Install rustup from https://rustup.rs/ and then run \
+                `rustup toolchain install nightly`."
+            )
+        })?;
+    if !output.status.success() {
+        bail!(
+            "`rustup toolchain list` failed:\n{}",
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+    let installed = String::from_utf8_lossy(&output.stdout);
+
+    match requested {
+        Some(requested) => {
+            if find_installed_toolchain(&installed, requested).is_none() {
+                bail!(
+                    "The requested toolchain {requested} is not installed. \
+                    Install it with `rustup toolchain install {requested}`."
+                );
+            }
+            Ok(requested.to_string())
+        }
+        None => find_nightly_toolchain(&installed).with_context(|| {
+            "Generating rustdoc JSON requires a nightly Rust toolchain, but none is installed. \
+            Install one with `rustup toolchain install nightly`, \
+            or choose a specific toolchain with `--toolchain <name>`."
+        }),
+    }
+}
+
+/// The version of the given rustup toolchain, as reported by its `rustc --version`,
+/// like `rustc 1.66.0-nightly (b8c35ca26 2022-10-15)`, or `None` if it can't be run.
+pub(crate) fn toolchain_version(toolchain: &str) -> Option<String> {
+    Command::new("rustc")
+        .arg(format!("+{toolchain}"))
+        .arg("--version")
+        .output()
+        .ok()
+        .filter(|output| output.status.success())
+        .and_then(|output| String::from_utf8(output.stdout).ok())
+        .map(|version| version.trim().to_string())
+}
+
+/// Toolchain names from `rustup toolchain list` output, without annotations like `(default)`.
+fn toolchain_names(rustup_output: &str) -> impl Iterator<Item = &str> {
+    rustup_output
+        .lines()
+        .filter_map(|line| line.split_whitespace().next())
+}
+
+fn find_installed_toolchain<'a>(rustup_output: &'a str, requested: &str) -> Option<&'a str> {
+    // Installed toolchains are listed with their host triple as a suffix,
+    // e.g. `nightly-2022-07-20-x86_64-unknown-linux-gnu`.
+    let prefix = format!("{requested}-");
+    toolchain_names(rustup_output).find(|name| {
+        *name == requested
+            || name
+                .strip_prefix(&prefix)
+                .map(|host| !host.starts_with(|c: char| c.is_ascii_digit()))
+                .unwrap_or(false)
+    })
+}
+
+fn find_nightly_toolchain(rustup_output: &str) -> Option<String> {
+    if find_installed_toolchain(rustup_output, "nightly").is_some() {
+        return Some("nightly".to_string());
+    }
+
+    // Fall back to the most recent date-pinned nightly.
+    toolchain_names(rustup_output)
+        .filter(|name| name.starts_with("nightly-"))
+        .max()
+        .map(ToString::to_string)
+}
+
+fn load_metadata(
+    manifest_path: &Path,
+    no_deps: bool,
+    cargo_flags: CargoFlags,
+) -> anyhow::Result<CargoMetadata> {
+    let mut cmd = Command::new("cargo");
+    cmd.args(["metadata", "--format-version", "1"])
+        .arg("--manifest-path")
+        .arg(manifest_path)
+        .args(cargo_flags.args());
+    if no_deps {
+        cmd.arg("--no-deps");
+    }
+    let output = cmd
+        .output()
+        .with_context(|| format!("Failed to run `cargo metadata` for {:?}", manifest_path))?;
+    if !output.status.success() {
+        bail!(
+            "`cargo metadata` failed for {:?}:\n{}",
+            manifest_path,
+            String::from_utf8_lossy(&output.stderr)
+        );
+    }
+
+    serde_json::from_slice(&output.stdout).with_context(|| {
+        format!(
+            "Failed to parse `cargo metadata` output for {:?}",
+            manifest_path
+        )
+    })
+}
+
+fn find_package<'a>(
+    metadata: &'a CargoMetadata,
+    manifest_path: &Path,
+) -> anyhow::Result<&'a MetadataPackage> {
+    let manifest_path = manifest_path
+        .canonicalize()
+        .with_context(|| format!("Failed to find manifest {:?}", manifest_path))?;
+    metadata
+        .packages
+        .iter()
+        .find(|package| package.manifest_path == manifest_path)
+        .with_context(|| format!("No package found for manifest {:?}", manifest_path))
+}
+
+fn find_lib_target_name(package: &MetadataPackage) -> anyhow::Result<String> {
+    package
+        .targets
+        .iter()
+        .find(|target| target.is_library())
+        .map(|target| target.name.clone())
+        .with_context(|| format!("Package {} does not have a library target", package.name))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::{
+        find_installed_toolchain, find_nightly_toolchain, BuildFlags, CargoFlags, MetadataTarget,
+        PackageInfo,
+    };
+
+    const RUSTUP_OUTPUT: &str = "\
+stable-x86_64-unknown-linux-gnu (default)
+nightly-2022-07-01-x86_64-unknown-linux-gnu
+nightly-2022-07-20-x86_64-unknown-linux-gnu
+";
+
+    #[test]
+    fn pinned_nightly_is_found() {
+        assert_eq!(
+            Some("nightly-2022-07-20-x86_64-unknown-linux-gnu"),
+            find_installed_toolchain(RUSTUP_OUTPUT, "nightly-2022-07-20"),
+        );
+        assert_eq!(None, find_installed_toolchain(RUSTUP_OUTPUT, "nightly"));
+        assert_eq!(None, find_installed_toolchain(RUSTUP_OUTPUT, "beta"));
+    }
+
+    #[test]
+    fn latest_pinned_nightly_is_chosen_without_plain_nightly() {
+        assert_eq!(
+            Some("nightly-2022-07-20-x86_64-unknown-linux-gnu".to_string()),
+            find_nightly_toolchain(RUSTUP_OUTPUT),
+        );
+    }
+
+    #[test]
+    fn locked_and_offline_combine_into_frozen() {
+        let flags = |locked, offline| CargoFlags { locked, offline }.args();
+        assert!(flags(false, false).is_empty());
+        assert_eq!(["--locked"], flags(true, false));
+        assert_eq!(["--offline"], flags(false, true));
+        assert_eq!(["--frozen"], flags(true, true));
+    }
+
+    #[test]
+    fn rustflags_cfgs_are_forwarded_to_rustdoc() {
+        let flags = BuildFlags {
+            rustflags: Some("-C opt-level=2 --cfg tokio_unstable --cfg=feature=\"x\"".into()),
+            rustdocflags: Some(String::new()),
+            profile: None,
+        };
+        assert_eq!(
+            vec!["tokio_unstable".to_string(), "feature=\"x\"".to_string()],
+            flags.rustflags_cfgs()
+        );
+        assert_eq!(
+            vec![r#"RUSTFLAGS="-C opt-level=2 --cfg tokio_unstable --cfg=feature=\"x\"""#],
+            flags.describe()
+        );
+    }
+
+    #[test]
+    fn proc_macros_and_cdylibs_are_libraries() {
+        let target = |kinds: &[&str]| MetadataTarget {
+            name: "foo".to_string(),
+            kind: kinds.iter().map(ToString::to_string).collect(),
+        };
+        assert!(target(&["lib"]).is_library());
+        assert!(target(&["proc-macro"]).is_library());
+        assert!(target(&["cdylib", "rlib"]).is_library());
+        assert!(!target(&["bin"]).is_library());
+        assert!(!target(&["test"]).is_library());
+    }
+
+    #[test]
+    fn plain_nightly_is_preferred() {
+        let output = format!("{RUSTUP_OUTPUT}nightly-x86_64-unknown-linux-gnu\n");
+        assert_eq!(Some("nightly".to_string()), find_nightly_toolchain(&output));
+        assert_eq!(
+            None,
+            find_nightly_toolchain("stable-x86_64-unknown-linux-gnu\n")
+        );
+    }
+
+    #[test]
+    fn features_enabled_by_requested_and_default_features_are_resolved() {
+        let features = [
+            ("default", vec!["std"]),
+            ("std", vec!["alloc", "serde?/std"]),
+            ("alloc", vec![]),
+            ("serde", vec!["dep:serde"]),
+            ("full", vec!["std", "serde", "extra"]),
+            ("extra", vec!["full"]),
+            ("unused", vec![]),
+        ];
+        let package = PackageInfo {
+            name: "demo".to_string(),
+            version: "1.0.0".to_string(),
+            manifest_path: PathBuf::from("Cargo.toml"),
+            has_library: true,
+            publish: None,
+            target_directory: PathBuf::from("target"),
+            metadata: serde_json::Value::Null,
+            features: features
+                .into_iter()
+                .map(|(name, enables)| {
+                    (
+                        name.to_string(),
+                        enables.into_iter().map(ToString::to_string).collect(),
+                    )
+                })
+                .collect(),
+        };
+        assert_eq!(
+            vec!["alloc", "default", "std"],
+            package.resolved_features(&[])
+        );
+        assert_eq!(
+            vec!["alloc", "default", "extra", "full", "serde", "std"],
+            package.resolved_features(&["full".to_string()])
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! Word-level differences between the baseline and current declarations of an item,
+//! for checks that report both as `baseline_signature` and `current_signature`.
+
+/// A part of a declaration, and whether it differs from the other version's.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Segment<'a> {
+    pub(crate) text: &'a str,
+    pub(crate) changed: bool,
+}
+
+/// Split the declaration into words, runs of whitespace, and single punctuation characters,
+/// so that e.g. changing `u8` to `u16` in `Vec<u8>` only marks the `u8`.
+fn tokenize(text: &str) -> Vec<&str> {
+    let is_word = |c: char| c.is_alphanumeric() || c == '_' || c == '\'';
+    let mut tokens = vec![];
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((index, c)) = chars.next() {
+        let end = if is_word(c) || c.is_whitespace() {
+            let same_kind = |other: char| {
+                if is_word(c) {
+                    is_word(other)
+                } else {
+                    other.is_whitespace()
+                }
+            };
+            let mut end = index + c.len_utf8();
+            while let Some(&(next_index, next)) = chars.peek() {
+                if !same_kind(next) {
+                    break;
+                }
+                end = next_index + next.len_utf8();
+                chars.next();
+            }
+            end
+        } else {
+            index + c.len_utf8()
+        };
+        tokens.push(&text[start..end]);
+        start = end;
+    }
+    tokens
+}
+
+/// The baseline and current declarations split into segments, with the words that aren't
+/// part of their longest common subsequence marked as changed.
+pub(crate) fn word_diff<'a>(
+    baseline: &'a str,
+    current: &'a str,
+) -> (Vec<Segment<'a>>, Vec<Segment<'a>>) {
+    let old = tokenize(baseline);
+    let new = tokenize(current);
+
+    // lengths[i][j] is the length of the longest common subsequence of old[i..] and new[j..].
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut old_segments = vec![];
+    let mut new_segments = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            old_segments.push((old[i], false));
+            new_segments.push((new[j], false));
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lengths[i][j + 1] >= lengths[i + 1][j]) {
+            new_segments.push((new[j], true));
+            j += 1;
+        } else {
+            old_segments.push((old[i], true));
+            i += 1;
+        }
+    }
+    (merge(baseline, old_segments), merge(current, new_segments))
+}
+
+/// Join adjacent tokens that are both changed or both unchanged into one segment.
+/// Whitespace between changed tokens joins them, so `&mut T` reads as one change.
+fn merge<'a>(text: &'a str, tokens: Vec<(&'a str, bool)>) -> Vec<Segment<'a>> {
+    let mut changed: Vec<bool> = tokens.iter().map(|(_, changed)| *changed).collect();
+    for index in 1..tokens.len().saturating_sub(1) {
+        if tokens[index].0.trim().is_empty() && changed[index - 1] && changed[index + 1] {
+            changed[index] = true;
+        }
+    }
+
+    // The tokens are consecutive parts of the text, so each segment is a range of it.
+    let mut ranges: Vec<(usize, usize, bool)> = vec![];
+    let mut offset = 0;
+    for ((token, _), changed) in tokens.iter().zip(changed) {
+        let end = offset + token.len();
+        match ranges.last_mut() {
+            Some(last) if last.2 == changed => last.1 = end,
+            _ => ranges.push((offset, end, changed)),
+        }
+        offset = end;
+    }
+    ranges
+        .into_iter()
+        .map(|(start, end, changed)| Segment {
+            text: &text[start..end],
+            changed,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{word_diff, Segment};
+
+    fn render(segments: &[Segment]) -> String {
+        segments
+            .iter()
+            .map(|segment| {
+                if segment.changed {
+                    format!("[{}]", segment.text)
+                } else {
+                    segment.text.to_string()
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn only_changed_words_are_marked() {
+        let (baseline, current) = word_diff(
+            "fn parse(input: &str) -> Vec<u8>",
+            "fn parse(input: &mut String) -> Vec<u16>",
+        );
+        assert_eq!("fn parse(input: &[str]) -> Vec<[u8]>", render(&baseline));
+        assert_eq!(
+            "fn parse(input: &[mut String]) -> Vec<[u16]>",
+            render(&current)
+        );
+
+        let (baseline, current) = word_diff("fn f()", "fn f()");
+        assert_eq!(
+            vec![Segment {
+                text: "fn f()",
+                changed: false
+            }],
+            baseline
+        );
+        assert_eq!(baseline, current);
+    }
+}
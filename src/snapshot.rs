@@ -0,0 +1,274 @@
+//! Snapshots of a crate's public API, written by `dump-api` and used as the baseline with
+//! `--baseline-snapshot` instead of rustdoc JSON.
+//!
+//! A snapshot is the crate's rustdoc JSON, normalized to be compact and stable:
+//! - only the items reachable from the crate root are kept, so private items that rustdoc
+//!   documents but no public item refers to are dropped;
+//! - item ids are renumbered in the order the items are reached from the root, so they only
+//!   change when the API does, rather than with every change to the crate or the toolchain;
+//! - docs are shortened to their first paragraph, and intra-doc links are dropped;
+//! - only the paths and external crates that the kept items refer to are kept;
+//! - object keys are sorted, so that the same API always gives the same file.
+//!
+//! The crate is stored in the rustdoc JSON format this version of cargo-semver-checks reads,
+//! so a committed snapshot keeps working when nightly rustdoc changes its output format.
+
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    io::Write,
+    path::Path,
+};
+
+use anyhow::{bail, Context};
+use clap::crate_version;
+use rustdoc_types::Crate;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::{adapter::doc_summary, util::parse_rustdoc};
+
+/// The version of the snapshot format, increased when snapshots are normalized differently.
+const SNAPSHOT_VERSION: u32 = 1;
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ApiSnapshot {
+    snapshot_version: u32,
+
+    /// The version of cargo-semver-checks that wrote the snapshot. Informational only.
+    generated_by: String,
+
+    /// The normalized rustdoc JSON of the crate.
+    #[serde(rename = "crate")]
+    crate_: Value,
+}
+
+/// Write a snapshot of the crate's public API.
+pub(crate) fn write_snapshot(crate_: &Crate, writer: &mut dyn Write) -> anyhow::Result<()> {
+    let snapshot = ApiSnapshot {
+        snapshot_version: SNAPSHOT_VERSION,
+        generated_by: format!("cargo-semver-checks {}", crate_version!()),
+        crate_: normalize(crate_)?,
+    };
+    serde_json::to_writer_pretty(&mut *writer, &snapshot)?;
+    writeln!(writer)?;
+    Ok(())
+}
+
+/// Load the crate in a snapshot written by `dump-api`.
+pub(crate) fn load_snapshot(path: &Path) -> anyhow::Result<Crate> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read API snapshot {:?}", path))?;
+    let snapshot: ApiSnapshot = serde_json::from_str(&contents)
+        .with_context(|| format!("{:?} is not an API snapshot written by `dump-api`", path))?;
+    if snapshot.snapshot_version != SNAPSHOT_VERSION {
+        bail!(
+            "API snapshot {:?} is in format version {}, but this version of cargo-semver-checks \
+            reads version {}. Regenerate it with `cargo semver-checks dump-api`.",
+            path,
+            snapshot.snapshot_version,
+            SNAPSHOT_VERSION,
+        );
+    }
+    parse_rustdoc(&snapshot.crate_.to_string())
+        .with_context(|| format!("Failed to parse API snapshot {:?}", path))
+}
+
+/// The crate's rustdoc JSON, normalized as described in the module documentation.
+fn normalize(crate_: &Crate) -> anyhow::Result<Value> {
+    let mut crate_ = crate_.clone();
+    for item in crate_.index.values_mut() {
+        item.docs = doc_summary(item.docs.as_deref());
+        item.links.clear();
+    }
+
+    let mut items: HashMap<String, Value> = crate_
+        .index
+        .iter()
+        .map(|(id, item)| Ok((id.0.clone(), serde_json::to_value(item)?)))
+        .collect::<anyhow::Result<_>>()?;
+
+    // Number the items in the order they're reached from the root, following every id
+    // an item refers to in the order it appears in the item's (sorted) JSON.
+    let mut new_ids: HashMap<String, String> = HashMap::new();
+    let mut stack = vec![crate_.root.0.clone()];
+    let mut order = vec![];
+    while let Some(id) = stack.pop() {
+        if new_ids.contains_key(&id) || !items.contains_key(&id) {
+            continue;
+        }
+        new_ids.insert(id.clone(), format!("0:{}", new_ids.len()));
+        order.push(id.clone());
+        let mut referenced = vec![];
+        collect_strings(&items[&id], &mut referenced);
+        stack.extend(
+            referenced
+                .into_iter()
+                .rev()
+                .filter(|other| items.contains_key(other) && !new_ids.contains_key(other)),
+        );
+    }
+
+    let mut referenced_paths = vec![];
+    let mut index = serde_json::Map::new();
+    for id in &order {
+        let mut item = items.remove(id).expect("reached item is in the index");
+        collect_strings(&item, &mut referenced_paths);
+        renumber(&mut item, &new_ids);
+        index.insert(new_ids[id].clone(), item);
+    }
+
+    // Keep the paths of the kept items, and of the items in other crates that they refer to.
+    let referenced_paths: HashSet<String> = referenced_paths.into_iter().collect();
+    let mut paths = serde_json::Map::new();
+    let mut crate_ids = HashSet::new();
+    for (id, summary) in &crate_.paths {
+        let new_id = match new_ids.get(&id.0) {
+            Some(new_id) => new_id.clone(),
+            None if summary.crate_id != 0 && referenced_paths.contains(&id.0) => id.0.clone(),
+            None => continue,
+        };
+        crate_ids.insert(summary.crate_id);
+        paths.insert(new_id, serde_json::to_value(summary)?);
+    }
+    let external_crates: BTreeMap<String, Value> = crate_
+        .external_crates
+        .iter()
+        .filter(|(crate_id, _)| crate_ids.contains(crate_id))
+        .map(|(crate_id, external)| Ok((crate_id.to_string(), serde_json::to_value(external)?)))
+        .collect::<anyhow::Result<_>>()?;
+
+    let mut value = serde_json::to_value(&Crate {
+        index: HashMap::new(),
+        paths: HashMap::new(),
+        external_crates: HashMap::new(),
+        ..crate_
+    })?;
+    renumber(&mut value, &new_ids);
+    value["index"] = Value::Object(index);
+    value["paths"] = Value::Object(paths);
+    value["external_crates"] = serde_json::to_value(external_crates)?;
+    Ok(value)
+}
+
+/// Every string in the value, in order, which includes the ids the value refers to.
+fn collect_strings(value: &Value, strings: &mut Vec<String>) {
+    match value {
+        Value::String(string) => strings.push(string.clone()),
+        Value::Array(values) => values
+            .iter()
+            .for_each(|value| collect_strings(value, strings)),
+        Value::Object(fields) => fields
+            .values()
+            .for_each(|value| collect_strings(value, strings)),
+        _ => {}
+    }
+}
+
+/// Replace the ids in the value by their new ids.
+fn renumber(value: &mut Value, new_ids: &HashMap<String, String>) {
+    match value {
+        Value::String(string) => {
+            if let Some(new_id) = new_ids.get(string.as_str()) {
+                *string = new_id.clone();
+            }
+        }
+        Value::Array(values) => values.iter_mut().for_each(|value| renumber(value, new_ids)),
+        Value::Object(fields) => fields
+            .values_mut()
+            .for_each(|value| renumber(value, new_ids)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rustdoc_types::{Crate, FORMAT_VERSION};
+    use serde_json::json;
+
+    use super::{load_snapshot, write_snapshot};
+
+    /// A crate with a public struct and function, with the given ids, and a private item
+    /// that nothing refers to.
+    fn example_crate(struct_id: &str, fn_id: &str) -> Crate {
+        let item = |id: &str, name: &str, kind: &str, inner: serde_json::Value| {
+            json!({
+                "id": id, "crate_id": 0, "name": name, "span": null, "visibility": "public",
+                "docs": "Summary line.\n\nDetails that are left out.", "links": { "Other": "1:5" },
+                "attrs": [], "deprecation": null, "kind": kind, "inner": inner,
+            })
+        };
+        let generics = json!({ "params": [], "where_predicates": [] });
+        serde_json::from_value(json!({
+            "root": "0:0",
+            "crate_version": "1.0.0",
+            "includes_private": false,
+            "index": {
+                "0:0": item("0:0", "demo", "module", json!({ "is_crate": true, "items": [struct_id, fn_id] })),
+                struct_id: item(struct_id, "Foo", "struct", json!({
+                    "struct_type": "unit", "generics": generics, "fields_stripped": false,
+                    "fields": [], "impls": [],
+                })),
+                fn_id: item(fn_id, "bar", "function", json!({
+                    "decl": { "inputs": [], "output": { "kind": "resolved_path", "inner": {
+                        "name": "String", "id": "5:100", "args": null, "param_names": [],
+                    } }, "c_variadic": false },
+                    "generics": generics,
+                    "header": { "const": false, "unsafe": false, "async": false, "abi": "Rust" },
+                })),
+                "0:99": item("0:99", "unused", "struct", json!({
+                    "struct_type": "unit", "generics": generics, "fields_stripped": false,
+                    "fields": [], "impls": [],
+                })),
+            },
+            "paths": {
+                "0:0": { "crate_id": 0, "path": ["demo"], "kind": "module" },
+                struct_id: { "crate_id": 0, "path": ["demo", "Foo"], "kind": "struct" },
+                fn_id: { "crate_id": 0, "path": ["demo", "bar"], "kind": "function" },
+                "5:100": { "crate_id": 5, "path": ["alloc", "string", "String"], "kind": "struct" },
+                "7:1": { "crate_id": 7, "path": ["std", "unused"], "kind": "function" },
+            },
+            "external_crates": {
+                "5": { "name": "alloc", "html_root_url": null },
+                "7": { "name": "std", "html_root_url": null },
+            },
+            "format_version": FORMAT_VERSION,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn snapshots_are_stable_and_load_as_the_crate() {
+        let mut snapshot = vec![];
+        write_snapshot(&example_crate("0:3", "0:8"), &mut snapshot).unwrap();
+        let mut renumbered = vec![];
+        write_snapshot(&example_crate("0:12", "0:4"), &mut renumbered).unwrap();
+        assert_eq!(
+            String::from_utf8(snapshot.clone()).unwrap(),
+            String::from_utf8(renumbered).unwrap(),
+        );
+
+        let path = std::env::temp_dir().join(format!("api-snapshot-{}.json", std::process::id()));
+        std::fs::write(&path, &snapshot).unwrap();
+        let crate_ = load_snapshot(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        let mut names: Vec<_> = crate_
+            .index
+            .values()
+            .map(|item| item.name.as_deref().unwrap())
+            .collect();
+        names.sort_unstable();
+        assert_eq!(vec!["Foo", "bar", "demo"], names);
+        assert!(crate_
+            .index
+            .values()
+            .all(|item| item.docs.as_deref() == Some("Summary line.") && item.links.is_empty()));
+        assert!(crate_
+            .paths
+            .contains_key(&rustdoc_types::Id("5:100".to_string())));
+        assert!(!crate_
+            .paths
+            .contains_key(&rustdoc_types::Id("7:1".to_string())));
+        assert_eq!(1, crate_.external_crates.len());
+    }
+}
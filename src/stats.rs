@@ -0,0 +1,271 @@
+//! Performance statistics for a `check-release` run, printed with `--stats`.
+
+use std::{collections::BTreeMap, io::Write, time::Duration};
+
+use serde_json::json;
+use termcolor::Color;
+use termcolor_output::{colored, colored_ln};
+
+use crate::{adapter::IndexUsage, GlobalConfig};
+
+/// How to print the statistics.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum StatsFormat {
+    /// A table, with the slowest checks first.
+    Human,
+    Json,
+}
+
+impl StatsFormat {
+    /// The names of the formats, as accepted by `--stats`.
+    pub(crate) const NAMES: [&'static str; 2] = ["human", "json"];
+
+    pub(crate) fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "human" => Some(Self::Human),
+            "json" => Some(Self::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Performance data for one check, summed over all the crates it checked.
+#[derive(Debug, Clone, Copy, Default)]
+struct CheckStats {
+    duration: Duration,
+    /// The number of results, including those suppressed by exceptions.
+    results: usize,
+    suppressed: usize,
+}
+
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RunStats<'a> {
+    checks: BTreeMap<&'a str, CheckStats>,
+    /// The time spent generating, downloading, or loading rustdoc JSON, if known.
+    rustdoc_duration: Option<Duration>,
+    /// How the checks used each of the adapter's indexes, over all the crates they checked.
+    indexes: BTreeMap<&'static str, IndexUsage>,
+}
+
+impl<'a> RunStats<'a> {
+    pub(crate) fn new(rustdoc_duration: Option<Duration>) -> Self {
+        Self {
+            checks: BTreeMap::new(),
+            rustdoc_duration,
+            indexes: BTreeMap::new(),
+        }
+    }
+
+    /// Record one run of a check.
+    pub(crate) fn record(
+        &mut self,
+        check_id: &'a str,
+        duration: Duration,
+        results: usize,
+        suppressed: usize,
+    ) {
+        let stats = self.checks.entry(check_id).or_default();
+        stats.duration += duration;
+        stats.results += results + suppressed;
+        stats.suppressed += suppressed;
+    }
+
+    /// Record how the checks of one comparison used the adapter's indexes.
+    pub(crate) fn record_index_usage(&mut self, usage: BTreeMap<&'static str, IndexUsage>) {
+        for (name, usage) in usage {
+            *self.indexes.entry(name).or_default() += usage;
+        }
+    }
+
+    fn total_check_duration(&self) -> Duration {
+        self.checks.values().map(|stats| stats.duration).sum()
+    }
+
+    /// The checks, from slowest to fastest.
+    fn slowest_first(&self) -> Vec<(&'a str, CheckStats)> {
+        let mut checks: Vec<_> = self
+            .checks
+            .iter()
+            .map(|(check_id, stats)| (*check_id, *stats))
+            .collect();
+        checks.sort_by_key(|(_, stats)| std::cmp::Reverse(stats.duration));
+        checks
+    }
+
+    pub(crate) fn print(&self, config: &mut GlobalConfig, format: StatsFormat) {
+        match format {
+            StatsFormat::Human => self.print_table(config),
+            StatsFormat::Json => {
+                let checks: Vec<_> = self
+                    .slowest_first()
+                    .into_iter()
+                    .map(|(check_id, stats)| {
+                        json!({
+                            "id": check_id,
+                            "seconds": stats.duration.as_secs_f64(),
+                            "results": stats.results,
+                            "suppressed": stats.suppressed,
+                        })
+                    })
+                    .collect();
+                let indexes: Vec<_> = self
+                    .indexes
+                    .iter()
+                    .map(|(name, usage)| {
+                        json!({
+                            "name": name,
+                            "uses": usage.uses,
+                            "hits": usage.hits,
+                            "hitRate": hit_rate(usage),
+                        })
+                    })
+                    .collect();
+                let stats = json!({
+                    "rustdocSeconds": self.rustdoc_duration.map(|duration| duration.as_secs_f64()),
+                    "checkSeconds": self.total_check_duration().as_secs_f64(),
+                    "checks": checks,
+                    "indexes": indexes,
+                });
+                writeln!(config.output_writer, "{stats}").expect("print failed");
+            }
+        }
+    }
+
+    fn print_table(&self, config: &mut GlobalConfig) {
+        if let Some(rustdoc_duration) = self.rustdoc_duration {
+            colored_ln(&mut config.output_writer, |w| {
+                colored!(
+                    w,
+                    "{}{}{:>12}{} [{:>8.3}s] generating and loading rustdoc",
+                    fg!(Some(Color::Cyan)),
+                    bold!(true),
+                    "Stats",
+                    reset!(),
+                    rustdoc_duration.as_secs_f32(),
+                )
+            })
+            .expect("print failed");
+        }
+        colored_ln(&mut config.output_writer, |w| {
+            colored!(
+                w,
+                "{}{}{:>12}{} [{:>8.3}s] running {} checks, slowest first:",
+                fg!(Some(Color::Cyan)),
+                bold!(true),
+                "Stats",
+                reset!(),
+                self.total_check_duration().as_secs_f32(),
+                self.checks.len(),
+            )
+        })
+        .expect("print failed");
+        colored_ln(&mut config.output_writer, |w| {
+            colored!(
+                w,
+                "{}{:>12} {:>11} {:>8} {:>10}  {}{}",
+                bold!(true),
+                "",
+                "time",
+                "results",
+                "suppressed",
+                "check",
+                reset!(),
+            )
+        })
+        .expect("print failed");
+        for (check_id, stats) in self.slowest_first() {
+            colored_ln(&mut config.output_writer, |w| {
+                colored!(
+                    w,
+                    "{:>12} [{:>8.3}s] {:>8} {:>10}  {}",
+                    "",
+                    stats.duration.as_secs_f32(),
+                    stats.results,
+                    stats.suppressed,
+                    check_id,
+                )
+            })
+            .expect("print failed");
+        }
+
+        let used_indexes: Vec<_> = self
+            .indexes
+            .iter()
+            .filter(|(_, usage)| usage.uses > 0)
+            .collect();
+        if used_indexes.is_empty() {
+            return;
+        }
+        colored_ln(&mut config.output_writer, |w| {
+            colored!(
+                w,
+                "{}{}{:>12}{} indexes, reused by the checks that needed them:",
+                fg!(Some(Color::Cyan)),
+                bold!(true),
+                "Stats",
+                reset!(),
+            )
+        })
+        .expect("print failed");
+        for (name, usage) in used_indexes {
+            colored_ln(&mut config.output_writer, |w| {
+                colored!(
+                    w,
+                    "{:>12} {:>9.1}% {:>8} of {:>5} uses  {}",
+                    "",
+                    hit_rate(usage).unwrap_or_default() * 100.0,
+                    usage.hits,
+                    usage.uses,
+                    name,
+                )
+            })
+            .expect("print failed");
+        }
+    }
+}
+
+/// The share of the uses of an index that found it already built, if it was used.
+fn hit_rate(usage: &IndexUsage) -> Option<f64> {
+    (usage.uses > 0).then(|| usage.hits as f64 / usage.uses as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, time::Duration};
+
+    use super::{hit_rate, RunStats};
+    use crate::adapter::IndexUsage;
+
+    #[test]
+    fn stats_are_summed_per_check_and_sorted_by_time() {
+        let mut stats = RunStats::new(None);
+        stats.record("fast", Duration::from_millis(1), 0, 0);
+        stats.record("slow", Duration::from_millis(5), 2, 1);
+        stats.record("slow", Duration::from_millis(5), 1, 0);
+
+        let checks = stats.slowest_first();
+        assert_eq!(
+            vec!["slow", "fast"],
+            checks.iter().map(|(id, _)| *id).collect::<Vec<_>>()
+        );
+        assert_eq!(Duration::from_millis(10), checks[0].1.duration);
+        assert_eq!(4, checks[0].1.results);
+        assert_eq!(1, checks[0].1.suppressed);
+        assert_eq!(Duration::from_millis(11), stats.total_check_duration());
+    }
+
+    #[test]
+    fn index_usage_is_summed_over_comparisons() {
+        let mut stats = RunStats::new(None);
+        let usage = |uses, hits| IndexUsage { uses, hits };
+        stats.record_index_usage(BTreeMap::from([("renames", usage(3, 2))]));
+        stats.record_index_usage(BTreeMap::from([
+            ("renames", usage(5, 4)),
+            ("visible_items", usage(0, 0)),
+        ]));
+
+        assert_eq!(usage(8, 6), stats.indexes["renames"]);
+        assert_eq!(Some(0.75), hit_rate(&stats.indexes["renames"]));
+        assert_eq!(None, hit_rate(&stats.indexes["visible_items"]));
+    }
+}
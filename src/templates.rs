@@ -0,0 +1,315 @@
+//! The Handlebars registry that renders the per-result templates of the queries,
+//! with helpers for turning query outputs into readable messages:
+//!
+//! - `{{join list ", "}}` joins the elements of a list with a separator, `", "` by default.
+//! - `{{pluralize count "field"}}` is `field` if the count is 1 and `fields` otherwise.
+//!   The plural can be given as a third argument, and the count can be a list, to use its length.
+//! - `{{code value}}` wraps a value in backticks. A list is joined with `::`, as a path.
+//! - `{{link_to_docs path "struct"}}` links to the item's page on docs.rs, given its path
+//!   and the kind of item as it appears in rustdoc page names, like `fn` or `trait`.
+//!   The version defaults to `latest`, and can be set with `version="1.2.3"`.
+//! - `{{#unless_empty value}}...{{else}}...{{/unless_empty}}` renders its block if the value
+//!   is present and isn't an empty string or list. Unlike `#if`, `0` and `false` count as present.
+//!
+//! Projects can override the templates of checks, and the summary at the end of the report,
+//! with their own in a directory passed to `--templates`.
+
+use std::{collections::BTreeMap, ffi::OsStr, path::Path};
+
+use anyhow::Context as _;
+use handlebars::{
+    Context, Handlebars, Helper, HelperResult, Output, RenderContext, RenderError, Renderable,
+};
+use serde_json::Value;
+
+use crate::{
+    docs_rs::item_page_url,
+    query::{unknown_check_id, SemverQuery},
+};
+
+/// The file in a `--templates` directory that replaces the summary at the end of the report.
+/// Every other `<check id>.hbs` file replaces the `per_result_error_template` of that check.
+const SUMMARY_TEMPLATE_FILE: &str = "summary.hbs";
+
+/// The registry used to render the templates of the queries.
+pub(crate) fn template_registry() -> Handlebars<'static> {
+    let mut reg = Handlebars::new();
+    // Messages are plain text and witnesses are Rust code, so values must not be HTML-escaped.
+    reg.register_escape_fn(handlebars::no_escape);
+    reg.register_helper("join", Box::new(join));
+    reg.register_helper("pluralize", Box::new(pluralize));
+    reg.register_helper("code", Box::new(code));
+    reg.register_helper("link_to_docs", Box::new(link_to_docs));
+    reg.register_helper("unless_empty", Box::new(unless_empty));
+    reg
+}
+
+/// The templates of a `--templates` directory, which have been checked to be valid Handlebars.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OutputTemplates {
+    /// The templates that replace the `per_result_error_template` of checks, by check id.
+    pub(crate) checks: BTreeMap<String, String>,
+    pub(crate) summary: Option<String>,
+}
+
+impl OutputTemplates {
+    pub(crate) fn load(dir: &Path) -> anyhow::Result<Self> {
+        let mut templates = Self::default();
+        let reg = template_registry();
+        for entry in std::fs::read_dir(dir)
+            .with_context(|| format!("Failed to read templates directory {:?}", dir))?
+        {
+            let path = entry
+                .with_context(|| format!("Failed to read templates directory {:?}", dir))?
+                .path();
+            if !path.is_file() || path.extension() != Some(OsStr::new("hbs")) {
+                continue;
+            }
+            let template = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read template {:?}", path))?;
+            // Files end with a newline, which isn't part of the message.
+            let template = template.trim_end_matches(['\n', '\r']).to_string();
+            reg.clone()
+                .register_template_string("template", &template)
+                .with_context(|| format!("Invalid template {:?}", path))?;
+
+            let name = path
+                .file_stem()
+                .and_then(OsStr::to_str)
+                .with_context(|| format!("Non-UTF-8 template file name {:?}", path))?;
+            if path.file_name() == Some(OsStr::new(SUMMARY_TEMPLATE_FILE)) {
+                templates.summary = Some(template);
+            } else {
+                templates.checks.insert(name.to_string(), template);
+            }
+        }
+        Ok(templates)
+    }
+
+    /// Use the templates of the checks instead of the ones in their query files.
+    /// Templates for checks that don't exist are an error, since they'd be silently unused.
+    pub(crate) fn override_checks(
+        &self,
+        queries: &mut BTreeMap<String, SemverQuery>,
+    ) -> anyhow::Result<()> {
+        for (id, template) in &self.checks {
+            match queries.get_mut(id) {
+                Some(query) => query.per_result_error_template = Some(template.clone()),
+                None => {
+                    return Err(unknown_check_id(id, queries))
+                        .with_context(|| format!("Invalid template file {id}.hbs"))
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn param<'a>(h: &'a Helper, index: usize) -> Result<&'a Value, RenderError> {
+    h.param(index).map(|param| param.value()).ok_or_else(|| {
+        RenderError::new(format!(
+            "Missing argument {} of helper \"{}\"",
+            index + 1,
+            h.name()
+        ))
+    })
+}
+
+/// The value as plain text: strings without quotes, and anything else as JSON.
+fn text(value: &Value) -> String {
+    match value {
+        Value::String(string) => string.clone(),
+        Value::Null => String::new(),
+        other => other.to_string(),
+    }
+}
+
+fn join_values(value: &Value, separator: &str) -> String {
+    match value {
+        Value::Array(values) => values.iter().map(text).collect::<Vec<_>>().join(separator),
+        other => text(other),
+    }
+}
+
+fn join(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let separator = h.param(1).and_then(|p| p.value().as_str()).unwrap_or(", ");
+    out.write(&join_values(param(h, 0)?, separator))?;
+    Ok(())
+}
+
+fn pluralize(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let count = match param(h, 0)? {
+        Value::Array(values) => values.len() as u64,
+        value => value
+            .as_u64()
+            .ok_or_else(|| RenderError::new("Helper \"pluralize\" needs a count or a list"))?,
+    };
+    let singular = text(param(h, 1)?);
+    let word = if count == 1 {
+        singular
+    } else {
+        match h.param(2) {
+            Some(plural) => text(plural.value()),
+            None => format!("{singular}s"),
+        }
+    };
+    out.write(&word)?;
+    Ok(())
+}
+
+fn code(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    out.write(&format!("`{}`", join_values(param(h, 0)?, "::")))?;
+    Ok(())
+}
+
+fn link_to_docs(
+    h: &Helper,
+    _: &Handlebars,
+    _: &Context,
+    _: &mut RenderContext,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let path: Vec<String> = match param(h, 0)? {
+        Value::Array(values) => values.iter().map(text).collect(),
+        value => text(value).split("::").map(str::to_string).collect(),
+    };
+    let kind = text(param(h, 1)?);
+    let version = h
+        .hash_get("version")
+        .map_or_else(|| "latest".to_string(), |v| text(v.value()));
+    let path: Vec<&str> = path.iter().map(String::as_str).collect();
+    let url = path
+        .first()
+        .and_then(|crate_name| item_page_url(crate_name, &version, &path, &kind))
+        .ok_or_else(|| {
+            RenderError::new("Helper \"link_to_docs\" needs the path of an item in a crate")
+        })?;
+    out.write(&url)?;
+    Ok(())
+}
+
+fn unless_empty<'reg, 'rc>(
+    h: &Helper<'reg, 'rc>,
+    r: &'reg Handlebars<'reg>,
+    ctx: &'rc Context,
+    rc: &mut RenderContext<'reg, 'rc>,
+    out: &mut dyn Output,
+) -> HelperResult {
+    let is_empty = match h.param(0).map(|p| p.value()) {
+        None | Some(Value::Null) => true,
+        Some(Value::String(string)) => string.is_empty(),
+        Some(Value::Array(values)) => values.is_empty(),
+        Some(Value::Object(fields)) => fields.is_empty(),
+        Some(_) => false,
+    };
+    let template = if is_empty { h.inverse() } else { h.template() };
+    match template {
+        Some(template) => template.render(r, ctx, rc, out),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use serde_json::json;
+
+    use crate::query::SemverQuery;
+
+    use super::{template_registry, OutputTemplates};
+
+    #[test]
+    fn helpers_render_multiple_values_readably() {
+        let reg = template_registry();
+        let data = json!({
+            "path": ["my_crate", "config", "Config"],
+            "renamed_to": ["Settings", "Options"],
+            "fields": ["quiet"],
+            "none": [],
+            "zero": 0,
+        });
+        let cases = [
+            ("{{join renamed_to \" or \"}}", "Settings or Options"),
+            ("{{join renamed_to}}", "Settings, Options"),
+            ("{{pluralize fields \"field\"}}", "field"),
+            ("{{pluralize renamed_to \"alias\" \"aliases\"}}", "aliases"),
+            ("{{pluralize zero \"field\"}}", "fields"),
+            ("{{code path}}", "`my_crate::config::Config`"),
+            (
+                "{{link_to_docs path \"struct\"}}",
+                "https://docs.rs/my_crate/latest/my_crate/config/struct.Config.html",
+            ),
+            (
+                "{{link_to_docs path \"mod\" version=\"1.2.3\"}}",
+                "https://docs.rs/my_crate/1.2.3/my_crate/config/Config/index.html",
+            ),
+            ("{{#unless_empty none}}yes{{else}}no{{/unless_empty}}", "no"),
+            (
+                "{{#unless_empty zero}}yes{{else}}no{{/unless_empty}}",
+                "yes",
+            ),
+            ("{{#unless_empty missing}}yes{{/unless_empty}}", ""),
+        ];
+        for (template, expected) in cases {
+            assert_eq!(
+                expected,
+                reg.render_template(template, &data).unwrap(),
+                "{template}"
+            );
+        }
+        assert!(reg
+            .render_template("{{link_to_docs zero \"fn\"}}", &data)
+            .is_err());
+    }
+
+    #[test]
+    fn templates_directory_overrides_checks_and_summary() {
+        let dir = std::env::temp_dir().join(format!("semver-templates-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("struct_missing.hbs"), "gone: {{code name}}\n").unwrap();
+        std::fs::write(dir.join("summary.hbs"), "{{checks_failed}} failed\n").unwrap();
+        std::fs::write(dir.join("notes.txt"), "{{ not a template").unwrap();
+
+        let templates = OutputTemplates::load(&dir).unwrap();
+        assert_eq!(
+            Some("{{checks_failed}} failed"),
+            templates.summary.as_deref()
+        );
+        let mut queries = SemverQuery::all_queries();
+        templates.override_checks(&mut queries).unwrap();
+        assert_eq!(
+            Some("gone: {{code name}}"),
+            queries["struct_missing"]
+                .per_result_error_template
+                .as_deref()
+        );
+
+        std::fs::write(dir.join("no_such_check.hbs"), "{{name}}").unwrap();
+        let templates = OutputTemplates::load(&dir).unwrap();
+        assert!(templates.override_checks(&mut queries).is_err());
+
+        std::fs::write(dir.join("no_such_check.hbs"), "{{#if name}}").unwrap();
+        assert!(OutputTemplates::load(&dir).is_err());
+        std::fs::remove_dir_all(&dir).unwrap();
+        assert!(OutputTemplates::load(Path::new(&dir)).is_err());
+    }
+}
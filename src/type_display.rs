@@ -0,0 +1,798 @@
+//! Rendering the types in rustdoc JSON as Rust code, so queries can compare and print them.
+
+use rustdoc_types::{
+    Abi, FnDecl, FunctionPointer, GenericArg, GenericArgs, GenericBound, GenericParamDef,
+    GenericParamDefKind, Generics, Header, Term, TraitBoundModifier, Type, TypeBinding,
+    TypeBindingKind, WherePredicate,
+};
+
+/// The type as it would be written in Rust code, e.g. `&'a mut Vec<u8>`.
+///
+/// Paths are rendered as rustdoc records them, which is usually the type's name
+/// without the module it's defined in.
+pub(crate) fn type_to_string(ty: &Type) -> String {
+    match ty {
+        Type::ResolvedPath {
+            name,
+            args,
+            param_names,
+            ..
+        } => {
+            let path = format!(
+                "{name}{}",
+                args.as_deref()
+                    .map(generic_args_to_string)
+                    .unwrap_or_default()
+            );
+            // Only trait objects have bounds alongside their path.
+            if param_names.is_empty() {
+                path
+            } else {
+                format!("dyn {path} + {}", bounds_to_string(param_names))
+            }
+        }
+        Type::Generic(name) | Type::Primitive(name) => name.clone(),
+        Type::FunctionPointer(pointer) => function_pointer_to_string(pointer),
+        Type::Tuple(types) => match types.as_slice() {
+            [ty] => format!("({},)", type_to_string(ty)),
+            types => format!("({})", types_to_string(types)),
+        },
+        Type::Slice(ty) => format!("[{}]", type_to_string(ty)),
+        Type::Array { type_, len } => format!("[{}; {len}]", type_to_string(type_)),
+        Type::ImplTrait(bounds) => format!("impl {}", bounds_to_string(bounds)),
+        Type::Infer => "_".to_string(),
+        Type::RawPointer { mutable, type_ } => format!(
+            "*{} {}",
+            if *mutable { "mut" } else { "const" },
+            type_to_string(type_)
+        ),
+        Type::BorrowedRef {
+            lifetime,
+            mutable,
+            type_,
+        } => {
+            let mut rendered = "&".to_string();
+            if let Some(lifetime) = lifetime {
+                rendered.push_str(lifetime);
+                rendered.push(' ');
+            }
+            if *mutable {
+                rendered.push_str("mut ");
+            }
+            rendered.push_str(&type_to_string(type_));
+            rendered
+        }
+        Type::QualifiedPath {
+            name,
+            args,
+            self_type,
+            trait_,
+        } => format!(
+            "<{} as {}>::{name}{}",
+            type_to_string(self_type),
+            type_to_string(trait_),
+            generic_args_to_string(args)
+        ),
+    }
+}
+
+/// The bounds as they would be written after a `:`, e.g. `for<'a> Fn(&'a str) + ?Sized`.
+pub(crate) fn bounds_to_string(bounds: &[GenericBound]) -> String {
+    bounds
+        .iter()
+        .map(|bound| match bound {
+            GenericBound::TraitBound {
+                trait_,
+                generic_params,
+                modifier,
+            } => {
+                let modifier = match modifier {
+                    TraitBoundModifier::None => "",
+                    TraitBoundModifier::Maybe => "?",
+                    TraitBoundModifier::MaybeConst => "~const ",
+                };
+                format!(
+                    "{}{modifier}{}",
+                    higher_ranked(generic_params),
+                    type_to_string(trait_)
+                )
+            }
+            GenericBound::Outlives(lifetime) => lifetime.clone(),
+        })
+        .collect::<Vec<_>>()
+        .join(" + ")
+}
+
+/// The type or lifetime constrained by a where-clause predicate, and each of the bounds
+/// it must satisfy, all rendered as Rust code. An equality predicate like `T::Item = u8`
+/// has its right-hand side as its only bound.
+pub(crate) fn where_predicate_parts(predicate: &WherePredicate) -> (String, Vec<String>) {
+    let each_bound = |bounds: &[GenericBound]| {
+        bounds
+            .iter()
+            .map(|bound| bounds_to_string(std::slice::from_ref(bound)))
+            .collect()
+    };
+    match predicate {
+        WherePredicate::BoundPredicate {
+            type_,
+            bounds,
+            generic_params,
+        } => (
+            format!("{}{}", higher_ranked(generic_params), type_to_string(type_)),
+            each_bound(bounds),
+        ),
+        WherePredicate::RegionPredicate { lifetime, bounds } => {
+            (lifetime.clone(), each_bound(bounds))
+        }
+        WherePredicate::EqPredicate { lhs, rhs } => {
+            (type_to_string(lhs), vec![term_to_string(rhs)])
+        }
+    }
+}
+
+/// What a lifetime bound constrains to outlive its lifetime.
+#[derive(Debug, Clone)]
+pub enum OutlivesSubject<'a> {
+    /// A lifetime, as in `'a: 'b`.
+    Lifetime(&'a str),
+    /// A generic type parameter, as in `T: 'a`.
+    Generic(&'a str),
+    /// Any other type, as in `where &'a T: 'b`.
+    Type(&'a Type),
+}
+
+/// A bound that `subject` outlives `lifetime`, like `'a: 'b` or `T: 'static`.
+#[derive(Debug, Clone)]
+pub struct LifetimeBound<'a> {
+    pub(crate) subject: OutlivesSubject<'a>,
+    pub(crate) lifetime: &'a str,
+    /// Whether the bound is written in the where clause, rather than with the parameter.
+    pub(crate) in_where_clause: bool,
+}
+
+impl LifetimeBound<'_> {
+    pub(crate) fn subject_to_string(&self) -> String {
+        match self.subject {
+            OutlivesSubject::Lifetime(name) | OutlivesSubject::Generic(name) => name.to_string(),
+            OutlivesSubject::Type(ty) => type_to_string(ty),
+        }
+    }
+}
+
+/// Every lifetime bound of the generics, those declared with their parameter first,
+/// and then those in the where clause, in the order they're written.
+pub(crate) fn lifetime_bounds(generics: &Generics) -> Vec<LifetimeBound<'_>> {
+    let declared = generics.params.iter().flat_map(|param| match &param.kind {
+        GenericParamDefKind::Lifetime { outlives } => outlives
+            .iter()
+            .map(|lifetime| LifetimeBound {
+                subject: OutlivesSubject::Lifetime(&param.name),
+                lifetime,
+                in_where_clause: false,
+            })
+            .collect(),
+        GenericParamDefKind::Type { bounds, .. } => {
+            outlives_bounds(OutlivesSubject::Generic(&param.name), bounds, false)
+        }
+        GenericParamDefKind::Const { .. } => vec![],
+    });
+    let in_where_clause = generics
+        .where_predicates
+        .iter()
+        .flat_map(|predicate| match predicate {
+            WherePredicate::BoundPredicate { type_, bounds, .. } => {
+                let subject = match type_ {
+                    Type::Generic(name) => OutlivesSubject::Generic(name),
+                    _ => OutlivesSubject::Type(type_),
+                };
+                outlives_bounds(subject, bounds, true)
+            }
+            WherePredicate::RegionPredicate { lifetime, bounds } => {
+                outlives_bounds(OutlivesSubject::Lifetime(lifetime), bounds, true)
+            }
+            WherePredicate::EqPredicate { .. } => vec![],
+        });
+    declared.chain(in_where_clause).collect()
+}
+
+/// The lifetimes among the bounds of `subject`, as bounds of their own.
+fn outlives_bounds<'a>(
+    subject: OutlivesSubject<'a>,
+    bounds: &'a [GenericBound],
+    in_where_clause: bool,
+) -> Vec<LifetimeBound<'a>> {
+    bounds
+        .iter()
+        .filter_map(|bound| match bound {
+            GenericBound::Outlives(lifetime) => Some(LifetimeBound {
+                subject: subject.clone(),
+                lifetime,
+                in_where_clause,
+            }),
+            GenericBound::TraitBound { .. } => None,
+        })
+        .collect()
+}
+
+/// The predicate as it would be written in a where clause, e.g. `T: Clone + 'a`.
+pub(crate) fn where_predicate_to_string(predicate: &WherePredicate) -> String {
+    let (subject, bounds) = where_predicate_parts(predicate);
+    let separator = match predicate {
+        WherePredicate::EqPredicate { .. } => " = ",
+        _ => ": ",
+    };
+    format!("{subject}{separator}{}", bounds.join(" + "))
+}
+
+/// The type and every type written within it, like the arguments of a generic type or of
+/// the traits that `impl` and `dyn` types are bounded by, outermost first and otherwise
+/// in the order they're written. The traits themselves aren't types, so they're left out.
+pub(crate) fn nested_types(ty: &Type) -> Vec<&Type> {
+    let mut types = vec![];
+    collect_nested_types(ty, &mut types);
+    types
+}
+
+fn collect_nested_types<'a>(ty: &'a Type, types: &mut Vec<&'a Type>) {
+    types.push(ty);
+    match ty {
+        Type::ResolvedPath {
+            args, param_names, ..
+        } => {
+            if let Some(args) = args {
+                collect_generic_args_types(args, types);
+            }
+            collect_bounds_types(param_names, types);
+        }
+        Type::Generic(..) | Type::Primitive(..) | Type::Infer => {}
+        Type::FunctionPointer(pointer) => {
+            for (_, input) in &pointer.decl.inputs {
+                collect_nested_types(input, types);
+            }
+            if let Some(output) = &pointer.decl.output {
+                collect_nested_types(output, types);
+            }
+        }
+        Type::Tuple(elements) => {
+            for element in elements {
+                collect_nested_types(element, types);
+            }
+        }
+        Type::Slice(type_)
+        | Type::Array { type_, .. }
+        | Type::RawPointer { type_, .. }
+        | Type::BorrowedRef { type_, .. } => collect_nested_types(type_, types),
+        Type::ImplTrait(bounds) => collect_bounds_types(bounds, types),
+        Type::QualifiedPath {
+            args,
+            self_type,
+            trait_,
+            ..
+        } => {
+            collect_nested_types(self_type, types);
+            collect_trait_types(trait_, types);
+            collect_generic_args_types(args, types);
+        }
+    }
+}
+
+fn collect_generic_args_types<'a>(args: &'a GenericArgs, types: &mut Vec<&'a Type>) {
+    match args {
+        GenericArgs::AngleBracketed { args, bindings } => {
+            for arg in args {
+                match arg {
+                    GenericArg::Type(ty) => collect_nested_types(ty, types),
+                    GenericArg::Lifetime(..) | GenericArg::Const(..) | GenericArg::Infer => {}
+                }
+            }
+            for binding in bindings {
+                collect_generic_args_types(&binding.args, types);
+                match &binding.binding {
+                    TypeBindingKind::Equality(Term::Type(ty)) => collect_nested_types(ty, types),
+                    TypeBindingKind::Equality(Term::Constant(..)) => {}
+                    TypeBindingKind::Constraint(bounds) => collect_bounds_types(bounds, types),
+                }
+            }
+        }
+        GenericArgs::Parenthesized { inputs, output } => {
+            for input in inputs {
+                collect_nested_types(input, types);
+            }
+            if let Some(output) = output {
+                collect_nested_types(output, types);
+            }
+        }
+    }
+}
+
+fn collect_bounds_types<'a>(bounds: &'a [GenericBound], types: &mut Vec<&'a Type>) {
+    for bound in bounds {
+        if let GenericBound::TraitBound { trait_, .. } = bound {
+            collect_trait_types(trait_, types);
+        }
+    }
+}
+
+/// Collect the types in the generic arguments of a trait, but not the trait itself.
+fn collect_trait_types<'a>(trait_: &'a Type, types: &mut Vec<&'a Type>) {
+    match trait_ {
+        Type::ResolvedPath {
+            args: Some(args), ..
+        } => collect_generic_args_types(args, types),
+        Type::ResolvedPath { args: None, .. } => {}
+        _ => collect_nested_types(trait_, types),
+    }
+}
+
+fn term_to_string(term: &Term) -> String {
+    match term {
+        Term::Type(ty) => type_to_string(ty),
+        Term::Constant(constant) => constant.expr.clone(),
+    }
+}
+
+fn types_to_string(types: &[Type]) -> String {
+    types
+        .iter()
+        .map(type_to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+fn generic_args_to_string(args: &GenericArgs) -> String {
+    match args {
+        GenericArgs::AngleBracketed { args, bindings } => {
+            let args: Vec<_> = args
+                .iter()
+                .map(|arg| match arg {
+                    GenericArg::Lifetime(lifetime) => lifetime.clone(),
+                    GenericArg::Type(ty) => type_to_string(ty),
+                    GenericArg::Const(constant) => constant.expr.clone(),
+                    GenericArg::Infer => "_".to_string(),
+                })
+                .chain(bindings.iter().map(binding_to_string))
+                .collect();
+            if args.is_empty() {
+                String::new()
+            } else {
+                format!("<{}>", args.join(", "))
+            }
+        }
+        GenericArgs::Parenthesized { inputs, output } => format!(
+            "({}){}",
+            types_to_string(inputs),
+            return_type_suffix(output.as_ref())
+        ),
+    }
+}
+
+fn binding_to_string(binding: &TypeBinding) -> String {
+    let value = match &binding.binding {
+        TypeBindingKind::Equality(term) => format!(" = {}", term_to_string(term)),
+        TypeBindingKind::Constraint(bounds) => format!(": {}", bounds_to_string(bounds)),
+    };
+    format!(
+        "{}{}{value}",
+        binding.name,
+        generic_args_to_string(&binding.args)
+    )
+}
+
+/// The declaration of a function or method as it would be written in Rust code, without its
+/// visibility or body, e.g. `const fn get<'a, T: Clone>(items: &'a [T]) -> Option<&'a T>`.
+pub(crate) fn signature_to_string(
+    name: &str,
+    header: &Header,
+    decl: &FnDecl,
+    generics: &Generics,
+) -> String {
+    let mut rendered = String::new();
+    for (qualifier, present) in [
+        ("const ", header.const_),
+        ("async ", header.async_),
+        ("unsafe ", header.unsafe_),
+    ] {
+        if present {
+            rendered.push_str(qualifier);
+        }
+    }
+    if let Some(abi) = abi_name(&header.abi) {
+        rendered.push_str(&format!("extern \"{abi}\" "));
+    }
+    rendered.push_str("fn ");
+    rendered.push_str(name);
+
+    // Parameters the compiler introduced for `impl Trait` arguments are written as arguments.
+    let params: Vec<_> = generics
+        .params
+        .iter()
+        .filter(|param| {
+            !matches!(
+                param.kind,
+                GenericParamDefKind::Type {
+                    synthetic: true,
+                    ..
+                }
+            )
+        })
+        .map(generic_param_to_string)
+        .collect();
+    if !params.is_empty() {
+        rendered.push_str(&format!("<{}>", params.join(", ")));
+    }
+
+    let mut inputs: Vec<_> = decl
+        .inputs
+        .iter()
+        .map(|(name, ty)| match (name.as_str(), ty) {
+            ("self", Type::Generic(self_type)) if self_type == "Self" => "self".to_string(),
+            ("self", Type::BorrowedRef { type_, .. })
+                if **type_ == Type::Generic("Self".to_string()) =>
+            {
+                type_to_string(ty).replacen("Self", "self", 1)
+            }
+            _ => format!("{name}: {}", type_to_string(ty)),
+        })
+        .collect();
+    if decl.c_variadic {
+        inputs.push("...".to_string());
+    }
+    rendered.push_str(&format!(
+        "({}){}",
+        inputs.join(", "),
+        return_type_suffix(decl.output.as_ref())
+    ));
+
+    if !generics.where_predicates.is_empty() {
+        let predicates: Vec<_> = generics
+            .where_predicates
+            .iter()
+            .map(where_predicate_to_string)
+            .collect();
+        rendered.push_str(&format!(" where {}", predicates.join(", ")));
+    }
+    rendered
+}
+
+/// A generic parameter as declared, e.g. `'a: 'b`, `T: Clone = u8`, or `const N: usize`.
+fn generic_param_to_string(param: &GenericParamDef) -> String {
+    match &param.kind {
+        GenericParamDefKind::Lifetime { outlives } if outlives.is_empty() => param.name.clone(),
+        GenericParamDefKind::Lifetime { outlives } => {
+            format!("{}: {}", param.name, outlives.join(" + "))
+        }
+        GenericParamDefKind::Type {
+            bounds, default, ..
+        } => {
+            let mut rendered = param.name.clone();
+            if !bounds.is_empty() {
+                rendered.push_str(&format!(": {}", bounds_to_string(bounds)));
+            }
+            if let Some(default) = default {
+                rendered.push_str(&format!(" = {}", type_to_string(default)));
+            }
+            rendered
+        }
+        GenericParamDefKind::Const { type_, .. } => {
+            format!("const {}: {}", param.name, type_to_string(type_))
+        }
+    }
+}
+
+fn function_pointer_to_string(pointer: &FunctionPointer) -> String {
+    let mut rendered = higher_ranked(&pointer.generic_params);
+    if pointer.header.unsafe_ {
+        rendered.push_str("unsafe ");
+    }
+    if let Some(abi) = abi_name(&pointer.header.abi) {
+        rendered.push_str(&format!("extern \"{abi}\" "));
+    }
+    let mut inputs: Vec<_> = pointer
+        .decl
+        .inputs
+        .iter()
+        .map(|(_, ty)| type_to_string(ty))
+        .collect();
+    if pointer.decl.c_variadic {
+        inputs.push("...".to_string());
+    }
+    rendered.push_str(&format!(
+        "fn({}){}",
+        inputs.join(", "),
+        return_type_suffix(pointer.decl.output.as_ref())
+    ));
+    rendered
+}
+
+fn return_type_suffix(output: Option<&Type>) -> String {
+    output
+        .map(|ty| format!(" -> {}", type_to_string(ty)))
+        .unwrap_or_default()
+}
+
+/// The `for<'a, 'b> ` prefix of higher-ranked bounds and function pointers, if any.
+fn higher_ranked(generic_params: &[GenericParamDef]) -> String {
+    if generic_params.is_empty() {
+        return String::new();
+    }
+    let names: Vec<_> = generic_params
+        .iter()
+        .map(|param| param.name.as_str())
+        .collect();
+    format!("for<{}> ", names.join(", "))
+}
+
+/// The name of the ABI, as written in `extern "..."`, unless it's the default Rust ABI.
+fn abi_name(abi: &Abi) -> Option<String> {
+    let (name, unwind) = match abi {
+        Abi::Rust => return None,
+        Abi::C { unwind } => ("C", unwind),
+        Abi::Cdecl { unwind } => ("cdecl", unwind),
+        Abi::Stdcall { unwind } => ("stdcall", unwind),
+        Abi::Fastcall { unwind } => ("fastcall", unwind),
+        Abi::Aapcs { unwind } => ("aapcs", unwind),
+        Abi::Win64 { unwind } => ("win64", unwind),
+        Abi::SysV64 { unwind } => ("sysv64", unwind),
+        Abi::System { unwind } => ("system", unwind),
+        Abi::Other(name) => return Some(name.clone()),
+    };
+    Some(if *unwind {
+        format!("{name}-unwind")
+    } else {
+        name.to_string()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rustdoc_types::{
+        Abi, FnDecl, GenericArg, GenericArgs, GenericBound, GenericParamDef, GenericParamDefKind,
+        Generics, Header, Id, TraitBoundModifier, Type, WherePredicate,
+    };
+
+    use super::{
+        lifetime_bounds, nested_types, signature_to_string, type_to_string,
+        where_predicate_to_string,
+    };
+
+    fn path(name: &str, args: Vec<GenericArg>) -> Type {
+        Type::ResolvedPath {
+            name: name.to_string(),
+            id: Id("0:1".to_string()),
+            args: Some(Box::new(GenericArgs::AngleBracketed {
+                args,
+                bindings: vec![],
+            })),
+            param_names: vec![],
+        }
+    }
+
+    #[test]
+    fn types_are_rendered_as_rust_code() {
+        let bytes = Type::BorrowedRef {
+            lifetime: Some("'a".to_string()),
+            mutable: true,
+            type_: Box::new(Type::Slice(Box::new(Type::Primitive("u8".to_string())))),
+        };
+        assert_eq!("&'a mut [u8]", type_to_string(&bytes));
+
+        let result = path(
+            "Result",
+            vec![
+                GenericArg::Type(Type::Tuple(vec![])),
+                GenericArg::Type(Type::Generic("E".to_string())),
+            ],
+        );
+        assert_eq!("Result<(), E>", type_to_string(&result));
+
+        let boxed = path(
+            "Box",
+            vec![GenericArg::Type(Type::ResolvedPath {
+                name: "Error".to_string(),
+                id: Id("0:2".to_string()),
+                args: None,
+                param_names: vec![GenericBound::TraitBound {
+                    trait_: path("Send", vec![]),
+                    generic_params: vec![],
+                    modifier: TraitBoundModifier::None,
+                }],
+            })],
+        );
+        assert_eq!("Box<dyn Error + Send>", type_to_string(&boxed));
+
+        let iterator = Type::ImplTrait(vec![
+            GenericBound::TraitBound {
+                trait_: path("Iterator", vec![]),
+                generic_params: vec![],
+                modifier: TraitBoundModifier::None,
+            },
+            GenericBound::Outlives("'static".to_string()),
+        ]);
+        assert_eq!("impl Iterator + 'static", type_to_string(&iterator));
+    }
+
+    #[test]
+    fn where_predicates_are_rendered_as_rust_code() {
+        let higher_ranked = WherePredicate::BoundPredicate {
+            type_: Type::BorrowedRef {
+                lifetime: Some("'a".to_string()),
+                mutable: false,
+                type_: Box::new(Type::Generic("T".to_string())),
+            },
+            bounds: vec![GenericBound::TraitBound {
+                trait_: path("IntoIterator", vec![]),
+                generic_params: vec![],
+                modifier: TraitBoundModifier::None,
+            }],
+            generic_params: vec![GenericParamDef {
+                name: "'a".to_string(),
+                kind: GenericParamDefKind::Lifetime { outlives: vec![] },
+            }],
+        };
+        assert_eq!(
+            "for<'a> &'a T: IntoIterator",
+            where_predicate_to_string(&higher_ranked)
+        );
+
+        let outlives = WherePredicate::RegionPredicate {
+            lifetime: "'a".to_string(),
+            bounds: vec![
+                GenericBound::Outlives("'b".to_string()),
+                GenericBound::Outlives("'c".to_string()),
+            ],
+        };
+        assert_eq!("'a: 'b + 'c", where_predicate_to_string(&outlives));
+    }
+
+    #[test]
+    fn nested_types_include_impl_trait_arguments() {
+        // Option<(u8, impl Display)>
+        let display = Type::ImplTrait(vec![GenericBound::TraitBound {
+            trait_: path("Display", vec![]),
+            generic_params: vec![],
+            modifier: TraitBoundModifier::None,
+        }]);
+        let option = path(
+            "Option",
+            vec![GenericArg::Type(Type::Tuple(vec![
+                Type::Primitive("u8".to_string()),
+                display,
+            ]))],
+        );
+        let rendered: Vec<_> = nested_types(&option)
+            .into_iter()
+            .map(type_to_string)
+            .collect();
+        assert_eq!(
+            vec![
+                "Option<(u8, impl Display)>",
+                "(u8, impl Display)",
+                "u8",
+                "impl Display",
+            ],
+            rendered
+        );
+    }
+
+    #[test]
+    fn lifetime_bounds_are_collected_from_parameters_and_where_clause() {
+        // <'a, 'b: 'a, T: Clone + 'b> where &'a T: 'static
+        let generics = Generics {
+            params: vec![
+                GenericParamDef {
+                    name: "'a".to_string(),
+                    kind: GenericParamDefKind::Lifetime { outlives: vec![] },
+                },
+                GenericParamDef {
+                    name: "'b".to_string(),
+                    kind: GenericParamDefKind::Lifetime {
+                        outlives: vec!["'a".to_string()],
+                    },
+                },
+                GenericParamDef {
+                    name: "T".to_string(),
+                    kind: GenericParamDefKind::Type {
+                        bounds: vec![
+                            GenericBound::TraitBound {
+                                trait_: path("Clone", vec![]),
+                                generic_params: vec![],
+                                modifier: TraitBoundModifier::None,
+                            },
+                            GenericBound::Outlives("'b".to_string()),
+                        ],
+                        default: None,
+                        synthetic: false,
+                    },
+                },
+            ],
+            where_predicates: vec![WherePredicate::BoundPredicate {
+                type_: Type::BorrowedRef {
+                    lifetime: Some("'a".to_string()),
+                    mutable: false,
+                    type_: Box::new(Type::Generic("T".to_string())),
+                },
+                bounds: vec![GenericBound::Outlives("'static".to_string())],
+                generic_params: vec![],
+            }],
+        };
+
+        let bounds: Vec<_> = lifetime_bounds(&generics)
+            .iter()
+            .map(|bound| {
+                (
+                    bound.subject_to_string(),
+                    bound.lifetime,
+                    bound.in_where_clause,
+                )
+            })
+            .collect();
+        assert_eq!(
+            vec![
+                ("'b".to_string(), "'a", false),
+                ("T".to_string(), "'b", false),
+                ("&'a T".to_string(), "'static", true),
+            ],
+            bounds
+        );
+    }
+
+    #[test]
+    fn signatures_are_rendered_as_rust_code() {
+        let generic = |name: &str| Type::Generic(name.to_string());
+        let generics = Generics {
+            params: vec![
+                GenericParamDef {
+                    name: "'a".to_string(),
+                    kind: GenericParamDefKind::Lifetime { outlives: vec![] },
+                },
+                GenericParamDef {
+                    name: "T".to_string(),
+                    kind: GenericParamDefKind::Type {
+                        bounds: vec![],
+                        default: None,
+                        synthetic: false,
+                    },
+                },
+                GenericParamDef {
+                    name: "impl Into<T>".to_string(),
+                    kind: GenericParamDefKind::Type {
+                        bounds: vec![],
+                        default: None,
+                        synthetic: true,
+                    },
+                },
+            ],
+            where_predicates: vec![WherePredicate::BoundPredicate {
+                type_: generic("T"),
+                bounds: vec![GenericBound::Outlives("'a".to_string())],
+                generic_params: vec![],
+            }],
+        };
+        let decl = FnDecl {
+            inputs: vec![
+                (
+                    "self".to_string(),
+                    Type::BorrowedRef {
+                        lifetime: Some("'a".to_string()),
+                        mutable: true,
+                        type_: Box::new(generic("Self")),
+                    },
+                ),
+                ("value".to_string(), generic("impl Into<T>")),
+            ],
+            output: Some(path("Option", vec![GenericArg::Type(generic("T"))])),
+            c_variadic: false,
+        };
+        let header = Header {
+            const_: true,
+            unsafe_: false,
+            async_: false,
+            abi: Abi::Rust,
+        };
+        assert_eq!(
+            "const fn put<'a, T>(&'a mut self, value: impl Into<T>) -> Option<T> where T: 'a",
+            signature_to_string("put", &header, &decl, &generics)
+        );
+    }
+}
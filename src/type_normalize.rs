@@ -0,0 +1,437 @@
+//! Normalizing the types in rustdoc JSON, so queries can tell whether a type changed
+//! rather than just how it's written.
+
+use std::collections::HashMap;
+
+use rustdoc_types::{
+    Constant, Crate, FnDecl, FunctionPointer, GenericArg, GenericArgs, GenericBound,
+    GenericParamDefKind, Generics, Id, ItemEnum, Term, Type, TypeBinding, TypeBindingKind,
+};
+
+use crate::type_display::{bounds_to_string, type_to_string};
+
+/// How many type aliases to expand within each other before giving up,
+/// in case rustdoc describes a cycle.
+const MAX_ALIAS_DEPTH: usize = 16;
+
+/// The type rendered like `type_to_string` does, but normalized so that two ways of
+/// writing the same type are rendered the same:
+/// - paths are the full path of the item they resolve to, whichever re-export or renamed
+///   import names it, e.g. `core::option::Option<u8>`,
+/// - type aliases defined in the crate are expanded,
+/// - the parameters of `generics` are named by their position, like `$0` and `'$1`,
+///   so renaming a generic parameter doesn't change the type,
+/// - the `'_` lifetime is elided, and the bounds of `impl` and `dyn` types are sorted.
+///
+/// The result isn't always valid Rust, and is only meant for comparing types.
+pub(crate) fn normalized_type_string(
+    crate_: &Crate,
+    generics: Option<&Generics>,
+    ty: &Type,
+) -> String {
+    let normalizer = Normalizer {
+        crate_,
+        substitutions: generics.map(positional_names).unwrap_or_default(),
+        alias_depth: 0,
+    };
+    type_to_string(&normalizer.type_(ty))
+}
+
+/// Each of the bounds, normalized like `normalized_type_string` normalizes types,
+/// and sorted since their order doesn't matter.
+pub(crate) fn normalized_bound_strings(
+    crate_: &Crate,
+    generics: Option<&Generics>,
+    bounds: &[GenericBound],
+) -> Vec<String> {
+    let normalizer = Normalizer {
+        crate_,
+        substitutions: generics.map(positional_names).unwrap_or_default(),
+        alias_depth: 0,
+    };
+    normalizer
+        .sorted_bounds(bounds)
+        .iter()
+        .map(|bound| bounds_to_string(std::slice::from_ref(bound)))
+        .collect()
+}
+
+/// The lifetime, named by its position if it's one of the parameters of `generics`,
+/// like `'$1`, and otherwise as written, like `'static`.
+pub(crate) fn normalized_lifetime(generics: Option<&Generics>, lifetime: &str) -> String {
+    match generics.and_then(|generics| positional_names(generics).remove(lifetime)) {
+        Some(GenericArg::Lifetime(positional)) => positional,
+        _ => lifetime.to_string(),
+    }
+}
+
+/// Names each generic parameter by its position in `generics`.
+fn positional_names(generics: &Generics) -> HashMap<String, GenericArg> {
+    generics
+        .params
+        .iter()
+        .enumerate()
+        .map(|(position, param)| {
+            let name = match param.kind {
+                GenericParamDefKind::Lifetime { .. } => {
+                    GenericArg::Lifetime(format!("'${position}"))
+                }
+                GenericParamDefKind::Type { .. } | GenericParamDefKind::Const { .. } => {
+                    GenericArg::Type(Type::Generic(format!("${position}")))
+                }
+            };
+            (param.name.clone(), name)
+        })
+        .collect()
+}
+
+struct Normalizer<'a> {
+    crate_: &'a Crate,
+    /// What each generic parameter in scope stands for, by its name.
+    substitutions: HashMap<String, GenericArg>,
+    alias_depth: usize,
+}
+
+impl Normalizer<'_> {
+    fn type_(&self, ty: &Type) -> Type {
+        match ty {
+            Type::ResolvedPath {
+                name,
+                id,
+                args,
+                param_names,
+            } => {
+                let args = args.as_deref().map(|args| self.generic_args(args));
+                if let Some(expanded) = self.expand_alias(id, args.as_ref()) {
+                    return expanded;
+                }
+                Type::ResolvedPath {
+                    name: self
+                        .crate_
+                        .paths
+                        .get(id)
+                        .map(|summary| summary.path.join("::"))
+                        .unwrap_or_else(|| name.clone()),
+                    id: id.clone(),
+                    args: args.map(Box::new),
+                    param_names: self.sorted_bounds(param_names),
+                }
+            }
+            Type::Generic(name) => match self.substitutions.get(name) {
+                Some(GenericArg::Type(substitute)) => substitute.clone(),
+                Some(GenericArg::Const(constant)) => Type::Generic(constant.expr.clone()),
+                _ => ty.clone(),
+            },
+            Type::Primitive(..) | Type::Infer => ty.clone(),
+            Type::FunctionPointer(pointer) => Type::FunctionPointer(Box::new(FunctionPointer {
+                decl: self.fn_decl(&pointer.decl),
+                ..(**pointer).clone()
+            })),
+            Type::Tuple(types) => Type::Tuple(types.iter().map(|ty| self.type_(ty)).collect()),
+            Type::Slice(ty) => Type::Slice(Box::new(self.type_(ty))),
+            Type::Array { type_, len } => Type::Array {
+                type_: Box::new(self.type_(type_)),
+                len: self.const_expr(len),
+            },
+            Type::ImplTrait(bounds) => Type::ImplTrait(self.sorted_bounds(bounds)),
+            Type::RawPointer { mutable, type_ } => Type::RawPointer {
+                mutable: *mutable,
+                type_: Box::new(self.type_(type_)),
+            },
+            Type::BorrowedRef {
+                lifetime,
+                mutable,
+                type_,
+            } => Type::BorrowedRef {
+                lifetime: lifetime
+                    .as_deref()
+                    .and_then(|lifetime| self.lifetime(lifetime)),
+                mutable: *mutable,
+                type_: Box::new(self.type_(type_)),
+            },
+            Type::QualifiedPath {
+                name,
+                args,
+                self_type,
+                trait_,
+            } => Type::QualifiedPath {
+                name: name.clone(),
+                args: Box::new(self.generic_args(args)),
+                self_type: Box::new(self.type_(self_type)),
+                trait_: Box::new(self.type_(trait_)),
+            },
+        }
+    }
+
+    /// The type the alias with the given id stands for, given the (normalized) arguments
+    /// it's used with, or `None` if the id isn't of a type alias defined in the crate.
+    fn expand_alias(&self, id: &Id, args: Option<&GenericArgs>) -> Option<Type> {
+        let alias = match &self.crate_.index.get(id)?.inner {
+            ItemEnum::Typedef(alias) => alias,
+            _ => return None,
+        };
+        if self.alias_depth >= MAX_ALIAS_DEPTH {
+            return None;
+        }
+
+        let args = match args {
+            Some(GenericArgs::AngleBracketed { args, .. }) => args.as_slice(),
+            _ => &[],
+        };
+        // Lifetime arguments may be elided while the others are given,
+        // so each kind of argument is matched up with its parameters separately.
+        let mut lifetimes = args
+            .iter()
+            .filter(|arg| matches!(arg, GenericArg::Lifetime(..)));
+        let mut others = args
+            .iter()
+            .filter(|arg| !matches!(arg, GenericArg::Lifetime(..)));
+        let mut expansion = Normalizer {
+            crate_: self.crate_,
+            substitutions: HashMap::new(),
+            alias_depth: self.alias_depth + 1,
+        };
+        for param in &alias.generics.params {
+            let arg = match &param.kind {
+                GenericParamDefKind::Lifetime { .. } => lifetimes
+                    .next()
+                    .cloned()
+                    .unwrap_or_else(|| GenericArg::Lifetime("'_".to_string())),
+                GenericParamDefKind::Type { default, .. } => match others.next() {
+                    Some(arg) => arg.clone(),
+                    None => match default {
+                        // Defaults may refer to the alias's earlier parameters.
+                        Some(default) => GenericArg::Type(expansion.type_(default)),
+                        None => continue,
+                    },
+                },
+                GenericParamDefKind::Const { .. } => match others.next() {
+                    Some(arg) => arg.clone(),
+                    None => continue,
+                },
+            };
+            expansion.substitutions.insert(param.name.clone(), arg);
+        }
+        Some(expansion.type_(&alias.type_))
+    }
+
+    /// The lifetime, or `None` if it's elided.
+    fn lifetime(&self, lifetime: &str) -> Option<String> {
+        let lifetime = match self.substitutions.get(lifetime) {
+            Some(GenericArg::Lifetime(substitute)) => substitute,
+            _ => lifetime,
+        };
+        (lifetime != "'_").then(|| lifetime.to_string())
+    }
+
+    fn const_expr(&self, expr: &str) -> String {
+        match self.substitutions.get(expr) {
+            Some(GenericArg::Type(Type::Generic(name))) => name.clone(),
+            Some(GenericArg::Const(constant)) => constant.expr.clone(),
+            _ => expr.to_string(),
+        }
+    }
+
+    fn generic_args(&self, args: &GenericArgs) -> GenericArgs {
+        match args {
+            GenericArgs::AngleBracketed { args, bindings } => GenericArgs::AngleBracketed {
+                args: args.iter().map(|arg| self.generic_arg(arg)).collect(),
+                bindings: bindings
+                    .iter()
+                    .map(|binding| self.binding(binding))
+                    .collect(),
+            },
+            GenericArgs::Parenthesized { inputs, output } => GenericArgs::Parenthesized {
+                inputs: inputs.iter().map(|ty| self.type_(ty)).collect(),
+                output: output.as_ref().map(|ty| self.type_(ty)),
+            },
+        }
+    }
+
+    fn generic_arg(&self, arg: &GenericArg) -> GenericArg {
+        match arg {
+            GenericArg::Lifetime(lifetime) => {
+                GenericArg::Lifetime(self.lifetime(lifetime).unwrap_or_else(|| "'_".to_string()))
+            }
+            GenericArg::Type(ty) => GenericArg::Type(self.type_(ty)),
+            GenericArg::Const(constant) => GenericArg::Const(self.constant(constant)),
+            GenericArg::Infer => GenericArg::Infer,
+        }
+    }
+
+    fn constant(&self, constant: &Constant) -> Constant {
+        Constant {
+            type_: self.type_(&constant.type_),
+            expr: self.const_expr(&constant.expr),
+            ..constant.clone()
+        }
+    }
+
+    fn binding(&self, binding: &TypeBinding) -> TypeBinding {
+        TypeBinding {
+            name: binding.name.clone(),
+            args: self.generic_args(&binding.args),
+            binding: match &binding.binding {
+                TypeBindingKind::Equality(Term::Type(ty)) => {
+                    TypeBindingKind::Equality(Term::Type(self.type_(ty)))
+                }
+                TypeBindingKind::Equality(Term::Constant(constant)) => {
+                    TypeBindingKind::Equality(Term::Constant(self.constant(constant)))
+                }
+                TypeBindingKind::Constraint(bounds) => {
+                    TypeBindingKind::Constraint(self.sorted_bounds(bounds))
+                }
+            },
+        }
+    }
+
+    /// The bounds, normalized and in a consistent order, since their order doesn't matter.
+    fn sorted_bounds(&self, bounds: &[GenericBound]) -> Vec<GenericBound> {
+        let mut bounds: Vec<_> = bounds
+            .iter()
+            .map(|bound| match bound {
+                GenericBound::TraitBound {
+                    trait_,
+                    generic_params,
+                    modifier,
+                } => GenericBound::TraitBound {
+                    trait_: self.type_(trait_),
+                    generic_params: generic_params.clone(),
+                    modifier: modifier.clone(),
+                },
+                GenericBound::Outlives(lifetime) => GenericBound::Outlives(
+                    self.lifetime(lifetime).unwrap_or_else(|| "'_".to_string()),
+                ),
+            })
+            .collect();
+        bounds.sort_by_cached_key(|bound| bounds_to_string(std::slice::from_ref(bound)));
+        bounds
+    }
+
+    fn fn_decl(&self, decl: &FnDecl) -> FnDecl {
+        FnDecl {
+            inputs: decl
+                .inputs
+                .iter()
+                .map(|(name, ty)| (name.clone(), self.type_(ty)))
+                .collect(),
+            output: decl.output.as_ref().map(|ty| self.type_(ty)),
+            c_variadic: decl.c_variadic,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use rustdoc_types::{
+        Crate, GenericArg, GenericArgs, GenericParamDef, GenericParamDefKind, Generics, Id, Item,
+        ItemEnum, ItemKind, ItemSummary, Type, Typedef, Visibility,
+    };
+
+    use super::normalized_type_string;
+
+    fn path(name: &str, id: &str, args: Vec<GenericArg>) -> Type {
+        Type::ResolvedPath {
+            name: name.to_string(),
+            id: Id(id.to_string()),
+            args: Some(Box::new(GenericArgs::AngleBracketed {
+                args,
+                bindings: vec![],
+            })),
+            param_names: vec![],
+        }
+    }
+
+    fn type_param(name: &str) -> GenericParamDef {
+        GenericParamDef {
+            name: name.to_string(),
+            kind: GenericParamDefKind::Type {
+                bounds: vec![],
+                default: None,
+                synthetic: false,
+            },
+        }
+    }
+
+    #[test]
+    fn renames_re_exports_and_aliases_are_normalized_away() {
+        // `type Res<T> = Result<T, Error>;`, with `Result` and `Error` imported under other names.
+        let alias = Item {
+            id: Id("0:1".to_string()),
+            crate_id: 0,
+            name: Some("Res".to_string()),
+            span: None,
+            visibility: Visibility::Public,
+            docs: None,
+            links: HashMap::new(),
+            attrs: vec![],
+            deprecation: None,
+            inner: ItemEnum::Typedef(Typedef {
+                type_: path(
+                    "StdResult",
+                    "2:1",
+                    vec![
+                        GenericArg::Type(Type::Generic("T".to_string())),
+                        GenericArg::Type(path("MyError", "0:2", vec![])),
+                    ],
+                ),
+                generics: Generics {
+                    params: vec![type_param("T")],
+                    where_predicates: vec![],
+                },
+            }),
+        };
+        let summary = |path: &[&str], kind| ItemSummary {
+            crate_id: 0,
+            path: path.iter().map(|segment| segment.to_string()).collect(),
+            kind,
+        };
+        let crate_ = Crate {
+            root: Id("0:0".to_string()),
+            crate_version: None,
+            includes_private: false,
+            index: HashMap::from([(alias.id.clone(), alias)]),
+            paths: HashMap::from([
+                (
+                    Id("2:1".to_string()),
+                    summary(&["core", "result", "Result"], ItemKind::Enum),
+                ),
+                (
+                    Id("0:2".to_string()),
+                    summary(&["krate", "error", "Error"], ItemKind::Struct),
+                ),
+            ]),
+            external_crates: HashMap::new(),
+            format_version: rustdoc_types::FORMAT_VERSION,
+        };
+
+        // `fn f<U>() -> Res<U>` and `fn f<T>() -> Result<T, Error>` return the same type.
+        let generics = |name| Generics {
+            params: vec![type_param(name)],
+            where_predicates: vec![],
+        };
+        let via_alias = path(
+            "Res",
+            "0:1",
+            vec![GenericArg::Type(Type::Generic("U".to_string()))],
+        );
+        let direct = path(
+            "Result",
+            "2:1",
+            vec![
+                GenericArg::Type(Type::Generic("T".to_string())),
+                GenericArg::Type(path("Error", "0:2", vec![])),
+            ],
+        );
+        assert_eq!(
+            "core::result::Result<$0, krate::error::Error>",
+            normalized_type_string(&crate_, Some(&generics("U")), &via_alias)
+        );
+        assert_eq!(
+            normalized_type_string(&crate_, Some(&generics("U")), &via_alias),
+            normalized_type_string(&crate_, Some(&generics("T")), &direct)
+        );
+    }
+}
@@ -1,7 +1,23 @@
-use std::{fs::File, io::Read};
+use std::{fs::File, io::Read, ops::RangeInclusive, path::Path};
 
-use anyhow::Context;
-use rustdoc_types::Crate;
+use anyhow::{bail, Context};
+use rustdoc_types::{Crate, FORMAT_VERSION};
+use serde::Deserialize;
+use tracing::debug;
+
+/// The rustdoc JSON format versions that can be loaded.
+///
+/// Files in `FORMAT_VERSION` are fully supported. Files in the adjacent format versions
+/// are deserialized with the same types, which succeeds unless the file contains items
+/// whose representation changed between the two formats.
+pub(crate) const SUPPORTED_FORMAT_VERSIONS: RangeInclusive<u32> =
+    (FORMAT_VERSION - 1)..=(FORMAT_VERSION + 1);
+
+/// Just the format version of a rustdoc JSON file, read before deciding how to parse the rest.
+#[derive(Debug, Deserialize)]
+struct FormatVersion {
+    format_version: Option<u32>,
+}
 
 pub(crate) fn load_rustdoc_from_file(path: &str) -> anyhow::Result<Crate> {
     // Parsing JSON after fully reading a file into memory is much faster than
@@ -13,6 +29,100 @@ pub(crate) fn load_rustdoc_from_file(path: &str) -> anyhow::Result<Crate> {
         .read_to_string(&mut s)
         .with_context(|| format!("Failed to read rustdoc JSON output file {:?}", path))?;
 
-    serde_json::from_str(&s)
+    parse_rustdoc(&s)
         .with_context(|| format!("Failed to parse rustdoc JSON output file {:?}", path))
 }
+
+/// The format version of a rustdoc JSON file, without parsing the rest of it.
+pub(crate) fn read_format_version(path: &Path) -> anyhow::Result<u32> {
+    let contents = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read rustdoc JSON output file {:?}", path))?;
+    serde_json::from_str::<FormatVersion>(&contents)
+        .ok()
+        .and_then(|version| version.format_version)
+        .with_context(|| format!("{:?} is not rustdoc JSON output", path))
+}
+
+pub(crate) fn parse_rustdoc(contents: &str) -> anyhow::Result<Crate> {
+    let format_version = match serde_json::from_str::<FormatVersion>(contents)?.format_version {
+        Some(format_version) => format_version,
+        None => bail!("The file is not rustdoc JSON output: it has no `format_version` field."),
+    };
+
+    debug!("parsing rustdoc JSON in format version {format_version}");
+    if format_version < *SUPPORTED_FORMAT_VERSIONS.start() {
+        bail!(
+            "The file uses rustdoc JSON format version {}, which is older than \
+            the supported versions {}-{}. It was likely generated by an older nightly \
+            toolchain: regenerate it with a more recent one.",
+            format_version,
+            SUPPORTED_FORMAT_VERSIONS.start(),
+            SUPPORTED_FORMAT_VERSIONS.end(),
+        );
+    }
+    if format_version > *SUPPORTED_FORMAT_VERSIONS.end() {
+        bail!(
+            "The file uses rustdoc JSON format version {}, which is newer than \
+            the supported versions {}-{}. Regenerate it with an older nightly toolchain, \
+            e.g. with `--toolchain`, or upgrade cargo-semver-checks.",
+            format_version,
+            SUPPORTED_FORMAT_VERSIONS.start(),
+            SUPPORTED_FORMAT_VERSIONS.end(),
+        );
+    }
+
+    let result = serde_json::from_str(contents);
+    if format_version == FORMAT_VERSION {
+        Ok(result?)
+    } else {
+        result.with_context(|| {
+            format!(
+                "The file uses rustdoc JSON format version {}, and contains items \
+                that cannot be read as format version {}. Regenerate it with a nightly \
+                toolchain that emits format version {}.",
+                format_version, FORMAT_VERSION, FORMAT_VERSION,
+            )
+        })
+    }
+}
+
+/// The 64-bit FNV-1a hash, which unlike the standard library's hasher
+/// is stable across Rust versions, as is needed for anything that's persisted,
+/// like cache paths and report fingerprints.
+pub(crate) fn fnv1a(value: &str) -> u64 {
+    value.bytes().fold(0xcbf29ce484222325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use rustdoc_types::FORMAT_VERSION;
+
+    use super::parse_rustdoc;
+
+    fn empty_crate(format_version: u32) -> String {
+        format!(
+            r#"{{"root":"0:0","crate_version":null,"includes_private":false,"index":{{}},"paths":{{}},"external_crates":{{}},"format_version":{}}}"#,
+            format_version
+        )
+    }
+
+    #[test]
+    fn format_version_is_checked_before_parsing() {
+        assert!(parse_rustdoc(&empty_crate(FORMAT_VERSION)).is_ok());
+        assert!(parse_rustdoc(&empty_crate(FORMAT_VERSION + 1)).is_ok());
+
+        let message = format!(
+            "{:#}",
+            parse_rustdoc(&empty_crate(FORMAT_VERSION - 5)).expect_err("old format accepted")
+        );
+        assert!(message.contains("older than"), "{}", message);
+
+        let message = format!(
+            "{:#}",
+            parse_rustdoc(r#"{"root":"0:0"}"#).expect_err("missing format version accepted")
+        );
+        assert!(message.contains("no `format_version` field"), "{}", message);
+    }
+}
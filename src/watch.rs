@@ -0,0 +1,117 @@
+//! Detecting changes to a package's files, for the `watch` subcommand.
+
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+/// The modification time of every watched file.
+type Snapshot = BTreeMap<PathBuf, SystemTime>;
+
+/// Watches the files in a directory for changes, by periodically comparing their
+/// modification times. Polling is slower than platform file notification APIs,
+/// but works everywhere and is cheap at the size of a crate's source tree.
+///
+/// Hidden files and directories, like `.git`, are not watched.
+#[derive(Debug, Clone)]
+pub(crate) struct SourceWatcher {
+    root: PathBuf,
+    /// Directories within `root` that aren't watched, like the target directory.
+    ignored: Vec<PathBuf>,
+    interval: Duration,
+    snapshot: Snapshot,
+}
+
+impl SourceWatcher {
+    pub(crate) fn new(root: &Path, ignored: Vec<PathBuf>, interval: Duration) -> Self {
+        let mut watcher = Self {
+            root: root.to_path_buf(),
+            ignored,
+            interval,
+            snapshot: Snapshot::new(),
+        };
+        watcher.snapshot = watcher.take_snapshot();
+        watcher
+    }
+
+    /// Block until any watched file has been created, modified, or removed since this was
+    /// last called, or since the watcher was created. Returns once the changes settle,
+    /// so that saving several files at once only counts as one change.
+    pub(crate) fn wait_for_change(&mut self) {
+        let mut snapshot = self.take_snapshot();
+        while snapshot == self.snapshot {
+            std::thread::sleep(self.interval);
+            snapshot = self.take_snapshot();
+        }
+        loop {
+            std::thread::sleep(self.interval);
+            let settled = self.take_snapshot();
+            if settled == snapshot {
+                break;
+            }
+            snapshot = settled;
+        }
+        self.snapshot = snapshot;
+    }
+
+    fn take_snapshot(&self) -> Snapshot {
+        let mut snapshot = Snapshot::new();
+        let mut dirs = vec![self.root.clone()];
+        while let Some(dir) = dirs.pop() {
+            // Files can disappear while we look at them: that's just another change.
+            let entries = match std::fs::read_dir(&dir) {
+                Ok(entries) => entries,
+                Err(_) => continue,
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let is_hidden = entry.file_name().to_string_lossy().starts_with('.');
+                if is_hidden || self.ignored.contains(&path) {
+                    continue;
+                }
+                let metadata = match entry.metadata() {
+                    Ok(metadata) => metadata,
+                    Err(_) => continue,
+                };
+                if metadata.is_dir() {
+                    dirs.push(path);
+                } else if let Ok(modified) = metadata.modified() {
+                    snapshot.insert(path, modified);
+                }
+            }
+        }
+        snapshot
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::SourceWatcher;
+
+    #[test]
+    fn changes_outside_ignored_and_hidden_dirs_are_seen() {
+        let dir =
+            std::env::temp_dir().join(format!("cargo-semver-checks-watch-{}", std::process::id()));
+        let target_dir = dir.join("target");
+        std::fs::create_dir_all(dir.join("src")).expect("failed to create directory");
+        std::fs::create_dir_all(&target_dir).expect("failed to create directory");
+        std::fs::create_dir_all(dir.join(".git")).expect("failed to create directory");
+        std::fs::write(dir.join("src/lib.rs"), "").expect("failed to write");
+
+        let watcher = SourceWatcher::new(&dir, vec![target_dir.clone()], Duration::ZERO);
+        let before = watcher.take_snapshot();
+        assert_eq!(1, before.len());
+
+        std::fs::write(target_dir.join("out.json"), "").expect("failed to write");
+        std::fs::write(dir.join(".git/index"), "").expect("failed to write");
+        assert_eq!(before, watcher.take_snapshot());
+
+        std::fs::write(dir.join("src/new.rs"), "").expect("failed to write");
+        assert_ne!(before, watcher.take_snapshot());
+
+        std::fs::remove_dir_all(&dir).expect("failed to remove directory");
+    }
+}